@@ -0,0 +1,155 @@
+//! Rows and records of the applications of a backup
+
+use serde::Serialize;
+use tabled::Tabled;
+
+use crate::application::apps_service::{AppSummary, DomainUsage};
+use crate::application::display_service::Listing;
+
+/// Represents an application for table display
+#[derive(Tabled)]
+pub struct AppRow {
+    #[tabled(rename = "Bundle ID")]
+    bundle_id: String,
+    #[tabled(rename = "Name")]
+    display_name: String,
+    #[tabled(rename = "Version")]
+    version: String,
+    #[tabled(rename = "Container")]
+    has_container: &'static str,
+    #[tabled(rename = "App Files")]
+    app: String,
+    #[tabled(rename = "Group Files")]
+    group: String,
+    #[tabled(rename = "Plugin Files")]
+    plugin: String,
+}
+
+impl AppRow {
+    fn new(summary: &AppSummary) -> Self {
+        let usage =
+            |usage: DomainUsage| format!("{} ({} bytes)", usage.file_count, usage.total_bytes);
+        let application = &summary.application;
+        Self {
+            bundle_id: application.bundle_id().to_owned(),
+            display_name: application.display_name().unwrap_or_default().to_owned(),
+            version: application.version().unwrap_or_default().to_owned(),
+            has_container: if summary.has_container { "yes" } else { "no" },
+            app: usage(summary.app),
+            group: usage(summary.group),
+            plugin: usage(summary.plugin),
+        }
+    }
+}
+
+/// Serialized form of an application
+#[derive(Serialize)]
+struct AppRecord<'a> {
+    bundle_id: &'a str,
+    display_name: Option<&'a str>,
+    version: Option<&'a str>,
+    has_container: bool,
+    app_files: usize,
+    app_bytes: u64,
+    group_files: usize,
+    group_bytes: u64,
+    plugin_files: usize,
+    plugin_bytes: u64,
+}
+
+impl<'a> AppRecord<'a> {
+    fn new(summary: &'a AppSummary) -> Self {
+        let application = &summary.application;
+        Self {
+            bundle_id: application.bundle_id(),
+            display_name: application.display_name(),
+            version: application.version(),
+            has_container: summary.has_container,
+            app_files: summary.app.file_count,
+            app_bytes: summary.app.total_bytes,
+            group_files: summary.group.file_count,
+            group_bytes: summary.group.total_bytes,
+            plugin_files: summary.plugin.file_count,
+            plugin_bytes: summary.plugin.total_bytes,
+        }
+    }
+}
+
+impl Listing for AppSummary {
+    type Row = AppRow;
+
+    const EMPTY: &'static str = "No applications found.";
+
+    fn row(&self) -> Self::Row {
+        AppRow::new(self)
+    }
+
+    fn record(&self) -> impl Serialize {
+        AppRecord::new(self)
+    }
+
+    fn summary(apps: &[Self]) -> String {
+        format!("Found {} application(s)", apps.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use super::*;
+    use crate::Application;
+    use crate::application::apps_service::DomainUsage;
+    use crate::application::{DelimitedFormatter, OutputFormatter as _, TableFormatter};
+    use pretty_assertions::assert_eq;
+
+    fn create_app_summary() -> AppSummary {
+        let mut summary = AppSummary::new(
+            Application::new("net.whatsapp.WhatsApp".to_owned())
+                .with_display_name(Some("WhatsApp".to_owned())),
+        );
+        summary.app = DomainUsage {
+            file_count: 2,
+            total_bytes: 300,
+        };
+        summary.has_container = true;
+        summary
+    }
+
+    #[test]
+    fn test_format_apps() -> Result<()> {
+        let formatter = TableFormatter::new();
+
+        let output = formatter.format_apps(&[create_app_summary()])?;
+
+        assert!(output.contains("net.whatsapp.WhatsApp"));
+        assert!(output.contains("WhatsApp"));
+        assert!(output.contains("2 (300 bytes)"));
+        assert!(output.ends_with("Found 1 application(s)"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_format_apps_empty() -> Result<()> {
+        let formatter = TableFormatter::new();
+
+        let output = formatter.format_apps(&[])?;
+
+        assert_eq!(output, "No applications found.");
+        Ok(())
+    }
+
+    #[test]
+    fn test_csv_format_apps() -> Result<()> {
+        let formatter = DelimitedFormatter::csv();
+
+        let output = formatter.format_apps(&[create_app_summary()])?;
+
+        assert_eq!(
+            output,
+            "bundle_id,display_name,version,has_container,app_files,app_bytes,group_files,group_bytes,plugin_files,plugin_bytes\n\
+             net.whatsapp.WhatsApp,WhatsApp,,true,2,300,0,0,0,0"
+        );
+        Ok(())
+    }
+}
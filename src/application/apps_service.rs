@@ -0,0 +1,323 @@
+//! Apps service for summarizing the applications recorded in a backup
+
+use crate::domain::entities::{Application, File};
+use crate::domain::queries::{BasicQuery, FileQuery};
+use crate::domain::repositories::{ApplicationRepository, FileRepository};
+use crate::domain::value_objects::DomainKind;
+use anyhow::Result;
+use std::collections::BTreeMap;
+
+/// Files stored in the backup for one kind of container
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct DomainUsage {
+    /// Number of regular files
+    pub file_count: usize,
+    /// Total size of the regular files in bytes
+    pub total_bytes: u64,
+}
+
+impl DomainUsage {
+    /// Counts a file towards the usage, ignoring directories and symbolic links
+    fn add(&mut self, file: &File) {
+        if file.flags().is_regular_file() {
+            self.file_count = self.file_count.saturating_add(1);
+            self.total_bytes = self
+                .total_bytes
+                .saturating_add(file.size().unwrap_or_default());
+        }
+    }
+}
+
+/// An application with the files the backup holds for it
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct AppSummary {
+    /// The application
+    pub application: Application,
+    /// Files of the `AppDomain` data container
+    pub app: DomainUsage,
+    /// Files of the `AppDomainGroup` containers shared with the app
+    pub group: DomainUsage,
+    /// Files of the `AppDomainPlugin` containers of its extensions
+    pub plugin: DomainUsage,
+    /// Whether the backup has a data container for the app
+    pub has_container: bool,
+}
+
+impl AppSummary {
+    /// Creates a summary without files
+    pub(crate) fn new(application: Application) -> Self {
+        Self {
+            application,
+            app: DomainUsage::default(),
+            group: DomainUsage::default(),
+            plugin: DomainUsage::default(),
+            has_container: false,
+        }
+    }
+}
+
+/// Service for listing the applications recorded in a backup
+#[derive(Debug, Default)]
+#[non_exhaustive]
+pub struct AppsService;
+
+impl AppsService {
+    /// Creates a new `AppsService`
+    #[must_use]
+    #[inline]
+    pub const fn new() -> Self {
+        Self
+    }
+
+    /// Lists the applications of a backup with the files stored for them
+    ///
+    /// Apps that only have a data container in the backup are listed as well. Group and
+    /// plugin containers are counted towards the app whose bundle ID their identifier
+    /// extends, e.g. `group.net.whatsapp.WhatsApp.shared` towards `net.whatsapp.WhatsApp`;
+    /// containers without such an app are not counted.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if either repository fails
+    #[allow(
+        clippy::future_not_send,
+        reason = "Repository trait doesn't guarantee Send futures"
+    )]
+    #[inline]
+    pub async fn list<A: ApplicationRepository, F: FileRepository>(
+        &self,
+        applications: &A,
+        files: &F,
+    ) -> Result<Vec<AppSummary>> {
+        let mut summaries: BTreeMap<String, AppSummary> = applications
+            .find_all()
+            .await?
+            .into_iter()
+            .map(|application| {
+                (
+                    application.bundle_id().to_owned(),
+                    AppSummary::new(application),
+                )
+            })
+            .collect();
+
+        let query = FileQuery::Basic(BasicQuery::DomainKind(vec![
+            DomainKind::App,
+            DomainKind::AppGroup,
+            DomainKind::AppPlugin,
+        ]));
+        let files = files.search(query).await?;
+
+        for file in &files {
+            if let (DomainKind::App, Some(bundle_id)) =
+                (file.domain().kind(), file.domain().bundle_id())
+            {
+                summaries
+                    .entry(bundle_id.to_owned())
+                    .or_insert_with(|| AppSummary::new(Application::new(bundle_id.to_owned())))
+                    .has_container = true;
+            }
+        }
+
+        for file in &files {
+            let Some(id) = file.domain().bundle_id() else {
+                continue;
+            };
+            let Some(summary) = Self::owner(&mut summaries, file.domain().kind(), id) else {
+                continue;
+            };
+
+            match file.domain().kind() {
+                DomainKind::App => summary.app.add(file),
+                DomainKind::AppGroup => summary.group.add(file),
+                _ => summary.plugin.add(file),
+            }
+        }
+
+        Ok(summaries.into_values().collect())
+    }
+
+    /// Finds the app a container belongs to
+    ///
+    /// The owner of a group or plugin container is the app with the longest bundle ID
+    /// that the container identifier, without its `group.` prefix, equals or extends.
+    fn owner<'a>(
+        summaries: &'a mut BTreeMap<String, AppSummary>,
+        kind: DomainKind,
+        id: &str,
+    ) -> Option<&'a mut AppSummary> {
+        if kind == DomainKind::App {
+            return summaries.get_mut(id);
+        }
+
+        let id = id.strip_prefix("group.").unwrap_or(id);
+        let owner = summaries
+            .keys()
+            .filter(|bundle_id| {
+                id.strip_prefix(bundle_id.as_str())
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+            })
+            .max_by_key(|bundle_id| bundle_id.len())?
+            .clone();
+        summaries.get_mut(&owner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::value_objects::{Domain, FileFlags, FileId, FileMetadata, RelativePath};
+    use pretty_assertions::assert_eq;
+
+    struct MockApplicationRepository {
+        applications: Vec<Application>,
+    }
+
+    impl ApplicationRepository for MockApplicationRepository {
+        async fn find_all(&self) -> Result<Vec<Application>> {
+            Ok(self.applications.clone())
+        }
+    }
+
+    // Mock repository returning every file
+    struct MockFileRepository {
+        files: Vec<File>,
+    }
+
+    impl FileRepository for MockFileRepository {
+        async fn search(&self, _query: FileQuery) -> Result<Vec<File>> {
+            Ok(self.files.clone())
+        }
+    }
+
+    fn create_test_file(domain: &str, flags: FileFlags, size: u64) -> Result<File> {
+        Ok(File::new(
+            FileId::new("da39a3ee5e6b4b0d3255bfef95601890afd80709")?,
+            Domain::new(domain.to_owned())?,
+            RelativePath::new("Library/data".to_owned())?,
+            flags,
+            vec![],
+        )
+        .with_file_metadata(FileMetadata::new(size, 0o100_644)))
+    }
+
+    async fn list(applications: Vec<Application>, files: Vec<File>) -> Result<Vec<AppSummary>> {
+        AppsService::new()
+            .list(
+                &MockApplicationRepository { applications },
+                &MockFileRepository { files },
+            )
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_list_counts_containers_of_app() -> Result<()> {
+        let summaries = list(
+            vec![
+                Application::new("net.whatsapp.WhatsApp".to_owned())
+                    .with_version(Some("24.1.78".to_owned())),
+            ],
+            vec![
+                create_test_file(
+                    "AppDomain-net.whatsapp.WhatsApp",
+                    FileFlags::REGULAR_FILE,
+                    100,
+                )?,
+                create_test_file("AppDomain-net.whatsapp.WhatsApp", FileFlags::DIRECTORY, 0)?,
+                create_test_file(
+                    "AppDomainGroup-group.net.whatsapp.WhatsApp.shared",
+                    FileFlags::REGULAR_FILE,
+                    20,
+                )?,
+                create_test_file(
+                    "AppDomainGroup-group.net.whatsapp.WhatsApp.shared",
+                    FileFlags::REGULAR_FILE,
+                    30,
+                )?,
+                create_test_file(
+                    "AppDomainPlugin-net.whatsapp.WhatsApp.ShareExtension",
+                    FileFlags::REGULAR_FILE,
+                    5,
+                )?,
+                // Belongs to no app
+                create_test_file(
+                    "AppDomainGroup-group.com.example.shared",
+                    FileFlags::REGULAR_FILE,
+                    1,
+                )?,
+            ],
+        )
+        .await?;
+
+        assert_eq!(summaries.len(), 1);
+        let summary = &summaries[0];
+        assert_eq!(summary.application.version(), Some("24.1.78"));
+        assert!(summary.has_container);
+        assert_eq!(
+            (summary.app, summary.group, summary.plugin),
+            (
+                DomainUsage {
+                    file_count: 1,
+                    total_bytes: 100
+                },
+                DomainUsage {
+                    file_count: 2,
+                    total_bytes: 50
+                },
+                DomainUsage {
+                    file_count: 1,
+                    total_bytes: 5
+                },
+            )
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_list_includes_apps_without_records_or_containers() -> Result<()> {
+        let summaries = list(
+            vec![Application::new("com.burbn.instagram".to_owned())],
+            vec![create_test_file(
+                "AppDomain-com.example.app",
+                FileFlags::REGULAR_FILE,
+                10,
+            )?],
+        )
+        .await?;
+
+        let ids: Vec<_> = summaries
+            .iter()
+            .map(|summary| (summary.application.bundle_id(), summary.has_container))
+            .collect();
+        assert_eq!(
+            ids,
+            vec![("com.burbn.instagram", false), ("com.example.app", true)]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_list_prefers_longest_bundle_id() -> Result<()> {
+        let summaries = list(
+            vec![
+                Application::new("com.example".to_owned()),
+                Application::new("com.example.app".to_owned()),
+            ],
+            vec![create_test_file(
+                "AppDomainPlugin-com.example.app.widget",
+                FileFlags::REGULAR_FILE,
+                10,
+            )?],
+        )
+        .await?;
+
+        let plugin_files: Vec<_> = summaries
+            .iter()
+            .map(|summary| summary.plugin.file_count)
+            .collect();
+        assert_eq!(plugin_files, vec![0, 1]);
+        Ok(())
+    }
+}
@@ -0,0 +1,140 @@
+//! Rows and records of the call history
+
+use serde::Serialize;
+use tabled::Tabled;
+
+use crate::application::display_service::{Listing, table_duration, table_time};
+use crate::domain::entities::Call;
+
+/// Represents a call for table display
+#[derive(Tabled)]
+pub struct CallRow {
+    #[tabled(rename = "Date")]
+    date: String,
+    #[tabled(rename = "Direction")]
+    direction: &'static str,
+    #[tabled(rename = "Address")]
+    address: String,
+    #[tabled(rename = "Name")]
+    name: String,
+    #[tabled(rename = "Service")]
+    service: String,
+    #[tabled(rename = "Answered")]
+    answered: &'static str,
+    #[tabled(rename = "Duration")]
+    duration: String,
+}
+
+impl CallRow {
+    fn new(call: &Call) -> Self {
+        Self {
+            date: table_time(call.date),
+            direction: call.direction.name(),
+            address: call.address.clone().unwrap_or_default(),
+            name: call.name.clone().unwrap_or_default(),
+            service: call.service().unwrap_or_default().to_owned(),
+            answered: if call.answered { "yes" } else { "no" },
+            duration: table_duration(call.duration),
+        }
+    }
+}
+
+/// Serialized form of a call
+#[derive(Serialize)]
+struct CallRecord<'a> {
+    id: i64,
+    date: Option<String>,
+    direction: &'static str,
+    address: Option<&'a str>,
+    name: Option<&'a str>,
+    service: Option<&'a str>,
+    service_provider: Option<&'a str>,
+    answered: bool,
+    duration_seconds: u64,
+}
+
+impl<'a> CallRecord<'a> {
+    fn new(call: &'a Call) -> Self {
+        Self {
+            id: call.id,
+            date: call.date.map(|date| date.to_rfc3339()),
+            direction: call.direction.name(),
+            address: call.address.as_deref(),
+            name: call.name.as_deref(),
+            service: call.service(),
+            service_provider: call.service_provider.as_deref(),
+            answered: call.answered,
+            duration_seconds: call.duration.as_secs(),
+        }
+    }
+}
+
+impl Listing for Call {
+    type Row = CallRow;
+
+    const EMPTY: &'static str = "No calls found.";
+
+    fn row(&self) -> Self::Row {
+        CallRow::new(self)
+    }
+
+    fn record(&self) -> impl Serialize {
+        CallRecord::new(self)
+    }
+
+    fn summary(calls: &[Self]) -> String {
+        format!("Found {} call(s)", calls.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use std::time::Duration;
+
+    use super::*;
+    use crate::application::{DelimitedFormatter, OutputFormatter as _, TableFormatter};
+    use crate::domain::entities::CallDirection;
+    use pretty_assertions::assert_eq;
+
+    fn create_call() -> Result<Call> {
+        Ok(Call {
+            id: 5,
+            address: Some("+15550001".to_owned()),
+            name: None,
+            date: Some("2024-01-15T02:30:00Z".parse()?),
+            duration: Duration::from_secs(62),
+            direction: CallDirection::Outgoing,
+            answered: true,
+            service_provider: Some("com.apple.FaceTime".to_owned()),
+        })
+    }
+
+    #[test]
+    fn test_format_calls() -> Result<()> {
+        let formatter = TableFormatter::new();
+
+        let output = formatter.format_calls(&[create_call()?])?;
+
+        assert!(output.contains("2024-01-15 02:30:00"));
+        assert!(output.contains("outgoing"));
+        assert!(output.contains("FaceTime"));
+        assert!(output.contains("1:02"));
+        assert!(output.ends_with("Found 1 call(s)"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_csv_format_calls() -> Result<()> {
+        let formatter = DelimitedFormatter::csv();
+
+        let output = formatter.format_calls(&[create_call()?])?;
+
+        assert_eq!(
+            output,
+            "id,date,direction,address,name,service,service_provider,answered,duration_seconds\n\
+             5,2024-01-15T02:30:00+00:00,outgoing,+15550001,,FaceTime,com.apple.FaceTime,true,62"
+        );
+        Ok(())
+    }
+}
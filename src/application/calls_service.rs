@@ -0,0 +1,36 @@
+//! Calls service for listing the call history
+
+use crate::domain::entities::Call;
+use crate::domain::repositories::CallRepository;
+use anyhow::Result;
+
+/// Service for listing the call history of the Phone app
+#[derive(Debug, Default)]
+#[non_exhaustive]
+pub struct CallsService;
+
+impl CallsService {
+    /// Creates a new `CallsService`
+    #[must_use]
+    #[inline]
+    pub const fn new() -> Self {
+        Self
+    }
+
+    /// Lists every call of the call history, most recent first
+    ///
+    /// The history covers phone and `FaceTime` calls as well as calls of apps using
+    /// `CallKit`, such as `WhatsApp`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the repository fails
+    #[allow(
+        clippy::future_not_send,
+        reason = "Repository trait doesn't guarantee Send futures"
+    )]
+    #[inline]
+    pub async fn list<C: CallRepository>(&self, calls: &C) -> Result<Vec<Call>> {
+        calls.find_all().await
+    }
+}
@@ -0,0 +1,129 @@
+//! Rows and records of the contacts written by a contacts export
+
+use serde::Serialize;
+use tabled::Tabled;
+
+use crate::application::contacts_service::ExportedContact;
+use crate::application::display_service::Listing;
+
+/// Represents an exported contact for table display
+#[derive(Tabled)]
+pub struct ExportedContactRow {
+    #[tabled(rename = "Name")]
+    name: String,
+    #[tabled(rename = "Organization")]
+    organization: String,
+    #[tabled(rename = "Phones")]
+    phone_count: usize,
+    #[tabled(rename = "Emails")]
+    email_count: usize,
+    #[tabled(rename = "Photo")]
+    has_photo: &'static str,
+    #[tabled(rename = "vCard")]
+    vcard: String,
+}
+
+impl ExportedContactRow {
+    fn new(contact: &ExportedContact) -> Self {
+        Self {
+            name: contact.name.clone(),
+            organization: contact.organization.clone().unwrap_or_default(),
+            phone_count: contact.phone_count,
+            email_count: contact.email_count,
+            has_photo: if contact.has_photo { "yes" } else { "no" },
+            vcard: contact.vcard.display().to_string(),
+        }
+    }
+}
+
+/// Serialized form of an exported contact
+#[derive(Serialize)]
+struct ExportedContactRecord<'a> {
+    id: i64,
+    name: &'a str,
+    organization: Option<&'a str>,
+    phone_count: usize,
+    email_count: usize,
+    has_photo: bool,
+    vcard: String,
+}
+
+impl<'a> ExportedContactRecord<'a> {
+    fn new(contact: &'a ExportedContact) -> Self {
+        Self {
+            id: contact.id,
+            name: &contact.name,
+            organization: contact.organization.as_deref(),
+            phone_count: contact.phone_count,
+            email_count: contact.email_count,
+            has_photo: contact.has_photo,
+            vcard: contact.vcard.display().to_string(),
+        }
+    }
+}
+
+impl Listing for ExportedContact {
+    type Row = ExportedContactRow;
+
+    const EMPTY: &'static str = "No contacts found.";
+
+    fn row(&self) -> Self::Row {
+        ExportedContactRow::new(self)
+    }
+
+    fn record(&self) -> impl Serialize {
+        ExportedContactRecord::new(self)
+    }
+
+    fn summary(contacts: &[Self]) -> String {
+        format!("Exported {} contact(s)", contacts.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use serde_json::Value;
+
+    use super::*;
+    use crate::application::{JsonFormatter, OutputFormatter as _, TableFormatter};
+
+    fn create_exported_contact() -> ExportedContact {
+        ExportedContact {
+            id: 7,
+            name: "Jane Appleseed".to_owned(),
+            organization: None,
+            phone_count: 2,
+            email_count: 1,
+            has_photo: true,
+            vcard: "out/contacts.vcf".into(),
+        }
+    }
+
+    #[test]
+    fn test_format_contacts_export() -> Result<()> {
+        let formatter = TableFormatter::new();
+
+        let output = formatter.format_contacts_export(&[create_exported_contact()])?;
+
+        assert!(output.contains("Jane Appleseed"));
+        assert!(output.contains("out/contacts.vcf"));
+        assert!(output.ends_with("Exported 1 contact(s)"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_json_format_contacts_export() -> Result<()> {
+        let formatter = JsonFormatter::new();
+
+        let output = formatter.format_contacts_export(&[create_exported_contact()])?;
+
+        let value: Value = serde_json::from_str(&output)?;
+        assert_eq!(value[0]["name"], "Jane Appleseed");
+        assert_eq!(value[0]["organization"], Value::Null);
+        assert_eq!(value[0]["phone_count"], 2);
+        assert_eq!(value[0]["has_photo"], true);
+        assert_eq!(value[0]["vcard"], "out/contacts.vcf");
+        Ok(())
+    }
+}
@@ -0,0 +1,347 @@
+//! Contacts service for exporting the address book as vCards and CSV
+
+use crate::application::export_util::{UniqueNames, file_name};
+use crate::application::vcard::{VCardVersion, to_vcard};
+use crate::domain::entities::contact::is_year_omitted;
+use crate::domain::entities::{Contact, Labeled, PostalAddress};
+use crate::domain::repositories::ContactRepository;
+use anyhow::{Context as _, Result};
+use chrono::NaiveDate;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Name of the vCard file holding every contact
+const COMBINED_FILE_NAME: &str = "contacts.vcf";
+
+/// Name of the CSV file
+const CSV_FILE_NAME: &str = "contacts.csv";
+
+/// A contact written to the output directory
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ExportedContact {
+    /// Row ID of the contact
+    pub id: i64,
+    /// Name shown for the contact
+    pub name: String,
+    /// Company
+    pub organization: Option<String>,
+    /// Number of phone numbers
+    pub phone_count: usize,
+    /// Number of email addresses
+    pub email_count: usize,
+    /// Whether a photo was embedded
+    pub has_photo: bool,
+    /// Location of the vCard file holding the contact
+    pub vcard: PathBuf,
+}
+
+/// Flat form of a contact for the CSV file
+///
+/// Labeled values are joined with `; ` and prefixed with their label, e.g. `Mobile: +1 555 0001`.
+#[derive(Serialize)]
+struct ContactCsvRecord<'a> {
+    id: i64,
+    display_name: String,
+    first_name: Option<&'a str>,
+    middle_name: Option<&'a str>,
+    last_name: Option<&'a str>,
+    prefix: Option<&'a str>,
+    suffix: Option<&'a str>,
+    nickname: Option<&'a str>,
+    organization: Option<&'a str>,
+    department: Option<&'a str>,
+    job_title: Option<&'a str>,
+    birthday: Option<String>,
+    phones: String,
+    emails: String,
+    addresses: String,
+    urls: String,
+    dates: String,
+    related_names: String,
+    note: Option<&'a str>,
+    has_photo: bool,
+}
+
+impl<'a> ContactCsvRecord<'a> {
+    fn new(contact: &'a Contact) -> Self {
+        Self {
+            id: contact.id,
+            display_name: contact.display_name(),
+            first_name: contact.first_name.as_deref(),
+            middle_name: contact.middle_name.as_deref(),
+            last_name: contact.last_name.as_deref(),
+            prefix: contact.prefix.as_deref(),
+            suffix: contact.suffix.as_deref(),
+            nickname: contact.nickname.as_deref(),
+            organization: contact.organization.as_deref(),
+            department: contact.department.as_deref(),
+            job_title: contact.job_title.as_deref(),
+            birthday: contact.birthday.map(format_date),
+            phones: join(&contact.phones, Clone::clone),
+            emails: join(&contact.emails, Clone::clone),
+            addresses: join(&contact.addresses, PostalAddress::to_single_line),
+            urls: join(&contact.urls, Clone::clone),
+            dates: join(&contact.dates, |date| format_date(*date)),
+            related_names: join(&contact.related_names, Clone::clone),
+            note: contact.note.as_deref(),
+            has_photo: contact.photo.is_some(),
+        }
+    }
+}
+
+/// Formats a date as `YYYY-MM-DD`, or `--MM-DD` if it was entered without a year
+fn format_date(date: NaiveDate) -> String {
+    if is_year_omitted(date) {
+        date.format("--%m-%d").to_string()
+    } else {
+        date.format("%Y-%m-%d").to_string()
+    }
+}
+
+/// Joins labeled values into one field
+fn join<T>(values: &[Labeled<T>], format: impl Fn(&T) -> String) -> String {
+    values
+        .iter()
+        .map(|labeled| {
+            let value = format(&labeled.value);
+            labeled
+                .label
+                .as_ref()
+                .map_or_else(|| value.clone(), |label| format!("{label}: {value}"))
+        })
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Service for exporting the address book of the Contacts app
+#[derive(Debug, Default)]
+#[non_exhaustive]
+pub struct ContactsService {
+    /// Version of the written vCards
+    version: VCardVersion,
+    /// Whether to write one vCard file per contact
+    split: bool,
+}
+
+impl ContactsService {
+    /// Creates a new `ContactsService` writing a single vCard 3.0 file
+    #[must_use]
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            version: VCardVersion::V3,
+            split: false,
+        }
+    }
+
+    /// Sets the version of the written vCards
+    #[must_use]
+    #[inline]
+    pub const fn with_version(mut self, version: VCardVersion) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Sets whether to write one vCard file per contact instead of a combined file
+    #[must_use]
+    #[inline]
+    pub const fn with_split(mut self, split: bool) -> Self {
+        self.split = split;
+        self
+    }
+
+    /// Exports every contact to vCards and to `contacts.csv` in the output directory
+    ///
+    /// The vCards are written to `contacts.vcf`, or to one file per contact named after
+    /// it. Photos are embedded in the vCards. Existing files are overwritten.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the repository fails or an output file cannot be written
+    #[allow(
+        clippy::future_not_send,
+        reason = "Repository trait doesn't guarantee Send futures"
+    )]
+    #[inline]
+    pub async fn export<C: ContactRepository>(
+        &self,
+        contacts: &C,
+        output_dir: &Path,
+    ) -> Result<Vec<ExportedContact>> {
+        let contacts = contacts.find_all().await?;
+        fs::create_dir_all(output_dir)
+            .with_context(|| format!("Failed to create directory: {}", output_dir.display()))?;
+
+        let combined_path = output_dir.join(COMBINED_FILE_NAME);
+        let mut combined = String::new();
+        let mut names = UniqueNames::default();
+        let mut exported = Vec::with_capacity(contacts.len());
+        for contact in &contacts {
+            let vcard = to_vcard(contact, self.version);
+            let path = if self.split {
+                let name = file_name(&contact.display_name())
+                    .unwrap_or_else(|| format!("contact-{}", contact.id));
+                let path = output_dir.join(format!("{}.vcf", names.claim(name, contact.id)));
+                write(&path, &vcard)?;
+                path
+            } else {
+                combined.push_str(&vcard);
+                combined_path.clone()
+            };
+
+            exported.push(ExportedContact {
+                id: contact.id,
+                name: contact.display_name(),
+                organization: contact.organization.clone(),
+                phone_count: contact.phones.len(),
+                email_count: contact.emails.len(),
+                has_photo: contact.photo.is_some(),
+                vcard: path,
+            });
+        }
+        if !self.split {
+            write(&combined_path, &combined)?;
+        }
+
+        let csv_path = output_dir.join(CSV_FILE_NAME);
+        let mut writer = csv::Writer::from_path(&csv_path)
+            .with_context(|| format!("Failed to create {}", csv_path.display()))?;
+        for contact in &contacts {
+            writer.serialize(ContactCsvRecord::new(contact))?;
+        }
+        writer
+            .flush()
+            .with_context(|| format!("Failed to write {}", csv_path.display()))?;
+
+        Ok(exported)
+    }
+}
+
+/// Writes an output file
+fn write(path: &Path, content: &str) -> Result<()> {
+    fs::write(path, content).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_fs::TempDir;
+    use assert_fs::prelude::*;
+    use predicates::prelude::*;
+    use pretty_assertions::assert_eq;
+
+    struct MockContactRepository {
+        contacts: Vec<Contact>,
+    }
+
+    impl ContactRepository for MockContactRepository {
+        async fn find_all(&self) -> Result<Vec<Contact>> {
+            Ok(self.contacts.clone())
+        }
+    }
+
+    fn create_contact(id: i64, first_name: &str) -> Contact {
+        let mut contact = Contact::new(id);
+        contact.first_name = Some(first_name.to_owned());
+        contact.phones = vec![
+            Labeled::new(Some("Mobile".to_owned()), "+1 555 0001".to_owned()),
+            Labeled::new(None, "+1 555 0002".to_owned()),
+        ];
+        contact
+    }
+
+    #[tokio::test]
+    async fn test_export_combined() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let mut jane = create_contact(1, "Jane");
+        jane.birthday = NaiveDate::from_ymd_opt(1604, 3, 15);
+        jane.addresses = vec![Labeled::new(
+            Some("Home".to_owned()),
+            PostalAddress {
+                city: Some("Cupertino".to_owned()),
+                country: Some("USA".to_owned()),
+                ..PostalAddress::default()
+            },
+        )];
+        jane.photo = Some(vec![0xFF, 0xD8]);
+        let repo = MockContactRepository {
+            contacts: vec![jane, create_contact(2, "John")],
+        };
+
+        let exported = ContactsService::new()
+            .export(&repo, temp_dir.path())
+            .await?;
+
+        let vcards: Vec<_> = exported
+            .iter()
+            .map(|contact| contact.vcard.clone())
+            .collect();
+        assert_eq!(vcards, vec![temp_dir.path().join("contacts.vcf"); 2]);
+        assert!(exported[0].has_photo);
+        temp_dir.child("contacts.vcf").assert(
+            predicate::str::contains("FN:Jane\r\n")
+                .and(predicate::str::contains("FN:John\r\n"))
+                .and(predicate::str::contains(
+                    "PHOTO;ENCODING=b;TYPE=JPEG:/9g=\r\n",
+                )),
+        );
+
+        let csv = fs::read_to_string(temp_dir.path().join("contacts.csv"))?;
+        let lines: Vec<_> = csv.lines().collect();
+        assert_eq!(
+            lines,
+            vec![
+                "id,display_name,first_name,middle_name,last_name,prefix,suffix,nickname,\
+                 organization,department,job_title,birthday,phones,emails,addresses,urls,dates,\
+                 related_names,note,has_photo",
+                "1,Jane,Jane,,,,,,,,,--03-15,Mobile: +1 555 0001; +1 555 0002,,\
+                 \"Home: Cupertino, USA\",,,,,true",
+                "2,John,John,,,,,,,,,,Mobile: +1 555 0001; +1 555 0002,,,,,,,false",
+            ]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_export_split() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let repo = MockContactRepository {
+            contacts: vec![
+                create_contact(1, "Jane"),
+                create_contact(2, "Jane"),
+                Contact::new(3),
+            ],
+        };
+
+        let exported = ContactsService::new()
+            .with_version(VCardVersion::V4)
+            .with_split(true)
+            .export(&repo, temp_dir.path())
+            .await?;
+
+        let vcards: Vec<_> = exported
+            .iter()
+            .map(|contact| contact.vcard.clone())
+            .collect();
+        assert_eq!(
+            vcards,
+            vec![
+                temp_dir.path().join("Jane.vcf"),
+                temp_dir.path().join("Jane-2.vcf"),
+                temp_dir.path().join("contact-3.vcf"),
+            ]
+        );
+        temp_dir
+            .child("Jane-2.vcf")
+            .assert(predicate::str::contains("VERSION:4.0\r\n"));
+        temp_dir
+            .child("contacts.vcf")
+            .assert(predicate::path::missing());
+        temp_dir
+            .child("contacts.csv")
+            .assert(predicate::path::exists());
+        Ok(())
+    }
+}
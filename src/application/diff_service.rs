@@ -0,0 +1,387 @@
+//! Diff service for comparing two backups of the same device
+
+use crate::application::search_service::{FileType, SearchParams};
+use crate::domain::entities::File;
+use crate::domain::queries::FileQuery;
+use crate::domain::repositories::FileRepository;
+use crate::domain::value_objects::FileMetadata;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+
+/// Kind of change of a file between two backups
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ChangeKind {
+    /// The file only exists in the newer backup
+    Added,
+    /// The file only exists in the older backup
+    Removed,
+    /// The file exists in both backups with different attributes
+    Modified,
+}
+
+impl ChangeKind {
+    /// Returns the name used in output
+    #[must_use]
+    #[inline]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Added => "added",
+            Self::Removed => "removed",
+            Self::Modified => "modified",
+        }
+    }
+}
+
+/// Attribute of a file that is compared between two backups
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ChangedField {
+    /// Whether the entry is a file, directory or symbolic link
+    Type,
+    /// Size of the content
+    Size,
+    /// Last modification time
+    Modified,
+    /// Inode number on the device
+    Inode,
+}
+
+impl ChangedField {
+    /// All compared attributes, in the order they are reported
+    pub const ALL: [Self; 4] = [Self::Type, Self::Size, Self::Modified, Self::Inode];
+
+    /// Returns the name used in output
+    #[must_use]
+    #[inline]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Type => "type",
+            Self::Size => "size",
+            Self::Modified => "modified",
+            Self::Inode => "inode",
+        }
+    }
+
+    /// Checks whether the attribute differs between two versions of a file
+    ///
+    /// Attributes that could not be decoded from the metadata of either version count as
+    /// different only if the other version has them.
+    fn differs(self, old: &File, new: &File) -> bool {
+        match self {
+            Self::Type => FileType::of(old) != FileType::of(new),
+            Self::Size => old.size() != new.size(),
+            Self::Modified => old.last_modified() != new.last_modified(),
+            Self::Inode => {
+                old.file_metadata().map(FileMetadata::inode)
+                    != new.file_metadata().map(FileMetadata::inode)
+            }
+        }
+    }
+}
+
+/// A file that differs between two backups
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct FileChange {
+    /// Kind of the change
+    pub kind: ChangeKind,
+    /// File ID in the backups
+    pub file_id: String,
+    /// Domain of the file
+    pub domain: String,
+    /// Relative path of the file
+    pub relative_path: String,
+    /// Attributes that differ, or empty for added and removed files
+    pub fields: Vec<ChangedField>,
+    /// Size in the older backup
+    pub old_size: Option<u64>,
+    /// Size in the newer backup
+    pub new_size: Option<u64>,
+    /// Last modification time in the older backup
+    pub old_modified: Option<DateTime<Utc>>,
+    /// Last modification time in the newer backup
+    pub new_modified: Option<DateTime<Utc>>,
+}
+
+impl FileChange {
+    /// Compares the versions of a file in the older and newer backup
+    ///
+    /// Returns `None` if neither version exists.
+    fn new(old: Option<&File>, new: Option<&File>) -> Option<Self> {
+        let file = new.or(old)?;
+        let (kind, fields) = match (old, new) {
+            (Some(old), Some(new)) => (
+                ChangeKind::Modified,
+                ChangedField::ALL
+                    .into_iter()
+                    .filter(|field| field.differs(old, new))
+                    .collect(),
+            ),
+            (Some(_), None) => (ChangeKind::Removed, Vec::new()),
+            _ => (ChangeKind::Added, Vec::new()),
+        };
+
+        Some(Self {
+            kind,
+            file_id: file.id().to_string(),
+            domain: file.domain().to_string(),
+            relative_path: file.relative_path().to_string(),
+            fields,
+            old_size: old.and_then(File::size),
+            new_size: new.and_then(File::size),
+            old_modified: old.and_then(File::last_modified).copied(),
+            new_modified: new.and_then(File::last_modified).copied(),
+        })
+    }
+}
+
+/// Result of comparing two backups
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct DiffReport {
+    /// Number of compared files in the older backup
+    pub from_count: usize,
+    /// Number of compared files in the newer backup
+    pub to_count: usize,
+    /// Number of files that exist in both backups with the same attributes
+    pub unchanged_count: usize,
+    /// Changed files, ordered by domain and relative path
+    pub changes: Vec<FileChange>,
+}
+
+impl DiffReport {
+    /// Returns the number of changes of the given kind
+    #[must_use]
+    #[inline]
+    pub fn change_count(&self, kind: ChangeKind) -> usize {
+        self.changes
+            .iter()
+            .filter(|change| change.kind == kind)
+            .count()
+    }
+}
+
+/// Service for comparing two backups of the same device
+#[derive(Debug, Default)]
+#[non_exhaustive]
+pub struct DiffService;
+
+impl DiffService {
+    /// Creates a new `DiffService`
+    #[must_use]
+    #[inline]
+    pub const fn new() -> Self {
+        Self
+    }
+
+    /// Compares the files of an older and a newer backup
+    ///
+    /// Files are matched by domain and relative path, which their file ID is derived from.
+    /// A file is compared if it matches the search parameters in either backup, so that a
+    /// file that stopped matching a size or time filter is reported as modified rather
+    /// than removed. Without search conditions, every file is compared.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the search parameters are invalid or either repository fails
+    #[allow(
+        clippy::future_not_send,
+        reason = "Repository trait doesn't guarantee Send futures"
+    )]
+    #[inline]
+    pub async fn diff<F: FileRepository, T: FileRepository>(
+        &self,
+        from: &F,
+        to: &T,
+        params: SearchParams,
+    ) -> Result<DiffReport> {
+        let query = params.build_query_or_all()?;
+
+        let mut versions: BTreeMap<(String, String), (Option<File>, Option<File>)> =
+            BTreeMap::new();
+        for file in from.search(FileQuery::all()).await? {
+            let key = Self::key(&file);
+            versions.entry(key).or_default().0 = Some(file);
+        }
+        for file in to.search(FileQuery::all()).await? {
+            let key = Self::key(&file);
+            versions.entry(key).or_default().1 = Some(file);
+        }
+
+        let mut report = DiffReport::default();
+        for (old, new) in versions.into_values() {
+            if !old.iter().chain(&new).any(|file| query.matches(file)) {
+                continue;
+            }
+
+            if old.is_some() {
+                report.from_count = report.from_count.saturating_add(1);
+            }
+            if new.is_some() {
+                report.to_count = report.to_count.saturating_add(1);
+            }
+
+            let Some(change) = FileChange::new(old.as_ref(), new.as_ref()) else {
+                continue;
+            };
+            if change.kind == ChangeKind::Modified && change.fields.is_empty() {
+                report.unchanged_count = report.unchanged_count.saturating_add(1);
+            } else {
+                report.changes.push(change);
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Returns the key files of both backups are matched by
+    fn key(file: &File) -> (String, String) {
+        (file.domain().to_string(), file.relative_path().to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::value_objects::{Domain, FileFlags, FileId, RelativePath};
+    use pretty_assertions::assert_eq;
+
+    // Mock repository returning every file
+    struct MockFileRepository {
+        files: Vec<File>,
+    }
+
+    impl FileRepository for MockFileRepository {
+        async fn search(&self, _query: FileQuery) -> Result<Vec<File>> {
+            Ok(self.files.clone())
+        }
+    }
+
+    fn create_test_file(relative_path: &str, size: u64, inode: u64) -> Result<File> {
+        let modified = DateTime::from_timestamp(1_700_000_000, 0)
+            .ok_or_else(|| anyhow::anyhow!("invalid time"))?;
+        Ok(File::new(
+            FileId::new("da39a3ee5e6b4b0d3255bfef95601890afd80709")?,
+            Domain::new("HomeDomain".to_owned())?,
+            RelativePath::new(relative_path.to_owned())?,
+            FileFlags::REGULAR_FILE,
+            vec![],
+        )
+        .with_file_metadata(
+            FileMetadata::new(size, 0o100_644)
+                .with_inode(inode)
+                .with_timestamps(modified, modified, modified),
+        ))
+    }
+
+    async fn diff(old: Vec<File>, new: Vec<File>, params: SearchParams) -> Result<DiffReport> {
+        DiffService::new()
+            .diff(
+                &MockFileRepository { files: old },
+                &MockFileRepository { files: new },
+                params,
+            )
+            .await
+    }
+
+    fn changes(report: &DiffReport) -> Vec<(ChangeKind, &str, Vec<ChangedField>)> {
+        report
+            .changes
+            .iter()
+            .map(|change| {
+                (
+                    change.kind,
+                    change.relative_path.as_str(),
+                    change.fields.clone(),
+                )
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_diff() -> Result<()> {
+        let old = vec![
+            create_test_file("Library/SMS/sms.db", 100, 1)?,
+            create_test_file("Library/Notes/notes.sqlite", 10, 2)?,
+            create_test_file("Library/Safari/History.db", 30, 3)?,
+            create_test_file("Library/Mail/Envelope Index", 40, 4)?,
+        ];
+        let new = vec![
+            create_test_file("Library/SMS/sms.db", 200, 1)?,
+            create_test_file("Library/Notes/notes.sqlite", 10, 2)?,
+            create_test_file("Library/Safari/History.db", 30, 5)?,
+            create_test_file("Library/Photos/photo.heic", 50, 6)?,
+        ];
+
+        let report = diff(old, new, SearchParams::new(None, None, None, None, false)).await?;
+
+        assert_eq!(report.from_count, 4);
+        assert_eq!(report.to_count, 4);
+        assert_eq!(report.unchanged_count, 1);
+        assert_eq!(
+            changes(&report),
+            vec![
+                (
+                    ChangeKind::Removed,
+                    "Library/Mail/Envelope Index",
+                    Vec::new()
+                ),
+                (ChangeKind::Added, "Library/Photos/photo.heic", Vec::new()),
+                (
+                    ChangeKind::Modified,
+                    "Library/SMS/sms.db",
+                    vec![ChangedField::Size]
+                ),
+                (
+                    ChangeKind::Modified,
+                    "Library/Safari/History.db",
+                    vec![ChangedField::Inode]
+                ),
+            ]
+        );
+        assert_eq!(report.changes[2].old_size, Some(100));
+        assert_eq!(report.changes[2].new_size, Some(200));
+        assert_eq!(report.change_count(ChangeKind::Modified), 2);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_diff_filters_either_version() -> Result<()> {
+        let old = vec![
+            create_test_file("Library/SMS/sms.db", 100, 1)?,
+            create_test_file("Library/Notes/notes.sqlite", 10, 2)?,
+        ];
+        let new = vec![
+            create_test_file("Library/SMS/sms.db", 2000, 1)?,
+            create_test_file("Library/Notes/notes.sqlite", 20, 2)?,
+        ];
+        let params = SearchParams::new(None, None, None, None, false)
+            .with_size_range(Some("1KB".to_owned()), None);
+
+        let report = diff(old, new, params).await?;
+
+        // Only the newer version of sms.db is large enough, but it is still a modification
+        assert_eq!(report.from_count, 1);
+        assert_eq!(report.to_count, 1);
+        assert_eq!(
+            changes(&report),
+            vec![(
+                ChangeKind::Modified,
+                "Library/SMS/sms.db",
+                vec![ChangedField::Size]
+            )]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_diff_invalid_params() -> Result<()> {
+        let params =
+            SearchParams::new(None, None, None, None, false).with_path_glob(Some("[".to_owned()));
+
+        assert!(diff(vec![], vec![], params).await.is_err());
+        Ok(())
+    }
+}
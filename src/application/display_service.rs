@@ -1,8 +1,464 @@
 //! Display service for formatting and presenting search results
 
-// BackupEntry is no longer used since ListService only returns Metadata
-use crate::{ExtractResult, File, Metadata};
-use tabled::{Table, Tabled, settings::Style};
+use crate::application::apps_service::AppSummary;
+use crate::application::contacts_service::ExportedContact;
+use crate::application::diff_service::{ChangeKind, DiffReport, FileChange};
+use crate::application::extract_plan::{ExtractPlan, PlannedAction};
+use crate::application::messages_service::ExportedConversation;
+use crate::application::photos_service::ExportedPhoto;
+use crate::application::safari_service::ExportedSafariFile;
+use crate::application::verify_service::{IssueKind, VerifyIssue, VerifyReport};
+use crate::application::voicemail_service::VoicemailSummary;
+use crate::domain::entities::Call;
+use crate::{
+    ExtractError, ExtractErrorKind, ExtractResult, ExtractStatus, File, FileMetadata, FileOutcome,
+    Metadata,
+};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
+use serde::ser::SerializeMap as _;
+use serde::{Serialize, Serializer};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::time::Duration;
+use tabled::{Table, Tabled, builder::Builder, settings::Style};
+
+/// Output format of command results
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+#[non_exhaustive]
+pub enum OutputFormat {
+    /// Human-readable tables
+    #[default]
+    Table,
+    /// A single JSON document
+    Json,
+    /// One JSON object per line
+    Ndjson,
+    /// Comma-separated values with a header row
+    Csv,
+    /// Tab-separated values with a header row
+    Tsv,
+}
+
+impl OutputFormat {
+    /// Creates the formatter for this output format
+    #[must_use]
+    #[inline]
+    pub fn formatter(self) -> Box<dyn OutputFormatter> {
+        match self {
+            Self::Table => Box::new(TableFormatter::new()),
+            Self::Json => Box::new(JsonFormatter::new()),
+            Self::Ndjson => Box::new(NdjsonFormatter::new()),
+            Self::Csv => Box::new(DelimitedFormatter::csv()),
+            Self::Tsv => Box::new(DelimitedFormatter::tsv()),
+        }
+    }
+}
+
+/// Column of the backup list
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+#[non_exhaustive]
+pub enum MetadataColumn {
+    /// Unique identifier of the backup
+    Id,
+    /// Name of the device
+    Device,
+    /// Product name of the device, e.g. `iPhone16,1`
+    Product,
+    /// Date of the last backup
+    LastBackup,
+    /// iOS version
+    ProductVersion,
+    /// iOS build
+    BuildVersion,
+    /// Serial number
+    SerialNumber,
+    /// IMEI of a GSM device
+    Imei,
+    /// MEID of a CDMA device
+    Meid,
+    /// ICCID of the SIM card
+    Iccid,
+    /// Phone number of the SIM card
+    PhoneNumber,
+    /// GUID of the device
+    Guid,
+    /// Kind of device
+    TargetType,
+    /// Version of iTunes or Finder that took the backup
+    ItunesVersion,
+    /// Whether the backup is encrypted
+    Encrypted,
+    /// Version of the backup format
+    Version,
+    /// Whether the device had a passcode
+    PasscodeSet,
+    /// Date the backup was taken, as recorded in Manifest.plist
+    Date,
+    /// State of the snapshot
+    SnapshotState,
+    /// Whether the last backup was a full backup
+    FullBackup,
+    /// State of the backup
+    BackupState,
+}
+
+impl MetadataColumn {
+    /// Columns shown by default
+    pub const DEFAULT: [Self; 4] = [Self::Id, Self::Device, Self::Product, Self::LastBackup];
+
+    /// All columns, in display order
+    pub const ALL: [Self; 21] = [
+        Self::Id,
+        Self::Device,
+        Self::Product,
+        Self::LastBackup,
+        Self::ProductVersion,
+        Self::BuildVersion,
+        Self::SerialNumber,
+        Self::Imei,
+        Self::Meid,
+        Self::Iccid,
+        Self::PhoneNumber,
+        Self::Guid,
+        Self::TargetType,
+        Self::ItunesVersion,
+        Self::Encrypted,
+        Self::Version,
+        Self::PasscodeSet,
+        Self::Date,
+        Self::SnapshotState,
+        Self::FullBackup,
+        Self::BackupState,
+    ];
+
+    /// Returns the key of the column in serialized output
+    #[must_use]
+    #[inline]
+    pub const fn key(self) -> &'static str {
+        match self {
+            Self::Id => "id",
+            Self::Device => "device_name",
+            Self::Product => "product_name",
+            Self::LastBackup => "last_backup_date",
+            Self::ProductVersion => "product_version",
+            Self::BuildVersion => "build_version",
+            Self::SerialNumber => "serial_number",
+            Self::Imei => "imei",
+            Self::Meid => "meid",
+            Self::Iccid => "iccid",
+            Self::PhoneNumber => "phone_number",
+            Self::Guid => "guid",
+            Self::TargetType => "target_type",
+            Self::ItunesVersion => "itunes_version",
+            Self::Encrypted => "is_encrypted",
+            Self::Version => "version",
+            Self::PasscodeSet => "was_passcode_set",
+            Self::Date => "date",
+            Self::SnapshotState => "snapshot_state",
+            Self::FullBackup => "is_full_backup",
+            Self::BackupState => "backup_state",
+        }
+    }
+
+    /// Returns the header of the column in tables
+    #[must_use]
+    #[inline]
+    pub const fn header(self) -> &'static str {
+        match self {
+            Self::Id => "ID",
+            Self::Device => "Device",
+            Self::Product => "Product",
+            Self::LastBackup => "Last Backup",
+            Self::ProductVersion => "iOS",
+            Self::BuildVersion => "Build",
+            Self::SerialNumber => "Serial Number",
+            Self::Imei => "IMEI",
+            Self::Meid => "MEID",
+            Self::Iccid => "ICCID",
+            Self::PhoneNumber => "Phone Number",
+            Self::Guid => "GUID",
+            Self::TargetType => "Target Type",
+            Self::ItunesVersion => "iTunes Version",
+            Self::Encrypted => "Encrypted",
+            Self::Version => "Version",
+            Self::PasscodeSet => "Passcode Set",
+            Self::Date => "Date",
+            Self::SnapshotState => "Snapshot State",
+            Self::FullBackup => "Full Backup",
+            Self::BackupState => "Backup State",
+        }
+    }
+
+    /// Returns the value of the column for a backup, `null` if it is unknown
+    #[must_use]
+    #[inline]
+    pub fn value(self, metadata: &Metadata) -> Value {
+        let device = metadata.device();
+        let manifest = metadata.manifest();
+        let status = metadata.status();
+        let text = |value: &Option<String>| value.as_deref().map_or(Value::Null, Value::from);
+
+        match self {
+            Self::Id => metadata.id().to_string().into(),
+            Self::Device => metadata.device_name().into(),
+            Self::Product => metadata.product_name().into(),
+            Self::LastBackup => metadata.last_backup_date().to_rfc3339().into(),
+            Self::ProductVersion => text(&device.product_version),
+            Self::BuildVersion => text(&device.build_version),
+            Self::SerialNumber => text(&device.serial_number),
+            Self::Imei => text(&device.imei),
+            Self::Meid => text(&device.meid),
+            Self::Iccid => text(&device.iccid),
+            Self::PhoneNumber => text(&device.phone_number),
+            Self::Guid => text(&device.guid),
+            Self::TargetType => text(&device.target_type),
+            Self::ItunesVersion => text(&device.itunes_version),
+            Self::Encrypted => manifest.is_encrypted.into(),
+            Self::Version => text(&manifest.version),
+            Self::PasscodeSet => manifest.was_passcode_set.into(),
+            Self::Date => manifest.date.map(|date| date.to_rfc3339()).into(),
+            Self::SnapshotState => text(&status.snapshot_state),
+            Self::FullBackup => status.is_full_backup.into(),
+            Self::BackupState => text(&status.backup_state),
+        }
+    }
+
+    /// Returns the value of the column as text, empty if it is unknown
+    fn text(self, metadata: &Metadata) -> String {
+        value_text(&self.value(metadata))
+    }
+}
+
+/// Formats command results for output
+///
+/// Implement this trait to plug a custom output format into `DisplayService`. Only file
+/// lists, backup lists and extraction results must be formatted; every other result is
+/// handed to [`OutputFormatter::format_records`] unless its method is overridden, so
+/// custom formatters keep compiling as commands are added.
+pub trait OutputFormatter: Send + Sync {
+    /// Formats file search results
+    ///
+    /// # Errors
+    /// Returns an error if the files cannot be serialized
+    fn format_files(&self, files: &[File]) -> Result<String>;
+
+    /// Formats a list of backup metadata
+    ///
+    /// # Errors
+    /// Returns an error if the metadata cannot be serialized
+    fn format_metadata_list(&self, metadata_list: &[Metadata]) -> Result<String>;
+
+    /// Formats the result of an extraction
+    ///
+    /// # Errors
+    /// Returns an error if the result cannot be serialized
+    fn format_extract_result(&self, result: &ExtractResult) -> Result<String>;
+
+    /// Formats the serialized records of a result without a method of its own
+    ///
+    /// Each record is a JSON object whose keys are in column order. The default writes the
+    /// records as a pretty-printed JSON array.
+    ///
+    /// # Errors
+    /// Returns an error if the records cannot be serialized
+    #[inline]
+    fn format_records(&self, records: &[Value]) -> Result<String> {
+        Ok(serde_json::to_string_pretty(records)?)
+    }
+
+    /// Formats a list of backup metadata, showing the given columns
+    ///
+    /// The default hands the default columns to `format_metadata_list`, and other columns
+    /// to `format_records`.
+    ///
+    /// # Errors
+    /// Returns an error if the metadata cannot be serialized
+    #[inline]
+    fn format_metadata_columns(
+        &self,
+        metadata_list: &[Metadata],
+        columns: &[MetadataColumn],
+    ) -> Result<String> {
+        if columns == MetadataColumn::DEFAULT {
+            return self.format_metadata_list(metadata_list);
+        }
+        self.format_records(&to_records(
+            metadata_list
+                .iter()
+                .map(|metadata| MetadataRecord::new(metadata, columns)),
+        )?)
+    }
+
+    /// Formats every detail of a single backup
+    ///
+    /// # Errors
+    /// Returns an error if the metadata cannot be serialized
+    #[inline]
+    fn format_metadata(&self, metadata: &Metadata) -> Result<String> {
+        self.format_records(&to_records([MetadataRecord::new(
+            metadata,
+            &MetadataColumn::ALL,
+        )])?)
+    }
+
+    /// Formats the plan of an extraction
+    ///
+    /// The default formats one record per planned or rejected file.
+    ///
+    /// # Errors
+    /// Returns an error if the plan cannot be serialized
+    #[inline]
+    fn format_extract_plan(&self, plan: &ExtractPlan) -> Result<String> {
+        self.format_records(&to_records(PlanRowRecord::rows(plan))?)
+    }
+
+    /// Formats the report of a backup verification
+    ///
+    /// The default formats one record per problem.
+    ///
+    /// # Errors
+    /// Returns an error if the report cannot be serialized
+    #[inline]
+    fn format_verify_report(&self, report: &VerifyReport) -> Result<String> {
+        self.format_records(&to_records(
+            report.issues.iter().map(VerifyIssueRecord::new),
+        )?)
+    }
+
+    /// Formats the comparison of two backups
+    ///
+    /// The default formats one record per changed file.
+    ///
+    /// # Errors
+    /// Returns an error if the report cannot be serialized
+    #[inline]
+    fn format_diff_report(&self, report: &DiffReport) -> Result<String> {
+        self.format_records(&to_records(
+            report.changes.iter().map(FileChangeRecord::new),
+        )?)
+    }
+
+    /// Formats the applications of a backup
+    ///
+    /// # Errors
+    /// Returns an error if the applications cannot be serialized
+    #[inline]
+    fn format_apps(&self, apps: &[AppSummary]) -> Result<String> {
+        self.format_records(&listing_records(apps)?)
+    }
+
+    /// Formats the conversations written by a messages export
+    ///
+    /// # Errors
+    /// Returns an error if the conversations cannot be serialized
+    #[inline]
+    fn format_messages_export(&self, conversations: &[ExportedConversation]) -> Result<String> {
+        self.format_records(&listing_records(conversations)?)
+    }
+
+    /// Formats the contacts written by a contacts export
+    ///
+    /// # Errors
+    /// Returns an error if the contacts cannot be serialized
+    #[inline]
+    fn format_contacts_export(&self, contacts: &[ExportedContact]) -> Result<String> {
+        self.format_records(&listing_records(contacts)?)
+    }
+
+    /// Formats the call history
+    ///
+    /// # Errors
+    /// Returns an error if the calls cannot be serialized
+    #[inline]
+    fn format_calls(&self, calls: &[Call]) -> Result<String> {
+        self.format_records(&listing_records(calls)?)
+    }
+
+    /// Formats voicemail messages
+    ///
+    /// # Errors
+    /// Returns an error if the voicemails cannot be serialized
+    #[inline]
+    fn format_voicemails(&self, voicemails: &[VoicemailSummary]) -> Result<String> {
+        self.format_records(&listing_records(voicemails)?)
+    }
+
+    /// Formats the photos and videos written by a photos export
+    ///
+    /// # Errors
+    /// Returns an error if the photos cannot be serialized
+    #[inline]
+    fn format_photos_export(&self, photos: &[ExportedPhoto]) -> Result<String> {
+        self.format_records(&listing_records(photos)?)
+    }
+
+    /// Formats the files written by a Safari export
+    ///
+    /// # Errors
+    /// Returns an error if the files cannot be serialized
+    #[inline]
+    fn format_safari_export(&self, files: &[ExportedSafariFile]) -> Result<String> {
+        self.format_records(&listing_records(files)?)
+    }
+}
+
+/// Serializes records into the JSON values handed to `OutputFormatter::format_records`
+fn to_records<T: Serialize>(records: impl IntoIterator<Item = T>) -> Result<Vec<Value>> {
+    Ok(records
+        .into_iter()
+        .map(serde_json::to_value)
+        .collect::<Result<_, _>>()?)
+}
+
+/// Returns a serialized value as text for tabular output, empty for `null`
+fn value_text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        value => value.to_string(),
+    }
+}
+
+/// Item of a command result listing one item per row, such as calls or exported files
+///
+/// The rows and records of each listing live in a module next to the service producing it.
+pub(crate) trait Listing: Sized {
+    /// Row of the item for table display
+    type Row: Tabled;
+
+    /// Text shown instead of a table when nothing is listed
+    const EMPTY: &'static str;
+
+    /// Returns the row of the item for table display
+    fn row(&self) -> Self::Row;
+
+    /// Returns the serialized form of the item
+    fn record(&self) -> impl Serialize;
+
+    /// Returns the line shown below the table of the items
+    fn summary(items: &[Self]) -> String;
+}
+
+/// Formats a listing as a table followed by its summary
+fn listing_table<T: Listing>(items: &[T]) -> String {
+    if items.is_empty() {
+        return T::EMPTY.to_owned();
+    }
+
+    let table = Table::new(items.iter().map(T::row))
+        .with(Style::rounded())
+        .to_string();
+
+    format!("{table}\n\n{}", T::summary(items))
+}
+
+/// Serializes a listing into the records handed to `OutputFormatter::format_records`
+fn listing_records<T: Listing>(items: &[T]) -> Result<Vec<Value>> {
+    to_records(items.iter().map(T::record))
+}
 
 /// Represents a file for table display
 #[derive(Tabled)]
@@ -24,17 +480,13 @@ struct ExtractionStatsRow {
     count: usize,
 }
 
-/// Represents a metadata row for table display
+/// Represents a detail of a backup for table display
 #[derive(Tabled)]
-struct MetadataTableRow {
-    #[tabled(rename = "ID")]
-    id: String,
-    #[tabled(rename = "Device")]
-    device_name: String,
-    #[tabled(rename = "Product")]
-    product_name: String,
-    #[tabled(rename = "Last Backup")]
-    last_backup_date: String,
+struct MetadataFieldRow {
+    #[tabled(rename = "Field")]
+    field: &'static str,
+    #[tabled(rename = "Value")]
+    value: String,
 }
 
 /// Represents extraction errors for table display
@@ -46,50 +498,538 @@ struct ExtractionErrorRow {
     error: String,
 }
 
-/// Service for handling result display operations
+/// Represents a planned operation for table display
+#[derive(Tabled)]
+struct PlannedOperationRow {
+    #[tabled(rename = "Action")]
+    action: &'static str,
+    #[tabled(rename = "Domain")]
+    domain: String,
+    #[tabled(rename = "Path")]
+    path: String,
+    #[tabled(rename = "Destination")]
+    destination: String,
+}
+
+/// Represents the number of planned files of a domain for table display
+#[derive(Tabled)]
+struct DomainCountRow {
+    #[tabled(rename = "Domain")]
+    domain: String,
+    #[tabled(rename = "Files")]
+    count: usize,
+}
+
+/// Represents a rejected file for table display
+#[derive(Tabled)]
+struct RejectedFileRow {
+    #[tabled(rename = "Domain")]
+    domain: String,
+    #[tabled(rename = "Path")]
+    path: String,
+    #[tabled(rename = "Reason")]
+    reason: String,
+}
+
+/// Represents an integrity problem for table display
+#[derive(Tabled)]
+struct VerifyIssueRow {
+    #[tabled(rename = "Problem")]
+    kind: &'static str,
+    #[tabled(rename = "File ID")]
+    file_id: String,
+    #[tabled(rename = "Path")]
+    path: String,
+    #[tabled(rename = "Detail")]
+    detail: String,
+}
+
+/// Represents a changed file for table display
+#[derive(Tabled)]
+struct FileChangeRow {
+    #[tabled(rename = "Change")]
+    kind: &'static str,
+    #[tabled(rename = "Domain")]
+    domain: String,
+    #[tabled(rename = "Path")]
+    path: String,
+    #[tabled(rename = "Size")]
+    size: String,
+    #[tabled(rename = "Changed")]
+    fields: String,
+}
+
+impl FileChangeRow {
+    fn new(change: &FileChange) -> Self {
+        let size = |size: Option<u64>| size.map(|size| size.to_string()).unwrap_or_default();
+        Self {
+            kind: change.kind.name(),
+            domain: change.domain.clone(),
+            path: change.relative_path.clone(),
+            size: match change.kind {
+                ChangeKind::Removed => size(change.old_size),
+                _ if change.old_size != change.new_size && change.old_size.is_some() => {
+                    format!("{} → {}", size(change.old_size), size(change.new_size))
+                }
+                _ => size(change.new_size),
+            },
+            fields: FileChangeRecord::fields(change).join(", "),
+        }
+    }
+}
+
+/// Formats a time for table display
+pub(crate) fn table_time(time: Option<DateTime<Utc>>) -> String {
+    time.map(|time| time.format("%Y-%m-%d %H:%M:%S").to_string())
+        .unwrap_or_default()
+}
+
+/// Formats a duration for table display, e.g. `1:02` or `1:00:05`
+pub(crate) fn table_duration(duration: Duration) -> String {
+    let total = duration.as_secs();
+    let hours = total.checked_div(3600).unwrap_or_default();
+    let minutes = total
+        .checked_div(60)
+        .and_then(|minutes| minutes.checked_rem(60))
+        .unwrap_or_default();
+    let seconds = total.checked_rem(60).unwrap_or_default();
+    if hours > 0 {
+        format!("{hours}:{minutes:02}:{seconds:02}")
+    } else {
+        format!("{minutes}:{seconds:02}")
+    }
+}
+
+/// Serialized form of a file
+///
+/// The decoded metadata fields are empty when the metadata could not be decoded.
+#[derive(Serialize)]
+struct FileRecord<'a> {
+    id: &'a str,
+    domain: &'a str,
+    relative_path: &'a str,
+    file_type: &'static str,
+    size: Option<u64>,
+    mode: Option<u32>,
+    user_id: Option<u32>,
+    group_id: Option<u32>,
+    inode: Option<u64>,
+    last_modified: Option<String>,
+    last_status_change: Option<String>,
+    birth: Option<String>,
+    protection_class: Option<u32>,
+    symlink_target: Option<&'a str>,
+    encrypted: Option<bool>,
+}
+
+impl<'a> FileRecord<'a> {
+    fn new(file: &'a File) -> Self {
+        let file_metadata = file.file_metadata();
+        let timestamp = |getter: fn(&FileMetadata) -> &DateTime<Utc>| {
+            file_metadata.map(|metadata| getter(metadata).to_rfc3339())
+        };
+
+        let file_type = if file.flags().is_directory() {
+            "directory"
+        } else if file.flags().is_symbolic_link() {
+            "symlink"
+        } else if file.flags().is_regular_file() {
+            "file"
+        } else {
+            "unknown"
+        };
+
+        Self {
+            id: file.id().value(),
+            domain: file.domain().value(),
+            relative_path: file.relative_path().value(),
+            file_type,
+            size: file.size(),
+            mode: file.mode(),
+            user_id: file_metadata.map(FileMetadata::user_id),
+            group_id: file_metadata.map(FileMetadata::group_id),
+            inode: file_metadata.map(FileMetadata::inode),
+            last_modified: timestamp(FileMetadata::last_modified),
+            last_status_change: timestamp(FileMetadata::last_status_change),
+            birth: timestamp(FileMetadata::birth),
+            protection_class: file.protection_class(),
+            symlink_target: file.symlink_target(),
+            encrypted: file_metadata.map(|metadata| metadata.encryption_key().is_some()),
+        }
+    }
+}
+
+/// Serialized form of backup metadata, holding the selected columns in order
+struct MetadataRecord<'a> {
+    metadata: &'a Metadata,
+    columns: &'a [MetadataColumn],
+}
+
+impl<'a> MetadataRecord<'a> {
+    const fn new(metadata: &'a Metadata, columns: &'a [MetadataColumn]) -> Self {
+        Self { metadata, columns }
+    }
+}
+
+impl Serialize for MetadataRecord<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.columns.len()))?;
+        for column in self.columns {
+            map.serialize_entry(column.key(), &column.value(self.metadata))?;
+        }
+        map.end()
+    }
+}
+
+/// Serialized form of an extraction result
+#[derive(Serialize)]
+struct ExtractResultRecord<'a> {
+    extracted_count: usize,
+    directory_count: usize,
+    symlink_count: usize,
+    skipped_count: usize,
+    error_count: usize,
+    errors: Vec<ExtractErrorRecord<'a>>,
+    files: Vec<FileOutcomeRecord<'a>>,
+}
+
+impl<'a> ExtractResultRecord<'a> {
+    fn new(result: &'a ExtractResult) -> Self {
+        Self {
+            extracted_count: result.extracted_count,
+            directory_count: result.directory_count,
+            symlink_count: result.symlink_count,
+            skipped_count: result.skipped_count,
+            error_count: result.errors.len(),
+            errors: result.errors.iter().map(ExtractErrorRecord::new).collect(),
+            files: result.files.iter().map(FileOutcomeRecord::new).collect(),
+        }
+    }
+}
+
+/// Serialized form of the outcome of a single file
+#[derive(Serialize)]
+struct FileOutcomeRecord<'a> {
+    file_id: &'a str,
+    relative_path: &'a str,
+    status: &'static str,
+    destination: Option<String>,
+}
+
+impl<'a> FileOutcomeRecord<'a> {
+    fn new(outcome: &'a FileOutcome) -> Self {
+        Self {
+            file_id: outcome.file_id.as_str(),
+            relative_path: outcome.relative_path.as_str(),
+            status: outcome.status.name(),
+            destination: outcome
+                .destination
+                .as_ref()
+                .map(|path| path.display().to_string()),
+        }
+    }
+}
+
+/// Serialized form of an extraction plan
+///
+/// The summary fields are ignored when the plan is loaded again.
+#[derive(Serialize)]
+struct ExtractPlanRecord<'a> {
+    total_bytes: u64,
+    domain_counts: BTreeMap<&'a str, usize>,
+    #[serde(flatten)]
+    plan: &'a ExtractPlan,
+}
+
+impl<'a> ExtractPlanRecord<'a> {
+    fn new(plan: &'a ExtractPlan) -> Self {
+        Self {
+            total_bytes: plan.total_bytes(),
+            domain_counts: plan.domain_counts(),
+            plan,
+        }
+    }
+}
+
+/// Serialized form of a planned or rejected file for tabular formats
+#[derive(Serialize)]
+struct PlanRowRecord<'a> {
+    action: &'static str,
+    file_id: &'a str,
+    domain: &'a str,
+    relative_path: &'a str,
+    size: Option<u64>,
+    destination: Option<String>,
+    reason: &'a str,
+}
+
+impl<'a> PlanRowRecord<'a> {
+    /// Creates one row per planned operation, followed by one row per rejected file
+    fn rows(plan: &'a ExtractPlan) -> impl Iterator<Item = Self> {
+        let operations = plan.operations.iter().map(|operation| Self {
+            action: operation.action.name(),
+            file_id: &operation.file_id,
+            domain: &operation.domain,
+            relative_path: &operation.relative_path,
+            size: Some(operation.size),
+            destination: Some(operation.destination.display().to_string()),
+            reason: "",
+        });
+        let rejected = plan.rejected.iter().map(|rejected| Self {
+            action: "reject",
+            file_id: &rejected.file_id,
+            domain: &rejected.domain,
+            relative_path: &rejected.relative_path,
+            size: None,
+            destination: None,
+            reason: &rejected.reason,
+        });
+        operations.chain(rejected)
+    }
+}
+
+/// Serialized form of a verification report
+#[derive(Serialize)]
+struct VerifyReportRecord<'a> {
+    healthy: bool,
+    file_count: usize,
+    checked_count: usize,
+    blob_count: usize,
+    missing_count: usize,
+    mismatched_count: usize,
+    unreadable_count: usize,
+    orphaned_count: usize,
+    issues: Vec<VerifyIssueRecord<'a>>,
+}
+
+impl<'a> VerifyReportRecord<'a> {
+    fn new(report: &'a VerifyReport) -> Self {
+        Self {
+            healthy: report.is_healthy(),
+            file_count: report.file_count,
+            checked_count: report.checked_count,
+            blob_count: report.blob_count,
+            missing_count: report.issue_count(IssueKind::Missing),
+            mismatched_count: report
+                .issue_count(IssueKind::SizeMismatch)
+                .saturating_add(report.issue_count(IssueKind::DigestMismatch)),
+            unreadable_count: report.issue_count(IssueKind::Unreadable),
+            orphaned_count: report.issue_count(IssueKind::Orphaned),
+            issues: report.issues.iter().map(VerifyIssueRecord::new).collect(),
+        }
+    }
+}
+
+/// Serialized form of an integrity problem
+#[derive(Serialize)]
+struct VerifyIssueRecord<'a> {
+    kind: &'static str,
+    file_id: &'a str,
+    domain: &'a str,
+    relative_path: &'a str,
+    path: Option<String>,
+    detail: &'a str,
+}
+
+impl<'a> VerifyIssueRecord<'a> {
+    fn new(issue: &'a VerifyIssue) -> Self {
+        Self {
+            kind: issue.kind.name(),
+            file_id: &issue.file_id,
+            domain: &issue.domain,
+            relative_path: &issue.relative_path,
+            path: issue.path.as_ref().map(|path| path.display().to_string()),
+            detail: &issue.detail,
+        }
+    }
+}
+
+/// Serialized form of the comparison of two backups
+#[derive(Serialize)]
+struct DiffReportRecord<'a> {
+    from_count: usize,
+    to_count: usize,
+    added_count: usize,
+    removed_count: usize,
+    modified_count: usize,
+    unchanged_count: usize,
+    changes: Vec<FileChangeRecord<'a>>,
+}
+
+impl<'a> DiffReportRecord<'a> {
+    fn new(report: &'a DiffReport) -> Self {
+        Self {
+            from_count: report.from_count,
+            to_count: report.to_count,
+            added_count: report.change_count(ChangeKind::Added),
+            removed_count: report.change_count(ChangeKind::Removed),
+            modified_count: report.change_count(ChangeKind::Modified),
+            unchanged_count: report.unchanged_count,
+            changes: report.changes.iter().map(FileChangeRecord::new).collect(),
+        }
+    }
+}
+
+/// Serialized form of a changed file
+///
+/// The changed attributes are joined with commas, so that tabular formats can hold them.
+#[derive(Serialize)]
+struct FileChangeRecord<'a> {
+    change: &'static str,
+    file_id: &'a str,
+    domain: &'a str,
+    relative_path: &'a str,
+    changed: String,
+    old_size: Option<u64>,
+    new_size: Option<u64>,
+    old_modified: Option<String>,
+    new_modified: Option<String>,
+}
+
+impl<'a> FileChangeRecord<'a> {
+    fn new(change: &'a FileChange) -> Self {
+        Self {
+            change: change.kind.name(),
+            file_id: &change.file_id,
+            domain: &change.domain,
+            relative_path: &change.relative_path,
+            changed: Self::fields(change).join(","),
+            old_size: change.old_size,
+            new_size: change.new_size,
+            old_modified: change.old_modified.map(|time| time.to_rfc3339()),
+            new_modified: change.new_modified.map(|time| time.to_rfc3339()),
+        }
+    }
+
+    /// Returns the names of the changed attributes
+    fn fields(change: &FileChange) -> Vec<&'static str> {
+        change.fields.iter().map(|field| field.name()).collect()
+    }
+}
+
+/// Serialized form of an extraction error
+#[derive(Serialize)]
+struct ExtractErrorRecord<'a> {
+    file_id: &'a str,
+    relative_path: &'a str,
+    kind: &'static str,
+    error: &'a str,
+}
+
+impl<'a> ExtractErrorRecord<'a> {
+    const fn new(error: &'a ExtractError) -> Self {
+        Self {
+            file_id: error.file_id.as_str(),
+            relative_path: error.relative_path.as_str(),
+            kind: match error.kind {
+                ExtractErrorKind::UnsafePath => "unsafe_path",
+                ExtractErrorKind::Failed => "failed",
+            },
+            error: error.error.as_str(),
+        }
+    }
+}
+
+/// Formats results as human-readable tables
+#[derive(Debug, Default)]
 #[non_exhaustive]
-pub struct DisplayService;
+pub struct TableFormatter;
 
-impl DisplayService {
-    /// Create a new display service instance
+impl TableFormatter {
+    /// Creates a new `TableFormatter`
     #[must_use]
     #[inline]
     pub const fn new() -> Self {
         Self
     }
+}
 
-    /// Display search results to stdout
+impl OutputFormatter for TableFormatter {
     #[inline]
-    pub fn display_search_results(&self, results: Vec<File>) {
-        if results.is_empty() {
-            println!("No files found matching the search criteria.");
-        } else {
-            println!("Found {} file(s):", results.len());
-            let table_rows: Vec<FileTableRow> = results
-                .into_iter()
-                .map(|file| FileTableRow {
-                    id: file.id().value().to_owned(),
-                    domain: file.domain().value().to_owned(),
-                    path: file.relative_path().value().to_owned(),
-                })
-                .collect();
-
-            let mut table = Table::new(table_rows);
-            table.with(Style::rounded());
-            println!("{table}");
+    fn format_files(&self, files: &[File]) -> Result<String> {
+        if files.is_empty() {
+            return Ok("No files found matching the search criteria.".to_owned());
         }
+
+        let mut output = format!("Found {} file(s):\n", files.len());
+        let table_rows: Vec<FileTableRow> = files
+            .iter()
+            .map(|file| FileTableRow {
+                id: file.id().value().to_owned(),
+                domain: file.domain().value().to_owned(),
+                path: file.relative_path().value().to_owned(),
+            })
+            .collect();
+
+        let mut table = Table::new(table_rows);
+        table.with(Style::rounded());
+        output.push_str(&table.to_string());
+        Ok(output)
     }
 
-    /// Display extract results to stdout
     #[inline]
-    pub fn display_extract_results(&self, result: &ExtractResult) {
-        println!("Extraction completed:");
+    fn format_metadata_list(&self, metadata_list: &[Metadata]) -> Result<String> {
+        self.format_metadata_columns(metadata_list, &MetadataColumn::DEFAULT)
+    }
 
-        // Display statistics table
-        let stats_rows = vec![
-            ExtractionStatsRow {
-                status: "Extracted".to_owned(),
-                count: result.extracted_count,
+    #[inline]
+    fn format_metadata_columns(
+        &self,
+        metadata_list: &[Metadata],
+        columns: &[MetadataColumn],
+    ) -> Result<String> {
+        if metadata_list.is_empty() {
+            return Ok("No backups found.".to_owned());
+        }
+
+        let mut builder = Builder::default();
+        builder.push_record(columns.iter().map(|column| column.header()));
+        for metadata in metadata_list {
+            builder.push_record(columns.iter().map(|column| match column {
+                // Tables show the date in its more readable display form
+                MetadataColumn::LastBackup => metadata.last_backup_date().to_string(),
+                _ => column.text(metadata),
+            }));
+        }
+
+        let table = builder.build().with(Style::rounded()).to_string();
+
+        Ok(format!(
+            "{table}\n\nFound {} backup(s)",
+            metadata_list.len()
+        ))
+    }
+
+    #[inline]
+    fn format_metadata(&self, metadata: &Metadata) -> Result<String> {
+        let rows: Vec<MetadataFieldRow> = MetadataColumn::ALL
+            .iter()
+            .map(|column| MetadataFieldRow {
+                field: column.header(),
+                value: column.text(metadata),
+            })
+            .collect();
+
+        Ok(Table::new(rows).with(Style::rounded()).to_string())
+    }
+
+    #[inline]
+    fn format_extract_result(&self, result: &ExtractResult) -> Result<String> {
+        let mut output = "Extraction completed:\n".to_owned();
+
+        // Format statistics table
+        let mut stats_rows = vec![
+            ExtractionStatsRow {
+                status: "Extracted".to_owned(),
+                count: result.extracted_count,
+            },
+            ExtractionStatsRow {
+                status: "Directories".to_owned(),
+                count: result.directory_count,
+            },
+            ExtractionStatsRow {
+                status: "Symlinks".to_owned(),
+                count: result.symlink_count,
             },
             ExtractionStatsRow {
                 status: "Skipped".to_owned(),
@@ -101,13 +1041,28 @@ impl DisplayService {
             },
         ];
 
+        // Conflicts with existing files are only listed when they happened
+        for (status, label) in [
+            (ExtractStatus::Overwritten, "Overwritten"),
+            (ExtractStatus::Renamed, "Renamed"),
+            (ExtractStatus::Kept, "Kept existing"),
+        ] {
+            let count = result.status_count(status);
+            if count > 0 {
+                stats_rows.push(ExtractionStatsRow {
+                    status: label.to_owned(),
+                    count,
+                });
+            }
+        }
+
         let mut stats_table = Table::new(stats_rows);
         stats_table.with(Style::rounded());
-        println!("{stats_table}");
+        output.push_str(&stats_table.to_string());
 
-        // Display errors table if there are any errors
+        // Format errors table if there are any errors
         if !result.errors.is_empty() {
-            println!("\nError details:");
+            output.push_str("\n\nError details:\n");
             let error_rows: Vec<ExtractionErrorRow> = result
                 .errors
                 .iter()
@@ -119,113 +1074,803 @@ impl DisplayService {
 
             let mut error_table = Table::new(error_rows);
             error_table.with(Style::rounded());
-            println!("{error_table}");
+            output.push_str(&error_table.to_string());
+        }
+
+        Ok(output)
+    }
+
+    #[inline]
+    fn format_extract_plan(&self, plan: &ExtractPlan) -> Result<String> {
+        if plan.operations.is_empty() && plan.rejected.is_empty() {
+            return Ok("No files found matching the search criteria.".to_owned());
+        }
+
+        let mut output = format!("Extraction plan for {}:\n", plan.output_dir.display());
+        if !plan.operations.is_empty() {
+            let operation_rows: Vec<PlannedOperationRow> = plan
+                .operations
+                .iter()
+                .map(|operation| PlannedOperationRow {
+                    action: operation.action.name(),
+                    domain: operation.domain.clone(),
+                    path: operation.relative_path.clone(),
+                    destination: plan
+                        .output_dir
+                        .join(&operation.destination)
+                        .display()
+                        .to_string(),
+                })
+                .collect();
+            let mut operation_table = Table::new(operation_rows);
+            operation_table.with(Style::rounded());
+            output.push_str(&operation_table.to_string());
+            output.push_str("\n\n");
+        }
+
+        let mut stats_rows: Vec<ExtractionStatsRow> = [
+            PlannedAction::Create,
+            PlannedAction::Overwrite,
+            PlannedAction::Rename,
+            PlannedAction::Keep,
+        ]
+        .into_iter()
+        .map(|action| ExtractionStatsRow {
+            status: action.name().to_owned(),
+            count: plan.action_count(action),
+        })
+        .collect();
+        stats_rows.push(ExtractionStatsRow {
+            status: "reject".to_owned(),
+            count: plan.rejected.len(),
+        });
+        let mut stats_table = Table::new(stats_rows);
+        stats_table.with(Style::rounded());
+        output.push_str("Summary:\n");
+        output.push_str(&stats_table.to_string());
+        write!(output, "\nTotal size: {} bytes", plan.total_bytes())?;
+
+        let domain_counts = plan.domain_counts();
+        if !domain_counts.is_empty() {
+            let domain_rows: Vec<DomainCountRow> = domain_counts
+                .into_iter()
+                .map(|(domain, count)| DomainCountRow {
+                    domain: domain.to_owned(),
+                    count,
+                })
+                .collect();
+            let mut domain_table = Table::new(domain_rows);
+            domain_table.with(Style::rounded());
+            output.push_str("\n\nFiles by domain:\n");
+            output.push_str(&domain_table.to_string());
+        }
+
+        if !plan.rejected.is_empty() {
+            let rejected_rows: Vec<RejectedFileRow> = plan
+                .rejected
+                .iter()
+                .map(|rejected| RejectedFileRow {
+                    domain: rejected.domain.clone(),
+                    path: rejected.relative_path.clone(),
+                    reason: rejected.reason.clone(),
+                })
+                .collect();
+            let mut rejected_table = Table::new(rejected_rows);
+            rejected_table.with(Style::rounded());
+            output.push_str("\n\nRejected paths:\n");
+            output.push_str(&rejected_table.to_string());
+        }
+
+        Ok(output)
+    }
+
+    #[inline]
+    fn format_verify_report(&self, report: &VerifyReport) -> Result<String> {
+        let mut output = if report.blob_count > 0 {
+            format!(
+                "Verified {} file(s) and {} blob(s):\n",
+                report.checked_count, report.blob_count
+            )
+        } else {
+            format!("Verified {} file(s):\n", report.checked_count)
+        };
+
+        let stats_rows: Vec<ExtractionStatsRow> = [
+            (IssueKind::Missing, "Missing"),
+            (IssueKind::SizeMismatch, "Size mismatch"),
+            (IssueKind::DigestMismatch, "Digest mismatch"),
+            (IssueKind::Unreadable, "Unreadable"),
+            (IssueKind::Orphaned, "Orphaned"),
+        ]
+        .into_iter()
+        .map(|(kind, label)| ExtractionStatsRow {
+            status: label.to_owned(),
+            count: report.issue_count(kind),
+        })
+        .collect();
+        let mut stats_table = Table::new(stats_rows);
+        stats_table.with(Style::rounded());
+        output.push_str(&stats_table.to_string());
+
+        if report.is_healthy() {
+            output.push_str("\n\nAll files match the manifest.");
+            return Ok(output);
+        }
+
+        let issue_rows: Vec<VerifyIssueRow> = report
+            .issues
+            .iter()
+            .map(|issue| VerifyIssueRow {
+                kind: issue.kind.name(),
+                file_id: issue.file_id.clone(),
+                path: if issue.relative_path.is_empty() {
+                    String::new()
+                } else {
+                    format!("{}/{}", issue.domain, issue.relative_path)
+                },
+                detail: issue.detail.clone(),
+            })
+            .collect();
+        let mut issue_table = Table::new(issue_rows);
+        issue_table.with(Style::rounded());
+        output.push_str("\n\nProblems:\n");
+        output.push_str(&issue_table.to_string());
+
+        Ok(output)
+    }
+
+    #[inline]
+    fn format_diff_report(&self, report: &DiffReport) -> Result<String> {
+        let mut output = format!(
+            "Compared {} file(s) with {} file(s):\n",
+            report.from_count, report.to_count
+        );
+
+        let mut stats_rows: Vec<ExtractionStatsRow> = [
+            (ChangeKind::Added, "Added"),
+            (ChangeKind::Removed, "Removed"),
+            (ChangeKind::Modified, "Modified"),
+        ]
+        .into_iter()
+        .map(|(kind, label)| ExtractionStatsRow {
+            status: label.to_owned(),
+            count: report.change_count(kind),
+        })
+        .collect();
+        stats_rows.push(ExtractionStatsRow {
+            status: "Unchanged".to_owned(),
+            count: report.unchanged_count,
+        });
+        let mut stats_table = Table::new(stats_rows);
+        stats_table.with(Style::rounded());
+        output.push_str(&stats_table.to_string());
+
+        if report.changes.is_empty() {
+            output.push_str("\n\nNo changes.");
+            return Ok(output);
+        }
+
+        let mut change_table = Table::new(report.changes.iter().map(FileChangeRow::new));
+        change_table.with(Style::rounded());
+        output.push_str("\n\nChanges:\n");
+        output.push_str(&change_table.to_string());
+
+        Ok(output)
+    }
+
+    #[inline]
+    fn format_apps(&self, apps: &[AppSummary]) -> Result<String> {
+        Ok(listing_table(apps))
+    }
+
+    #[inline]
+    fn format_messages_export(&self, conversations: &[ExportedConversation]) -> Result<String> {
+        Ok(listing_table(conversations))
+    }
+
+    #[inline]
+    fn format_contacts_export(&self, contacts: &[ExportedContact]) -> Result<String> {
+        Ok(listing_table(contacts))
+    }
+
+    #[inline]
+    fn format_calls(&self, calls: &[Call]) -> Result<String> {
+        Ok(listing_table(calls))
+    }
+
+    #[inline]
+    fn format_voicemails(&self, voicemails: &[VoicemailSummary]) -> Result<String> {
+        Ok(listing_table(voicemails))
+    }
+
+    #[inline]
+    fn format_photos_export(&self, photos: &[ExportedPhoto]) -> Result<String> {
+        Ok(listing_table(photos))
+    }
+
+    #[inline]
+    fn format_safari_export(&self, files: &[ExportedSafariFile]) -> Result<String> {
+        Ok(listing_table(files))
+    }
+}
+
+/// Formats results as a single pretty-printed JSON document
+#[derive(Debug, Default)]
+#[non_exhaustive]
+pub struct JsonFormatter;
+
+impl JsonFormatter {
+    /// Creates a new `JsonFormatter`
+    #[must_use]
+    #[inline]
+    pub const fn new() -> Self {
+        Self
+    }
+}
+
+impl OutputFormatter for JsonFormatter {
+    #[inline]
+    fn format_files(&self, files: &[File]) -> Result<String> {
+        let records: Vec<_> = files.iter().map(FileRecord::new).collect();
+        Ok(serde_json::to_string_pretty(&records)?)
+    }
+
+    #[inline]
+    fn format_metadata_list(&self, metadata_list: &[Metadata]) -> Result<String> {
+        self.format_metadata_columns(metadata_list, &MetadataColumn::DEFAULT)
+    }
+
+    #[inline]
+    fn format_metadata_columns(
+        &self,
+        metadata_list: &[Metadata],
+        columns: &[MetadataColumn],
+    ) -> Result<String> {
+        let records: Vec<_> = metadata_list
+            .iter()
+            .map(|metadata| MetadataRecord::new(metadata, columns))
+            .collect();
+        Ok(serde_json::to_string_pretty(&records)?)
+    }
+
+    #[inline]
+    fn format_metadata(&self, metadata: &Metadata) -> Result<String> {
+        Ok(serde_json::to_string_pretty(&MetadataRecord::new(
+            metadata,
+            &MetadataColumn::ALL,
+        ))?)
+    }
+
+    #[inline]
+    fn format_extract_result(&self, result: &ExtractResult) -> Result<String> {
+        Ok(serde_json::to_string_pretty(&ExtractResultRecord::new(
+            result,
+        ))?)
+    }
+
+    #[inline]
+    fn format_extract_plan(&self, plan: &ExtractPlan) -> Result<String> {
+        Ok(serde_json::to_string_pretty(&ExtractPlanRecord::new(plan))?)
+    }
+
+    #[inline]
+    fn format_verify_report(&self, report: &VerifyReport) -> Result<String> {
+        Ok(serde_json::to_string_pretty(&VerifyReportRecord::new(
+            report,
+        ))?)
+    }
+
+    #[inline]
+    fn format_diff_report(&self, report: &DiffReport) -> Result<String> {
+        Ok(serde_json::to_string_pretty(&DiffReportRecord::new(
+            report,
+        ))?)
+    }
+}
+
+/// Formats results as newline-delimited JSON, one object per line
+#[derive(Debug, Default)]
+#[non_exhaustive]
+pub struct NdjsonFormatter;
+
+impl NdjsonFormatter {
+    /// Creates a new `NdjsonFormatter`
+    #[must_use]
+    #[inline]
+    pub const fn new() -> Self {
+        Self
+    }
+
+    fn to_lines<T: Serialize>(records: impl IntoIterator<Item = T>) -> Result<String> {
+        let lines = records
+            .into_iter()
+            .map(|record| serde_json::to_string(&record))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(lines.join("\n"))
+    }
+}
+
+impl OutputFormatter for NdjsonFormatter {
+    #[inline]
+    fn format_records(&self, records: &[Value]) -> Result<String> {
+        Self::to_lines(records)
+    }
+
+    #[inline]
+    fn format_files(&self, files: &[File]) -> Result<String> {
+        Self::to_lines(files.iter().map(FileRecord::new))
+    }
+
+    #[inline]
+    fn format_metadata_list(&self, metadata_list: &[Metadata]) -> Result<String> {
+        self.format_metadata_columns(metadata_list, &MetadataColumn::DEFAULT)
+    }
+
+    #[inline]
+    fn format_metadata_columns(
+        &self,
+        metadata_list: &[Metadata],
+        columns: &[MetadataColumn],
+    ) -> Result<String> {
+        Self::to_lines(
+            metadata_list
+                .iter()
+                .map(|metadata| MetadataRecord::new(metadata, columns)),
+        )
+    }
+
+    #[inline]
+    fn format_metadata(&self, metadata: &Metadata) -> Result<String> {
+        Self::to_lines([MetadataRecord::new(metadata, &MetadataColumn::ALL)])
+    }
+
+    #[inline]
+    fn format_extract_result(&self, result: &ExtractResult) -> Result<String> {
+        Self::to_lines([ExtractResultRecord::new(result)])
+    }
+
+    #[inline]
+    fn format_extract_plan(&self, plan: &ExtractPlan) -> Result<String> {
+        Self::to_lines([ExtractPlanRecord::new(plan)])
+    }
+
+    #[inline]
+    fn format_verify_report(&self, report: &VerifyReport) -> Result<String> {
+        Self::to_lines([VerifyReportRecord::new(report)])
+    }
+
+    #[inline]
+    fn format_diff_report(&self, report: &DiffReport) -> Result<String> {
+        Self::to_lines([DiffReportRecord::new(report)])
+    }
+}
+
+/// Formats results as delimiter-separated values with a header row
+///
+/// Tabular formats cannot hold nested data, so extraction results are written as
+/// one row per failed file, verification reports as one row per problem and backup
+/// comparisons as one row per changed file.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct DelimitedFormatter {
+    /// Field delimiter
+    delimiter: u8,
+}
+
+impl DelimitedFormatter {
+    /// Creates a formatter for comma-separated values
+    #[must_use]
+    #[inline]
+    pub const fn csv() -> Self {
+        Self { delimiter: b',' }
+    }
+
+    /// Creates a formatter for tab-separated values
+    #[must_use]
+    #[inline]
+    pub const fn tsv() -> Self {
+        Self { delimiter: b'\t' }
+    }
+
+    fn to_rows<T: Serialize>(&self, records: impl IntoIterator<Item = T>) -> Result<String> {
+        let mut writer = csv::WriterBuilder::new()
+            .delimiter(self.delimiter)
+            .from_writer(Vec::new());
+        for record in records {
+            writer.serialize(record)?;
+        }
+
+        let output = String::from_utf8(writer.into_inner()?)?;
+        Ok(output.strip_suffix('\n').unwrap_or(&output).to_owned())
+    }
+
+    /// Writes rows whose columns are only known at runtime
+    ///
+    /// The header is written even without rows, unlike serialized records.
+    fn to_table<R, F>(&self, header: R, rows: impl IntoIterator<Item = F>) -> Result<String>
+    where
+        R: IntoIterator<Item: AsRef<[u8]>>,
+        F: IntoIterator<Item: AsRef<[u8]>>,
+    {
+        let mut writer = csv::WriterBuilder::new()
+            .delimiter(self.delimiter)
+            .from_writer(Vec::new());
+        writer.write_record(header)?;
+        for row in rows {
+            writer.write_record(row)?;
+        }
+
+        let output = String::from_utf8(writer.into_inner()?)?;
+        Ok(output.strip_suffix('\n').unwrap_or(&output).to_owned())
+    }
+}
+
+impl OutputFormatter for DelimitedFormatter {
+    #[inline]
+    fn format_records(&self, records: &[Value]) -> Result<String> {
+        // Columns are taken from the first record, as with serialized structs
+        let Some(Value::Object(first)) = records.first() else {
+            return Ok(String::new());
+        };
+        self.to_table(
+            first.keys(),
+            records.iter().map(|record| {
+                first
+                    .keys()
+                    .map(|key| record.get(key).map_or_else(String::new, value_text))
+            }),
+        )
+    }
+
+    #[inline]
+    fn format_files(&self, files: &[File]) -> Result<String> {
+        self.to_rows(files.iter().map(FileRecord::new))
+    }
+
+    #[inline]
+    fn format_metadata_list(&self, metadata_list: &[Metadata]) -> Result<String> {
+        self.format_metadata_columns(metadata_list, &MetadataColumn::DEFAULT)
+    }
+
+    #[inline]
+    fn format_metadata_columns(
+        &self,
+        metadata_list: &[Metadata],
+        columns: &[MetadataColumn],
+    ) -> Result<String> {
+        self.to_table(
+            columns.iter().map(|column| column.key()),
+            metadata_list
+                .iter()
+                .map(|metadata| columns.iter().map(|column| column.text(metadata))),
+        )
+    }
+
+    #[inline]
+    fn format_extract_result(&self, result: &ExtractResult) -> Result<String> {
+        self.to_rows(result.errors.iter().map(ExtractErrorRecord::new))
+    }
+}
+
+/// Service for handling result display operations
+#[non_exhaustive]
+pub struct DisplayService {
+    /// Formatter used for all output
+    formatter: Box<dyn OutputFormatter>,
+}
+
+impl DisplayService {
+    /// Create a new display service instance that prints tables
+    #[must_use]
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            formatter: Box::new(TableFormatter::new()),
         }
     }
 
-    /// Display backup list in a formatted table
-    /// Note: This method is deprecated since `BackupEntry` is no longer used
+    /// Sets the formatter used for all output
+    #[must_use]
+    #[inline]
+    pub fn with_formatter(mut self, formatter: Box<dyn OutputFormatter>) -> Self {
+        self.formatter = formatter;
+        self
+    }
+
+    /// Display search results to stdout
+    ///
+    /// # Errors
+    /// Returns an error if the results cannot be formatted
+    #[inline]
+    pub fn display_search_results(&self, results: &[File]) -> Result<()> {
+        Self::print(&self.format_search_results(results)?);
+        Ok(())
+    }
+
+    /// Display extract results to stdout
+    ///
+    /// # Errors
+    /// Returns an error if the result cannot be formatted
+    #[inline]
+    pub fn display_extract_results(&self, result: &ExtractResult) -> Result<()> {
+        Self::print(&self.format_extract_results(result)?);
+        Ok(())
+    }
+
+    /// Display an extraction plan to stdout
+    ///
+    /// # Errors
+    /// Returns an error if the plan cannot be formatted
+    #[inline]
+    pub fn display_extract_plan(&self, plan: &ExtractPlan) -> Result<()> {
+        Self::print(&self.format_extract_plan(plan)?);
+        Ok(())
+    }
+
+    /// Display a verification report to stdout
+    ///
+    /// # Errors
+    /// Returns an error if the report cannot be formatted
+    #[inline]
+    pub fn display_verify_report(&self, report: &VerifyReport) -> Result<()> {
+        Self::print(&self.format_verify_report(report)?);
+        Ok(())
+    }
+
+    /// Display the comparison of two backups to stdout
+    ///
+    /// # Errors
+    /// Returns an error if the report cannot be formatted
+    #[inline]
+    pub fn display_diff_report(&self, report: &DiffReport) -> Result<()> {
+        Self::print(&self.format_diff_report(report)?);
+        Ok(())
+    }
+
+    /// Display the applications of a backup to stdout
+    ///
+    /// # Errors
+    /// Returns an error if the applications cannot be formatted
+    #[inline]
+    pub fn display_apps(&self, apps: &[AppSummary]) -> Result<()> {
+        Self::print(&self.format_apps(apps)?);
+        Ok(())
+    }
+
+    /// Display the conversations written by a messages export to stdout
+    ///
+    /// # Errors
+    /// Returns an error if the conversations cannot be formatted
+    #[inline]
+    pub fn display_messages_export(&self, conversations: &[ExportedConversation]) -> Result<()> {
+        Self::print(&self.format_messages_export(conversations)?);
+        Ok(())
+    }
+
+    /// Display the contacts written by a contacts export to stdout
+    ///
+    /// # Errors
+    /// Returns an error if the contacts cannot be formatted
+    #[inline]
+    pub fn display_contacts_export(&self, contacts: &[ExportedContact]) -> Result<()> {
+        Self::print(&self.format_contacts_export(contacts)?);
+        Ok(())
+    }
+
+    /// Display the call history to stdout
+    ///
+    /// # Errors
+    /// Returns an error if the calls cannot be formatted
+    #[inline]
+    pub fn display_calls(&self, calls: &[Call]) -> Result<()> {
+        Self::print(&self.format_calls(calls)?);
+        Ok(())
+    }
+
+    /// Display voicemail messages to stdout
+    ///
+    /// # Errors
+    /// Returns an error if the voicemails cannot be formatted
+    #[inline]
+    pub fn display_voicemails(&self, voicemails: &[VoicemailSummary]) -> Result<()> {
+        Self::print(&self.format_voicemails(voicemails)?);
+        Ok(())
+    }
+
+    /// Display the photos and videos written by a photos export to stdout
+    ///
+    /// # Errors
+    /// Returns an error if the photos cannot be formatted
+    #[inline]
+    pub fn display_photos_export(&self, photos: &[ExportedPhoto]) -> Result<()> {
+        Self::print(&self.format_photos_export(photos)?);
+        Ok(())
+    }
+
+    /// Display the files written by a Safari export to stdout
+    ///
+    /// # Errors
+    /// Returns an error if the files cannot be formatted
+    #[inline]
+    pub fn display_safari_export(&self, files: &[ExportedSafariFile]) -> Result<()> {
+        Self::print(&self.format_safari_export(files)?);
+        Ok(())
+    }
+
+    /// Display backup list in a formatted table
+    /// Note: This method is deprecated since `BackupEntry` is no longer used
+    ///
+    /// # Arguments
+    /// * `backups` - List of metadata to display
+    ///
+    /// # Errors
+    /// Returns an error if the list cannot be formatted
+    #[inline]
+    pub fn display_backup_list(&self, backups: &[crate::Metadata]) -> Result<()> {
+        // Simply delegate to display_metadata_list since they do the same thing now
+        self.display_metadata_list(backups, &MetadataColumn::DEFAULT)
+    }
+
+    /// Displays a list of metadata (without directory paths) with the given columns
+    ///
+    /// # Errors
+    /// Returns an error if the list cannot be formatted
+    #[inline]
+    pub fn display_metadata_list(
+        &self,
+        metadata_list: &[Metadata],
+        columns: &[MetadataColumn],
+    ) -> Result<()> {
+        Self::print(&self.format_metadata_list(metadata_list, columns)?);
+        Ok(())
+    }
+
+    /// Displays every detail of a single backup
+    ///
+    /// # Errors
+    /// Returns an error if the metadata cannot be formatted
+    #[inline]
+    pub fn display_metadata(&self, metadata: &Metadata) -> Result<()> {
+        Self::print(&self.format_metadata(metadata)?);
+        Ok(())
+    }
+
+    /// Format search results as a string (useful for testing)
+    ///
+    /// # Errors
+    /// Returns an error if the results cannot be formatted
+    #[inline]
+    pub fn format_search_results(&self, results: &[File]) -> Result<String> {
+        self.formatter.format_files(results)
+    }
+
+    /// Format extract results as a string (useful for testing)
+    ///
+    /// # Errors
+    /// Returns an error if the result cannot be formatted
+    #[inline]
+    pub fn format_extract_results(&self, result: &ExtractResult) -> Result<String> {
+        self.formatter.format_extract_result(result)
+    }
+
+    /// Format an extraction plan as a string (useful for testing)
+    ///
+    /// # Errors
+    /// Returns an error if the plan cannot be formatted
+    #[inline]
+    pub fn format_extract_plan(&self, plan: &ExtractPlan) -> Result<String> {
+        self.formatter.format_extract_plan(plan)
+    }
+
+    /// Format a verification report as a string (useful for testing)
+    ///
+    /// # Errors
+    /// Returns an error if the report cannot be formatted
+    #[inline]
+    pub fn format_verify_report(&self, report: &VerifyReport) -> Result<String> {
+        self.formatter.format_verify_report(report)
+    }
+
+    /// Format a list of metadata as a string (useful for testing)
+    ///
+    /// # Errors
+    /// Returns an error if the list cannot be formatted
+    #[inline]
+    pub fn format_metadata_list(
+        &self,
+        metadata_list: &[Metadata],
+        columns: &[MetadataColumn],
+    ) -> Result<String> {
+        self.formatter
+            .format_metadata_columns(metadata_list, columns)
+    }
+
+    /// Format every detail of a single backup as a string (useful for testing)
+    ///
+    /// # Errors
+    /// Returns an error if the metadata cannot be formatted
+    #[inline]
+    pub fn format_metadata(&self, metadata: &Metadata) -> Result<String> {
+        self.formatter.format_metadata(metadata)
+    }
+
+    /// Format the comparison of two backups as a string (useful for testing)
+    ///
+    /// # Errors
+    /// Returns an error if the report cannot be formatted
+    #[inline]
+    pub fn format_diff_report(&self, report: &DiffReport) -> Result<String> {
+        self.formatter.format_diff_report(report)
+    }
+
+    /// Format the applications of a backup as a string (useful for testing)
+    ///
+    /// # Errors
+    /// Returns an error if the applications cannot be formatted
+    #[inline]
+    pub fn format_apps(&self, apps: &[AppSummary]) -> Result<String> {
+        self.formatter.format_apps(apps)
+    }
+
+    /// Format the conversations written by a messages export as a string (useful for testing)
+    ///
+    /// # Errors
+    /// Returns an error if the conversations cannot be formatted
+    #[inline]
+    pub fn format_messages_export(&self, conversations: &[ExportedConversation]) -> Result<String> {
+        self.formatter.format_messages_export(conversations)
+    }
+
+    /// Format the contacts written by a contacts export as a string (useful for testing)
+    ///
+    /// # Errors
+    /// Returns an error if the contacts cannot be formatted
+    #[inline]
+    pub fn format_contacts_export(&self, contacts: &[ExportedContact]) -> Result<String> {
+        self.formatter.format_contacts_export(contacts)
+    }
+
+    /// Format the call history as a string (useful for testing)
     ///
-    /// # Arguments
-    /// * `backups` - List of metadata to display
+    /// # Errors
+    /// Returns an error if the calls cannot be formatted
     #[inline]
-    pub fn display_backup_list(&self, backups: &[crate::Metadata]) {
-        // Simply delegate to display_metadata_list since they do the same thing now
-        self.display_metadata_list(backups);
+    pub fn format_calls(&self, calls: &[Call]) -> Result<String> {
+        self.formatter.format_calls(calls)
     }
 
-    /// Displays a list of metadata in a formatted table (without directory paths)
+    /// Format voicemail messages as a string (useful for testing)
+    ///
+    /// # Errors
+    /// Returns an error if the voicemails cannot be formatted
     #[inline]
-    pub fn display_metadata_list(&self, metadata_list: &[Metadata]) {
-        if metadata_list.is_empty() {
-            println!("No backups found.");
-            return;
-        }
-
-        let rows: Vec<MetadataTableRow> = metadata_list
-            .iter()
-            .map(|metadata| MetadataTableRow {
-                id: metadata.id().to_string(),
-                device_name: metadata.device_name().to_owned(),
-                product_name: metadata.product_name().to_owned(),
-                last_backup_date: metadata.last_backup_date().to_string(),
-            })
-            .collect();
-
-        let table = Table::new(rows).with(Style::rounded()).to_string();
-
-        println!("{table}");
-        println!("\nFound {} backup(s)", metadata_list.len());
+    pub fn format_voicemails(&self, voicemails: &[VoicemailSummary]) -> Result<String> {
+        self.formatter.format_voicemails(voicemails)
     }
 
-    /// Format search results as a string (useful for testing)
-    #[must_use]
+    /// Format the photos and videos written by a photos export as a string (useful for testing)
+    ///
+    /// # Errors
+    /// Returns an error if the photos cannot be formatted
     #[inline]
-    pub fn format_search_results(&self, results: Vec<File>) -> String {
-        if results.is_empty() {
-            "No files found matching the search criteria.".to_owned()
-        } else {
-            let mut output = format!("Found {} file(s):\n", results.len());
-            let table_rows: Vec<FileTableRow> = results
-                .into_iter()
-                .map(|file| FileTableRow {
-                    id: file.id().value().to_owned(),
-                    domain: file.domain().value().to_owned(),
-                    path: file.relative_path().value().to_owned(),
-                })
-                .collect();
-
-            let mut table = Table::new(table_rows);
-            table.with(Style::rounded());
-            output.push_str(&table.to_string());
-            output
-        }
+    pub fn format_photos_export(&self, photos: &[ExportedPhoto]) -> Result<String> {
+        self.formatter.format_photos_export(photos)
     }
 
-    /// Format extract results as a string (useful for testing)
-    #[must_use]
+    /// Format the files written by a Safari export as a string (useful for testing)
+    ///
+    /// # Errors
+    /// Returns an error if the files cannot be formatted
     #[inline]
-    pub fn format_extract_results(&self, result: &ExtractResult) -> String {
-        let mut output = "Extraction completed:\n".to_owned();
-
-        // Format statistics table
-        let stats_rows = vec![
-            ExtractionStatsRow {
-                status: "Extracted".to_owned(),
-                count: result.extracted_count,
-            },
-            ExtractionStatsRow {
-                status: "Skipped".to_owned(),
-                count: result.skipped_count,
-            },
-            ExtractionStatsRow {
-                status: "Errors".to_owned(),
-                count: result.errors.len(),
-            },
-        ];
-
-        let mut stats_table = Table::new(stats_rows);
-        stats_table.with(Style::rounded());
-        output.push_str(&stats_table.to_string());
-
-        // Format errors table if there are any errors
-        if !result.errors.is_empty() {
-            output.push_str("\nError details:\n");
-            let error_rows: Vec<ExtractionErrorRow> = result
-                .errors
-                .iter()
-                .map(|error| ExtractionErrorRow {
-                    path: error.relative_path.clone(),
-                    error: error.error.clone(),
-                })
-                .collect();
+    pub fn format_safari_export(&self, files: &[ExportedSafariFile]) -> Result<String> {
+        self.formatter.format_safari_export(files)
+    }
 
-            let mut error_table = Table::new(error_rows);
-            error_table.with(Style::rounded());
-            output.push_str(&error_table.to_string());
+    /// Prints formatted output, skipping empty output (e.g. NDJSON without records)
+    fn print(output: &str) {
+        if !output.is_empty() {
+            println!("{output}");
         }
-
-        output
     }
 }
 
@@ -238,10 +1883,18 @@ impl Default for DisplayService {
 
 #[cfg(test)]
 mod tests {
-    use anyhow::Result;
+    use anyhow::{Context as _, Result};
 
     use super::*;
-    use crate::{Domain, ExtractError, FileFlags, FileId, RelativePath};
+    use crate::application::diff_service::ChangedField;
+    use crate::application::extract_plan::{PlannedOperation, RejectedFile};
+    use crate::application::{ConflictPolicy, OutputLayout, SafariContent};
+    use crate::domain::value_objects::MetadataId;
+    use crate::{
+        DeviceDetails, Domain, ExtractError, FileFlags, FileId, ManifestDetails, RelativePath,
+        UnsafePathError,
+    };
+    use std::path::PathBuf;
 
     fn create_test_file(id: &str, domain: &str, path: &str) -> Result<File> {
         // Create a valid 40-character SHA1 hash by padding the id
@@ -256,12 +1909,13 @@ mod tests {
     }
 
     #[test]
-    fn test_format_search_results_empty() {
+    fn test_format_search_results_empty() -> Result<()> {
         let service = DisplayService::new();
         let results = vec![];
 
-        let output = service.format_search_results(results);
+        let output = service.format_search_results(&results)?;
         assert_eq!(output, "No files found matching the search criteria.");
+        Ok(())
     }
 
     #[test]
@@ -270,7 +1924,7 @@ mod tests {
         let file = create_test_file("123", "com.apple.test", "Documents/test.txt")?;
         let results = vec![file];
 
-        let output = service.format_search_results(results);
+        let output = service.format_search_results(&results)?;
         // Test that the output contains the expected data in table format
         assert!(output.contains("Found 1 file(s):"));
         assert!(output.contains("1230000000000000000000000000000000000000"));
@@ -289,7 +1943,7 @@ mod tests {
         let file2 = create_test_file("456", "com.apple.photos", "Library/photo.jpg")?;
         let results = vec![file1, file2];
 
-        let output = service.format_search_results(results);
+        let output = service.format_search_results(&results)?;
         // Test that the output contains the expected data in table format
         assert!(output.contains("Found 2 file(s):"));
         assert!(output.contains("1230000000000000000000000000000000000000"));
@@ -302,15 +1956,18 @@ mod tests {
     }
 
     #[test]
-    fn test_format_extract_results_success_only() {
+    fn test_format_extract_results_success_only() -> Result<()> {
         let service = DisplayService::new();
         let result = ExtractResult {
             extracted_count: 5,
+            directory_count: 0,
+            symlink_count: 0,
             skipped_count: 2,
             errors: vec![],
+            files: vec![],
         };
 
-        let output = service.format_extract_results(&result);
+        let output = service.format_extract_results(&result)?;
         // Test that the output contains the expected data in table format
         assert!(output.contains("Extraction completed:"));
         assert!(output.contains("Extracted"));
@@ -319,38 +1976,587 @@ mod tests {
         assert!(output.contains('2'));
         assert!(output.contains("Errors"));
         assert!(output.contains('0'));
+        Ok(())
     }
 
     #[test]
-    fn test_format_extract_results_with_errors() {
+    fn test_format_extract_results_with_errors() -> Result<()> {
         let service = DisplayService::new();
-        let result = ExtractResult {
+        let result = create_extract_result_with_errors();
+
+        let output = service.format_extract_results(&result)?;
+        // Test that the output contains the expected data in table format
+        assert!(output.contains("Extraction completed:"));
+        assert!(output.contains('3'));
+        assert!(output.contains('1'));
+        assert!(output.contains('2'));
+        assert!(output.contains("Directories"));
+        assert!(output.contains('4'));
+        assert!(output.contains("Symlinks"));
+        assert!(output.contains('6'));
+        assert!(output.contains("Error details:"));
+        assert!(output.contains("Documents/test.txt"));
+        assert!(output.contains("Permission denied"));
+        assert!(output.contains("Photos/image.jpg"));
+        assert!(output.contains("Disk full"));
+        assert!(output.contains("Renamed"));
+        assert!(!output.contains("Overwritten"));
+        Ok(())
+    }
+
+    fn create_extract_result_with_errors() -> ExtractResult {
+        ExtractResult {
             extracted_count: 3,
+            directory_count: 4,
+            symlink_count: 6,
             skipped_count: 1,
             errors: vec![
                 ExtractError {
                     file_id: "abc123".to_owned(),
                     relative_path: "Documents/test.txt".to_owned(),
+                    kind: ExtractErrorKind::Failed,
                     error: "Permission denied".to_owned(),
                 },
                 ExtractError {
                     file_id: "def456".to_owned(),
                     relative_path: "Photos/image.jpg".to_owned(),
+                    kind: ExtractErrorKind::Failed,
                     error: "Disk full".to_owned(),
                 },
             ],
-        };
+            files: vec![
+                FileOutcome {
+                    file_id: "abc123".to_owned(),
+                    domain: "HomeDomain".to_owned(),
+                    relative_path: "Documents/test.txt".to_owned(),
+                    destination: None,
+                    status: ExtractStatus::Failed,
+                    hashes: None,
+                },
+                FileOutcome {
+                    file_id: "ghi789".to_owned(),
+                    domain: "HomeDomain".to_owned(),
+                    relative_path: "Documents/notes.txt".to_owned(),
+                    destination: Some("out/Documents/notes (1).txt".into()),
+                    status: ExtractStatus::Renamed,
+                    hashes: None,
+                },
+            ],
+        }
+    }
 
-        let output = service.format_extract_results(&result);
-        // Test that the output contains the expected data in table format
-        assert!(output.contains("Extraction completed:"));
-        assert!(output.contains('3'));
-        assert!(output.contains('1'));
-        assert!(output.contains('2'));
-        assert!(output.contains("Error details:"));
-        assert!(output.contains("Documents/test.txt"));
-        assert!(output.contains("Permission denied"));
-        assert!(output.contains("Photos/image.jpg"));
-        assert!(output.contains("Disk full"));
+    fn create_file_with_metadata() -> Result<File> {
+        let modified = DateTime::from_timestamp(1_700_000_000, 0)
+            .ok_or_else(|| anyhow::anyhow!("invalid timestamp"))?;
+        let file_metadata = FileMetadata::new(1024, 0o100_644)
+            .with_owner(501, 501)
+            .with_timestamps(modified, modified, modified)
+            .with_protection_class(3);
+
+        Ok(File::new(
+            FileId::new("3d0d7e5fb2ce288813306e4d4636395e047a3d28")?,
+            Domain::new("HomeDomain".to_owned())?,
+            RelativePath::new("Library/SMS/sms.db".to_owned())?,
+            FileFlags::REGULAR_FILE,
+            vec![],
+        )
+        .with_file_metadata(file_metadata))
+    }
+
+    #[test]
+    fn test_json_format_files() -> Result<()> {
+        let service = DisplayService::new().with_formatter(OutputFormat::Json.formatter());
+        let files = vec![
+            create_file_with_metadata()?,
+            create_test_file("123", "com.apple.test", "Documents/test.txt")?,
+        ];
+
+        let output = service.format_search_results(&files)?;
+        let value: serde_json::Value = serde_json::from_str(&output)?;
+
+        assert_eq!(value[0]["id"], "3d0d7e5fb2ce288813306e4d4636395e047a3d28");
+        assert_eq!(value[0]["domain"], "HomeDomain");
+        assert_eq!(value[0]["relative_path"], "Library/SMS/sms.db");
+        assert_eq!(value[0]["file_type"], "file");
+        assert_eq!(value[0]["size"], 1024);
+        assert_eq!(value[0]["mode"], 0o100_644);
+        assert_eq!(value[0]["last_modified"], "2023-11-14T22:13:20+00:00");
+        assert_eq!(value[0]["protection_class"], 3);
+        assert_eq!(value[0]["encrypted"], false);
+        assert_eq!(value[1]["file_type"], "unknown");
+        assert!(value[1]["size"].is_null());
+        Ok(())
+    }
+
+    #[test]
+    fn test_json_format_extract_result() -> Result<()> {
+        let formatter = JsonFormatter::new();
+
+        let output = formatter.format_extract_result(&create_extract_result_with_errors())?;
+        let value: serde_json::Value = serde_json::from_str(&output)?;
+
+        assert_eq!(value["extracted_count"], 3);
+        assert_eq!(value["directory_count"], 4);
+        assert_eq!(value["symlink_count"], 6);
+        assert_eq!(value["skipped_count"], 1);
+        assert_eq!(value["error_count"], 2);
+        assert_eq!(value["errors"][1]["file_id"], "def456");
+        assert_eq!(value["errors"][1]["relative_path"], "Photos/image.jpg");
+        assert_eq!(value["errors"][1]["kind"], "failed");
+        assert_eq!(value["errors"][1]["error"], "Disk full");
+        assert_eq!(value["files"][0]["status"], "failed");
+        assert_eq!(value["files"][0]["destination"], serde_json::Value::Null);
+        assert_eq!(value["files"][1]["status"], "renamed");
+        assert_eq!(
+            value["files"][1]["destination"],
+            "out/Documents/notes (1).txt"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_ndjson_format_files() -> Result<()> {
+        let formatter = NdjsonFormatter::new();
+        let files = vec![
+            create_test_file("123", "com.apple.test", "Documents/test1.txt")?,
+            create_test_file("456", "com.apple.photos", "Library/photo.jpg")?,
+        ];
+
+        let output = formatter.format_files(&files)?;
+        let lines: Vec<&str> = output.lines().collect();
+
+        assert_eq!(lines.len(), 2);
+        let value: serde_json::Value = serde_json::from_str(lines[1])?;
+        assert_eq!(value["relative_path"], "Library/photo.jpg");
+        assert_eq!(formatter.format_files(&[])?, "");
+        Ok(())
+    }
+
+    #[test]
+    fn test_csv_format_files() -> Result<()> {
+        let formatter = DelimitedFormatter::csv();
+
+        let output = formatter.format_files(&[create_file_with_metadata()?])?;
+        let lines: Vec<&str> = output.lines().collect();
+
+        assert_eq!(
+            lines,
+            vec![
+                "id,domain,relative_path,file_type,size,mode,user_id,group_id,inode,last_modified,last_status_change,birth,protection_class,symlink_target,encrypted",
+                "3d0d7e5fb2ce288813306e4d4636395e047a3d28,HomeDomain,Library/SMS/sms.db,file,1024,33188,501,501,0,2023-11-14T22:13:20+00:00,2023-11-14T22:13:20+00:00,2023-11-14T22:13:20+00:00,3,,false",
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_tsv_format_extract_result() -> Result<()> {
+        let formatter = DelimitedFormatter::tsv();
+
+        let output = formatter.format_extract_result(&create_extract_result_with_errors())?;
+
+        assert_eq!(
+            output,
+            "file_id\trelative_path\tkind\terror\nabc123\tDocuments/test.txt\tfailed\tPermission denied\ndef456\tPhotos/image.jpg\tfailed\tDisk full"
+        );
+        Ok(())
+    }
+
+    fn create_extract_plan() -> Result<ExtractPlan> {
+        let file = create_test_file("123", "HomeDomain", "Library/SMS/sms.db")?
+            .with_file_metadata(FileMetadata::new(2048, 0o100_644));
+        let escaping = create_test_file("456", "HomeDomain", "../escape.txt")?;
+
+        let mut plan = ExtractPlan::new(
+            PathBuf::from("out"),
+            OutputLayout::Merged,
+            ConflictPolicy::Rename,
+        );
+        plan.operations = vec![PlannedOperation::new(
+            &file,
+            PathBuf::from("Library/SMS/sms (1).db"),
+            PlannedAction::Rename,
+        )];
+        plan.rejected = vec![RejectedFile::new(
+            &escaping,
+            &UnsafePathError::ParentDirectory.into(),
+        )];
+        Ok(plan)
+    }
+
+    #[test]
+    fn test_format_extract_plan() -> Result<()> {
+        let service = DisplayService::new();
+
+        let output = service.format_extract_plan(&create_extract_plan()?)?;
+
+        assert!(output.contains("Extraction plan for out:"));
+        assert!(output.contains("out/Library/SMS/sms (1).db"));
+        assert!(output.contains("Total size: 2048 bytes"));
+        assert!(output.contains("Files by domain:"));
+        assert!(output.contains("Rejected paths:"));
+        assert!(output.contains("../escape.txt"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_json_format_extract_plan() -> Result<()> {
+        let formatter = JsonFormatter::new();
+        let plan = create_extract_plan()?;
+
+        let output = formatter.format_extract_plan(&plan)?;
+        let value: serde_json::Value = serde_json::from_str(&output)?;
+
+        assert_eq!(value["total_bytes"], 2048);
+        assert_eq!(value["domain_counts"]["HomeDomain"], 1);
+        assert_eq!(value["operations"][0]["action"], "rename");
+        assert_eq!(value["rejected"][0]["kind"], "unsafe_path");
+        // The output can be executed as a plan
+        assert_eq!(serde_json::from_str::<ExtractPlan>(&output)?, plan);
+        Ok(())
+    }
+
+    #[test]
+    fn test_csv_format_extract_plan() -> Result<()> {
+        let formatter = DelimitedFormatter::csv();
+
+        let output = formatter.format_extract_plan(&create_extract_plan()?)?;
+
+        assert_eq!(
+            output,
+            "action,file_id,domain,relative_path,size,destination,reason\n\
+             rename,1230000000000000000000000000000000000000,HomeDomain,Library/SMS/sms.db,2048,Library/SMS/sms (1).db,\n\
+             reject,4560000000000000000000000000000000000000,HomeDomain,../escape.txt,,,Unsafe path: contains a '..' component"
+        );
+        Ok(())
+    }
+
+    fn create_verify_report() -> VerifyReport {
+        VerifyReport {
+            file_count: 3,
+            checked_count: 2,
+            blob_count: 2,
+            issues: vec![
+                VerifyIssue {
+                    kind: IssueKind::SizeMismatch,
+                    file_id: "abc123".to_owned(),
+                    domain: "HomeDomain".to_owned(),
+                    relative_path: "Library/SMS/sms.db".to_owned(),
+                    path: Some(PathBuf::from("backup/ab/abc123")),
+                    detail: "Expected 7 bytes, found 4".to_owned(),
+                },
+                VerifyIssue {
+                    kind: IssueKind::Orphaned,
+                    file_id: "def456".to_owned(),
+                    domain: String::new(),
+                    relative_path: String::new(),
+                    path: Some(PathBuf::from("backup/de/def456")),
+                    detail: "Blob has no row in the manifest".to_owned(),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_format_verify_report() -> Result<()> {
+        let service = DisplayService::new();
+
+        let output = service.format_verify_report(&create_verify_report())?;
+        assert!(output.contains("Verified 2 file(s) and 2 blob(s):"));
+        assert!(output.contains("Size mismatch"));
+        assert!(output.contains("HomeDomain/Library/SMS/sms.db"));
+        assert!(output.contains("Blob has no row in the manifest"));
+
+        let healthy = service.format_verify_report(&VerifyReport::default())?;
+        assert!(healthy.contains("All files match the manifest."));
+        Ok(())
+    }
+
+    #[test]
+    fn test_json_format_verify_report() -> Result<()> {
+        let formatter = JsonFormatter::new();
+
+        let output = formatter.format_verify_report(&create_verify_report())?;
+        let value: serde_json::Value = serde_json::from_str(&output)?;
+
+        assert_eq!(value["healthy"], false);
+        assert_eq!(value["mismatched_count"], 1);
+        assert_eq!(value["orphaned_count"], 1);
+        assert_eq!(value["issues"][0]["kind"], "size_mismatch");
+        assert_eq!(value["issues"][1]["path"], "backup/de/def456");
+        Ok(())
+    }
+
+    #[test]
+    fn test_csv_format_verify_report() -> Result<()> {
+        let formatter = DelimitedFormatter::csv();
+
+        let output = formatter.format_verify_report(&create_verify_report())?;
+
+        assert_eq!(
+            output,
+            "kind,file_id,domain,relative_path,path,detail\nsize_mismatch,abc123,HomeDomain,Library/SMS/sms.db,backup/ab/abc123,\"Expected 7 bytes, found 4\"\norphaned,def456,,,backup/de/def456,Blob has no row in the manifest"
+        );
+        Ok(())
+    }
+
+    fn create_diff_report() -> Result<DiffReport> {
+        let modified = DateTime::from_timestamp(1_700_000_000, 0).context("invalid time")?;
+        Ok(DiffReport {
+            from_count: 2,
+            to_count: 2,
+            unchanged_count: 0,
+            changes: vec![
+                FileChange {
+                    kind: ChangeKind::Added,
+                    file_id: "abc123".to_owned(),
+                    domain: "CameraRollDomain".to_owned(),
+                    relative_path: "Media/DCIM/100APPLE/IMG_0001.HEIC".to_owned(),
+                    fields: Vec::new(),
+                    old_size: None,
+                    new_size: Some(2048),
+                    old_modified: None,
+                    new_modified: Some(modified),
+                },
+                FileChange {
+                    kind: ChangeKind::Modified,
+                    file_id: "def456".to_owned(),
+                    domain: "HomeDomain".to_owned(),
+                    relative_path: "Library/SMS/sms.db".to_owned(),
+                    fields: vec![ChangedField::Size, ChangedField::Modified],
+                    old_size: Some(100),
+                    new_size: Some(200),
+                    old_modified: None,
+                    new_modified: Some(modified),
+                },
+            ],
+        })
+    }
+
+    #[test]
+    fn test_format_diff_report() -> Result<()> {
+        let service = DisplayService::new();
+
+        let output = service.format_diff_report(&create_diff_report()?)?;
+        assert!(output.contains("Compared 2 file(s) with 2 file(s):"));
+        assert!(output.contains("Media/DCIM/100APPLE/IMG_0001.HEIC"));
+        assert!(output.contains("100 → 200"));
+        assert!(output.contains("size, modified"));
+
+        let unchanged = service.format_diff_report(&DiffReport::default())?;
+        assert!(unchanged.contains("No changes."));
+        Ok(())
+    }
+
+    #[test]
+    fn test_json_format_diff_report() -> Result<()> {
+        let formatter = JsonFormatter::new();
+
+        let output = formatter.format_diff_report(&create_diff_report()?)?;
+        let value: serde_json::Value = serde_json::from_str(&output)?;
+
+        assert_eq!(value["added_count"], 1);
+        assert_eq!(value["modified_count"], 1);
+        assert_eq!(value["changes"][0]["change"], "added");
+        assert_eq!(value["changes"][1]["changed"], "size,modified");
+        assert_eq!(
+            value["changes"][1]["new_modified"],
+            "2023-11-14T22:13:20+00:00"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_csv_format_diff_report() -> Result<()> {
+        let formatter = DelimitedFormatter::csv();
+
+        let output = formatter.format_diff_report(&create_diff_report()?)?;
+
+        assert_eq!(
+            output,
+            "change,file_id,domain,relative_path,changed,old_size,new_size,old_modified,new_modified\n\
+             added,abc123,CameraRollDomain,Media/DCIM/100APPLE/IMG_0001.HEIC,,,2048,,2023-11-14T22:13:20+00:00\n\
+             modified,def456,HomeDomain,Library/SMS/sms.db,\"size,modified\",100,200,,2023-11-14T22:13:20+00:00"
+        );
+        Ok(())
+    }
+
+    fn create_metadata() -> Result<Metadata> {
+        Ok(Metadata::new(
+            MetadataId::new("00008030-001a2b3c4d5e6f70")?,
+            "iPhone 15 Pro".to_owned(),
+            "iPhone16,1".to_owned(),
+            "2024-01-15T10:30:00Z".parse()?,
+        )
+        .with_device(DeviceDetails {
+            product_version: Some("17.2.1".to_owned()),
+            ..DeviceDetails::default()
+        })
+        .with_manifest(ManifestDetails {
+            is_encrypted: Some(true),
+            ..ManifestDetails::default()
+        }))
+    }
+
+    #[test]
+    fn test_format_metadata_list_columns() -> Result<()> {
+        let formatter = TableFormatter::new();
+
+        let output = formatter.format_metadata_columns(
+            &[create_metadata()?],
+            &[
+                MetadataColumn::Device,
+                MetadataColumn::ProductVersion,
+                MetadataColumn::Encrypted,
+                MetadataColumn::SerialNumber,
+            ],
+        )?;
+
+        assert!(output.contains("Device"));
+        assert!(output.contains("iOS"));
+        assert!(output.contains("17.2.1"));
+        assert!(output.contains("true"));
+        assert!(output.contains("Serial Number"));
+        assert!(!output.contains("iPhone16,1"));
+        assert!(output.ends_with("Found 1 backup(s)"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_json_format_metadata_list_default_columns() -> Result<()> {
+        let formatter = JsonFormatter::new();
+
+        let output = formatter.format_metadata_list(&[create_metadata()?])?;
+        let value: serde_json::Value = serde_json::from_str(&output)?;
+
+        assert_eq!(
+            value,
+            serde_json::json!([{
+                "id": "00008030-001a2b3c4d5e6f70",
+                "device_name": "iPhone 15 Pro",
+                "product_name": "iPhone16,1",
+                "last_backup_date": "2024-01-15T10:30:00+00:00",
+            }])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_json_format_metadata() -> Result<()> {
+        let formatter = JsonFormatter::new();
+
+        let output = formatter.format_metadata(&create_metadata()?)?;
+        let value: serde_json::Value = serde_json::from_str(&output)?;
+
+        assert_eq!(
+            value.as_object().map(serde_json::Map::len),
+            Some(MetadataColumn::ALL.len())
+        );
+        assert_eq!(value["product_version"], "17.2.1");
+        assert_eq!(value["is_encrypted"], true);
+        assert_eq!(value["serial_number"], serde_json::Value::Null);
+        Ok(())
+    }
+
+    #[test]
+    fn test_csv_format_metadata_list_columns() -> Result<()> {
+        let formatter = DelimitedFormatter::csv();
+
+        let output = formatter.format_metadata_columns(
+            &[create_metadata()?],
+            &[
+                MetadataColumn::Product,
+                MetadataColumn::Encrypted,
+                MetadataColumn::Imei,
+            ],
+        )?;
+
+        assert_eq!(
+            output,
+            "product_name,is_encrypted,imei\n\"iPhone16,1\",true,"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_custom_formatter() -> Result<()> {
+        struct CountFormatter;
+
+        impl OutputFormatter for CountFormatter {
+            fn format_files(&self, files: &[File]) -> Result<String> {
+                Ok(files.len().to_string())
+            }
+
+            fn format_metadata_list(&self, metadata_list: &[Metadata]) -> Result<String> {
+                Ok(metadata_list.len().to_string())
+            }
+
+            fn format_extract_result(&self, result: &ExtractResult) -> Result<String> {
+                Ok(result.extracted_count.to_string())
+            }
+        }
+
+        let service = DisplayService::new().with_formatter(Box::new(CountFormatter));
+        let files = vec![create_test_file(
+            "123",
+            "com.apple.test",
+            "Documents/test.txt",
+        )?];
+
+        assert_eq!(service.format_search_results(&files)?, "1");
+        Ok(())
+    }
+
+    #[test]
+    fn test_custom_formatter_records() -> Result<()> {
+        // Writes the keys of each record, relying on the defaults for everything else
+        struct KeysFormatter;
+
+        impl OutputFormatter for KeysFormatter {
+            fn format_files(&self, files: &[File]) -> Result<String> {
+                Ok(files.len().to_string())
+            }
+
+            fn format_metadata_list(&self, metadata_list: &[Metadata]) -> Result<String> {
+                Ok(metadata_list.len().to_string())
+            }
+
+            fn format_extract_result(&self, result: &ExtractResult) -> Result<String> {
+                Ok(result.extracted_count.to_string())
+            }
+
+            fn format_records(&self, records: &[Value]) -> Result<String> {
+                let lines: Vec<_> = records
+                    .iter()
+                    .filter_map(Value::as_object)
+                    .map(|record| record.keys().cloned().collect::<Vec<_>>().join(","))
+                    .collect();
+                Ok(lines.join("\n"))
+            }
+        }
+
+        let service = DisplayService::new().with_formatter(Box::new(KeysFormatter));
+        let metadata_list = [create_metadata()?];
+
+        assert_eq!(
+            service.format_metadata_list(&metadata_list, &MetadataColumn::DEFAULT)?,
+            "1"
+        );
+        assert_eq!(
+            service.format_metadata_list(
+                &metadata_list,
+                &[MetadataColumn::Id, MetadataColumn::Imei]
+            )?,
+            "id,imei"
+        );
+        assert_eq!(
+            service.format_safari_export(&[ExportedSafariFile {
+                content: SafariContent::NetscapeBookmarks,
+                count: 12,
+                path: PathBuf::from("safari/bookmarks-netscape.html"),
+            }])?,
+            "content,count,path"
+        );
+        Ok(())
     }
 }
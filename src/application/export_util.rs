@@ -0,0 +1,105 @@
+//! Helpers shared by the exporters of app data, such as messages and contacts
+
+use std::collections::HashSet;
+
+/// Turns an identifier into a file name, or `None` if nothing usable is left
+///
+/// Characters that are unsafe or awkward in file names are replaced with `_`.
+pub fn file_name(name: &str) -> Option<String> {
+    let sanitized: String = name
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || matches!(c, '+' | '-' | '_' | '.' | '@' | ' ') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let sanitized = sanitized.trim().trim_start_matches('.');
+    (!sanitized.is_empty()).then(|| sanitized.to_owned())
+}
+
+/// Hands out file names that are unique within an export
+///
+/// Names are compared case-insensitively, as on the default file systems of macOS and
+/// Windows.
+#[derive(Debug, Default)]
+pub struct UniqueNames {
+    /// Case-folded names handed out so far
+    used: HashSet<String>,
+}
+
+impl UniqueNames {
+    /// Returns the name, suffixed with the row ID of the item if it was already taken
+    ///
+    /// If the suffixed name is taken too, a counter is appended until the name is free.
+    pub fn claim(&mut self, name: String, id: i64) -> String {
+        if self.used.insert(name.to_lowercase()) {
+            return name;
+        }
+        let suffixed = format!("{name}-{id}");
+        let mut candidate = suffixed.clone();
+        let mut counter: u32 = 1;
+        while !self.used.insert(candidate.to_lowercase()) {
+            counter = counter.saturating_add(1);
+            candidate = format!("{suffixed}-{counter}");
+        }
+        candidate
+    }
+}
+
+/// Escapes text for use in HTML content and attribute values
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_name() {
+        assert_eq!(file_name("+1 (555) 0001"), Some("+1 _555_ 0001".to_owned()));
+        assert_eq!(file_name("../chat"), Some("_chat".to_owned()));
+        assert_eq!(file_name(".."), None);
+    }
+
+    #[test]
+    fn test_unique_names() {
+        let mut names = UniqueNames::default();
+
+        assert_eq!(names.claim("Jane".to_owned(), 1), "Jane");
+        assert_eq!(names.claim("Jane".to_owned(), 2), "Jane-2");
+    }
+
+    #[test]
+    fn test_unique_names_suffix_taken() {
+        let mut names = UniqueNames::default();
+
+        assert_eq!(names.claim("Bob-7".to_owned(), 3), "Bob-7");
+        assert_eq!(names.claim("Bob".to_owned(), 5), "Bob");
+        assert_eq!(names.claim("Bob".to_owned(), 7), "Bob-7-2");
+        assert_eq!(names.claim("Bob".to_owned(), 7), "Bob-7-3");
+    }
+
+    #[test]
+    fn test_unique_names_case_insensitive() {
+        let mut names = UniqueNames::default();
+
+        assert_eq!(names.claim("Bob".to_owned(), 1), "Bob");
+        assert_eq!(names.claim("bob".to_owned(), 2), "bob-2");
+        assert_eq!(names.claim("BOB-2".to_owned(), 3), "BOB-2-3");
+    }
+}
@@ -0,0 +1,375 @@
+//! Extraction plans for previewing and reviewing extractions
+
+use crate::application::extract_service::{ConflictPolicy, ExtractErrorKind, OutputLayout};
+use crate::application::search_service::FileType;
+use crate::domain::entities::File;
+use crate::domain::value_objects::UnsafePathError;
+use anyhow::{Context as _, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// Planned operations of an extraction
+///
+/// A plan is made by `ExtractService::plan` without writing anything, and can be saved as
+/// JSON, reviewed and executed later with `ExtractService::execute`. Destinations are
+/// relative to `output_dir`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct ExtractPlan {
+    /// Output directory for extracted files
+    pub output_dir: PathBuf,
+    /// Directory layout the destinations were mapped with
+    pub layout: OutputLayout,
+    /// Policy conflicts with existing files were resolved with
+    pub on_conflict: ConflictPolicy,
+    /// Files to extract, in the order of the search results
+    pub operations: Vec<PlannedOperation>,
+    /// Files that would be refused
+    pub rejected: Vec<RejectedFile>,
+}
+
+impl ExtractPlan {
+    /// Creates an empty plan
+    #[must_use]
+    #[inline]
+    pub const fn new(
+        output_dir: PathBuf,
+        layout: OutputLayout,
+        on_conflict: ConflictPolicy,
+    ) -> Self {
+        Self {
+            output_dir,
+            layout,
+            on_conflict,
+            operations: Vec::new(),
+            rejected: Vec::new(),
+        }
+    }
+
+    /// Reads a plan saved as JSON
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or is not a valid plan
+    #[inline]
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let json = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read plan: {}", path.display()))?;
+        serde_json::from_str(&json)
+            .with_context(|| format!("Failed to parse plan: {}", path.display()))
+    }
+
+    /// Returns the total size of the regular files that will be written
+    #[must_use]
+    #[inline]
+    pub fn total_bytes(&self) -> u64 {
+        self.operations.iter().fold(0, |total, operation| {
+            total.saturating_add(operation.written_bytes())
+        })
+    }
+
+    /// Returns the number of files that will be written, by domain
+    #[must_use]
+    #[inline]
+    pub fn domain_counts(&self) -> BTreeMap<&str, usize> {
+        let mut counts = BTreeMap::new();
+        for operation in self
+            .operations
+            .iter()
+            .filter(|operation| operation.action.writes())
+        {
+            let count: &mut usize = counts.entry(operation.domain.as_str()).or_default();
+            *count = count.saturating_add(1);
+        }
+        counts
+    }
+
+    /// Returns the number of operations with the given action
+    #[must_use]
+    #[inline]
+    pub fn action_count(&self, action: PlannedAction) -> usize {
+        self.operations
+            .iter()
+            .filter(|operation| operation.action == action)
+            .count()
+    }
+}
+
+/// What an extraction will do with a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum PlannedAction {
+    /// Write to a free destination, or merge into an existing directory
+    Create,
+    /// Replace an existing file
+    Overwrite,
+    /// Write to a free name because the destination already exists
+    Rename,
+    /// Keep the existing file
+    Keep,
+}
+
+impl PlannedAction {
+    /// Returns the name used in output
+    #[must_use]
+    #[inline]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Create => "create",
+            Self::Overwrite => "overwrite",
+            Self::Rename => "rename",
+            Self::Keep => "keep",
+        }
+    }
+
+    /// Checks if the action writes the file
+    #[must_use]
+    #[inline]
+    pub const fn writes(self) -> bool {
+        !matches!(self, Self::Keep)
+    }
+}
+
+/// A single planned file operation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct PlannedOperation {
+    /// File ID in the backup
+    pub file_id: String,
+    /// Domain of the file
+    pub domain: String,
+    /// Relative path of the file
+    pub relative_path: String,
+    /// Type of the file
+    pub file_type: FileType,
+    /// File size in bytes, or 0 if unknown
+    pub size: u64,
+    /// Destination relative to the output directory
+    pub destination: PathBuf,
+    /// What will be done with the file
+    pub action: PlannedAction,
+}
+
+impl PlannedOperation {
+    /// Creates the operation for a file
+    pub(crate) fn new(file: &File, destination: PathBuf, action: PlannedAction) -> Self {
+        Self {
+            file_id: file.id().to_string(),
+            domain: file.domain().to_string(),
+            relative_path: file.relative_path().to_string(),
+            file_type: FileType::of(file),
+            size: file.size().unwrap_or_default(),
+            destination,
+            action,
+        }
+    }
+
+    /// Returns the number of bytes the operation writes, or 0 unless it writes a regular file
+    pub(crate) const fn written_bytes(&self) -> u64 {
+        if self.action.writes() && matches!(self.file_type, FileType::File) {
+            self.size
+        } else {
+            0
+        }
+    }
+
+    /// Checks that the operation was planned for the given file and returns its destination
+    ///
+    /// Plans may have been edited since they were made, so the destination is checked to stay
+    /// within the output directory as well.
+    pub(crate) fn checked_destination(&self, file: &File) -> Result<&Path> {
+        if file.domain().value() != self.domain
+            || file.relative_path().value() != self.relative_path
+            || FileType::of(file) != self.file_type
+        {
+            anyhow::bail!(
+                "Plan does not match the backup: {} is {}/{} in the backup",
+                self.file_id,
+                file.domain(),
+                file.relative_path()
+            );
+        }
+
+        for component in self.destination.components() {
+            match component {
+                Component::Normal(_) => {}
+                Component::ParentDir => return Err(UnsafePathError::ParentDirectory.into()),
+                Component::CurDir | Component::RootDir | Component::Prefix(_) => {
+                    anyhow::bail!(
+                        "Destination must be relative: {}",
+                        self.destination.display()
+                    )
+                }
+            }
+        }
+
+        Ok(&self.destination)
+    }
+}
+
+/// A file that would be refused by an extraction
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct RejectedFile {
+    /// File ID in the backup
+    pub file_id: String,
+    /// Domain of the file
+    pub domain: String,
+    /// Relative path of the file
+    pub relative_path: String,
+    /// Kind of the rejection
+    pub kind: ExtractErrorKind,
+    /// Reason of the rejection
+    pub reason: String,
+}
+
+impl RejectedFile {
+    /// Creates the rejection of a file
+    pub(crate) fn new(file: &File, error: &anyhow::Error) -> Self {
+        Self {
+            file_id: file.id().to_string(),
+            domain: file.domain().to_string(),
+            relative_path: file.relative_path().to_string(),
+            kind: ExtractErrorKind::of(error),
+            reason: error.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::value_objects::{Domain, FileFlags, FileId, FileMetadata, RelativePath};
+    use assert_fs::TempDir;
+    use assert_fs::prelude::*;
+    use pretty_assertions::assert_eq;
+
+    fn create_test_file(id: &str, domain: &str, path: &str, size: u64) -> Result<File> {
+        Ok(File::new(
+            FileId::new(id)?,
+            Domain::new(domain.to_owned())?,
+            RelativePath::new(path.to_owned())?,
+            FileFlags::REGULAR_FILE,
+            vec![],
+        )
+        .with_file_metadata(FileMetadata::new(size, 0o100_644)))
+    }
+
+    fn create_test_plan() -> Result<ExtractPlan> {
+        let home = create_test_file(
+            "3d0d7e5fb2ce288813306e4d4636395e047a3d28",
+            "HomeDomain",
+            "Library/SMS/sms.db",
+            1024,
+        )?;
+        let app = create_test_file(
+            "da39a3ee5e6b4b0d3255bfef95601890afd80709",
+            "AppDomain-net.whatsapp.WhatsApp",
+            "Documents/a.sqlite",
+            512,
+        )?;
+        let kept = create_test_file(
+            "356a192b7913b04c54574d18c28d46e6395428ab",
+            "HomeDomain",
+            "Library/Notes/notes.sqlite",
+            2048,
+        )?;
+
+        let mut plan = ExtractPlan::new(
+            PathBuf::from("out"),
+            OutputLayout::Merged,
+            ConflictPolicy::Skip,
+        );
+        plan.operations = vec![
+            PlannedOperation::new(
+                &home,
+                home.relative_path().to_path()?,
+                PlannedAction::Create,
+            ),
+            PlannedOperation::new(&app, app.relative_path().to_path()?, PlannedAction::Rename),
+            PlannedOperation::new(&kept, kept.relative_path().to_path()?, PlannedAction::Keep),
+        ];
+        plan.rejected = vec![RejectedFile::new(
+            &home,
+            &UnsafePathError::ParentDirectory.into(),
+        )];
+        Ok(plan)
+    }
+
+    #[test]
+    fn test_extract_plan_summary() -> Result<()> {
+        let plan = create_test_plan()?;
+
+        assert_eq!(plan.total_bytes(), 1536);
+        assert_eq!(
+            plan.domain_counts(),
+            BTreeMap::from([("AppDomain-net.whatsapp.WhatsApp", 1), ("HomeDomain", 1)])
+        );
+        assert_eq!(plan.action_count(PlannedAction::Keep), 1);
+        assert_eq!(plan.rejected[0].kind, ExtractErrorKind::UnsafePath);
+        Ok(())
+    }
+
+    #[test]
+    fn test_extract_plan_round_trip() -> Result<()> {
+        let plan = create_test_plan()?;
+        let temp_dir = TempDir::new()?;
+        let plan_file = temp_dir.child("plan.json");
+        plan_file.write_str(&serde_json::to_string_pretty(&plan)?)?;
+
+        assert_eq!(ExtractPlan::load(plan_file.path())?, plan);
+
+        let value = serde_json::to_value(&plan)?;
+        assert_eq!(value["layout"], "merged");
+        assert_eq!(value["on_conflict"], "skip");
+        assert_eq!(value["operations"][1]["action"], "rename");
+        assert_eq!(value["operations"][1]["file_type"], "file");
+        assert_eq!(value["rejected"][0]["kind"], "unsafe_path");
+        Ok(())
+    }
+
+    #[test]
+    fn test_extract_plan_load_invalid() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let plan_file = temp_dir.child("plan.json");
+        plan_file.write_str("{\"operations\": []}")?;
+
+        let result = ExtractPlan::load(plan_file.path());
+
+        assert!(result.is_err());
+        if let Err(error) = result {
+            assert!(error.to_string().contains("Failed to parse plan"));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_planned_operation_checked_destination() -> Result<()> {
+        let plan = create_test_plan()?;
+        let home = create_test_file(
+            "3d0d7e5fb2ce288813306e4d4636395e047a3d28",
+            "HomeDomain",
+            "Library/SMS/sms.db",
+            1024,
+        )?;
+        let mut operation = plan.operations[0].clone();
+
+        assert_eq!(
+            operation.checked_destination(&home)?,
+            Path::new("Library/SMS/sms.db")
+        );
+
+        operation.destination = PathBuf::from("../escape");
+        assert!(operation.checked_destination(&home).is_err());
+        operation.destination = PathBuf::from("/etc/passwd");
+        assert!(operation.checked_destination(&home).is_err());
+
+        // Operations must match the file in the backup
+        assert!(plan.operations[1].checked_destination(&home).is_err());
+        Ok(())
+    }
+}
@@ -1,130 +1,1015 @@
 //! Extract service for copying files from iPhone backups
 
-use crate::SearchParams;
+use crate::application::extract_plan::{
+    ExtractPlan, PlannedAction, PlannedOperation, RejectedFile,
+};
+use crate::application::hash_manifest::{FileHashes, HashAlgorithm, HashingWriter};
+use crate::application::search_service::{FileType, SearchParams};
 use crate::domain::entities::File;
-use crate::domain::repositories::FileRepository;
+use crate::domain::queries::{BasicQuery, FileQuery};
+use crate::domain::repositories::{BlobRepository, FileRepository};
+use crate::domain::value_objects::{FileId, FileMetadata, UnsafePathError};
 use anyhow::{Context as _, Result};
-use std::fs;
-use std::path::Path;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs::{self, FileTimes};
+use std::io;
+use std::num::NonZeroUsize;
+use std::ops::Range;
+#[cfg(target_os = "macos")]
+use std::os::macos::fs::FileTimesExt as _;
+#[cfg(unix)]
+use std::os::unix::fs::{PermissionsExt as _, chown, lchown, symlink};
+use std::panic::resume_unwind;
+use std::path::{Component, Path, PathBuf, absolute};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread;
+use std::time::SystemTime;
+use tokio::task;
+
+/// Number of file IDs looked up per query when executing a plan
+///
+/// Older versions of `SQLite` accept at most 999 bound parameters per statement.
+const FILE_ID_CHUNK_SIZE: usize = 900;
+
+/// Directory layout of extracted files
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub enum OutputLayout {
+    /// `<output>/<path>`; files of different domains with the same path collide
+    #[default]
+    Merged,
+    /// `<output>/<domain>/<path>`
+    ByDomain,
+    /// Mirrors the device file system, e.g. `<output>/private/var/mobile/<path>` for
+    /// `HomeDomain`
+    Device,
+    /// `<output>/<file ID>`
+    FlatId,
+}
+
+impl OutputLayout {
+    /// Returns the destination of a file relative to the output directory
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the domain or path of the file could lead outside the output
+    /// directory
+    #[inline]
+    pub fn destination(self, file: &File) -> Result<PathBuf, UnsafePathError> {
+        match self {
+            Self::Merged => file.relative_path().to_path(),
+            Self::ByDomain => Ok(file
+                .domain()
+                .to_path()?
+                .join(file.relative_path().to_path()?)),
+            Self::Device => Ok(file
+                .domain()
+                .device_path()?
+                .join(file.relative_path().to_path()?)),
+            Self::FlatId => Ok(PathBuf::from(file.id().value())),
+        }
+    }
+}
+
+/// What to do when the destination of a file already exists
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub enum ConflictPolicy {
+    /// Replace the existing file
+    #[default]
+    Overwrite,
+    /// Keep the existing file
+    Skip,
+    /// Write to a free name such as `photo (1).jpg`
+    Rename,
+    /// Report the file as an error
+    Fail,
+    /// Replace the existing file only if the file in the backup was modified later
+    Newer,
+}
+
+impl ConflictPolicy {
+    /// Decides what to do with a file whose destination may already exist
+    ///
+    /// Returns the action and the destination, which differs from `dest_path` when the file
+    /// is renamed. Files without a modification time are never considered newer.
+    fn resolve(
+        self,
+        file: &File,
+        dest_path: PathBuf,
+        destinations: &Destinations,
+    ) -> Result<(PlannedAction, PathBuf)> {
+        let Some(existing) = destinations.existing(&dest_path)? else {
+            return Ok((PlannedAction::Create, dest_path));
+        };
+
+        match self {
+            Self::Overwrite => Ok((PlannedAction::Overwrite, dest_path)),
+            Self::Skip => Ok((PlannedAction::Keep, dest_path)),
+            Self::Rename => Ok((PlannedAction::Rename, destinations.free_path(&dest_path)?)),
+            Self::Fail => anyhow::bail!("Destination already exists: {}", dest_path.display()),
+            Self::Newer => {
+                let is_newer = file
+                    .last_modified()
+                    .zip(existing.modified)
+                    .is_some_and(|(modified, existing)| SystemTime::from(*modified) > existing);
+                let action = if is_newer {
+                    PlannedAction::Overwrite
+                } else {
+                    PlannedAction::Keep
+                };
+                Ok((action, dest_path))
+            }
+        }
+    }
+}
+
+/// An entry found at a destination
+struct ExistingEntry {
+    /// Modification time of the entry, if known
+    modified: Option<SystemTime>,
+}
+
+/// Destinations of a plan
+///
+/// Destinations claimed by earlier files of the same plan count as existing, so that files
+/// mapped to the same destination are planned the way they will be extracted.
+#[derive(Debug, Default)]
+struct Destinations {
+    /// Claimed destinations and the modification times they will get
+    claimed: HashMap<PathBuf, Option<SystemTime>>,
+}
+
+impl Destinations {
+    /// Returns the entry at a destination, either on disk or claimed by an earlier file
+    fn existing(&self, path: &Path) -> Result<Option<ExistingEntry>> {
+        if let Some(modified) = self.claimed.get(path) {
+            return Ok(Some(ExistingEntry {
+                modified: *modified,
+            }));
+        }
+
+        match fs::symlink_metadata(path) {
+            Ok(metadata) => Ok(Some(ExistingEntry {
+                modified: metadata.modified().ok(),
+            })),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(error) => {
+                Err(error).with_context(|| format!("Failed to inspect {}", path.display()))
+            }
+        }
+    }
+
+    /// Claims a destination for a file
+    fn claim(&mut self, path: PathBuf, file: &File) {
+        let modified = file.last_modified().map(|time| SystemTime::from(*time));
+        self.claimed.insert(path, modified);
+    }
+
+    /// Finds a free name next to an existing file, e.g. `photo (1).jpg` for `photo.jpg`
+    fn free_path(&self, path: &Path) -> Result<PathBuf> {
+        let stem = path.file_stem().unwrap_or_default();
+        for number in 1..=u16::MAX {
+            let mut name = stem.to_owned();
+            name.push(format!(" ({number})"));
+            if let Some(extension) = path.extension() {
+                name.push(".");
+                name.push(extension);
+            }
+
+            let candidate = path.with_file_name(name);
+            if self.existing(&candidate)?.is_none() {
+                return Ok(candidate);
+            }
+        }
+
+        anyhow::bail!("No free name left for {}", path.display())
+    }
+}
+
+/// Resolves a path that may not exist yet into an absolute path
+///
+/// Symbolic links are resolved in the part of the path that exists; the rest is appended
+/// as is.
+fn resolve_existing(path: &Path) -> Result<PathBuf> {
+    let absolute_path = absolute(path)
+        .with_context(|| format!("Failed to resolve directory: {}", path.display()))?;
+
+    let mut missing = Vec::new();
+    let mut existing = absolute_path.as_path();
+    loop {
+        match fs::canonicalize(existing) {
+            Ok(resolved) => {
+                return Ok(missing
+                    .iter()
+                    .rev()
+                    .fold(resolved, |resolved, name| resolved.join(name)));
+            }
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                let (Some(parent), Some(name)) = (existing.parent(), existing.file_name()) else {
+                    return Err(error).with_context(|| {
+                        format!("Failed to resolve directory: {}", path.display())
+                    });
+                };
+                missing.push(name);
+                existing = parent;
+            }
+            Err(error) => {
+                return Err(error)
+                    .with_context(|| format!("Failed to resolve directory: {}", path.display()));
+            }
+        }
+    }
+}
 
 /// Service for extracting files from iPhone backups
+///
+/// Extracted files and directories get the timestamps and permissions of the originals.
+/// Symbolic links are recreated with their original targets. Paths and link targets that
+/// could lead outside the output directory are refused.
+///
+/// Files are copied on blocking threads, up to `jobs` at a time. The results are the same
+/// regardless of the number of jobs.
+#[derive(Debug, Clone)]
 #[non_exhaustive]
-pub struct ExtractService;
+pub struct ExtractService {
+    /// Directory layout of extracted files
+    layout: OutputLayout,
+    /// What to do when a destination already exists
+    on_conflict: ConflictPolicy,
+    /// Whether to restore the owner user and group of the original files
+    preserve_ownership: bool,
+    /// Maximum number of files copied at the same time
+    jobs: NonZeroUsize,
+    /// Algorithms the content of copied files is hashed with
+    hashes: Vec<HashAlgorithm>,
+    /// Callback receiving the progress of extractions
+    progress: Option<ProgressHandler>,
+}
 
 impl ExtractService {
     /// Creates a new `ExtractService`
     #[must_use]
     #[inline]
     pub const fn new() -> Self {
-        Self
+        Self {
+            layout: OutputLayout::Merged,
+            on_conflict: ConflictPolicy::Overwrite,
+            preserve_ownership: false,
+            jobs: NonZeroUsize::MIN,
+            hashes: Vec::new(),
+            progress: None,
+        }
+    }
+
+    /// Sets the directory layout of extracted files
+    #[must_use]
+    #[inline]
+    pub const fn with_layout(mut self, layout: OutputLayout) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    /// Sets what to do when the destination of a file already exists
+    ///
+    /// Directories are merged into existing directories regardless of the policy.
+    #[must_use]
+    #[inline]
+    pub const fn with_on_conflict(mut self, on_conflict: ConflictPolicy) -> Self {
+        self.on_conflict = on_conflict;
+        self
+    }
+
+    /// Restores the owner user and group of the original files
+    ///
+    /// Changing the owner usually requires root privileges; files that cannot be
+    /// changed are reported as errors.
+    #[must_use]
+    #[inline]
+    pub const fn with_preserve_ownership(mut self, preserve_ownership: bool) -> Self {
+        self.preserve_ownership = preserve_ownership;
+        self
+    }
+
+    /// Sets the maximum number of files copied at the same time
+    #[must_use]
+    #[inline]
+    pub const fn with_jobs(mut self, jobs: NonZeroUsize) -> Self {
+        self.jobs = jobs;
+        self
+    }
+
+    /// Hashes the content of regular files with the given algorithms while copying them
+    ///
+    /// The digests are recorded in the outcome of each file.
+    #[must_use]
+    #[inline]
+    pub fn with_hashes(mut self, algorithms: Vec<HashAlgorithm>) -> Self {
+        self.hashes = algorithms;
+        self
+    }
+
+    /// Sets a callback receiving the progress of extractions
+    ///
+    /// The callback is called from the copying threads once before the first file and
+    /// after every processed file.
+    #[must_use]
+    #[inline]
+    pub fn with_progress(
+        mut self,
+        progress: impl Fn(ExtractProgress) + Send + Sync + 'static,
+    ) -> Self {
+        self.progress = Some(ProgressHandler(Arc::new(progress)));
+        self
     }
 
     /// Extracts files based on search parameters
     ///
+    /// This plans the extraction and executes the plan right away.
+    ///
     /// # Errors
     ///
     /// Returns an error if:
     /// - Search fails
     /// - File system operations fail
-    /// - Source files are not found
     #[allow(
         clippy::future_not_send,
         reason = "Repository trait doesn't guarantee Send futures"
     )]
     #[inline]
-    pub async fn extract<R: FileRepository>(
+    pub async fn extract<R: FileRepository, B: BlobRepository + Clone + 'static>(
+        &self,
+        repository: &R,
+        blobs: &B,
+        output_dir: impl AsRef<Path>,
+        params: SearchParams,
+    ) -> Result<ExtractResult> {
+        let files = Self::search(repository, params).await?;
+        let plan = self.plan_files(&files, output_dir.as_ref())?;
+
+        let files = files
+            .into_iter()
+            .map(|file| (file.id().to_string(), file))
+            .collect();
+        self.execute_plan(plan, files, blobs).await
+    }
+
+    /// Plans an extraction without writing anything
+    ///
+    /// Destinations are mapped with the layout and conflicts with existing files are
+    /// resolved with the conflict policy of the service. Files that would be refused are
+    /// listed in the plan instead of failing it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Search fails
+    /// - The output directory cannot be resolved
+    #[allow(
+        clippy::future_not_send,
+        reason = "Repository trait doesn't guarantee Send futures"
+    )]
+    #[inline]
+    pub async fn plan<R: FileRepository>(
         &self,
         repository: &R,
-        backup_dir: impl AsRef<Path>,
         output_dir: impl AsRef<Path>,
         params: SearchParams,
+    ) -> Result<ExtractPlan> {
+        let files = Self::search(repository, params).await?;
+        self.plan_files(&files, output_dir.as_ref())
+    }
+
+    /// Executes a plan made earlier
+    ///
+    /// Files are looked up by their IDs and written exactly as planned, so files that appeared
+    /// at planned destinations since then are reported as errors rather than overwritten.
+    /// The layout and conflict policy of the service are not used.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The plan holds an invalid file ID
+    /// - Looking up the files fails
+    /// - The output directory cannot be created
+    #[allow(
+        clippy::future_not_send,
+        reason = "Repository trait doesn't guarantee Send futures"
+    )]
+    #[inline]
+    pub async fn execute<R: FileRepository, B: BlobRepository + Clone + 'static>(
+        &self,
+        repository: &R,
+        blobs: &B,
+        plan: &ExtractPlan,
     ) -> Result<ExtractResult> {
-        // Search for files matching the criteria
+        let ids = plan
+            .operations
+            .iter()
+            .map(|operation| FileId::new(&operation.file_id))
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut files = HashMap::new();
+        for chunk in ids.chunks(FILE_ID_CHUNK_SIZE) {
+            let query = FileQuery::Basic(BasicQuery::FileId(chunk.to_vec()));
+            let found = repository
+                .search(query)
+                .await
+                .context("Failed to look up planned files")?;
+            files.extend(found.into_iter().map(|file| (file.id().to_string(), file)));
+        }
+
+        self.execute_plan(plan.clone(), files, blobs).await
+    }
+
+    /// Searches for the files to extract
+    #[allow(
+        clippy::future_not_send,
+        reason = "Repository trait doesn't guarantee Send futures"
+    )]
+    async fn search<R: FileRepository>(repository: &R, params: SearchParams) -> Result<Vec<File>> {
         let query = params.build_query()?;
-        let files = repository
+        repository
             .search(query)
             .await
-            .context("Failed to search for files")?;
+            .context("Failed to search for files")
+    }
 
+    /// Plans the extraction of files
+    fn plan_files(&self, files: &[File], output_dir: &Path) -> Result<ExtractPlan> {
+        let mut plan = ExtractPlan::new(output_dir.to_path_buf(), self.layout, self.on_conflict);
         if files.is_empty() {
-            return Ok(ExtractResult {
-                extracted_count: 0,
-                skipped_count: 0,
-                errors: Vec::new(),
-            });
+            return Ok(plan);
         }
 
-        let mut result = ExtractResult {
-            extracted_count: 0,
-            skipped_count: 0,
-            errors: Vec::new(),
-        };
+        let output_root = resolve_existing(output_dir)?;
+        let mut destinations = Destinations::default();
+        for file in files {
+            match self.plan_file(file, output_dir, &output_root, &mut destinations) {
+                Ok(operation) => plan.operations.push(operation),
+                Err(e) => plan.rejected.push(RejectedFile::new(file, &e)),
+            }
+        }
 
-        // Create output directory if it doesn't exist
-        let output_dir = output_dir.as_ref();
-        fs::create_dir_all(output_dir).with_context(|| {
-            format!(
-                "Failed to create output directory: {}",
-                output_dir.display()
-            )
-        })?;
+        Ok(plan)
+    }
 
-        let backup_dir = backup_dir.as_ref();
-        for file in files {
-            match Self::extract_single_file(&file, backup_dir, output_dir) {
-                Ok(true) => {
-                    result.extracted_count = result.extracted_count.saturating_add(1);
-                }
-                Ok(false) => {
-                    result.skipped_count = result.skipped_count.saturating_add(1);
+    /// Plans the extraction of a single backup entry
+    fn plan_file(
+        &self,
+        file: &File,
+        output_dir: &Path,
+        output_root: &Path,
+        destinations: &mut Destinations,
+    ) -> Result<PlannedOperation> {
+        let destination = self.layout.destination(file)?;
+        let dest_path = output_dir.join(&destination);
+
+        // Directories are merged into existing ones
+        if file.flags().is_directory() {
+            destinations.claim(dest_path, file);
+            return Ok(PlannedOperation::new(
+                file,
+                destination,
+                PlannedAction::Create,
+            ));
+        }
+
+        if file.flags().is_symbolic_link() {
+            Self::symlink_target(file, &dest_path, output_root)?;
+        }
+
+        let (action, dest_path) = self.on_conflict.resolve(file, dest_path, destinations)?;
+        let resolved = dest_path.strip_prefix(output_dir)?.to_path_buf();
+        if action.writes() {
+            destinations.claim(dest_path, file);
+        }
+
+        Ok(PlannedOperation::new(file, resolved, action))
+    }
+
+    /// Executes a plan with the given files, keyed by their IDs
+    ///
+    /// The file system work runs on a blocking thread.
+    async fn execute_plan<B: BlobRepository + Clone + 'static>(
+        &self,
+        plan: ExtractPlan,
+        files: HashMap<String, File>,
+        blobs: &B,
+    ) -> Result<ExtractResult> {
+        let service = self.clone();
+        let blobs = blobs.clone();
+        task::spawn_blocking(move || service.run_plan(&plan, &files, &blobs))
+            .await
+            .context("Extraction task failed")?
+    }
+
+    /// Runs the operations of a plan, stage by stage
+    fn run_plan<B: BlobRepository>(
+        &self,
+        plan: &ExtractPlan,
+        files: &HashMap<String, File>,
+        blobs: &B,
+    ) -> Result<ExtractResult> {
+        let mut result = ExtractResult::default();
+
+        let output_dir = plan.output_dir.as_path();
+        if !plan.operations.is_empty() {
+            // Create output directory if it doesn't exist
+            fs::create_dir_all(output_dir).with_context(|| {
+                format!(
+                    "Failed to create output directory: {}",
+                    output_dir.display()
+                )
+            })?;
+        }
+        let output_root = resolve_existing(output_dir)?;
+
+        let progress = Mutex::new(ExtractProgress {
+            total_files: plan.operations.len(),
+            total_bytes: plan.total_bytes(),
+            ..ExtractProgress::default()
+        });
+        self.report(&progress.lock().unwrap_or_else(PoisonError::into_inner));
+
+        let context = StageContext {
+            files,
+            blobs,
+            output_dir,
+            output_root: &output_root,
+            progress: &progress,
+        };
+        let mut outcomes = Vec::with_capacity(plan.operations.len());
+        for stage in stages(&plan.operations) {
+            outcomes.extend(self.run_stage(&plan.operations[stage], &context));
+        }
+
+        let mut directories = Vec::new();
+        for (operation, outcome) in plan.operations.iter().zip(outcomes) {
+            let (status, destination, hashes) = match outcome {
+                Ok(outcome) => outcome,
+                Err(e) => {
+                    result.errors.push(ExtractError::new(
+                        &operation.file_id,
+                        &operation.relative_path,
+                        &e,
+                    ));
+                    (ExtractStatus::Failed, None, None)
                 }
-                Err(e) => result.errors.push(ExtractError {
-                    file_id: file.id().to_string(),
-                    relative_path: file.relative_path().to_string(),
-                    error: e.to_string(),
-                }),
+            };
+
+            result.record(operation.file_type, status);
+            if let (Some(file), Some(path)) = (files.get(&operation.file_id), &destination)
+                && operation.file_type == FileType::Dir
+            {
+                directories.push((file, path.clone()));
             }
+            result.files.push(FileOutcome {
+                file_id: operation.file_id.clone(),
+                domain: operation.domain.clone(),
+                relative_path: operation.relative_path.clone(),
+                destination,
+                status,
+                hashes,
+            });
         }
 
+        for rejected in &plan.rejected {
+            result.errors.push(ExtractError::from(rejected));
+            result.files.push(FileOutcome {
+                file_id: rejected.file_id.clone(),
+                domain: rejected.domain.clone(),
+                relative_path: rejected.relative_path.clone(),
+                destination: None,
+                status: ExtractStatus::Failed,
+                hashes: None,
+            });
+        }
+
+        self.restore_directories(directories, &mut result);
+
         Ok(result)
     }
 
-    /// Extracts a single file
+    /// Runs the operations of a stage concurrently and returns their outcomes in order
+    fn run_stage<B: BlobRepository>(
+        &self,
+        operations: &[PlannedOperation],
+        context: &StageContext<'_, B>,
+    ) -> Vec<Result<OperationOutcome>> {
+        let run = |operation: &PlannedOperation| {
+            let outcome = context
+                .files
+                .get(&operation.file_id)
+                .with_context(|| format!("File not found in backup: {}", operation.file_id))
+                .and_then(|file| {
+                    self.execute_operation(
+                        file,
+                        operation,
+                        context.blobs,
+                        context.output_dir,
+                        context.output_root,
+                    )
+                });
+            self.advance(context.progress, operation);
+            outcome
+        };
+
+        let workers = self.jobs.get().min(operations.len());
+        if workers <= 1 {
+            return operations.iter().map(run).collect();
+        }
+
+        // Workers take the next operation until none are left
+        let next = AtomicUsize::new(0);
+        let mut outcomes: Vec<_> = operations.iter().map(|_| None).collect();
+        thread::scope(|scope| {
+            let handles: Vec<_> = (0..workers)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut done = Vec::new();
+                        loop {
+                            let index = next.fetch_add(1, Ordering::Relaxed);
+                            let Some(operation) = operations.get(index) else {
+                                break done;
+                            };
+                            done.push((index, run(operation)));
+                        }
+                    })
+                })
+                .collect();
+
+            for handle in handles {
+                let done = handle.join().unwrap_or_else(|panic| resume_unwind(panic));
+                for (index, outcome) in done {
+                    outcomes[index] = Some(outcome);
+                }
+            }
+        });
+
+        outcomes.into_iter().flatten().collect()
+    }
+
+    /// Counts a processed operation and reports the progress
+    fn advance(&self, progress: &Mutex<ExtractProgress>, operation: &PlannedOperation) {
+        let mut current = progress.lock().unwrap_or_else(PoisonError::into_inner);
+        current.completed_files = current.completed_files.saturating_add(1);
+        current.completed_bytes = current
+            .completed_bytes
+            .saturating_add(operation.written_bytes());
+        // Report while holding the lock, so that the callback sees increasing progress
+        self.report(&current);
+        drop(current);
+    }
+
+    /// Passes the progress to the progress callback, if any
+    fn report(&self, progress: &ExtractProgress) {
+        if let Some(ProgressHandler(handler)) = &self.progress {
+            handler(*progress);
+        }
+    }
+
+    /// Extracts a single backup entry as planned
     ///
-    /// Returns Ok(true) if extracted, Ok(false) if skipped, Err if failed
-    fn extract_single_file(file: &File, backup_dir: &Path, output_dir: &Path) -> Result<bool> {
-        let file_id_str = file.id().to_string();
+    /// Returns the status, the destination unless the file was not written, and the hashes
+    /// of a copied regular file if hashing is enabled.
+    fn execute_operation<B: BlobRepository>(
+        &self,
+        file: &File,
+        operation: &PlannedOperation,
+        blobs: &B,
+        output_dir: &Path,
+        output_root: &Path,
+    ) -> Result<OperationOutcome> {
+        let dest_path = output_dir.join(operation.checked_destination(file)?);
+        let status = match operation.action {
+            PlannedAction::Create => ExtractStatus::Extracted,
+            PlannedAction::Overwrite => ExtractStatus::Overwritten,
+            PlannedAction::Rename => ExtractStatus::Renamed,
+            PlannedAction::Keep => return Ok((ExtractStatus::Kept, None, None)),
+        };
 
-        // Construct source path: backup_dir/XX/fileID (where XX is first 2 chars of fileID)
-        let prefix = &file_id_str[0..2];
-        let source_path = backup_dir.join(prefix).join(&file_id_str);
+        if file.flags().is_directory() {
+            fs::create_dir_all(&dest_path)
+                .with_context(|| format!("Failed to create directory: {}", dest_path.display()))?;
+            return Ok((status, Some(dest_path), None));
+        }
+
+        if file.flags().is_symbolic_link() {
+            Self::create_parent(&dest_path)?;
+            let target = Self::symlink_target(file, &dest_path, output_root)?;
+            Self::remove_overwritten(&dest_path, status)?;
+            self.create_symlink(file, target, &dest_path)?;
+            return Ok((status, Some(dest_path), None));
+        }
+
+        // Skip if the backup holds no content for the file
+        let Some(mut source) = blobs.open(file)? else {
+            return Ok((ExtractStatus::Missing, None, None));
+        };
+
+        Self::create_parent(&dest_path)?;
+        Self::remove_overwritten(&dest_path, status)?;
+
+        // Copy the file content, decrypting it if needed, and hash it on the way
+        let destination = fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&dest_path)
+            .with_context(|| format!("Failed to create file: {}", dest_path.display()))?;
+        let mut writer = HashingWriter::new(&destination, &self.hashes);
+        io::copy(&mut source, &mut writer).with_context(|| {
+            format!(
+                "Failed to copy file {} to {}",
+                file.id(),
+                dest_path.display()
+            )
+        })?;
+        let hashes = writer.finish();
 
-        // Skip if source file doesn't exist
-        if !source_path.exists() {
-            return Ok(false);
+        if let Some(metadata) = file.file_metadata() {
+            self.restore_attributes(&destination, &dest_path, metadata)?;
         }
 
-        // Construct destination path preserving relative path structure
-        let dest_path = output_dir.join(file.relative_path().to_string());
+        let hashes = (!self.hashes.is_empty()).then_some(hashes);
+        Ok((status, Some(dest_path), hashes))
+    }
 
-        // Create parent directories if they don't exist
+    /// Creates the parent directories of a destination if they don't exist
+    fn create_parent(dest_path: &Path) -> Result<()> {
         if let Some(parent) = dest_path.parent() {
             fs::create_dir_all(parent).with_context(|| {
                 format!("Failed to create parent directory: {}", parent.display())
             })?;
         }
+        Ok(())
+    }
 
-        // Copy the file
-        fs::copy(&source_path, &dest_path).with_context(|| {
-            format!(
-                "Failed to copy file from {} to {}",
-                source_path.display(),
-                dest_path.display()
+    /// Removes an existing file before it is replaced
+    ///
+    /// Replacing the file rather than writing into it avoids following a symbolic link at
+    /// the destination and works for read-only files.
+    fn remove_overwritten(dest_path: &Path, status: ExtractStatus) -> Result<()> {
+        if status != ExtractStatus::Overwritten {
+            return Ok(());
+        }
+
+        match fs::remove_file(dest_path) {
+            Err(error) if error.kind() != io::ErrorKind::NotFound => {
+                Err(error).with_context(|| format!("Failed to replace {}", dest_path.display()))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the target of a symbolic link after checking that it stays within the
+    /// output directory
+    fn symlink_target<'a>(file: &'a File, dest_path: &Path, output_root: &Path) -> Result<&'a str> {
+        let target = file
+            .symlink_target()
+            .with_context(|| format!("Symbolic link has no target: {}", file.relative_path()))?;
+
+        // Resolve symbolic links that were extracted earlier, so that chained links
+        // cannot be used to escape either
+        let link_dir = resolve_existing(dest_path.parent().unwrap_or(dest_path))?;
+        if !is_contained(output_root, &link_dir, Path::new(target)) {
+            return Err(UnsafePathError::EscapingLinkTarget(target.to_owned()).into());
+        }
+
+        Ok(target)
+    }
+
+    /// Recreates a symbolic link
+    ///
+    /// Only the ownership of the link itself is restored.
+    fn create_symlink(&self, file: &File, target: &str, dest_path: &Path) -> Result<()> {
+        #[cfg(unix)]
+        {
+            symlink(target, dest_path).with_context(|| {
+                format!("Failed to create symbolic link: {}", dest_path.display())
+            })?;
+
+            if self.preserve_ownership
+                && let Some(metadata) = file.file_metadata()
+            {
+                lchown(
+                    dest_path,
+                    Some(metadata.user_id()),
+                    Some(metadata.group_id()),
+                )
+                .with_context(|| {
+                    format!("Failed to restore ownership of {}", dest_path.display())
+                })?;
+            }
+
+            Ok(())
+        }
+
+        #[cfg(not(unix))]
+        anyhow::bail!("Symbolic links are not supported on this platform")
+    }
+
+    /// Restores the attributes of extracted directories
+    ///
+    /// This runs after all entries have been extracted, deepest directories first, so that
+    /// creating their contents does not change the timestamps and a read-only mode does
+    /// not get in the way.
+    fn restore_directories(
+        &self,
+        mut directories: Vec<(&File, PathBuf)>,
+        result: &mut ExtractResult,
+    ) {
+        directories.sort_by(|(_, a), (_, b)| b.cmp(a));
+
+        for (file, path) in directories {
+            let Some(metadata) = file.file_metadata() else {
+                continue;
+            };
+
+            let restored = fs::File::open(&path)
+                .with_context(|| format!("Failed to open directory: {}", path.display()))
+                .and_then(|directory| self.restore_attributes(&directory, &path, metadata));
+            if let Err(e) = restored {
+                result.errors.push(ExtractError::new(
+                    file.id().value(),
+                    file.relative_path().value(),
+                    &e,
+                ));
+            }
+        }
+    }
+
+    /// Restores the timestamps, ownership and permissions of an extracted file
+    ///
+    /// Permissions are restored last, so that a read-only mode does not get in the way.
+    fn restore_attributes(
+        &self,
+        destination: &fs::File,
+        dest_path: &Path,
+        metadata: &FileMetadata,
+    ) -> Result<()> {
+        let last_modified = SystemTime::from(*metadata.last_modified());
+        let last_accessed = metadata
+            .last_accessed()
+            .map_or(last_modified, |time| SystemTime::from(*time));
+        let times = FileTimes::new()
+            .set_modified(last_modified)
+            .set_accessed(last_accessed);
+        #[cfg(target_os = "macos")]
+        let times = times.set_created(SystemTime::from(*metadata.birth()));
+
+        destination
+            .set_times(times)
+            .with_context(|| format!("Failed to restore timestamps of {}", dest_path.display()))?;
+
+        #[cfg(unix)]
+        {
+            if self.preserve_ownership {
+                chown(
+                    dest_path,
+                    Some(metadata.user_id()),
+                    Some(metadata.group_id()),
+                )
+                .with_context(|| {
+                    format!("Failed to restore ownership of {}", dest_path.display())
+                })?;
+            }
+
+            fs::set_permissions(
+                dest_path,
+                fs::Permissions::from_mode(metadata.permissions()),
             )
-        })?;
+            .with_context(|| format!("Failed to restore permissions of {}", dest_path.display()))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Splits planned operations into stages of operations that can run concurrently
+///
+/// Stages keep the order of the plan. Operations are put in separate stages when running
+/// them in a different order could change the outcome: when they share a destination, when
+/// one is written below the other unless that is a directory, and around symbolic links,
+/// which may redirect any later path.
+fn stages(operations: &[PlannedOperation]) -> Vec<Range<usize>> {
+    let mut stages = Vec::new();
+    let mut start = 0;
+    let mut destinations = HashSet::new();
+    let mut non_directories = HashSet::new();
+    let mut parents = HashSet::new();
+    let mut after_symlink = false;
+
+    for (index, operation) in operations.iter().enumerate() {
+        let destination = operation.destination.as_path();
+        let is_symlink = operation.file_type == FileType::Symlink;
+        let is_directory = operation.file_type == FileType::Dir;
+        let conflicts = after_symlink
+            || is_symlink
+            || destination
+                .ancestors()
+                .any(|ancestor| non_directories.contains(ancestor))
+            || (!is_directory
+                && (destinations.contains(destination) || parents.contains(destination)));
+
+        if conflicts && index > start {
+            stages.push(start..index);
+            start = index;
+            destinations.clear();
+            non_directories.clear();
+            parents.clear();
+        }
+
+        destinations.insert(destination);
+        if !is_directory {
+            non_directories.insert(destination);
+        }
+        parents.extend(destination.ancestors().skip(1));
+        after_symlink = is_symlink;
+    }
+
+    if start < operations.len() {
+        stages.push(start..operations.len());
+    }
+    stages
+}
+
+/// Status, destination and hashes of an extracted file
+type OperationOutcome = (ExtractStatus, Option<PathBuf>, Option<FileHashes>);
+
+/// Shared state of the operations of a stage
+struct StageContext<'a, B> {
+    /// Files of the plan, keyed by their IDs
+    files: &'a HashMap<String, File>,
+    /// Stored content of the files
+    blobs: &'a B,
+    /// Output directory
+    output_dir: &'a Path,
+    /// Resolved output directory
+    output_root: &'a Path,
+    /// Progress of the extraction
+    progress: &'a Mutex<ExtractProgress>,
+}
+
+/// Callback receiving the progress of an extraction
+#[derive(Clone)]
+struct ProgressHandler(Arc<dyn Fn(ExtractProgress) + Send + Sync>);
 
-        Ok(true)
+impl fmt::Debug for ProgressHandler {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ProgressHandler")
+    }
+}
+
+/// Progress of an extraction
+///
+/// Every planned operation counts as a file, including directories and kept files. Bytes
+/// are counted from the sizes recorded in the backup for regular files that are written.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ExtractProgress {
+    /// Number of processed files
+    pub completed_files: usize,
+    /// Number of files to process
+    pub total_files: usize,
+    /// Size of the processed files in bytes
+    pub completed_bytes: u64,
+    /// Size of the files to write in bytes
+    pub total_bytes: u64,
+}
+
+/// Checks whether a symbolic link target stays within the output directory
+///
+/// `link_dir` is the resolved directory containing the link. `..` is only accepted at the
+/// start of the target, where it refers to real directories: after a component that may
+/// itself be a symbolic link, it could lead anywhere.
+fn is_contained(output_root: &Path, link_dir: &Path, target: &Path) -> bool {
+    let Ok(relative_dir) = link_dir.strip_prefix(output_root) else {
+        return false;
+    };
+
+    let mut depth = relative_dir.components().count();
+    let mut descended = false;
+    for component in target.components() {
+        match component {
+            Component::CurDir => {}
+            Component::Normal(_) => descended = true,
+            Component::ParentDir if !descended => match depth.checked_sub(1) {
+                Some(parent_depth) => depth = parent_depth,
+                None => return false,
+            },
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => return false,
+        }
     }
+
+    true
 }
 
 impl Default for ExtractService {
@@ -135,41 +1020,188 @@ impl Default for ExtractService {
 }
 
 /// Result of an extraction operation
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 #[non_exhaustive]
 pub struct ExtractResult {
     /// Number of files successfully extracted
     pub extracted_count: usize,
-    /// Number of files skipped (source not found)
+    /// Number of directories created
+    pub directory_count: usize,
+    /// Number of symbolic links created
+    pub symlink_count: usize,
+    /// Number of files skipped (source not found or existing file kept)
     pub skipped_count: usize,
     /// Errors encountered during extraction
     pub errors: Vec<ExtractError>,
+    /// Outcome of every file, in the order of the search results; refused files come last
+    pub files: Vec<FileOutcome>,
 }
 
-/// Error information for a failed file extraction
-#[derive(Debug, Clone, PartialEq, Eq)]
-#[non_exhaustive]
-pub struct ExtractError {
-    /// File ID that failed to extract
-    pub file_id: String,
+impl ExtractResult {
+    /// Returns the number of files with the given status
+    #[must_use]
+    #[inline]
+    pub fn status_count(&self, status: ExtractStatus) -> usize {
+        self.files
+            .iter()
+            .filter(|outcome| outcome.status == status)
+            .count()
+    }
+
+    /// Adds a file to the aggregate counts
+    const fn record(&mut self, file_type: FileType, status: ExtractStatus) {
+        let count = match status {
+            ExtractStatus::Extracted | ExtractStatus::Overwritten | ExtractStatus::Renamed => {
+                match file_type {
+                    FileType::Dir => &mut self.directory_count,
+                    FileType::Symlink => &mut self.symlink_count,
+                    FileType::File => &mut self.extracted_count,
+                }
+            }
+            ExtractStatus::Kept | ExtractStatus::Missing => &mut self.skipped_count,
+            ExtractStatus::Failed => return,
+        };
+        *count = count.saturating_add(1);
+    }
+}
+
+/// Status of a single file after extraction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ExtractStatus {
+    /// Written to a new destination, or merged into an existing directory
+    Extracted,
+    /// Replaced an existing file
+    Overwritten,
+    /// Written to a free name because the destination already existed
+    Renamed,
+    /// The existing file was kept
+    Kept,
+    /// The backup holds no content for the file
+    Missing,
+    /// Extraction failed; see `ExtractResult::errors`
+    Failed,
+}
+
+impl ExtractStatus {
+    /// Returns the name used in machine-readable output
+    #[must_use]
+    #[inline]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Extracted => "extracted",
+            Self::Overwritten => "overwritten",
+            Self::Renamed => "renamed",
+            Self::Kept => "kept",
+            Self::Missing => "missing",
+            Self::Failed => "failed",
+        }
+    }
+}
+
+/// Outcome of extracting a single file
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct FileOutcome {
+    /// File ID in the backup
+    pub file_id: String,
+    /// Domain of the file
+    pub domain: String,
+    /// Relative path of the file
+    pub relative_path: String,
+    /// Where the file was written, if it was
+    pub destination: Option<PathBuf>,
+    /// Status of the file
+    pub status: ExtractStatus,
+    /// Size and digests of a copied regular file, if hashing was enabled
+    pub hashes: Option<FileHashes>,
+}
+
+/// Error information for a failed file extraction
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ExtractError {
+    /// File ID that failed to extract
+    pub file_id: String,
     /// Relative path of the file
     pub relative_path: String,
+    /// Kind of the failure
+    pub kind: ExtractErrorKind,
     /// Error message
     pub error: String,
 }
 
+/// Kind of a failed file extraction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum ExtractErrorKind {
+    /// The path or link target could lead outside the output directory
+    UnsafePath,
+    /// Reading the backup or writing the file failed
+    Failed,
+}
+
+impl ExtractErrorKind {
+    /// Returns the kind of an error
+    pub(crate) fn of(error: &anyhow::Error) -> Self {
+        if error.downcast_ref::<UnsafePathError>().is_some() {
+            Self::UnsafePath
+        } else {
+            Self::Failed
+        }
+    }
+}
+
+impl ExtractError {
+    /// Creates the error information for a file
+    fn new(file_id: &str, relative_path: &str, error: &anyhow::Error) -> Self {
+        Self {
+            file_id: file_id.to_owned(),
+            relative_path: relative_path.to_owned(),
+            kind: ExtractErrorKind::of(error),
+            error: error.to_string(),
+        }
+    }
+}
+
+impl From<&RejectedFile> for ExtractError {
+    #[inline]
+    fn from(rejected: &RejectedFile) -> Self {
+        Self {
+            file_id: rejected.file_id.clone(),
+            relative_path: rejected.relative_path.clone(),
+            kind: rejected.kind,
+            error: rejected.reason.clone(),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::domain::entities::File;
     use crate::domain::queries::FileQuery;
     use crate::domain::repositories::FileRepository;
-    use crate::domain::value_objects::{Domain, FileFlags, FileId, RelativePath};
+    use crate::domain::value_objects::{Domain, FileFlags, FileId, FileMetadata, RelativePath};
+    use crate::infrastructure::backup::BackupDirectory;
+    use crate::infrastructure::crypto::Keybag;
+    use crate::infrastructure::crypto::cipher::encrypt_cbc_for_test;
+    use crate::infrastructure::database::entities::files::{ActiveModel, Entity};
+    use crate::infrastructure::mbdb::{ManifestMbdb, MbdbRecord};
+    use crate::infrastructure::repositories::BlobRepositoryImpl;
     use anyhow::Result;
     use assert_fs::TempDir;
     use assert_fs::prelude::*;
+    use chrono::DateTime;
     use predicates::path;
     use pretty_assertions::assert_eq;
+    use sea_orm::{
+        ActiveModelTrait as _, ConnectionTrait as _, Database, DatabaseBackend, Schema, Set,
+    };
+    use std::collections::BTreeMap;
+    #[cfg(unix)]
+    use std::os::unix::fs::MetadataExt as _;
 
     // Mock repository for testing
     struct MockFileRepository {
@@ -231,10 +1263,8 @@ mod tests {
         let service = ExtractService::new();
         let service2 = ExtractService::default();
 
-        // Both should be unit structs and create successfully
-        // We can't directly compare unit structs, but we can test that they behave the same
-        assert!(matches!(service, ExtractService));
-        assert!(matches!(service2, ExtractService));
+        assert!(matches!(service, ExtractService { .. }));
+        assert!(matches!(service2, ExtractService { .. }));
     }
 
     #[tokio::test]
@@ -245,195 +1275,1351 @@ mod tests {
 
         let temp_backup = TempDir::new()?;
         let temp_output = TempDir::new()?;
-
-        let result = service
-            .extract(&repo, temp_backup.path(), temp_output.path(), params)
+
+        let result = service
+            .extract(
+                &repo,
+                &BlobRepositoryImpl::new(temp_backup.path()),
+                temp_output.path(),
+                params,
+            )
+            .await?;
+
+        assert_eq!(result.extracted_count, 0);
+        assert_eq!(result.skipped_count, 0);
+        assert!(result.errors.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_extract_service_repository_error() -> Result<()> {
+        let service = ExtractService::new();
+        let repo = MockFileRepository::new_failing();
+        let params = SearchParams::new(Some("test.domain".to_owned()), None, None, None, false);
+
+        let temp_backup = TempDir::new()?;
+        let temp_output = TempDir::new()?;
+
+        let result = service
+            .extract(
+                &repo,
+                &BlobRepositoryImpl::new(temp_backup.path()),
+                temp_output.path(),
+                params,
+            )
+            .await;
+
+        assert!(result.is_err());
+        if let Err(error) = result {
+            let error_message = error.to_string();
+            assert!(error_message.contains("Failed to search for files"));
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_extract_service_file_not_found_skipped() -> Result<()> {
+        let service = ExtractService::new();
+        let test_file = create_test_file()?;
+        let repo = MockFileRepository::new(vec![test_file]);
+        let params = SearchParams::new(Some("test.domain".to_owned()), None, None, None, false);
+
+        let temp_backup = TempDir::new()?;
+        let temp_output = TempDir::new()?;
+
+        // Don't create the source file - it should be skipped
+
+        let result = service
+            .extract(
+                &repo,
+                &BlobRepositoryImpl::new(temp_backup.path()),
+                temp_output.path(),
+                params,
+            )
+            .await?;
+
+        assert_eq!(result.extracted_count, 0);
+        assert_eq!(result.skipped_count, 1);
+        assert!(result.errors.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_extract_service_successful_extraction() -> Result<()> {
+        let service = ExtractService::new();
+        let test_file = create_test_file()?;
+        let repo = MockFileRepository::new(vec![test_file.clone()]);
+        let params = SearchParams::new(Some("test.domain".to_owned()), None, None, None, false);
+
+        let temp_backup = TempDir::new()?;
+        let temp_output = TempDir::new()?;
+
+        // Create the source file in backup directory structure
+        let file_id_str = test_file.id().to_string();
+        let prefix = &file_id_str[0..2];
+        temp_backup
+            .child(prefix)
+            .child(&file_id_str)
+            .write_str("test file content")?;
+
+        let result = service
+            .extract(
+                &repo,
+                &BlobRepositoryImpl::new(temp_backup.path()),
+                temp_output.path(),
+                params,
+            )
+            .await?;
+
+        assert_eq!(result.extracted_count, 1);
+        assert_eq!(result.skipped_count, 0);
+        assert!(result.errors.is_empty());
+        assert_eq!(
+            result.files,
+            vec![FileOutcome {
+                file_id: file_id_str.clone(),
+                domain: "AppDomain-com.apple.test".to_owned(),
+                relative_path: "Documents/test.txt".to_owned(),
+                destination: Some(temp_output.path().join("Documents/test.txt")),
+                status: ExtractStatus::Extracted,
+                hashes: None,
+            }]
+        );
+
+        // Verify the file was copied to the correct destination
+        temp_output
+            .child("Documents")
+            .child("test.txt")
+            .assert("test file content");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_extract_service_multiple_files_mixed_results() -> Result<()> {
+        let service = ExtractService::new();
+
+        // Create multiple test files
+        let file1 = create_test_file_with_params(
+            "da39a3ee5e6b4b0d3255bfef95601890afd80709",
+            "AppDomain-com.apple.test1",
+            "Documents/file1.txt",
+        )?;
+        let file2 = create_test_file_with_params(
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4",
+            "AppDomain-com.apple.test2",
+            "Photos/image.jpg",
+        )?;
+        let file3 = create_test_file_with_params(
+            "356a192b7913b04c54574d18c28d46e6395428ab",
+            "AppDomain-com.apple.test3",
+            "Music/song.mp3",
+        )?;
+
+        let repo = MockFileRepository::new(vec![file1.clone(), file2.clone(), file3.clone()]);
+        let params = SearchParams::new(Some("test.domain".to_owned()), None, None, None, false);
+
+        let temp_backup = TempDir::new()?;
+        let temp_output = TempDir::new()?;
+
+        // Create source files for file1 and file3 only (file2 will be skipped)
+        for file in [&file1, &file3] {
+            let file_id_str = file.id().to_string();
+            let prefix = &file_id_str[0..2];
+            temp_backup
+                .child(prefix)
+                .child(&file_id_str)
+                .write_str(&format!("content for {file_id_str}"))?;
+        }
+
+        let result = service
+            .extract(
+                &repo,
+                &BlobRepositoryImpl::new(temp_backup.path()),
+                temp_output.path(),
+                params,
+            )
+            .await?;
+
+        assert_eq!(result.extracted_count, 2);
+        assert_eq!(result.skipped_count, 1);
+        assert!(result.errors.is_empty());
+
+        // Verify the extracted files exist
+        temp_output
+            .child("Documents")
+            .child("file1.txt")
+            .assert(path::exists());
+        temp_output
+            .child("Music")
+            .child("song.mp3")
+            .assert(path::exists());
+        temp_output
+            .child("Photos")
+            .child("image.jpg")
+            .assert(path::missing());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_extract_service_nested_directory_structure() -> Result<()> {
+        let service = ExtractService::new();
+        let test_file = create_test_file_with_params(
+            "da627651e4e11a916ba8a9cd3f235e9a25a3b58e",
+            "AppDomain-com.apple.test",
+            "Documents/Projects/MyApp/src/main.rs",
+        )?;
+        let repo = MockFileRepository::new(vec![test_file.clone()]);
+        let params = SearchParams::new(Some("test.domain".to_owned()), None, None, None, false);
+
+        let temp_backup = TempDir::new()?;
+        let temp_output = TempDir::new()?;
+
+        // Create the source file
+        let file_id_str = test_file.id().to_string();
+        let prefix = &file_id_str[0..2];
+        temp_backup
+            .child(prefix)
+            .child(&file_id_str)
+            .write_str("fn main() { println!(\"Hello!\"); }")?;
+
+        let result = service
+            .extract(
+                &repo,
+                &BlobRepositoryImpl::new(temp_backup.path()),
+                temp_output.path(),
+                params,
+            )
+            .await?;
+
+        assert_eq!(result.extracted_count, 1);
+        assert_eq!(result.skipped_count, 0);
+        assert!(result.errors.is_empty());
+
+        // Verify nested directory structure is created correctly
+        temp_output
+            .child("Documents")
+            .child("Projects")
+            .child("MyApp")
+            .child("src")
+            .child("main.rs")
+            .assert("fn main() { println!(\"Hello!\"); }");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_extract_service_encrypted_backup() -> Result<()> {
+        let service = ExtractService::new();
+        let class_key = [3; 32];
+        let file_key = [9; 32];
+
+        let file_metadata = FileMetadata::new(17, 0o100_644)
+            .with_protection_class(3)
+            .with_encryption_key(Some(Keybag::wrap_for_test(3, class_key, &file_key)?));
+        let test_file = create_test_file()?.with_file_metadata(file_metadata);
+        let repo = MockFileRepository::new(vec![test_file.clone()]);
+        let params = SearchParams::new(Some("test.domain".to_owned()), None, None, None, false);
+
+        let temp_backup = TempDir::new()?;
+        let temp_output = TempDir::new()?;
+
+        let file_id_str = test_file.id().to_string();
+        let prefix = &file_id_str[0..2];
+        temp_backup
+            .child(prefix)
+            .child(&file_id_str)
+            .write_binary(&encrypt_cbc_for_test(&file_key, b"encrypted content"))?;
+
+        let mut keybag = Keybag::parse(&Keybag::create_for_test("secret", &[(3, class_key)])?)?;
+        keybag.unlock("secret")?;
+        let blobs = BlobRepositoryImpl::new(temp_backup.path()).with_keybag(keybag);
+
+        let result = service
+            .extract(&repo, &blobs, temp_output.path(), params)
+            .await?;
+
+        assert_eq!(result.extracted_count, 1);
+        assert!(result.errors.is_empty());
+        temp_output
+            .child("Documents")
+            .child("test.txt")
+            .assert("encrypted content");
+
+        Ok(())
+    }
+
+    async fn extract_with_metadata(
+        service: ExtractService,
+        file_metadata: FileMetadata,
+    ) -> Result<(ExtractResult, TempDir)> {
+        let test_file = create_test_file()?.with_file_metadata(file_metadata);
+        let repo = MockFileRepository::new(vec![test_file.clone()]);
+        let params = SearchParams::new(Some("test.domain".to_owned()), None, None, None, false);
+
+        let temp_backup = TempDir::new()?;
+        let temp_output = TempDir::new()?;
+
+        let file_id_str = test_file.id().to_string();
+        temp_backup
+            .child(&file_id_str[0..2])
+            .child(&file_id_str)
+            .write_str("test content")?;
+
+        let result = service
+            .extract(
+                &repo,
+                &BlobRepositoryImpl::new(temp_backup.path()),
+                temp_output.path(),
+                params,
+            )
+            .await?;
+
+        Ok((result, temp_output))
+    }
+
+    #[tokio::test]
+    async fn test_extract_service_restores_timestamps() -> Result<()> {
+        let modified = DateTime::from_timestamp(1_600_000_000, 0).context("invalid time")?;
+        let accessed = DateTime::from_timestamp(1_600_000_100, 0).context("invalid time")?;
+        let file_metadata = FileMetadata::new(12, 0o100_644)
+            .with_timestamps(modified, modified, modified)
+            .with_last_accessed(Some(accessed));
+
+        let (result, temp_output) =
+            extract_with_metadata(ExtractService::new(), file_metadata).await?;
+
+        assert_eq!(result.extracted_count, 1);
+        assert!(result.errors.is_empty());
+        let extracted = fs::metadata(temp_output.path().join("Documents/test.txt"))?;
+        assert_eq!(extracted.modified()?, SystemTime::from(modified));
+        assert_eq!(extracted.accessed()?, SystemTime::from(accessed));
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_extract_service_restores_permissions() -> Result<()> {
+        let file_metadata = FileMetadata::new(12, 0o100_600);
+
+        let (result, temp_output) =
+            extract_with_metadata(ExtractService::new(), file_metadata).await?;
+
+        assert_eq!(result.extracted_count, 1);
+        let extracted = fs::metadata(temp_output.path().join("Documents/test.txt"))?;
+        assert_eq!(extracted.permissions().mode() & 0o7777, 0o600);
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_extract_service_restores_ownership() -> Result<()> {
+        // Restoring the current owner is allowed without root privileges
+        let temp_dir = TempDir::new()?;
+        let current = fs::metadata(temp_dir.path())?;
+        let file_metadata =
+            FileMetadata::new(12, 0o100_644).with_owner(current.uid(), current.gid());
+        let service = ExtractService::new().with_preserve_ownership(true);
+
+        let (result, temp_output) = extract_with_metadata(service, file_metadata).await?;
+
+        assert_eq!(result.extracted_count, 1);
+        assert!(result.errors.is_empty());
+        let extracted = fs::metadata(temp_output.path().join("Documents/test.txt"))?;
+        assert_eq!(extracted.uid(), current.uid());
+        assert_eq!(extracted.gid(), current.gid());
+
+        Ok(())
+    }
+
+    /// Extracts `Documents/test.txt`, last modified at `1_600_000_000`, over an existing file
+    async fn extract_over_existing(
+        on_conflict: ConflictPolicy,
+        existing_modified: SystemTime,
+    ) -> Result<(ExtractResult, TempDir)> {
+        let modified = DateTime::from_timestamp(1_600_000_000, 0).context("invalid time")?;
+        let file_metadata =
+            FileMetadata::new(12, 0o100_644).with_timestamps(modified, modified, modified);
+
+        let temp_dir = TempDir::new()?;
+        let existing = temp_dir.child("Documents/test.txt");
+        existing.write_str("existing")?;
+        fs::File::options()
+            .write(true)
+            .open(existing.path())?
+            .set_modified(existing_modified)?;
+
+        let service = ExtractService::new().with_on_conflict(on_conflict);
+        let test_file = create_test_file()?.with_file_metadata(file_metadata);
+        let repo = MockFileRepository::new(vec![test_file.clone()]);
+        let params = SearchParams::new(Some("test.domain".to_owned()), None, None, None, false);
+
+        let temp_backup = TempDir::new()?;
+        let file_id_str = test_file.id().to_string();
+        temp_backup
+            .child(&file_id_str[0..2])
+            .child(&file_id_str)
+            .write_str("test content")?;
+
+        let result = service
+            .extract(
+                &repo,
+                &BlobRepositoryImpl::new(temp_backup.path()),
+                temp_dir.path(),
+                params,
+            )
+            .await?;
+
+        Ok((result, temp_dir))
+    }
+
+    #[tokio::test]
+    async fn test_extract_service_conflict_overwrite() -> Result<()> {
+        let (result, temp_output) =
+            extract_over_existing(ConflictPolicy::Overwrite, SystemTime::now()).await?;
+
+        assert_eq!(result.extracted_count, 1);
+        assert_eq!(result.status_count(ExtractStatus::Overwritten), 1);
+        temp_output
+            .child("Documents/test.txt")
+            .assert("test content");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_extract_service_conflict_skip() -> Result<()> {
+        let (result, temp_output) =
+            extract_over_existing(ConflictPolicy::Skip, SystemTime::UNIX_EPOCH).await?;
+
+        assert_eq!(result.extracted_count, 0);
+        assert_eq!(result.skipped_count, 1);
+        assert_eq!(result.files[0].status, ExtractStatus::Kept);
+        assert_eq!(result.files[0].destination, None);
+        temp_output.child("Documents/test.txt").assert("existing");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_extract_service_conflict_rename() -> Result<()> {
+        let (result, temp_output) =
+            extract_over_existing(ConflictPolicy::Rename, SystemTime::now()).await?;
+
+        assert_eq!(result.extracted_count, 1);
+        assert_eq!(result.files[0].status, ExtractStatus::Renamed);
+        assert_eq!(
+            result.files[0].destination,
+            Some(temp_output.path().join("Documents/test (1).txt"))
+        );
+        temp_output.child("Documents/test.txt").assert("existing");
+        temp_output
+            .child("Documents/test (1).txt")
+            .assert("test content");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_destinations_free_path() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        temp_dir.child("photo.jpg").touch()?;
+        temp_dir.child("photo (1).jpg").touch()?;
+        temp_dir.child(".profile").touch()?;
+        let mut destinations = Destinations::default();
+
+        assert_eq!(
+            destinations.free_path(&temp_dir.path().join("photo.jpg"))?,
+            temp_dir.path().join("photo (2).jpg")
+        );
+        assert_eq!(
+            destinations.free_path(&temp_dir.path().join(".profile"))?,
+            temp_dir.path().join(".profile (1)")
+        );
+
+        // Destinations claimed by earlier files are taken as well
+        destinations.claim(temp_dir.path().join("photo (2).jpg"), &create_test_file()?);
+        assert_eq!(
+            destinations.free_path(&temp_dir.path().join("photo.jpg"))?,
+            temp_dir.path().join("photo (3).jpg")
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_extract_service_conflict_fail() -> Result<()> {
+        let (result, temp_output) =
+            extract_over_existing(ConflictPolicy::Fail, SystemTime::UNIX_EPOCH).await?;
+
+        assert_eq!(result.extracted_count, 0);
+        assert_eq!(result.errors.len(), 1);
+        assert!(
+            result.errors[0]
+                .error
+                .contains("Destination already exists")
+        );
+        assert_eq!(result.files[0].status, ExtractStatus::Failed);
+        temp_output.child("Documents/test.txt").assert("existing");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_extract_service_conflict_newer() -> Result<()> {
+        let (older_result, older_output) =
+            extract_over_existing(ConflictPolicy::Newer, SystemTime::UNIX_EPOCH).await?;
+        assert_eq!(older_result.files[0].status, ExtractStatus::Overwritten);
+        older_output
+            .child("Documents/test.txt")
+            .assert("test content");
+
+        let (newer_result, newer_output) =
+            extract_over_existing(ConflictPolicy::Newer, SystemTime::now()).await?;
+        assert_eq!(newer_result.files[0].status, ExtractStatus::Kept);
+        newer_output.child("Documents/test.txt").assert("existing");
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_extract_service_overwrite_does_not_follow_symlinks() -> Result<()> {
+        let service = ExtractService::new();
+        let test_file = create_test_file()?;
+        let repo = MockFileRepository::new(vec![test_file.clone()]);
+        let params = SearchParams::new(Some("test.domain".to_owned()), None, None, None, false);
+
+        let temp_backup = TempDir::new()?;
+        let temp_output = TempDir::new()?;
+        let outside = TempDir::new()?;
+        outside.child("victim.txt").write_str("untouched")?;
+        temp_output.child("Documents").create_dir_all()?;
+        symlink(
+            outside.path().join("victim.txt"),
+            temp_output.path().join("Documents/test.txt"),
+        )?;
+
+        let file_id_str = test_file.id().to_string();
+        temp_backup
+            .child(&file_id_str[0..2])
+            .child(&file_id_str)
+            .write_str("test content")?;
+
+        let result = service
+            .extract(
+                &repo,
+                &BlobRepositoryImpl::new(temp_backup.path()),
+                temp_output.path(),
+                params,
+            )
+            .await?;
+
+        assert_eq!(result.status_count(ExtractStatus::Overwritten), 1);
+        outside.child("victim.txt").assert("untouched");
+        temp_output
+            .child("Documents/test.txt")
+            .assert("test content");
+        assert!(!fs::symlink_metadata(temp_output.path().join("Documents/test.txt"))?.is_symlink());
+
+        Ok(())
+    }
+
+    fn create_entry(
+        file_id_str: &str,
+        relative_path_str: &str,
+        flags: FileFlags,
+        file_metadata: FileMetadata,
+    ) -> Result<File> {
+        let mut file = create_test_file_with_params(
+            file_id_str,
+            "AppDomain-com.apple.test",
+            relative_path_str,
+        )?
+        .with_file_metadata(file_metadata);
+        file.update_flags(flags);
+        Ok(file)
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_extract_service_directories() -> Result<()> {
+        let modified = DateTime::from_timestamp(1_600_000_000, 0).context("invalid time")?;
+        let directory = create_entry(
+            "356a192b7913b04c54574d18c28d46e6395428ab",
+            "Documents",
+            FileFlags::DIRECTORY,
+            FileMetadata::new(0, 0o040_700).with_timestamps(modified, modified, modified),
+        )?;
+        let empty_directory = create_entry(
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4",
+            "Library/Caches",
+            FileFlags::DIRECTORY,
+            FileMetadata::new(0, 0o040_755),
+        )?;
+        let repo = MockFileRepository::new(vec![directory, empty_directory, create_test_file()?]);
+        let params = SearchParams::new(Some("test.domain".to_owned()), None, None, None, false);
+
+        let temp_backup = TempDir::new()?;
+        let temp_output = TempDir::new()?;
+        temp_backup
+            .child("da")
+            .child("da39a3ee5e6b4b0d3255bfef95601890afd80709")
+            .write_str("test content")?;
+
+        let result = ExtractService::new()
+            .extract(
+                &repo,
+                &BlobRepositoryImpl::new(temp_backup.path()),
+                temp_output.path(),
+                params,
+            )
             .await?;
 
-        assert_eq!(result.extracted_count, 0);
+        assert_eq!(result.extracted_count, 1);
+        assert_eq!(result.directory_count, 2);
         assert_eq!(result.skipped_count, 0);
         assert!(result.errors.is_empty());
+        temp_output.child("Library/Caches").assert(path::is_dir());
+        temp_output
+            .child("Documents/test.txt")
+            .assert("test content");
+
+        // Extracting the contents must not change the restored directory attributes
+        let extracted = fs::metadata(temp_output.path().join("Documents"))?;
+        assert_eq!(extracted.permissions().mode() & 0o7777, 0o700);
+        assert_eq!(extracted.modified()?, SystemTime::from(modified));
+
         Ok(())
     }
 
+    #[cfg(unix)]
     #[tokio::test]
-    async fn test_extract_service_repository_error() -> Result<()> {
-        let service = ExtractService::new();
-        let repo = MockFileRepository::new_failing();
+    async fn test_extract_service_symlinks() -> Result<()> {
+        let link = create_entry(
+            "356a192b7913b04c54574d18c28d46e6395428ab",
+            "Documents/link.txt",
+            FileFlags::SYMBOLIC_LINK,
+            FileMetadata::new(8, 0o120_755).with_target(Some("test.txt".to_owned())),
+        )?;
+        let parent_link = create_entry(
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4",
+            "Library/Documents",
+            FileFlags::SYMBOLIC_LINK,
+            FileMetadata::new(12, 0o120_755).with_target(Some("../Documents".to_owned())),
+        )?;
+        let repo = MockFileRepository::new(vec![create_test_file()?, link, parent_link]);
         let params = SearchParams::new(Some("test.domain".to_owned()), None, None, None, false);
 
         let temp_backup = TempDir::new()?;
         let temp_output = TempDir::new()?;
+        temp_backup
+            .child("da")
+            .child("da39a3ee5e6b4b0d3255bfef95601890afd80709")
+            .write_str("test content")?;
+
+        let result = ExtractService::new()
+            .extract(
+                &repo,
+                &BlobRepositoryImpl::new(temp_backup.path()),
+                temp_output.path(),
+                params,
+            )
+            .await?;
 
-        let result = service
-            .extract(&repo, temp_backup.path(), temp_output.path(), params)
-            .await;
+        assert_eq!(result.extracted_count, 1);
+        assert_eq!(result.symlink_count, 2);
+        assert!(result.errors.is_empty());
+        assert_eq!(
+            fs::read_link(temp_output.path().join("Documents/link.txt"))?,
+            Path::new("test.txt")
+        );
+        temp_output
+            .child("Documents/link.txt")
+            .assert("test content");
+        temp_output
+            .child("Library/Documents/test.txt")
+            .assert("test content");
 
-        assert!(result.is_err());
-        if let Err(error) = result {
-            let error_message = error.to_string();
-            assert!(error_message.contains("Failed to search for files"));
-        }
         Ok(())
     }
 
+    #[cfg(unix)]
     #[tokio::test]
-    async fn test_extract_service_file_not_found_skipped() -> Result<()> {
-        let service = ExtractService::new();
-        let test_file = create_test_file()?;
-        let repo = MockFileRepository::new(vec![test_file]);
+    async fn test_extract_service_refuses_escaping_symlinks() -> Result<()> {
+        let symlink_entry = |file_id: &str, path: &str, target: &str| {
+            create_entry(
+                file_id,
+                path,
+                FileFlags::SYMBOLIC_LINK,
+                FileMetadata::new(0, 0o120_755).with_target(Some(target.to_owned())),
+            )
+        };
+        let repo = MockFileRepository::new(vec![
+            symlink_entry(
+                "356a192b7913b04c54574d18c28d46e6395428ab",
+                "Documents/up",
+                "../../outside",
+            )?,
+            symlink_entry(
+                "e3b0c44298fc1c149afbf4c8996fb92427ae41e4",
+                "Documents/absolute",
+                "/etc/passwd",
+            )?,
+            // Points at the output directory itself, which is fine on its own...
+            symlink_entry(
+                "da39a3ee5e6b4b0d3255bfef95601890afd80709",
+                "Documents/root",
+                "..",
+            )?,
+            // ...but must not be usable as a stepping stone
+            symlink_entry(
+                "77de68daecd823babbb58edb1c8e14d7106e83bb",
+                "Documents/root/chained",
+                "../outside",
+            )?,
+            symlink_entry(
+                "1b6453892473a467d07372d45eb05abc2031647a",
+                "Documents/sneaky",
+                "root/../../outside",
+            )?,
+        ]);
         let params = SearchParams::new(Some("test.domain".to_owned()), None, None, None, false);
 
         let temp_backup = TempDir::new()?;
         let temp_output = TempDir::new()?;
 
-        // Don't create the source file - it should be skipped
-
-        let result = service
-            .extract(&repo, temp_backup.path(), temp_output.path(), params)
+        let result = ExtractService::new()
+            .extract(
+                &repo,
+                &BlobRepositoryImpl::new(temp_backup.path()),
+                temp_output.path().join("output"),
+                params,
+            )
             .await?;
 
-        assert_eq!(result.extracted_count, 0);
-        assert_eq!(result.skipped_count, 1);
-        assert!(result.errors.is_empty());
+        assert_eq!(result.symlink_count, 1);
+        assert_eq!(result.errors.len(), 4);
+        for error in &result.errors {
+            assert!(
+                error.error.contains("escapes the output directory"),
+                "{}",
+                error.error
+            );
+        }
+        temp_output
+            .child("output/Documents/up")
+            .assert(path::missing());
+        temp_output
+            .child("output/Documents/absolute")
+            .assert(path::missing());
+        temp_output.child("output/chained").assert(path::missing());
+
         Ok(())
     }
 
     #[tokio::test]
-    async fn test_extract_service_successful_extraction() -> Result<()> {
-        let service = ExtractService::new();
-        let test_file = create_test_file()?;
-        let repo = MockFileRepository::new(vec![test_file.clone()]);
+    async fn test_extract_service_symlink_without_target() -> Result<()> {
+        let link = create_entry(
+            "356a192b7913b04c54574d18c28d46e6395428ab",
+            "Documents/link.txt",
+            FileFlags::SYMBOLIC_LINK,
+            FileMetadata::new(0, 0o120_755),
+        )?;
+        let repo = MockFileRepository::new(vec![link]);
         let params = SearchParams::new(Some("test.domain".to_owned()), None, None, None, false);
 
         let temp_backup = TempDir::new()?;
         let temp_output = TempDir::new()?;
 
-        // Create the source file in backup directory structure
-        let file_id_str = test_file.id().to_string();
-        let prefix = &file_id_str[0..2];
-        temp_backup
-            .child(prefix)
-            .child(&file_id_str)
-            .write_str("test file content")?;
+        let result = ExtractService::new()
+            .extract(
+                &repo,
+                &BlobRepositoryImpl::new(temp_backup.path()),
+                temp_output.path(),
+                params,
+            )
+            .await?;
 
-        let result = service
-            .extract(&repo, temp_backup.path(), temp_output.path(), params)
+        assert_eq!(result.symlink_count, 0);
+        assert_eq!(result.errors.len(), 1);
+        assert!(result.errors[0].error.contains("no target"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_extract_service_malicious_manifest_db() -> Result<()> {
+        let temp_root = TempDir::new()?;
+        let backup_dir = temp_root.child("backup");
+        backup_dir.create_dir_all()?;
+        let manifest_path = backup_dir.child("Manifest.db");
+        let db = Database::connect(format!(
+            "sqlite://{}?mode=rwc",
+            manifest_path.path().display()
+        ))
+        .await?;
+        let schema = Schema::new(DatabaseBackend::Sqlite);
+        db.execute(
+            db.get_database_backend()
+                .build(&schema.create_table_from_entity(Entity)),
+        )
+        .await?;
+
+        let rows = [
+            ("356a192b7913b04c54574d18c28d46e6395428ab", "../../evil.txt"),
+            ("ac3478d69a3c81fa62e60f5c3696165a4e5e6ac4", "/tmp/evil.txt"),
+            (
+                "da4b9237bacccdf19c0760cab7aec4a8359010b0",
+                "Library/../../../evil.txt",
+            ),
+            (
+                "77de68daecd823babbb58edb1c8e14d7106e83bb",
+                "Library/evil\0.txt",
+            ),
+            (
+                "1b6453892473a467d07372d45eb05abc2031647a",
+                "Documents/ok.txt",
+            ),
+        ];
+        for (file_id, relative_path) in rows {
+            ActiveModel {
+                file_id: Set(file_id.to_owned()),
+                domain: Set("HomeDomain".to_owned()),
+                relative_path: Set(relative_path.to_owned()),
+                flags: Set(1),
+                file: Set(vec![]),
+            }
+            .insert(&db)
+            .await?;
+            backup_dir
+                .child(&file_id[0..2])
+                .child(file_id)
+                .write_str("malicious content")?;
+        }
+        db.close().await?;
+
+        let backup = BackupDirectory::open(backup_dir.path()).await?;
+        let params = SearchParams::new(Some("HomeDomain".to_owned()), None, None, None, false);
+        let result = ExtractService::new()
+            .extract(
+                &backup.file_repository().await?,
+                &backup.blob_repository(),
+                temp_root.path().join("output/nested"),
+                params,
+            )
             .await?;
 
         assert_eq!(result.extracted_count, 1);
-        assert_eq!(result.skipped_count, 0);
-        assert!(result.errors.is_empty());
-
-        // Verify the file was copied to the correct destination
-        temp_output
-            .child("Documents")
-            .child("test.txt")
-            .assert("test file content");
+        assert_eq!(result.errors.len(), 4);
+        assert!(
+            result
+                .errors
+                .iter()
+                .all(|error| error.kind == ExtractErrorKind::UnsafePath)
+        );
+        assert!(result.errors[0].error.contains("'..'"));
+        assert!(result.errors[1].error.contains("absolute path"));
+        assert!(result.errors[3].error.contains("NUL byte"));
+        temp_root.child("evil.txt").assert(path::missing());
+        temp_root.child("output/evil.txt").assert(path::missing());
+        temp_root
+            .child("output/nested/Documents/ok.txt")
+            .assert("malicious content");
 
         Ok(())
     }
 
     #[tokio::test]
-    async fn test_extract_service_multiple_files_mixed_results() -> Result<()> {
-        let service = ExtractService::new();
+    async fn test_extract_service_malicious_manifest_mbdb() -> Result<()> {
+        let temp_root = TempDir::new()?;
+        let backup_dir = temp_root.child("backup");
+        let manifest = ManifestMbdb {
+            records: vec![
+                MbdbRecord {
+                    domain: "HomeDomain".to_owned(),
+                    path: "Library/../../evil.txt".to_owned(),
+                    mode: 0o100_644,
+                    ..MbdbRecord::default()
+                },
+                MbdbRecord {
+                    domain: "HomeDomain".to_owned(),
+                    path: "Library/Preferences".to_owned(),
+                    link_target: Some("../../../etc".to_owned()),
+                    mode: 0o120_755,
+                    ..MbdbRecord::default()
+                },
+            ],
+        };
+        backup_dir
+            .child("Manifest.mbdb")
+            .write_binary(&manifest.to_bytes()?)?;
+        for record in &manifest.records {
+            backup_dir
+                .child(record.file_id())
+                .write_str("malicious content")?;
+        }
 
-        // Create multiple test files
-        let file1 = create_test_file_with_params(
+        let backup = BackupDirectory::open(backup_dir.path()).await?;
+        let params = SearchParams::new(Some("HomeDomain".to_owned()), None, None, None, false);
+        let result = ExtractService::new()
+            .extract(
+                &backup.file_repository().await?,
+                &backup.blob_repository(),
+                temp_root.path().join("output"),
+                params,
+            )
+            .await?;
+
+        assert_eq!(result.extracted_count, 0);
+        assert_eq!(result.symlink_count, 0);
+        assert_eq!(result.errors.len(), 2);
+        assert!(
+            result
+                .errors
+                .iter()
+                .all(|error| error.kind == ExtractErrorKind::UnsafePath)
+        );
+        temp_root.child("evil.txt").assert(path::missing());
+        temp_root
+            .child("output/Library/Preferences")
+            .assert(path::missing());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_output_layout_destination() -> Result<()> {
+        let file = create_test_file_with_params(
             "da39a3ee5e6b4b0d3255bfef95601890afd80709",
-            "AppDomain-com.apple.test1",
-            "Documents/file1.txt",
+            "AppDomain-net.whatsapp.WhatsApp",
+            "Library/Preferences/net.whatsapp.WhatsApp.plist",
         )?;
-        let file2 = create_test_file_with_params(
-            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4",
-            "AppDomain-com.apple.test2",
-            "Photos/image.jpg",
+
+        assert_eq!(
+            OutputLayout::Merged.destination(&file)?,
+            Path::new("Library/Preferences/net.whatsapp.WhatsApp.plist")
+        );
+        assert_eq!(
+            OutputLayout::ByDomain.destination(&file)?,
+            Path::new(
+                "AppDomain-net.whatsapp.WhatsApp/Library/Preferences/net.whatsapp.WhatsApp.plist"
+            )
+        );
+        assert_eq!(
+            OutputLayout::Device.destination(&file)?,
+            Path::new(
+                "private/var/mobile/Containers/Data/Application/net.whatsapp.WhatsApp/Library/Preferences/net.whatsapp.WhatsApp.plist"
+            )
+        );
+        assert_eq!(
+            OutputLayout::FlatId.destination(&file)?,
+            Path::new("da39a3ee5e6b4b0d3255bfef95601890afd80709")
+        );
+
+        let unsafe_domain = create_test_file_with_params(
+            "da39a3ee5e6b4b0d3255bfef95601890afd80709",
+            "AppDomain-../../evil",
+            "Library/evil.plist",
         )?;
-        let file3 = create_test_file_with_params(
+        assert!(OutputLayout::ByDomain.destination(&unsafe_domain).is_err());
+        assert!(OutputLayout::Device.destination(&unsafe_domain).is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_extract_service_by_domain_layout() -> Result<()> {
+        let home = create_test_file_with_params(
+            "da39a3ee5e6b4b0d3255bfef95601890afd80709",
+            "HomeDomain",
+            "Library/Preferences/com.apple.test.plist",
+        )?;
+        let app = create_test_file_with_params(
             "356a192b7913b04c54574d18c28d46e6395428ab",
-            "AppDomain-com.apple.test3",
-            "Music/song.mp3",
+            "AppDomain-com.apple.test",
+            "Library/Preferences/com.apple.test.plist",
         )?;
-
-        let repo = MockFileRepository::new(vec![file1.clone(), file2.clone(), file3.clone()]);
-        let params = SearchParams::new(Some("test.domain".to_owned()), None, None, None, false);
+        let repo = MockFileRepository::new(vec![home.clone(), app.clone()]);
 
         let temp_backup = TempDir::new()?;
         let temp_output = TempDir::new()?;
-
-        // Create source files for file1 and file3 only (file2 will be skipped)
-        for file in [&file1, &file3] {
+        for (file, content) in [(&home, "home"), (&app, "app")] {
             let file_id_str = file.id().to_string();
-            let prefix = &file_id_str[0..2];
             temp_backup
-                .child(prefix)
+                .child(&file_id_str[0..2])
                 .child(&file_id_str)
-                .write_str(&format!("content for {file_id_str}"))?;
+                .write_str(content)?;
         }
 
-        let result = service
-            .extract(&repo, temp_backup.path(), temp_output.path(), params)
+        let params = SearchParams::new(Some("test.domain".to_owned()), None, None, None, false);
+        let result = ExtractService::new()
+            .with_layout(OutputLayout::ByDomain)
+            .extract(
+                &repo,
+                &BlobRepositoryImpl::new(temp_backup.path()),
+                temp_output.path(),
+                params,
+            )
             .await?;
 
         assert_eq!(result.extracted_count, 2);
-        assert_eq!(result.skipped_count, 1);
-        assert!(result.errors.is_empty());
-
-        // Verify the extracted files exist
-        temp_output
-            .child("Documents")
-            .child("file1.txt")
-            .assert(path::exists());
         temp_output
-            .child("Music")
-            .child("song.mp3")
-            .assert(path::exists());
+            .child("HomeDomain/Library/Preferences/com.apple.test.plist")
+            .assert("home");
         temp_output
-            .child("Photos")
-            .child("image.jpg")
-            .assert(path::missing());
+            .child("AppDomain-com.apple.test/Library/Preferences/com.apple.test.plist")
+            .assert("app");
 
         Ok(())
     }
 
-    #[tokio::test]
-    async fn test_extract_service_nested_directory_structure() -> Result<()> {
-        let service = ExtractService::new();
-        let test_file = create_test_file_with_params(
-            "da627651e4e11a916ba8a9cd3f235e9a25a3b58e",
+    /// Creates two files of different domains with the same path, and their blobs
+    fn create_colliding_files(temp_backup: &TempDir) -> Result<Vec<File>> {
+        let home = create_test_file_with_params(
+            "da39a3ee5e6b4b0d3255bfef95601890afd80709",
+            "HomeDomain",
+            "Documents/test.txt",
+        )?
+        .with_file_metadata(FileMetadata::new(4, 0o100_644));
+        let app = create_test_file_with_params(
+            "356a192b7913b04c54574d18c28d46e6395428ab",
             "AppDomain-com.apple.test",
-            "Documents/Projects/MyApp/src/main.rs",
-        )?;
-        let repo = MockFileRepository::new(vec![test_file.clone()]);
+            "Documents/test.txt",
+        )?
+        .with_file_metadata(FileMetadata::new(3, 0o100_644));
+
+        for (file, content) in [(&home, "home"), (&app, "app")] {
+            let file_id_str = file.id().to_string();
+            temp_backup
+                .child(&file_id_str[0..2])
+                .child(&file_id_str)
+                .write_str(content)?;
+        }
+
+        Ok(vec![home, app])
+    }
+
+    #[tokio::test]
+    async fn test_extract_service_plan_writes_nothing() -> Result<()> {
+        let temp_backup = TempDir::new()?;
+        let mut files = create_colliding_files(&temp_backup)?;
+        files.push(create_test_file_with_params(
+            "77de68daecd823babbb58edb1c8e14d7106e83bb",
+            "HomeDomain",
+            "../escape.txt",
+        )?);
+        let repo = MockFileRepository::new(files);
+        let temp_dir = TempDir::new()?;
+        let output_dir = temp_dir.path().join("output");
+
         let params = SearchParams::new(Some("test.domain".to_owned()), None, None, None, false);
+        let plan = ExtractService::new()
+            .with_on_conflict(ConflictPolicy::Rename)
+            .plan(&repo, &output_dir, params)
+            .await?;
+
+        assert!(!output_dir.exists());
+        assert_eq!(plan.output_dir, output_dir);
+        let planned: Vec<_> = plan
+            .operations
+            .iter()
+            .map(|operation| (operation.destination.as_path(), operation.action))
+            .collect();
+        assert_eq!(
+            planned,
+            vec![
+                (Path::new("Documents/test.txt"), PlannedAction::Create),
+                (Path::new("Documents/test (1).txt"), PlannedAction::Rename),
+            ]
+        );
+        assert_eq!(plan.total_bytes(), 7);
+        assert_eq!(plan.rejected.len(), 1);
+        assert_eq!(plan.rejected[0].relative_path, "../escape.txt");
+        assert_eq!(plan.rejected[0].kind, ExtractErrorKind::UnsafePath);
+
+        Ok(())
+    }
 
+    #[tokio::test]
+    async fn test_extract_service_plan_conflicts_within_backup() -> Result<()> {
         let temp_backup = TempDir::new()?;
+        let repo = MockFileRepository::new(create_colliding_files(&temp_backup)?);
         let temp_output = TempDir::new()?;
 
-        // Create the source file
-        let file_id_str = test_file.id().to_string();
-        let prefix = &file_id_str[0..2];
-        temp_backup
-            .child(prefix)
-            .child(&file_id_str)
-            .write_str("fn main() { println!(\"Hello!\"); }")?;
+        let params = SearchParams::new(Some("test.domain".to_owned()), None, None, None, false);
+        let plan = ExtractService::new()
+            .with_on_conflict(ConflictPolicy::Skip)
+            .plan(&repo, temp_output.path(), params.clone())
+            .await?;
+        assert_eq!(plan.action_count(PlannedAction::Create), 1);
+        assert_eq!(plan.action_count(PlannedAction::Keep), 1);
+        assert_eq!(plan.domain_counts().len(), 1);
 
-        let result = service
-            .extract(&repo, temp_backup.path(), temp_output.path(), params)
+        let fail_plan = ExtractService::new()
+            .with_on_conflict(ConflictPolicy::Fail)
+            .plan(&repo, temp_output.path(), params)
             .await?;
+        assert_eq!(fail_plan.operations.len(), 1);
+        assert_eq!(fail_plan.rejected.len(), 1);
+        assert!(
+            fail_plan.rejected[0]
+                .reason
+                .contains("Destination already exists")
+        );
 
-        assert_eq!(result.extracted_count, 1);
-        assert_eq!(result.skipped_count, 0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_extract_service_execute_plan() -> Result<()> {
+        let temp_backup = TempDir::new()?;
+        let repo = MockFileRepository::new(create_colliding_files(&temp_backup)?);
+        let blobs = BlobRepositoryImpl::new(temp_backup.path());
+        let temp_output = TempDir::new()?;
+        let service = ExtractService::new().with_on_conflict(ConflictPolicy::Rename);
+
+        let params = SearchParams::new(Some("test.domain".to_owned()), None, None, None, false);
+        let plan = service.plan(&repo, temp_output.path(), params).await?;
+        let saved = temp_output.child("plan.json");
+        saved.write_str(&serde_json::to_string(&plan)?)?;
+
+        // The plan is executed as saved, regardless of the settings of the service
+        let result = ExtractService::new()
+            .execute(&repo, &blobs, &ExtractPlan::load(saved.path())?)
+            .await?;
+
+        assert_eq!(result.extracted_count, 2);
         assert!(result.errors.is_empty());
+        assert_eq!(result.status_count(ExtractStatus::Renamed), 1);
+        temp_output.child("Documents/test.txt").assert("home");
+        temp_output.child("Documents/test (1).txt").assert("app");
 
-        // Verify nested directory structure is created correctly
-        temp_output
-            .child("Documents")
-            .child("Projects")
-            .child("MyApp")
-            .child("src")
-            .child("main.rs")
-            .assert("fn main() { println!(\"Hello!\"); }");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_extract_service_execute_stale_plan() -> Result<()> {
+        let temp_backup = TempDir::new()?;
+        let repo = MockFileRepository::new(create_colliding_files(&temp_backup)?);
+        let blobs = BlobRepositoryImpl::new(temp_backup.path());
+        let temp_output = TempDir::new()?;
+        let service = ExtractService::new().with_on_conflict(ConflictPolicy::Skip);
+
+        let params = SearchParams::new(Some("test.domain".to_owned()), None, None, None, false);
+        let mut plan = service.plan(&repo, temp_output.path(), params).await?;
+
+        // A file appearing at a planned destination is not overwritten
+        temp_output.child("Documents/test.txt").write_str("new")?;
+        // Edited plans cannot write outside the output directory
+        plan.operations[1].action = PlannedAction::Create;
+        plan.operations[1].destination = PathBuf::from("../escape.txt");
+
+        let result = service.execute(&repo, &blobs, &plan).await?;
+
+        assert_eq!(result.extracted_count, 0);
+        assert_eq!(result.errors.len(), 2);
+        assert_eq!(result.errors[0].kind, ExtractErrorKind::Failed);
+        assert_eq!(result.errors[1].kind, ExtractErrorKind::UnsafePath);
+        temp_output.child("Documents/test.txt").assert("new");
+        assert!(!temp_output.path().join("../escape.txt").exists());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_extract_service_parallel_jobs() -> Result<()> {
+        let temp_backup = TempDir::new()?;
+        let mut files = create_colliding_files(&temp_backup)?;
+        for index in 0..16_u64 {
+            let file_id_str = format!("{index:040x}");
+            files.push(
+                create_test_file_with_params(
+                    &file_id_str,
+                    "HomeDomain",
+                    &format!("Library/file{index}.txt"),
+                )?
+                .with_file_metadata(FileMetadata::new(index, 0o100_644)),
+            );
+            temp_backup
+                .child(&file_id_str[0..2])
+                .child(&file_id_str)
+                .write_str(&"x".repeat(usize::try_from(index)?))?;
+        }
+        let repo = MockFileRepository::new(files);
+        let blobs = BlobRepositoryImpl::new(temp_backup.path());
+        let params = SearchParams::new(Some("test.domain".to_owned()), None, None, None, false);
+
+        let mut outcomes = Vec::new();
+        for jobs in [
+            NonZeroUsize::MIN,
+            NonZeroUsize::new(4).context("zero jobs")?,
+        ] {
+            let temp_output = TempDir::new()?;
+            let reported = Arc::new(Mutex::new(Vec::new()));
+            let sink = Arc::clone(&reported);
+            let service = ExtractService::new()
+                .with_on_conflict(ConflictPolicy::Rename)
+                .with_jobs(jobs)
+                .with_progress(move |progress| {
+                    sink.lock()
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .push(progress);
+                });
+
+            let result = service
+                .extract(&repo, &blobs, temp_output.path(), params.clone())
+                .await?;
+
+            assert_eq!(result.extracted_count, 18);
+            assert!(result.errors.is_empty());
+            temp_output
+                .child("Library/file15.txt")
+                .assert("x".repeat(15));
+
+            // Progress starts empty and ends with every file processed
+            let reported = reported
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .clone();
+            assert_eq!(reported.len(), 19);
+            assert_eq!(reported[0].completed_files, 0);
+            assert_eq!(
+                reported.last().copied(),
+                Some(ExtractProgress {
+                    completed_files: 18,
+                    total_files: 18,
+                    completed_bytes: 127,
+                    total_bytes: 127,
+                })
+            );
+            assert!(
+                reported
+                    .windows(2)
+                    .all(|pair| pair[0].completed_bytes <= pair[1].completed_bytes)
+            );
+
+            let destinations: Vec<_> = result
+                .files
+                .iter()
+                .map(|outcome| {
+                    let destination = outcome
+                        .destination
+                        .as_ref()
+                        .and_then(|path| path.strip_prefix(temp_output.path()).ok());
+                    (
+                        outcome.file_id.clone(),
+                        outcome.status,
+                        destination.map(Path::to_path_buf),
+                    )
+                })
+                .collect();
+            outcomes.push(destinations);
+        }
+
+        // Results do not depend on the number of jobs
+        assert_eq!(outcomes[0], outcomes[1]);
+        assert_eq!(
+            outcomes[1][1].2.as_deref(),
+            Some(Path::new("Documents/test (1).txt"))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_stages() -> Result<()> {
+        let metadata = FileMetadata::new(1, 0o100_644);
+        let operations = [
+            ("Documents", FileFlags::DIRECTORY),
+            ("Documents/a.txt", FileFlags::REGULAR_FILE),
+            ("Documents/b.txt", FileFlags::REGULAR_FILE),
+            // Written below a file of the stage
+            ("Documents/a.txt/c.txt", FileFlags::REGULAR_FILE),
+            ("Library", FileFlags::DIRECTORY),
+            // Symbolic links get a stage of their own
+            ("Library/link", FileFlags::SYMBOLIC_LINK),
+            ("Library/d.txt", FileFlags::REGULAR_FILE),
+            // A file where a directory of the stage is written below
+            ("Library", FileFlags::REGULAR_FILE),
+        ]
+        .into_iter()
+        .map(|(path, flags)| {
+            let file = create_entry(
+                "3d0d7e5fb2ce288813306e4d4636395e047a3d28",
+                path,
+                flags,
+                metadata.clone(),
+            )?;
+            Ok(PlannedOperation::new(
+                &file,
+                PathBuf::from(path),
+                PlannedAction::Create,
+            ))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+        assert_eq!(stages(&operations), vec![0..3, 3..5, 5..6, 6..7, 7..8]);
+        assert!(stages(&[]).is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_extract_service_hashes() -> Result<()> {
+        let directory = create_entry(
+            "356a192b7913b04c54574d18c28d46e6395428ab",
+            "Documents",
+            FileFlags::DIRECTORY,
+            FileMetadata::new(0, 0o040_755),
+        )?;
+        let repo = MockFileRepository::new(vec![directory, create_test_file()?]);
+        let params = SearchParams::new(Some("test.domain".to_owned()), None, None, None, false);
+
+        let temp_backup = TempDir::new()?;
+        let temp_output = TempDir::new()?;
+        temp_backup
+            .child("da")
+            .child("da39a3ee5e6b4b0d3255bfef95601890afd80709")
+            .write_str("abc")?;
+
+        let result = ExtractService::new()
+            .with_hashes(vec![HashAlgorithm::Sha256, HashAlgorithm::Md5])
+            .extract(
+                &repo,
+                &BlobRepositoryImpl::new(temp_backup.path()),
+                temp_output.path(),
+                params,
+            )
+            .await?;
+
+        assert_eq!(result.extracted_count, 1);
+        temp_output.child("Documents/test.txt").assert("abc");
+
+        // Only the copied regular file is hashed
+        let hashes: Vec<_> = result.files.iter().map(|file| &file.hashes).collect();
+        assert_eq!(
+            hashes,
+            vec![
+                &None,
+                &Some(FileHashes {
+                    size: 3,
+                    digests: BTreeMap::from([
+                        (
+                            HashAlgorithm::Sha256,
+                            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+                                .to_owned()
+                        ),
+                        (
+                            HashAlgorithm::Md5,
+                            "900150983cd24fb0d6963f7d28e17f72".to_owned()
+                        ),
+                    ]),
+                }),
+            ]
+        );
 
         Ok(())
     }
@@ -442,22 +2628,30 @@ mod tests {
     fn test_extract_result_equality() {
         let result1 = ExtractResult {
             extracted_count: 1,
+            directory_count: 0,
+            symlink_count: 0,
             skipped_count: 2,
             errors: vec![ExtractError {
                 file_id: "test123".to_owned(),
                 relative_path: "test/path.txt".to_owned(),
+                kind: ExtractErrorKind::Failed,
                 error: "Test error".to_owned(),
             }],
+            files: vec![],
         };
 
         let result2 = ExtractResult {
             extracted_count: 1,
+            directory_count: 0,
+            symlink_count: 0,
             skipped_count: 2,
             errors: vec![ExtractError {
                 file_id: "test123".to_owned(),
                 relative_path: "test/path.txt".to_owned(),
+                kind: ExtractErrorKind::Failed,
                 error: "Test error".to_owned(),
             }],
+            files: vec![],
         };
 
         assert_eq!(result1, result2);
@@ -472,12 +2666,14 @@ mod tests {
         let error1 = ExtractError {
             file_id: "abc123".to_owned(),
             relative_path: "Documents/test.txt".to_owned(),
+            kind: ExtractErrorKind::Failed,
             error: "Permission denied".to_owned(),
         };
 
         let error2 = ExtractError {
             file_id: "abc123".to_owned(),
             relative_path: "Documents/test.txt".to_owned(),
+            kind: ExtractErrorKind::Failed,
             error: "Permission denied".to_owned(),
         };
 
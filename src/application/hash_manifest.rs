@@ -0,0 +1,555 @@
+//! Hash manifests recording the digests of extracted files
+
+use crate::application::extract_service::ExtractResult;
+use anyhow::{Context as _, Result};
+use clap::ValueEnum;
+use md5::Md5;
+use serde::{Deserialize, Serialize};
+use sha1::Sha1;
+use sha2::Sha256;
+use sha2::digest::DynDigest;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Hash algorithm used for the digests of extracted files
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, ValueEnum, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum HashAlgorithm {
+    /// SHA-256
+    Sha256,
+    /// SHA-1
+    Sha1,
+    /// MD5
+    Md5,
+}
+
+impl HashAlgorithm {
+    /// Returns the name used in output
+    #[must_use]
+    #[inline]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Sha256 => "sha256",
+            Self::Sha1 => "sha1",
+            Self::Md5 => "md5",
+        }
+    }
+
+    /// Creates a hasher for this algorithm
+    fn hasher(self) -> Box<dyn DynDigest + Send> {
+        match self {
+            Self::Sha256 => Box::new(Sha256::default()),
+            Self::Sha1 => Box::new(Sha1::default()),
+            Self::Md5 => Box::new(Md5::default()),
+        }
+    }
+}
+
+/// Size and digests of the content of a file
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub struct FileHashes {
+    /// Size of the content in bytes
+    pub size: u64,
+    /// Lowercase hexadecimal digests, by algorithm
+    pub digests: BTreeMap<HashAlgorithm, String>,
+}
+
+impl FileHashes {
+    /// Hashes the content of a file on disk
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read
+    #[inline]
+    pub fn of_file(path: &Path, algorithms: &[HashAlgorithm]) -> Result<Self> {
+        let mut file = fs::File::open(path)
+            .with_context(|| format!("Failed to open file: {}", path.display()))?;
+        let mut writer = HashingWriter::new(io::sink(), algorithms);
+        io::copy(&mut file, &mut writer)
+            .with_context(|| format!("Failed to read file: {}", path.display()))?;
+        Ok(writer.finish())
+    }
+}
+
+/// Writer that hashes everything written through it
+///
+/// This lets content be hashed while it is copied, in a single read pass.
+pub(crate) struct HashingWriter<W> {
+    /// Destination of the content
+    inner: W,
+    /// Hashers of the requested algorithms
+    hashers: Vec<(HashAlgorithm, Box<dyn DynDigest + Send>)>,
+    /// Number of bytes written
+    size: u64,
+}
+
+impl<W: Write> HashingWriter<W> {
+    /// Creates a writer hashing with the given algorithms
+    pub(crate) fn new(inner: W, algorithms: &[HashAlgorithm]) -> Self {
+        Self {
+            inner,
+            hashers: algorithms
+                .iter()
+                .map(|&algorithm| (algorithm, algorithm.hasher()))
+                .collect(),
+            size: 0,
+        }
+    }
+
+    /// Returns the size and digests of the content written so far
+    pub(crate) fn finish(self) -> FileHashes {
+        FileHashes {
+            size: self.size,
+            digests: self
+                .hashers
+                .into_iter()
+                .map(|(algorithm, hasher)| (algorithm, hex(&hasher.finalize())))
+                .collect(),
+        }
+    }
+}
+
+impl<W: Write> Write for HashingWriter<W> {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        let content = buf.get(..written).unwrap_or_default();
+        for (_, hasher) in &mut self.hashers {
+            hasher.update(content);
+        }
+        self.size = self
+            .size
+            .saturating_add(u64::try_from(written).unwrap_or(u64::MAX));
+        Ok(written)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Formats bytes as lowercase hexadecimal
+pub(crate) fn hex(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::new(), |mut output, byte| {
+        let _ = write!(output, "{byte:02x}");
+        output
+    })
+}
+
+/// File format of a hash manifest
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+#[non_exhaustive]
+pub enum ManifestFormat {
+    /// Comma-separated values with a header row
+    #[default]
+    Csv,
+    /// A single JSON document
+    Json,
+    /// Digital Forensics XML
+    Dfxml,
+}
+
+impl ManifestFormat {
+    /// Returns the usual file extension of the format
+    #[must_use]
+    #[inline]
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Json => "json",
+            Self::Dfxml => "xml",
+        }
+    }
+}
+
+/// Digests of the files written by an extraction
+///
+/// Manifests can be saved as CSV, JSON or DFXML. CSV and JSON manifests can be loaded
+/// again to check an extracted tree with `VerifyService::verify_extraction`.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct HashManifest {
+    /// Hashed files, in the order they were extracted
+    pub entries: Vec<HashManifestEntry>,
+}
+
+/// A hashed file of a manifest
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct HashManifestEntry {
+    /// File ID in the backup
+    pub file_id: String,
+    /// Domain of the file
+    pub domain: String,
+    /// Relative path of the file
+    pub relative_path: String,
+    /// Destination relative to the output directory
+    pub destination: PathBuf,
+    /// Size of the extracted file in bytes
+    pub size: u64,
+    /// SHA-256 digest, if computed
+    pub sha256: Option<String>,
+    /// SHA-1 digest, if computed
+    pub sha1: Option<String>,
+    /// MD5 digest, if computed
+    pub md5: Option<String>,
+}
+
+impl HashManifestEntry {
+    /// Returns the digest computed with the given algorithm
+    #[must_use]
+    #[inline]
+    pub fn digest(&self, algorithm: HashAlgorithm) -> Option<&str> {
+        match algorithm {
+            HashAlgorithm::Sha256 => self.sha256.as_deref(),
+            HashAlgorithm::Sha1 => self.sha1.as_deref(),
+            HashAlgorithm::Md5 => self.md5.as_deref(),
+        }
+    }
+
+    /// Returns the algorithms the entry has digests of
+    #[must_use]
+    #[inline]
+    pub fn algorithms(&self) -> Vec<HashAlgorithm> {
+        [
+            HashAlgorithm::Sha256,
+            HashAlgorithm::Sha1,
+            HashAlgorithm::Md5,
+        ]
+        .into_iter()
+        .filter(|&algorithm| self.digest(algorithm).is_some())
+        .collect()
+    }
+}
+
+impl HashManifest {
+    /// Collects the digests of the files an extraction wrote to `output_dir`
+    ///
+    /// Only files that were hashed while they were copied are included.
+    #[must_use]
+    #[inline]
+    pub fn from_result(result: &ExtractResult, output_dir: &Path) -> Self {
+        let entries = result
+            .files
+            .iter()
+            .filter_map(|outcome| {
+                let hashes = outcome.hashes.as_ref()?;
+                let destination = outcome.destination.as_ref()?;
+                let digest = |algorithm| hashes.digests.get(&algorithm).cloned();
+                Some(HashManifestEntry {
+                    file_id: outcome.file_id.clone(),
+                    domain: outcome.domain.clone(),
+                    relative_path: outcome.relative_path.clone(),
+                    destination: destination
+                        .strip_prefix(output_dir)
+                        .unwrap_or(destination)
+                        .to_path_buf(),
+                    size: hashes.size,
+                    sha256: digest(HashAlgorithm::Sha256),
+                    sha1: digest(HashAlgorithm::Sha1),
+                    md5: digest(HashAlgorithm::Md5),
+                })
+            })
+            .collect();
+
+        Self { entries }
+    }
+
+    /// Formats the manifest in the given format
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the manifest cannot be serialized
+    #[inline]
+    pub fn format(&self, format: ManifestFormat) -> Result<String> {
+        match format {
+            ManifestFormat::Csv => {
+                let mut writer = csv::Writer::from_writer(Vec::new());
+                for entry in &self.entries {
+                    writer.serialize(entry)?;
+                }
+                Ok(String::from_utf8(writer.into_inner()?)?)
+            }
+            ManifestFormat::Json => Ok(serde_json::to_string_pretty(self)?),
+            ManifestFormat::Dfxml => self.to_dfxml(),
+        }
+    }
+
+    /// Saves the manifest in the given format
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the manifest cannot be serialized or written
+    #[inline]
+    pub fn save(&self, path: &Path, format: ManifestFormat) -> Result<()> {
+        fs::write(path, self.format(format)?)
+            .with_context(|| format!("Failed to write hash manifest: {}", path.display()))
+    }
+
+    /// Reads a manifest saved as CSV or JSON
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or is not a CSV or JSON manifest
+    #[inline]
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read hash manifest: {}", path.display()))?;
+
+        let parsed = match content.trim_start().chars().next() {
+            Some('{') => serde_json::from_str(&content).map_err(anyhow::Error::from),
+            Some('<') => Err(anyhow::anyhow!(
+                "DFXML manifests cannot be loaded; save the manifest as CSV or JSON"
+            )),
+            _ => csv::Reader::from_reader(content.as_bytes())
+                .deserialize()
+                .collect::<Result<_, _>>()
+                .map(|entries| Self { entries })
+                .map_err(anyhow::Error::from),
+        };
+        parsed.with_context(|| format!("Failed to parse hash manifest: {}", path.display()))
+    }
+
+    /// Formats the manifest as Digital Forensics XML
+    ///
+    /// Each file is a `fileobject` named after its destination. The origin of the file in
+    /// the backup is recorded in elements of the `idig` namespace.
+    fn to_dfxml(&self) -> Result<String> {
+        let mut output = String::from(concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<dfxml xmlns=\"http://www.forensicswiki.org/wiki/Category:Digital_Forensics_XML\"",
+            " xmlns:idig=\"https://github.com/akiomik/idig\" version=\"1.0\">\n",
+        ));
+        writeln!(
+            output,
+            "  <creator>\n    <program>idig</program>\n    <version>{}</version>\n  </creator>",
+            env!("CARGO_PKG_VERSION")
+        )?;
+
+        for entry in &self.entries {
+            output.push_str("  <fileobject>\n");
+            writeln!(
+                output,
+                "    <filename>{}</filename>",
+                escape_xml(&entry.destination.to_string_lossy())
+            )?;
+            writeln!(output, "    <filesize>{}</filesize>", entry.size)?;
+            writeln!(
+                output,
+                "    <idig:file_id>{}</idig:file_id>",
+                escape_xml(&entry.file_id)
+            )?;
+            writeln!(
+                output,
+                "    <idig:domain>{}</idig:domain>",
+                escape_xml(&entry.domain)
+            )?;
+            writeln!(
+                output,
+                "    <idig:relative_path>{}</idig:relative_path>",
+                escape_xml(&entry.relative_path)
+            )?;
+            for algorithm in entry.algorithms() {
+                writeln!(
+                    output,
+                    "    <hashdigest type=\"{}\">{}</hashdigest>",
+                    algorithm.name(),
+                    escape_xml(entry.digest(algorithm).unwrap_or_default())
+                )?;
+            }
+            output.push_str("  </fileobject>\n");
+        }
+
+        output.push_str("</dfxml>\n");
+        Ok(output)
+    }
+}
+
+/// Escapes text for use in XML content and attributes
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for character in text.chars() {
+        match character {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(character),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::extract_service::{ExtractStatus, FileOutcome};
+    use assert_fs::TempDir;
+    use assert_fs::prelude::*;
+    use pretty_assertions::assert_eq;
+
+    const SHA256_ABC: &str = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+    const SHA1_ABC: &str = "a9993e364706816aba3e25717850c26c9cd0d89d";
+    const MD5_ABC: &str = "900150983cd24fb0d6963f7d28e17f72";
+
+    fn create_manifest() -> HashManifest {
+        HashManifest {
+            entries: vec![HashManifestEntry {
+                file_id: "da39a3ee5e6b4b0d3255bfef95601890afd80709".to_owned(),
+                domain: "HomeDomain".to_owned(),
+                relative_path: "Library/Notes & <Drafts>.txt".to_owned(),
+                destination: PathBuf::from("Library/Notes & <Drafts>.txt"),
+                size: 3,
+                sha256: Some(SHA256_ABC.to_owned()),
+                sha1: None,
+                md5: Some(MD5_ABC.to_owned()),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_hashing_writer() -> Result<()> {
+        let mut writer = HashingWriter::new(
+            Vec::new(),
+            &[
+                HashAlgorithm::Sha256,
+                HashAlgorithm::Sha1,
+                HashAlgorithm::Md5,
+            ],
+        );
+        writer.write_all(b"a")?;
+        writer.write_all(b"bc")?;
+
+        assert_eq!(writer.inner, b"abc");
+        assert_eq!(
+            writer.finish(),
+            FileHashes {
+                size: 3,
+                digests: BTreeMap::from([
+                    (HashAlgorithm::Sha256, SHA256_ABC.to_owned()),
+                    (HashAlgorithm::Sha1, SHA1_ABC.to_owned()),
+                    (HashAlgorithm::Md5, MD5_ABC.to_owned()),
+                ]),
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_hashes_of_file() -> Result<()> {
+        let temp = TempDir::new()?;
+        temp.child("abc.txt").write_str("abc")?;
+
+        let hashes = FileHashes::of_file(&temp.path().join("abc.txt"), &[HashAlgorithm::Sha1])?;
+
+        assert_eq!(hashes.size, 3);
+        assert_eq!(
+            hashes.digests,
+            BTreeMap::from([(HashAlgorithm::Sha1, SHA1_ABC.to_owned())])
+        );
+        assert!(FileHashes::of_file(&temp.path().join("missing"), &[]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_result() {
+        let output_dir = Path::new("/output");
+        let outcome = |file_id: &str, hashes: Option<FileHashes>| FileOutcome {
+            file_id: file_id.to_owned(),
+            domain: "HomeDomain".to_owned(),
+            relative_path: "Library/Notes & <Drafts>.txt".to_owned(),
+            destination: Some(output_dir.join("Library/Notes & <Drafts>.txt")),
+            status: ExtractStatus::Extracted,
+            hashes,
+        };
+        let result = ExtractResult {
+            files: vec![
+                outcome(
+                    "da39a3ee5e6b4b0d3255bfef95601890afd80709",
+                    Some(FileHashes {
+                        size: 3,
+                        digests: BTreeMap::from([
+                            (HashAlgorithm::Sha256, SHA256_ABC.to_owned()),
+                            (HashAlgorithm::Md5, MD5_ABC.to_owned()),
+                        ]),
+                    }),
+                ),
+                // Files that were not hashed, such as directories, are left out
+                outcome("356a192b7913b04c54574d18c28d46e6395428ab", None),
+            ],
+            ..ExtractResult::default()
+        };
+
+        assert_eq!(
+            HashManifest::from_result(&result, output_dir),
+            create_manifest()
+        );
+    }
+
+    #[test]
+    fn test_save_and_load() -> Result<()> {
+        let temp = TempDir::new()?;
+        let manifest = create_manifest();
+
+        for format in [ManifestFormat::Csv, ManifestFormat::Json] {
+            let path = temp.path().join(format!("manifest.{}", format.extension()));
+            manifest.save(&path, format)?;
+
+            assert_eq!(HashManifest::load(&path)?, manifest);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_format_csv() -> Result<()> {
+        assert_eq!(
+            create_manifest().format(ManifestFormat::Csv)?,
+            format!(
+                "file_id,domain,relative_path,destination,size,sha256,sha1,md5\n\
+                 da39a3ee5e6b4b0d3255bfef95601890afd80709,HomeDomain,\
+                 Library/Notes & <Drafts>.txt,Library/Notes & <Drafts>.txt,3,{SHA256_ABC},,{MD5_ABC}\n"
+            )
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_format_dfxml() -> Result<()> {
+        let output = create_manifest().format(ManifestFormat::Dfxml)?;
+
+        assert!(output.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<dfxml "));
+        assert!(output.contains(
+            "    <filename>Library/Notes &amp; &lt;Drafts&gt;.txt</filename>\n\
+             \x20   <filesize>3</filesize>\n"
+        ));
+        assert!(output.contains(&format!(
+            "    <hashdigest type=\"sha256\">{SHA256_ABC}</hashdigest>\n\
+             \x20   <hashdigest type=\"md5\">{MD5_ABC}</hashdigest>\n\
+             \x20 </fileobject>\n"
+        )));
+        assert!(!output.contains("type=\"sha1\""));
+        assert!(output.ends_with("</dfxml>\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_dfxml() -> Result<()> {
+        let temp = TempDir::new()?;
+        let path = temp.path().join("manifest.xml");
+        create_manifest().save(&path, ManifestFormat::Dfxml)?;
+
+        let error = HashManifest::load(&path).err().map(|e| format!("{e:#}"));
+
+        assert!(error.is_some_and(|e| e.contains("DFXML manifests cannot be loaded")));
+        Ok(())
+    }
+}
@@ -0,0 +1,150 @@
+//! Rows and records of the conversations written by a messages export
+
+use serde::Serialize;
+use tabled::Tabled;
+
+use crate::application::display_service::Listing;
+use crate::application::messages_service::ExportedConversation;
+
+/// Represents an exported conversation for table display
+#[derive(Tabled)]
+pub struct ExportedConversationRow {
+    #[tabled(rename = "Conversation")]
+    name: String,
+    #[tabled(rename = "Participants")]
+    participants: String,
+    #[tabled(rename = "Messages")]
+    message_count: usize,
+    #[tabled(rename = "Attachments")]
+    attachments: String,
+    #[tabled(rename = "Transcript")]
+    transcript: String,
+}
+
+impl ExportedConversationRow {
+    fn new(conversation: &ExportedConversation) -> Self {
+        let mut problems = Vec::new();
+        if conversation.missing_attachment_count > 0 {
+            problems.push(format!("{} missing", conversation.missing_attachment_count));
+        }
+        if conversation.failed_attachment_count > 0 {
+            problems.push(format!("{} failed", conversation.failed_attachment_count));
+        }
+        let attachments = if problems.is_empty() {
+            conversation.attachment_count.to_string()
+        } else {
+            format!(
+                "{} ({})",
+                conversation.attachment_count,
+                problems.join(", ")
+            )
+        };
+        Self {
+            name: conversation
+                .display_name
+                .as_ref()
+                .unwrap_or(&conversation.chat_identifier)
+                .clone(),
+            participants: conversation.participants.join(", "),
+            message_count: conversation.message_count,
+            attachments,
+            transcript: conversation.transcript.display().to_string(),
+        }
+    }
+}
+
+/// Serialized form of an exported conversation
+#[derive(Serialize)]
+struct ExportedConversationRecord<'a> {
+    chat_identifier: &'a str,
+    display_name: Option<&'a str>,
+    /// Comma-separated handles of the other participants
+    participants: String,
+    message_count: usize,
+    attachment_count: usize,
+    missing_attachment_count: usize,
+    failed_attachment_count: usize,
+    transcript: String,
+}
+
+impl<'a> ExportedConversationRecord<'a> {
+    fn new(conversation: &'a ExportedConversation) -> Self {
+        Self {
+            chat_identifier: &conversation.chat_identifier,
+            display_name: conversation.display_name.as_deref(),
+            participants: conversation.participants.join(","),
+            message_count: conversation.message_count,
+            attachment_count: conversation.attachment_count,
+            missing_attachment_count: conversation.missing_attachment_count,
+            failed_attachment_count: conversation.failed_attachment_count,
+            transcript: conversation.transcript.display().to_string(),
+        }
+    }
+}
+
+impl Listing for ExportedConversation {
+    type Row = ExportedConversationRow;
+
+    const EMPTY: &'static str = "No conversations found.";
+
+    fn row(&self) -> Self::Row {
+        ExportedConversationRow::new(self)
+    }
+
+    fn record(&self) -> impl Serialize {
+        ExportedConversationRecord::new(self)
+    }
+
+    fn summary(conversations: &[Self]) -> String {
+        format!("Exported {} conversation(s)", conversations.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use super::*;
+    use crate::application::{DelimitedFormatter, OutputFormatter as _, TableFormatter};
+    use pretty_assertions::assert_eq;
+
+    fn create_exported_conversation() -> ExportedConversation {
+        ExportedConversation {
+            chat_identifier: "chat123".to_owned(),
+            display_name: Some("Family".to_owned()),
+            participants: vec!["+15550001".to_owned(), "a@example.com".to_owned()],
+            message_count: 42,
+            attachment_count: 3,
+            missing_attachment_count: 1,
+            failed_attachment_count: 2,
+            transcript: "out/chat123/transcript.txt".into(),
+        }
+    }
+
+    #[test]
+    fn test_format_messages_export() -> Result<()> {
+        let formatter = TableFormatter::new();
+
+        let output = formatter.format_messages_export(&[create_exported_conversation()])?;
+
+        assert!(output.contains("Family"));
+        assert!(output.contains("+15550001, a@example.com"));
+        assert!(output.contains("3 (1 missing, 2 failed)"));
+        assert!(output.ends_with("Exported 1 conversation(s)"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_csv_format_messages_export() -> Result<()> {
+        let formatter = DelimitedFormatter::csv();
+
+        let output = formatter.format_messages_export(&[create_exported_conversation()])?;
+
+        assert_eq!(
+            output,
+            "chat_identifier,display_name,participants,message_count,attachment_count,missing_attachment_count,failed_attachment_count,transcript\n\
+             chat123,Family,\"+15550001,a@example.com\",42,3,1,2,out/chat123/transcript.txt"
+        );
+        Ok(())
+    }
+}
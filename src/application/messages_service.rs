@@ -0,0 +1,756 @@
+//! Messages service for exporting iMessage and SMS conversations
+
+use crate::application::export_util::{UniqueNames, escape_html, file_name};
+use crate::domain::entities::{Attachment, Conversation, File, Message};
+use crate::domain::queries::FileQuery;
+use crate::domain::repositories::{BlobRepository, FileRepository, MessageRepository};
+use anyhow::{Context as _, Result};
+use clap::ValueEnum;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Domain holding message attachments
+const ATTACHMENT_DOMAIN: &str = "MediaDomain";
+
+/// Directory of message attachments in their domain
+const ATTACHMENT_DIR: &str = "Library/SMS/Attachments/";
+
+/// Format of exported transcripts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+#[non_exhaustive]
+pub enum TranscriptFormat {
+    /// Plain text
+    #[default]
+    Text,
+    /// A web page showing image attachments inline
+    Html,
+    /// A JSON document
+    Json,
+}
+
+impl TranscriptFormat {
+    /// Returns the file extension of transcripts
+    #[must_use]
+    #[inline]
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Self::Text => "txt",
+            Self::Html => "html",
+            Self::Json => "json",
+        }
+    }
+}
+
+/// A conversation written to the output directory
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ExportedConversation {
+    /// Identifier of the chat
+    pub chat_identifier: String,
+    /// Name given to a group chat
+    pub display_name: Option<String>,
+    /// Handles of the other participants
+    pub participants: Vec<String>,
+    /// Number of messages
+    pub message_count: usize,
+    /// Number of attachments copied next to the transcript
+    pub attachment_count: usize,
+    /// Number of attachments whose file is not in the backup
+    pub missing_attachment_count: usize,
+    /// Number of attachments whose file could not be read or copied
+    pub failed_attachment_count: usize,
+    /// Location of the transcript
+    pub transcript: PathBuf,
+}
+
+/// Serialized form of a transcript
+#[derive(Serialize)]
+struct TranscriptRecord<'a> {
+    chat_identifier: &'a str,
+    display_name: Option<&'a str>,
+    service: Option<&'a str>,
+    participants: &'a [String],
+    messages: Vec<TranscriptMessageRecord<'a>>,
+}
+
+/// Serialized form of a message in a transcript
+#[derive(Serialize)]
+struct TranscriptMessageRecord<'a> {
+    id: i64,
+    guid: &'a str,
+    date: Option<String>,
+    sender: Option<&'a str>,
+    is_from_me: bool,
+    text: Option<&'a str>,
+    service: Option<&'a str>,
+    attachments: Vec<TranscriptAttachmentRecord<'a>>,
+}
+
+/// Serialized form of an attachment in a transcript
+#[derive(Serialize)]
+struct TranscriptAttachmentRecord<'a> {
+    name: Option<&'a str>,
+    mime_type: Option<&'a str>,
+    total_bytes: u64,
+    /// Location of the copy relative to the transcript, if it was copied
+    path: Option<&'a str>,
+}
+
+/// Copies of attachments by row ID, `None` for attachments that could not be copied
+///
+/// Attachments missing from the map are not in the backup.
+type Copies = HashMap<i64, Option<String>>;
+
+/// Service for exporting the conversations of the Messages app
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct MessagesService {
+    /// Format of the transcripts
+    format: TranscriptFormat,
+    /// Whether to copy attachments next to the transcripts
+    attachments: bool,
+}
+
+impl MessagesService {
+    /// Creates a new `MessagesService` writing text transcripts with attachments
+    #[must_use]
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            format: TranscriptFormat::Text,
+            attachments: true,
+        }
+    }
+
+    /// Sets the format of the transcripts
+    #[must_use]
+    #[inline]
+    pub const fn with_format(mut self, format: TranscriptFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Sets whether to copy attachments next to the transcripts
+    #[must_use]
+    #[inline]
+    pub const fn with_attachments(mut self, attachments: bool) -> Self {
+        self.attachments = attachments;
+        self
+    }
+
+    /// Exports every conversation to a directory of its own in the output directory
+    ///
+    /// Each directory holds a `transcript.<ext>` file and an `attachments` directory with
+    /// the attachments found in the backup. Existing files are overwritten. Attachments that
+    /// cannot be read or copied are counted as failed, and the export goes on.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a repository fails or an output file cannot be written
+    #[allow(
+        clippy::future_not_send,
+        reason = "Repository trait doesn't guarantee Send futures"
+    )]
+    #[inline]
+    pub async fn export<M: MessageRepository, F: FileRepository, B: BlobRepository>(
+        &self,
+        messages: &M,
+        files: &F,
+        blobs: &B,
+        output_dir: &Path,
+    ) -> Result<Vec<ExportedConversation>> {
+        let conversations = messages.find_conversations().await?;
+
+        let attachment_files: HashMap<String, File> = if self.attachments {
+            let query = FileQuery::and(vec![
+                FileQuery::domain_exact(ATTACHMENT_DOMAIN),
+                FileQuery::path_contains(ATTACHMENT_DIR),
+            ]);
+            files
+                .search(query)
+                .await?
+                .into_iter()
+                .map(|file| (file.relative_path().value().to_owned(), file))
+                .collect()
+        } else {
+            HashMap::new()
+        };
+
+        let mut names = UniqueNames::default();
+        let mut exported = Vec::with_capacity(conversations.len());
+        for conversation in &conversations {
+            let name = file_name(&conversation.chat_identifier)
+                .unwrap_or_else(|| format!("chat-{}", conversation.id));
+            let name = names.claim(name, conversation.id);
+
+            let dir = output_dir.join(name);
+            exported.push(self.export_conversation(
+                conversation,
+                &attachment_files,
+                blobs,
+                &dir,
+            )?);
+        }
+
+        Ok(exported)
+    }
+
+    /// Writes the transcript and attachments of a conversation into a directory
+    fn export_conversation<B: BlobRepository>(
+        &self,
+        conversation: &Conversation,
+        attachment_files: &HashMap<String, File>,
+        blobs: &B,
+        dir: &Path,
+    ) -> Result<ExportedConversation> {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create directory: {}", dir.display()))?;
+
+        let mut copies = Copies::new();
+        let mut attachment_count: usize = 0;
+        let mut missing_attachment_count: usize = 0;
+        let mut failed_attachment_count: usize = 0;
+        if self.attachments {
+            for attachment in conversation
+                .messages
+                .iter()
+                .flat_map(|message| &message.attachments)
+            {
+                match Self::copy_attachment(attachment, attachment_files, blobs, dir) {
+                    Ok(Some(path)) => {
+                        attachment_count = attachment_count.saturating_add(1);
+                        copies.insert(attachment.id, Some(path));
+                    }
+                    Ok(None) => {
+                        missing_attachment_count = missing_attachment_count.saturating_add(1);
+                    }
+                    Err(_) => {
+                        failed_attachment_count = failed_attachment_count.saturating_add(1);
+                        copies.insert(attachment.id, None);
+                    }
+                }
+            }
+        }
+
+        let content = match self.format {
+            TranscriptFormat::Text => render_text(conversation, &copies),
+            TranscriptFormat::Html => render_html(conversation, &copies),
+            TranscriptFormat::Json => render_json(conversation, &copies)?,
+        };
+        let transcript = dir.join(format!("transcript.{}", self.format.extension()));
+        fs::write(&transcript, content)
+            .with_context(|| format!("Failed to write transcript: {}", transcript.display()))?;
+
+        Ok(ExportedConversation {
+            chat_identifier: conversation.chat_identifier.clone(),
+            display_name: conversation.display_name.clone(),
+            participants: conversation.participants.clone(),
+            message_count: conversation.messages.len(),
+            attachment_count,
+            missing_attachment_count,
+            failed_attachment_count,
+            transcript,
+        })
+    }
+
+    /// Copies an attachment into the `attachments` directory of a conversation
+    ///
+    /// Returns the location of the copy relative to the conversation directory, or `None`
+    /// if the backup does not hold the file. A partial copy is removed when copying fails.
+    fn copy_attachment<B: BlobRepository>(
+        attachment: &Attachment,
+        attachment_files: &HashMap<String, File>,
+        blobs: &B,
+        dir: &Path,
+    ) -> Result<Option<String>> {
+        let Some(file) = attachment
+            .media_path()
+            .and_then(|path| attachment_files.get(path))
+        else {
+            return Ok(None);
+        };
+        let Some(mut source) = blobs.open(file)? else {
+            return Ok(None);
+        };
+
+        // Prefixed with the row ID, as attachments of a conversation often share names
+        let name = attachment.name().and_then(file_name).unwrap_or_default();
+        let relative_path = format!("attachments/{}-{name}", attachment.id);
+        let path = dir.join(&relative_path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+        }
+
+        let mut destination = fs::File::create(&path)
+            .with_context(|| format!("Failed to create attachment: {}", path.display()))?;
+        if let Err(e) = io::copy(&mut source, &mut destination) {
+            drop(destination);
+            let _ = fs::remove_file(&path);
+            return Err(e)
+                .with_context(|| format!("Failed to copy attachment: {}", path.display()));
+        }
+
+        Ok(Some(relative_path))
+    }
+}
+
+impl Default for MessagesService {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns the title of a conversation
+fn title(conversation: &Conversation) -> &str {
+    conversation
+        .display_name
+        .as_deref()
+        .unwrap_or(&conversation.chat_identifier)
+}
+
+/// Returns the name of the sender of a message
+fn sender(message: &Message) -> &str {
+    if message.is_from_me {
+        "Me"
+    } else {
+        message.sender.as_deref().unwrap_or("Unknown")
+    }
+}
+
+/// Returns the time of a message for transcripts
+fn date(message: &Message) -> String {
+    message
+        .date
+        .map_or_else(|| "unknown time".to_owned(), |date| date.to_string())
+}
+
+/// Renders a conversation as plain text
+fn render_text(conversation: &Conversation, copies: &Copies) -> String {
+    let mut output = format!("Conversation: {}\n", title(conversation));
+    if let Some(service) = &conversation.service {
+        let _ = writeln!(output, "Service: {service}");
+    }
+    let _ = writeln!(
+        output,
+        "Participants: {}\n",
+        conversation.participants.join(", ")
+    );
+
+    for message in &conversation.messages {
+        let _ = writeln!(
+            output,
+            "[{}] {}: {}",
+            date(message),
+            sender(message),
+            message.text.as_deref().unwrap_or_default()
+        );
+        for attachment in &message.attachments {
+            let location = match copies.get(&attachment.id) {
+                Some(Some(path)) => path.as_str(),
+                Some(None) => "unreadable",
+                None => "not in backup",
+            };
+            let _ = writeln!(
+                output,
+                "    [Attachment: {} ({location})]",
+                attachment.name().unwrap_or("unnamed")
+            );
+        }
+    }
+
+    output
+}
+
+/// Renders a conversation as a web page
+fn render_html(conversation: &Conversation, copies: &Copies) -> String {
+    let title = escape_html(title(conversation));
+    let mut output = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n\
+         <style>\n.message {{ margin: 0.5em 0; }}\n.me {{ text-align: right; }}\n\
+         .meta {{ color: #888; font-size: 0.8em; }}\nimg {{ max-width: 320px; }}\n</style>\n\
+         </head>\n<body>\n<h1>{title}</h1>\n<p>Participants: {}</p>\n",
+        escape_html(&conversation.participants.join(", "))
+    );
+
+    for message in &conversation.messages {
+        let class = if message.is_from_me {
+            "message me"
+        } else {
+            "message"
+        };
+        let _ = writeln!(
+            output,
+            "<div class=\"{class}\">\n<div class=\"meta\">{} · {}</div>",
+            escape_html(sender(message)),
+            escape_html(&date(message))
+        );
+        if let Some(text) = &message.text {
+            let _ = writeln!(
+                output,
+                "<div class=\"text\">{}</div>",
+                escape_html(text).replace('\n', "<br>")
+            );
+        }
+        for attachment in &message.attachments {
+            let name = escape_html(attachment.name().unwrap_or("unnamed"));
+            let _ = match copies.get(&attachment.id) {
+                Some(Some(path))
+                    if attachment
+                        .mime_type
+                        .as_deref()
+                        .is_some_and(|mime_type| mime_type.starts_with("image/")) =>
+                {
+                    writeln!(
+                        output,
+                        "<div class=\"attachment\"><img src=\"{}\" alt=\"{name}\"></div>",
+                        escape_html(path)
+                    )
+                }
+                Some(Some(path)) => writeln!(
+                    output,
+                    "<div class=\"attachment\"><a href=\"{}\">{name}</a></div>",
+                    escape_html(path)
+                ),
+                Some(None) => writeln!(
+                    output,
+                    "<div class=\"attachment\">{name} (unreadable)</div>"
+                ),
+                None => writeln!(
+                    output,
+                    "<div class=\"attachment\">{name} (not in backup)</div>"
+                ),
+            };
+        }
+        output.push_str("</div>\n");
+    }
+
+    output.push_str("</body>\n</html>\n");
+    output
+}
+
+/// Renders a conversation as JSON
+fn render_json(conversation: &Conversation, copies: &Copies) -> Result<String> {
+    let record = TranscriptRecord {
+        chat_identifier: &conversation.chat_identifier,
+        display_name: conversation.display_name.as_deref(),
+        service: conversation.service.as_deref(),
+        participants: &conversation.participants,
+        messages: conversation
+            .messages
+            .iter()
+            .map(|message| TranscriptMessageRecord {
+                id: message.id,
+                guid: &message.guid,
+                date: message.date.map(|date| date.to_rfc3339()),
+                sender: message.sender.as_deref(),
+                is_from_me: message.is_from_me,
+                text: message.text.as_deref(),
+                service: message.service.as_deref(),
+                attachments: message
+                    .attachments
+                    .iter()
+                    .map(|attachment| TranscriptAttachmentRecord {
+                        name: attachment.name(),
+                        mime_type: attachment.mime_type.as_deref(),
+                        total_bytes: attachment.total_bytes,
+                        path: copies.get(&attachment.id).and_then(Option::as_deref),
+                    })
+                    .collect(),
+            })
+            .collect(),
+    };
+
+    Ok(serde_json::to_string_pretty(&record)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::value_objects::{Domain, FileFlags, FileId, RelativePath};
+    use crate::infrastructure::repositories::BlobRepositoryImpl;
+    use assert_fs::TempDir;
+    use assert_fs::prelude::*;
+    use pretty_assertions::assert_eq;
+
+    struct MockMessageRepository {
+        conversations: Vec<Conversation>,
+    }
+
+    impl MessageRepository for MockMessageRepository {
+        async fn find_conversations(&self) -> Result<Vec<Conversation>> {
+            Ok(self.conversations.clone())
+        }
+    }
+
+    // Mock repository returning every file
+    struct MockFileRepository {
+        files: Vec<File>,
+    }
+
+    impl FileRepository for MockFileRepository {
+        async fn search(&self, _query: FileQuery) -> Result<Vec<File>> {
+            Ok(self.files.clone())
+        }
+    }
+
+    // Blob repository failing to open one file, like a blob that cannot be decrypted
+    struct CorruptBlobRepository {
+        inner: BlobRepositoryImpl,
+        corrupt_id: FileId,
+    }
+
+    impl BlobRepository for CorruptBlobRepository {
+        fn locate(&self, file: &File) -> Option<PathBuf> {
+            self.inner.locate(file)
+        }
+
+        fn open(&self, file: &File) -> Result<Option<Box<dyn io::Read + Send>>> {
+            if *file.id() == self.corrupt_id {
+                anyhow::bail!("Failed to decrypt backup file");
+            }
+            self.inner.open(file)
+        }
+
+        fn list(&self) -> Result<Vec<(FileId, PathBuf)>> {
+            self.inner.list()
+        }
+    }
+
+    const ATTACHMENT_PATH: &str = "Library/SMS/Attachments/ab/11/A/IMG_0001.jpeg";
+
+    fn create_conversation(chat_identifier: &str) -> Result<Conversation> {
+        let attachment = |id: i64, filename: &str| Attachment {
+            id,
+            filename: Some(filename.to_owned()),
+            mime_type: Some("image/jpeg".to_owned()),
+            transfer_name: None,
+            total_bytes: 5,
+        };
+
+        Ok(Conversation {
+            id: 1,
+            chat_identifier: chat_identifier.to_owned(),
+            display_name: None,
+            service: Some("iMessage".to_owned()),
+            participants: vec!["+15550001".to_owned()],
+            messages: vec![
+                Message {
+                    id: 11,
+                    guid: "G1".to_owned(),
+                    date: Some("2024-01-15T02:30:00Z".parse()?),
+                    sender: Some("+15550001".to_owned()),
+                    is_from_me: false,
+                    text: Some("Look <here>".to_owned()),
+                    service: Some("iMessage".to_owned()),
+                    attachments: vec![
+                        attachment(3, &format!("~/{ATTACHMENT_PATH}")),
+                        attachment(4, "~/Library/SMS/Attachments/cd/12/B/IMG_0002.jpeg"),
+                    ],
+                },
+                Message {
+                    id: 12,
+                    guid: "G2".to_owned(),
+                    date: Some("2024-01-15T02:31:00Z".parse()?),
+                    sender: None,
+                    is_from_me: true,
+                    text: Some("Nice".to_owned()),
+                    service: Some("iMessage".to_owned()),
+                    attachments: vec![],
+                },
+            ],
+        })
+    }
+
+    async fn export(
+        service: MessagesService,
+        conversations: Vec<Conversation>,
+    ) -> Result<(TempDir, Vec<ExportedConversation>)> {
+        let temp_backup = TempDir::new()?;
+        let temp_output = TempDir::new()?;
+        let file = File::new(
+            FileId::new("da39a3ee5e6b4b0d3255bfef95601890afd80709")?,
+            Domain::new(ATTACHMENT_DOMAIN.to_owned())?,
+            RelativePath::new(ATTACHMENT_PATH.to_owned())?,
+            FileFlags::REGULAR_FILE,
+            vec![],
+        );
+        temp_backup
+            .child("da")
+            .child(file.id().value())
+            .write_str("image")?;
+
+        let exported = service
+            .export(
+                &MockMessageRepository { conversations },
+                &MockFileRepository { files: vec![file] },
+                &BlobRepositoryImpl::new(temp_backup.path()),
+                temp_output.path(),
+            )
+            .await?;
+        Ok((temp_output, exported))
+    }
+
+    #[tokio::test]
+    async fn test_export_text_with_attachments() -> Result<()> {
+        let (temp_output, exported) = export(
+            MessagesService::new(),
+            vec![create_conversation("+15550001")?],
+        )
+        .await?;
+
+        assert_eq!(
+            exported,
+            vec![ExportedConversation {
+                chat_identifier: "+15550001".to_owned(),
+                display_name: None,
+                participants: vec!["+15550001".to_owned()],
+                message_count: 2,
+                attachment_count: 1,
+                missing_attachment_count: 1,
+                failed_attachment_count: 0,
+                transcript: temp_output.path().join("+15550001/transcript.txt"),
+            }]
+        );
+        temp_output
+            .child("+15550001/attachments/3-IMG_0001.jpeg")
+            .assert("image");
+        temp_output.child("+15550001/transcript.txt").assert(
+            "Conversation: +15550001\n\
+             Service: iMessage\n\
+             Participants: +15550001\n\
+             \n\
+             [2024-01-15 02:30:00 UTC] +15550001: Look <here>\n    \
+             [Attachment: IMG_0001.jpeg (attachments/3-IMG_0001.jpeg)]\n    \
+             [Attachment: IMG_0002.jpeg (not in backup)]\n\
+             [2024-01-15 02:31:00 UTC] Me: Nice\n",
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_export_corrupt_attachment() -> Result<()> {
+        let temp_backup = TempDir::new()?;
+        let temp_output = TempDir::new()?;
+        let readable = File::new(
+            FileId::new("da39a3ee5e6b4b0d3255bfef95601890afd80709")?,
+            Domain::new(ATTACHMENT_DOMAIN.to_owned())?,
+            RelativePath::new(ATTACHMENT_PATH.to_owned())?,
+            FileFlags::REGULAR_FILE,
+            vec![],
+        );
+        let corrupt = File::new(
+            FileId::new("2fd4e1c67a2d28fced849ee1bb76e7391b93eb12")?,
+            Domain::new(ATTACHMENT_DOMAIN.to_owned())?,
+            RelativePath::new("Library/SMS/Attachments/cd/12/B/IMG_0002.jpeg".to_owned())?,
+            FileFlags::REGULAR_FILE,
+            vec![],
+        );
+        for file in [&readable, &corrupt] {
+            temp_backup
+                .child(&file.id().value()[..2])
+                .child(file.id().value())
+                .write_str("image")?;
+        }
+
+        let exported = MessagesService::new()
+            .export(
+                &MockMessageRepository {
+                    conversations: vec![create_conversation("+15550001")?],
+                },
+                &MockFileRepository {
+                    files: vec![readable, corrupt.clone()],
+                },
+                &CorruptBlobRepository {
+                    inner: BlobRepositoryImpl::new(temp_backup.path()),
+                    corrupt_id: corrupt.id().clone(),
+                },
+                temp_output.path(),
+            )
+            .await?;
+
+        assert_eq!(exported[0].attachment_count, 1);
+        assert_eq!(exported[0].missing_attachment_count, 0);
+        assert_eq!(exported[0].failed_attachment_count, 1);
+        temp_output
+            .child("+15550001/attachments/3-IMG_0001.jpeg")
+            .assert("image");
+        assert!(
+            !temp_output
+                .path()
+                .join("+15550001/attachments/4-IMG_0002.jpeg")
+                .exists()
+        );
+        let transcript = fs::read_to_string(temp_output.path().join("+15550001/transcript.txt"))?;
+        assert!(transcript.contains("[Attachment: IMG_0002.jpeg (unreadable)]"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_export_html_escapes_text() -> Result<()> {
+        let (temp_output, _) = export(
+            MessagesService::new().with_format(TranscriptFormat::Html),
+            vec![create_conversation("+15550001")?],
+        )
+        .await?;
+
+        let html = fs::read_to_string(temp_output.path().join("+15550001/transcript.html"))?;
+        assert!(html.contains("Look &lt;here&gt;"));
+        assert!(html.contains("<img src=\"attachments/3-IMG_0001.jpeg\" alt=\"IMG_0001.jpeg\">"));
+        assert!(html.contains("IMG_0002.jpeg (not in backup)"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_export_json_without_attachments() -> Result<()> {
+        let (temp_output, exported) = export(
+            MessagesService::new()
+                .with_format(TranscriptFormat::Json)
+                .with_attachments(false),
+            vec![create_conversation("+15550001")?],
+        )
+        .await?;
+
+        assert_eq!(exported[0].attachment_count, 0);
+        assert!(!temp_output.path().join("+15550001/attachments").exists());
+        let json: serde_json::Value = serde_json::from_str(&fs::read_to_string(
+            temp_output.path().join("+15550001/transcript.json"),
+        )?)?;
+        assert_eq!(json["messages"][0]["date"], "2024-01-15T02:30:00+00:00");
+        assert_eq!(
+            json["messages"][0]["attachments"][0]["path"],
+            serde_json::Value::Null
+        );
+        assert_eq!(json["messages"][1]["is_from_me"], true);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_export_unique_directories() -> Result<()> {
+        let mut second = create_conversation("../chat")?;
+        second.id = 2;
+        let (temp_output, exported) = export(
+            MessagesService::new().with_attachments(false),
+            vec![create_conversation("../chat")?, second],
+        )
+        .await?;
+
+        let transcripts: Vec<_> = exported
+            .iter()
+            .map(|conversation| conversation.transcript.clone())
+            .collect();
+        assert_eq!(
+            transcripts,
+            vec![
+                temp_output.path().join("_chat/transcript.txt"),
+                temp_output.path().join("_chat-2/transcript.txt"),
+            ]
+        );
+        Ok(())
+    }
+}
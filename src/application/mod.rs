@@ -1,11 +1,51 @@
 //! Application layer containing business logic and services
 
+mod apps_output;
+pub mod apps_service;
+mod calls_output;
+pub mod calls_service;
+mod contacts_output;
+pub mod contacts_service;
+pub mod diff_service;
 pub mod display_service;
+mod export_util;
+pub mod extract_plan;
 pub mod extract_service;
+pub mod hash_manifest;
 pub mod list_service;
+mod messages_output;
+pub mod messages_service;
+mod photos_output;
+pub mod photos_service;
+mod safari_output;
+pub mod safari_service;
 pub mod search_service;
+pub mod vcard;
+pub mod verify_service;
+mod voicemail_output;
+pub mod voicemail_service;
 
-pub use display_service::DisplayService;
-pub use extract_service::{ExtractError, ExtractResult, ExtractService};
+pub use apps_service::{AppSummary, AppsService, DomainUsage};
+pub use calls_service::CallsService;
+pub use contacts_service::{ContactsService, ExportedContact};
+pub use diff_service::{ChangeKind, ChangedField, DiffReport, DiffService, FileChange};
+pub use display_service::{
+    DelimitedFormatter, DisplayService, JsonFormatter, MetadataColumn, NdjsonFormatter,
+    OutputFormat, OutputFormatter, TableFormatter,
+};
+pub use extract_plan::{ExtractPlan, PlannedAction, PlannedOperation, RejectedFile};
+pub use extract_service::{
+    ConflictPolicy, ExtractError, ExtractErrorKind, ExtractProgress, ExtractResult, ExtractService,
+    ExtractStatus, FileOutcome, OutputLayout,
+};
+pub use hash_manifest::{
+    FileHashes, HashAlgorithm, HashManifest, HashManifestEntry, ManifestFormat,
+};
 pub use list_service::ListService;
-pub use search_service::{SearchParams, SearchService};
+pub use messages_service::{ExportedConversation, MessagesService, TranscriptFormat};
+pub use photos_service::{AlbumExport, ExportedPhoto, PhotosService};
+pub use safari_service::{ExportedSafariFile, SafariContent, SafariFormat, SafariService};
+pub use search_service::{FileType, SearchParams, SearchService};
+pub use vcard::VCardVersion;
+pub use verify_service::{IssueKind, VerifyIssue, VerifyReport, VerifyService};
+pub use voicemail_service::{VoicemailService, VoicemailSummary};
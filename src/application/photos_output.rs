@@ -0,0 +1,165 @@
+//! Rows and records of the photos and videos written by a photos export
+
+use serde::Serialize;
+use tabled::Tabled;
+
+use crate::application::display_service::{Listing, table_time};
+use crate::application::photos_service::ExportedPhoto;
+
+/// Represents an exported photo or video for table display
+#[derive(Tabled)]
+pub struct ExportedPhotoRow {
+    #[tabled(rename = "Date")]
+    date: String,
+    #[tabled(rename = "Name")]
+    name: String,
+    #[tabled(rename = "Kind")]
+    kind: &'static str,
+    #[tabled(rename = "Live")]
+    live_photo: &'static str,
+    #[tabled(rename = "Albums")]
+    albums: String,
+    #[tabled(rename = "Hidden")]
+    hidden: &'static str,
+    #[tabled(rename = "Deleted")]
+    recently_deleted: &'static str,
+    #[tabled(rename = "Path")]
+    path: String,
+}
+
+impl ExportedPhotoRow {
+    fn new(photo: &ExportedPhoto) -> Self {
+        let asset = &photo.asset;
+        let flag = |value: bool| if value { "yes" } else { "no" };
+        Self {
+            date: table_time(asset.date_created),
+            name: asset.export_name().to_owned(),
+            kind: asset.kind.name(),
+            live_photo: flag(photo.live_photo_video.is_some()),
+            albums: asset.albums.join(", "),
+            hidden: flag(asset.hidden),
+            recently_deleted: flag(asset.trashed),
+            path: photo
+                .path
+                .as_ref()
+                .map_or_else(|| "missing".to_owned(), |path| path.display().to_string()),
+        }
+    }
+}
+
+/// Serialized form of an exported photo or video
+#[derive(Serialize)]
+struct ExportedPhotoRecord<'a> {
+    id: i64,
+    date_created: Option<String>,
+    filename: &'a str,
+    original_filename: Option<&'a str>,
+    kind: &'static str,
+    /// Comma-separated titles of the albums holding the asset
+    albums: String,
+    hidden: bool,
+    recently_deleted: bool,
+    trashed_date: Option<String>,
+    path: Option<String>,
+    live_photo_video: Option<String>,
+}
+
+impl<'a> ExportedPhotoRecord<'a> {
+    fn new(photo: &'a ExportedPhoto) -> Self {
+        let asset = &photo.asset;
+        Self {
+            id: asset.id,
+            date_created: asset.date_created.map(|date| date.to_rfc3339()),
+            filename: &asset.filename,
+            original_filename: asset.original_filename.as_deref(),
+            kind: asset.kind.name(),
+            albums: asset.albums.join(","),
+            hidden: asset.hidden,
+            recently_deleted: asset.trashed,
+            trashed_date: asset.trashed_date.map(|date| date.to_rfc3339()),
+            path: photo.path.as_ref().map(|path| path.display().to_string()),
+            live_photo_video: photo
+                .live_photo_video
+                .as_ref()
+                .map(|path| path.display().to_string()),
+        }
+    }
+}
+
+impl Listing for ExportedPhoto {
+    type Row = ExportedPhotoRow;
+
+    const EMPTY: &'static str = "No photos found.";
+
+    fn row(&self) -> Self::Row {
+        ExportedPhotoRow::new(self)
+    }
+
+    fn record(&self) -> impl Serialize {
+        ExportedPhotoRecord::new(self)
+    }
+
+    fn summary(photos: &[Self]) -> String {
+        let exported = photos.iter().filter(|photo| photo.path.is_some()).count();
+        format!(
+            "Exported {exported} of {} photo(s) and video(s)",
+            photos.len()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use super::*;
+    use crate::application::{DelimitedFormatter, OutputFormatter as _, TableFormatter};
+    use crate::domain::entities::{AssetKind, PhotoAsset};
+    use pretty_assertions::assert_eq;
+
+    fn create_exported_photo() -> Result<ExportedPhoto> {
+        Ok(ExportedPhoto {
+            asset: PhotoAsset {
+                id: 1,
+                directory: "DCIM/100APPLE".to_owned(),
+                filename: "IMG_0001.HEIC".to_owned(),
+                original_filename: None,
+                kind: AssetKind::Photo,
+                date_created: Some("2024-01-15T02:30:00Z".parse()?),
+                hidden: true,
+                trashed_date: None,
+                trashed: false,
+                albums: vec!["Trips".to_owned(), "Family".to_owned()],
+            },
+            path: None,
+            live_photo_video: None,
+        })
+    }
+
+    #[test]
+    fn test_format_photos_export() -> Result<()> {
+        let formatter = TableFormatter::new();
+
+        let output = formatter.format_photos_export(&[create_exported_photo()?])?;
+
+        assert!(output.contains("IMG_0001.HEIC"));
+        assert!(output.contains("Trips, Family"));
+        assert!(output.contains("missing"));
+        assert!(output.ends_with("Exported 0 of 1 photo(s) and video(s)"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_csv_format_photos_export() -> Result<()> {
+        let formatter = DelimitedFormatter::csv();
+
+        let output = formatter.format_photos_export(&[create_exported_photo()?])?;
+
+        assert_eq!(
+            output,
+            "id,date_created,filename,original_filename,kind,albums,hidden,recently_deleted,trashed_date,path,live_photo_video\n\
+             1,2024-01-15T02:30:00+00:00,IMG_0001.HEIC,,photo,\"Trips,Family\",true,false,,,"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,471 @@
+//! Photos service for exporting the camera roll with capture dates and albums
+
+use crate::application::export_util::{UniqueNames, file_name};
+use crate::domain::entities::{File, PhotoAsset};
+use crate::domain::queries::FileQuery;
+use crate::domain::repositories::{BlobRepository, FileRepository, PhotoRepository};
+use anyhow::{Context as _, Result};
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, FileTimes};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Domain holding the camera roll
+const MEDIA_DOMAIN: &str = "CameraRollDomain";
+
+/// Directory of the originals in their domain
+const MEDIA_DIR: &str = "Media/DCIM/";
+
+/// Directory of the album folders in the output directory
+const ALBUMS_DIR: &str = "Albums";
+
+/// How to record the albums holding each asset
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+#[non_exhaustive]
+pub enum AlbumExport {
+    /// Copy the assets of each album into `Albums/<title>`
+    Folders,
+    /// Write a JSON file with the albums and flags of each asset next to it
+    Sidecar,
+}
+
+/// A photo or video with the state of its export
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ExportedPhoto {
+    /// The asset
+    pub asset: PhotoAsset,
+    /// Location of the exported original, or `None` if the backup does not hold it
+    pub path: Option<PathBuf>,
+    /// Location of the exported video of a Live Photo
+    pub live_photo_video: Option<PathBuf>,
+}
+
+/// Sidecar file describing an exported asset
+#[derive(Serialize)]
+struct SidecarRecord<'a> {
+    id: i64,
+    filename: &'a str,
+    original_filename: Option<&'a str>,
+    kind: &'static str,
+    date_created: Option<String>,
+    albums: &'a [String],
+    hidden: bool,
+    recently_deleted: bool,
+    trashed_date: Option<String>,
+    live_photo_video: Option<String>,
+}
+
+/// Service for exporting the photos and videos of the Photos app
+#[derive(Debug, Default)]
+#[non_exhaustive]
+pub struct PhotosService {
+    /// Whether to sort the originals into `YYYY/MM` directories
+    date_folders: bool,
+    /// How to record album membership
+    albums: Option<AlbumExport>,
+}
+
+impl PhotosService {
+    /// Creates a new `PhotosService` writing every original into the output directory
+    #[must_use]
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            date_folders: false,
+            albums: None,
+        }
+    }
+
+    /// Sets whether to sort the originals into `YYYY/MM` directories by capture date
+    #[must_use]
+    #[inline]
+    pub const fn with_date_folders(mut self, date_folders: bool) -> Self {
+        self.date_folders = date_folders;
+        self
+    }
+
+    /// Sets how to record the albums holding each asset
+    #[must_use]
+    #[inline]
+    pub const fn with_albums(mut self, albums: Option<AlbumExport>) -> Self {
+        self.albums = albums;
+        self
+    }
+
+    /// Exports every photo and video of the camera roll into the output directory
+    ///
+    /// Originals are named as they were captured or imported, and their modification time
+    /// is set to the capture time. The video of a Live Photo is written next to its still,
+    /// with the same name and a `.MOV` extension. Existing files are overwritten.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a repository fails or an output file cannot be written
+    #[allow(
+        clippy::future_not_send,
+        reason = "Repository trait doesn't guarantee Send futures"
+    )]
+    #[inline]
+    pub async fn export<P: PhotoRepository, F: FileRepository, B: BlobRepository>(
+        &self,
+        photos: &P,
+        files: &F,
+        blobs: &B,
+        output_dir: &Path,
+    ) -> Result<Vec<ExportedPhoto>> {
+        let assets = photos.find_all().await?;
+
+        let query = FileQuery::and(vec![
+            FileQuery::domain_exact(MEDIA_DOMAIN),
+            FileQuery::path_contains(MEDIA_DIR),
+        ]);
+        let mut media_files: HashMap<String, File> = files
+            .search(query)
+            .await?
+            .into_iter()
+            .map(|file| (file.relative_path().value().to_owned(), file))
+            .collect();
+        // A video sharing its name with a photo is only a Live Photo if it is no asset itself
+        let asset_paths: HashSet<String> = assets.iter().map(PhotoAsset::relative_path).collect();
+        let mut live_photo_videos = HashMap::new();
+        for asset in &assets {
+            if let Some(video_path) = asset
+                .live_photo_video_path()
+                .filter(|path| !asset_paths.contains(path))
+                && let Some(file) = media_files.remove(&video_path)
+            {
+                live_photo_videos.insert(asset.id, file);
+            }
+        }
+
+        fs::create_dir_all(output_dir)
+            .with_context(|| format!("Failed to create directory: {}", output_dir.display()))?;
+
+        let mut names: HashMap<PathBuf, UniqueNames> = HashMap::new();
+        let mut exported = Vec::with_capacity(assets.len());
+        for asset in assets {
+            let Some(file) = media_files.get(&asset.relative_path()) else {
+                exported.push(ExportedPhoto {
+                    asset,
+                    path: None,
+                    live_photo_video: None,
+                });
+                continue;
+            };
+            let video = live_photo_videos.get(&asset.id);
+
+            let dir = output_dir.join(self.date_folder(&asset));
+            let (path, live_photo_video) =
+                copy_asset(&asset, file, video, blobs, &dir, &mut names)?;
+            let Some(path) = path else {
+                exported.push(ExportedPhoto {
+                    asset,
+                    path: None,
+                    live_photo_video: None,
+                });
+                continue;
+            };
+
+            match self.albums {
+                Some(AlbumExport::Folders) => {
+                    for album in &asset.albums {
+                        let name = file_name(album).unwrap_or_else(|| "album".to_owned());
+                        let album_dir = output_dir.join(ALBUMS_DIR).join(name);
+                        copy_asset(&asset, file, video, blobs, &album_dir, &mut names)?;
+                    }
+                }
+                Some(AlbumExport::Sidecar) => {
+                    write_sidecar(&asset, &path, live_photo_video.as_deref())?;
+                }
+                None => {}
+            }
+
+            exported.push(ExportedPhoto {
+                asset,
+                path: Some(path),
+                live_photo_video,
+            });
+        }
+
+        Ok(exported)
+    }
+
+    /// Returns the directory of an asset relative to the output directory
+    fn date_folder(&self, asset: &PhotoAsset) -> PathBuf {
+        if !self.date_folders {
+            return PathBuf::new();
+        }
+        asset.date_created.map_or_else(
+            || PathBuf::from("unknown-date"),
+            |date| PathBuf::from(date.format("%Y").to_string()).join(date.format("%m").to_string()),
+        )
+    }
+}
+
+/// Copies an original and the video of a Live Photo into a directory
+///
+/// Returns the locations of the copies, or `None` for files without content.
+fn copy_asset<B: BlobRepository>(
+    asset: &PhotoAsset,
+    file: &File,
+    video: Option<&File>,
+    blobs: &B,
+    dir: &Path,
+    names: &mut HashMap<PathBuf, UniqueNames>,
+) -> Result<(Option<PathBuf>, Option<PathBuf>)> {
+    let name = file_name(asset.export_name()).unwrap_or_else(|| asset.filename.clone());
+    let (stem, extension) = name.rsplit_once('.').unwrap_or((&name, ""));
+    let stem = names
+        .entry(dir.to_path_buf())
+        .or_default()
+        .claim(stem.to_owned(), asset.id);
+    let modified = asset.date_created.or_else(|| file.last_modified().copied());
+
+    let path = dir.join(if extension.is_empty() {
+        stem.clone()
+    } else {
+        format!("{stem}.{extension}")
+    });
+    if !copy_media(blobs, file, &path, modified)? {
+        return Ok((None, None));
+    }
+
+    let video_path = match video {
+        Some(video) => {
+            let video_path = dir.join(format!("{stem}.MOV"));
+            copy_media(blobs, video, &video_path, modified)?.then_some(video_path)
+        }
+        None => None,
+    };
+    Ok((Some(path), video_path))
+}
+
+/// Copies a file out of the backup and sets its modification time
+///
+/// Returns `false` if the file has no content.
+fn copy_media<B: BlobRepository>(
+    blobs: &B,
+    file: &File,
+    path: &Path,
+    modified: Option<DateTime<Utc>>,
+) -> Result<bool> {
+    let Some(mut source) = blobs.open(file)? else {
+        return Ok(false);
+    };
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+    }
+
+    let mut destination =
+        fs::File::create(path).with_context(|| format!("Failed to create {}", path.display()))?;
+    io::copy(&mut source, &mut destination)
+        .with_context(|| format!("Failed to copy {}", path.display()))?;
+    if let Some(modified) = modified {
+        destination
+            .set_times(FileTimes::new().set_modified(SystemTime::from(modified)))
+            .with_context(|| format!("Failed to set the timestamps of {}", path.display()))?;
+    }
+    Ok(true)
+}
+
+/// Writes the sidecar file of an exported original, named after it with `.json` appended
+fn write_sidecar(asset: &PhotoAsset, path: &Path, live_photo_video: Option<&Path>) -> Result<()> {
+    let record = SidecarRecord {
+        id: asset.id,
+        filename: &asset.filename,
+        original_filename: asset.original_filename.as_deref(),
+        kind: asset.kind.name(),
+        date_created: asset.date_created.map(|date| date.to_rfc3339()),
+        albums: &asset.albums,
+        hidden: asset.hidden,
+        recently_deleted: asset.trashed,
+        trashed_date: asset.trashed_date.map(|date| date.to_rfc3339()),
+        live_photo_video: live_photo_video
+            .and_then(Path::file_name)
+            .map(|name| name.to_string_lossy().into_owned()),
+    };
+
+    let mut sidecar = path.as_os_str().to_owned();
+    sidecar.push(".json");
+    let sidecar = PathBuf::from(sidecar);
+    fs::write(&sidecar, serde_json::to_string_pretty(&record)?)
+        .with_context(|| format!("Failed to write {}", sidecar.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entities::AssetKind;
+    use crate::domain::value_objects::{Domain, FileFlags, FileId, RelativePath};
+    use crate::infrastructure::repositories::BlobRepositoryImpl;
+    use assert_fs::TempDir;
+    use assert_fs::prelude::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::Value;
+
+    struct MockPhotoRepository {
+        assets: Vec<PhotoAsset>,
+    }
+
+    impl PhotoRepository for MockPhotoRepository {
+        async fn find_all(&self) -> Result<Vec<PhotoAsset>> {
+            Ok(self.assets.clone())
+        }
+    }
+
+    // Mock repository returning every file
+    struct MockFileRepository {
+        files: Vec<File>,
+    }
+
+    impl FileRepository for MockFileRepository {
+        async fn search(&self, _query: FileQuery) -> Result<Vec<File>> {
+            Ok(self.files.clone())
+        }
+    }
+
+    fn create_asset(id: i64, filename: &str, kind: AssetKind) -> Result<PhotoAsset> {
+        Ok(PhotoAsset {
+            id,
+            directory: "DCIM/100APPLE".to_owned(),
+            filename: filename.to_owned(),
+            original_filename: None,
+            kind,
+            date_created: Some("2024-01-15T02:30:00Z".parse()?),
+            hidden: false,
+            trashed_date: None,
+            trashed: false,
+            albums: vec![],
+        })
+    }
+
+    async fn export(
+        service: PhotosService,
+        output_dir: &Path,
+    ) -> Result<(TempDir, Vec<ExportedPhoto>)> {
+        let temp_backup = TempDir::new()?;
+        let mut files = Vec::new();
+        for (filename, hash) in [
+            ("IMG_0001.HEIC", "1111111111111111111111111111111111111111"),
+            ("IMG_0001.MOV", "2222222222222222222222222222222222222222"),
+            ("IMG_0002.MOV", "3333333333333333333333333333333333333333"),
+        ] {
+            files.push(File::new(
+                FileId::new(hash)?,
+                Domain::new(MEDIA_DOMAIN.to_owned())?,
+                RelativePath::new(format!("Media/DCIM/100APPLE/{filename}"))?,
+                FileFlags::REGULAR_FILE,
+                vec![],
+            ));
+            temp_backup
+                .child(&hash[..2])
+                .child(hash)
+                .write_str(filename)?;
+        }
+
+        let mut beach = create_asset(1, "IMG_0001.HEIC", AssetKind::Photo)?;
+        beach.original_filename = Some("beach.heic".to_owned());
+        beach.albums = vec!["Trips".to_owned()];
+        beach.hidden = true;
+        let mut video = create_asset(2, "IMG_0002.MOV", AssetKind::Video)?;
+        video.date_created = None;
+        video.trashed = true;
+        let missing = create_asset(3, "IMG_0003.JPG", AssetKind::Photo)?;
+
+        let exported = service
+            .export(
+                &MockPhotoRepository {
+                    assets: vec![beach, video, missing],
+                },
+                &MockFileRepository { files },
+                &BlobRepositoryImpl::new(temp_backup.path()),
+                output_dir,
+            )
+            .await?;
+        Ok((temp_backup, exported))
+    }
+
+    #[tokio::test]
+    async fn test_export_live_photos_with_dates() -> Result<()> {
+        let temp_output = TempDir::new()?;
+
+        let (_temp_backup, exported) = export(PhotosService::new(), temp_output.path()).await?;
+
+        let paths: Vec<_> = exported
+            .iter()
+            .map(|photo| (photo.path.clone(), photo.live_photo_video.clone()))
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                (
+                    Some(temp_output.path().join("beach.heic")),
+                    Some(temp_output.path().join("beach.MOV")),
+                ),
+                (Some(temp_output.path().join("IMG_0002.MOV")), None),
+                (None, None),
+            ]
+        );
+        temp_output.child("beach.MOV").assert("IMG_0001.MOV");
+        let modified = fs::metadata(temp_output.path().join("beach.heic"))?.modified()?;
+        assert_eq!(
+            DateTime::<Utc>::from(modified),
+            exported[0].asset.date_created.context("date")?
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_export_date_folders_and_album_folders() -> Result<()> {
+        let temp_output = TempDir::new()?;
+        let service = PhotosService::new()
+            .with_date_folders(true)
+            .with_albums(Some(AlbumExport::Folders));
+
+        let (_temp_backup, exported) = export(service, temp_output.path()).await?;
+
+        assert_eq!(
+            exported[0].path,
+            Some(temp_output.path().join("2024/01/beach.heic"))
+        );
+        assert_eq!(
+            exported[1].path,
+            Some(temp_output.path().join("unknown-date/IMG_0002.MOV"))
+        );
+        temp_output
+            .child("Albums/Trips/beach.heic")
+            .assert("IMG_0001.HEIC");
+        temp_output
+            .child("Albums/Trips/beach.MOV")
+            .assert("IMG_0001.MOV");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_export_sidecars() -> Result<()> {
+        let temp_output = TempDir::new()?;
+        let service = PhotosService::new().with_albums(Some(AlbumExport::Sidecar));
+
+        export(service, temp_output.path()).await?;
+
+        let photo: Value = serde_json::from_str(&fs::read_to_string(
+            temp_output.path().join("beach.heic.json"),
+        )?)?;
+        assert_eq!(photo["filename"], "IMG_0001.HEIC");
+        assert_eq!(photo["albums"][0], "Trips");
+        assert_eq!(photo["hidden"], true);
+        assert_eq!(photo["live_photo_video"], "beach.MOV");
+        let video: Value = serde_json::from_str(&fs::read_to_string(
+            temp_output.path().join("IMG_0002.MOV.json"),
+        )?)?;
+        assert_eq!(video["recently_deleted"], true);
+        assert_eq!(video["date_created"], Value::Null);
+        Ok(())
+    }
+}
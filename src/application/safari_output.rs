@@ -0,0 +1,107 @@
+//! Rows and records of the files written by a Safari export
+
+use serde::Serialize;
+use tabled::Tabled;
+
+use crate::application::display_service::Listing;
+use crate::application::safari_service::ExportedSafariFile;
+
+/// Represents a file written by a Safari export for table display
+#[derive(Tabled)]
+pub struct ExportedSafariFileRow {
+    #[tabled(rename = "Content")]
+    content: &'static str,
+    #[tabled(rename = "Items")]
+    count: usize,
+    #[tabled(rename = "File")]
+    path: String,
+}
+
+impl ExportedSafariFileRow {
+    fn new(file: &ExportedSafariFile) -> Self {
+        Self {
+            content: file.content.name(),
+            count: file.count,
+            path: file.path.display().to_string(),
+        }
+    }
+}
+
+/// Serialized form of a file written by a Safari export
+#[derive(Serialize)]
+struct ExportedSafariFileRecord {
+    content: &'static str,
+    count: usize,
+    path: String,
+}
+
+impl ExportedSafariFileRecord {
+    fn new(file: &ExportedSafariFile) -> Self {
+        Self {
+            content: file.content.name(),
+            count: file.count,
+            path: file.path.display().to_string(),
+        }
+    }
+}
+
+impl Listing for ExportedSafariFile {
+    type Row = ExportedSafariFileRow;
+
+    const EMPTY: &'static str = "No Safari data found.";
+
+    fn row(&self) -> Self::Row {
+        ExportedSafariFileRow::new(self)
+    }
+
+    fn record(&self) -> impl Serialize {
+        ExportedSafariFileRecord::new(self)
+    }
+
+    fn summary(files: &[Self]) -> String {
+        format!("Exported {} file(s)", files.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use serde_json::Value;
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::application::{JsonFormatter, OutputFormatter as _, SafariContent, TableFormatter};
+
+    fn create_exported_safari_file() -> ExportedSafariFile {
+        ExportedSafariFile {
+            content: SafariContent::NetscapeBookmarks,
+            count: 12,
+            path: PathBuf::from("safari/bookmarks-netscape.html"),
+        }
+    }
+
+    #[test]
+    fn test_format_safari_export() -> Result<()> {
+        let formatter = TableFormatter::new();
+
+        let output = formatter.format_safari_export(&[create_exported_safari_file()])?;
+
+        assert!(output.contains("netscape bookmarks"));
+        assert!(output.contains("safari/bookmarks-netscape.html"));
+        assert!(output.ends_with("Exported 1 file(s)"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_json_format_safari_export() -> Result<()> {
+        let formatter = JsonFormatter::new();
+
+        let output = formatter.format_safari_export(&[create_exported_safari_file()])?;
+        let value: Value = serde_json::from_str(&output)?;
+
+        assert_eq!(value[0]["content"], "netscape bookmarks");
+        assert_eq!(value[0]["count"], 12);
+        assert_eq!(value[0]["path"], "safari/bookmarks-netscape.html");
+        Ok(())
+    }
+}
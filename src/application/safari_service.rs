@@ -0,0 +1,617 @@
+//! Safari service for exporting the browsing history, bookmarks and open tabs
+
+use crate::application::export_util::escape_html;
+use crate::domain::entities::{Bookmark, BrowserTab, HistoryVisit};
+use crate::domain::repositories::SafariRepository;
+use anyhow::{Context as _, Result};
+use clap::ValueEnum;
+use serde::Serialize;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Name of the bookmark file in the Netscape format
+const NETSCAPE_FILE_NAME: &str = "bookmarks-netscape.html";
+
+/// Format of the exported history, bookmarks and tabs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+#[non_exhaustive]
+pub enum SafariFormat {
+    /// JSON documents, keeping the bookmark tree
+    #[default]
+    Json,
+    /// Comma-separated values, with each bookmark listed under its folder path
+    Csv,
+    /// Web pages
+    Html,
+}
+
+impl SafariFormat {
+    /// Returns the file extension of exported files
+    #[must_use]
+    #[inline]
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Csv => "csv",
+            Self::Html => "html",
+        }
+    }
+}
+
+/// Kind of data in an exported file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SafariContent {
+    /// Browsing history
+    History,
+    /// Bookmark tree
+    Bookmarks,
+    /// Tabs open on the device and on other devices
+    Tabs,
+    /// Bookmark tree in the Netscape bookmark file format
+    NetscapeBookmarks,
+}
+
+impl SafariContent {
+    /// Returns the lowercase name of the content
+    #[must_use]
+    #[inline]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::History => "history",
+            Self::Bookmarks => "bookmarks",
+            Self::Tabs => "tabs",
+            Self::NetscapeBookmarks => "netscape bookmarks",
+        }
+    }
+}
+
+/// A file written by a Safari export
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ExportedSafariFile {
+    /// Kind of data in the file
+    pub content: SafariContent,
+    /// Number of visits, bookmarks or tabs in the file
+    pub count: usize,
+    /// Location of the file
+    pub path: PathBuf,
+}
+
+/// Serialized form of a history visit
+#[derive(Serialize)]
+struct HistoryRecord<'a> {
+    id: i64,
+    visit_time: Option<String>,
+    title: Option<&'a str>,
+    url: &'a str,
+    visit_count: i64,
+}
+
+impl<'a> HistoryRecord<'a> {
+    fn new(visit: &'a HistoryVisit) -> Self {
+        Self {
+            id: visit.id,
+            visit_time: visit.visit_time.map(|time| time.to_rfc3339()),
+            title: visit.title.as_deref(),
+            url: &visit.url,
+            visit_count: visit.visit_count,
+        }
+    }
+}
+
+/// Serialized form of a bookmark and the tree below it
+#[derive(Serialize)]
+struct BookmarkRecord<'a> {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    title: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    url: Option<&'a str>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    children: Vec<Self>,
+}
+
+impl<'a> BookmarkRecord<'a> {
+    fn new(bookmark: &'a Bookmark) -> Self {
+        Self {
+            kind: if bookmark.is_folder {
+                "folder"
+            } else {
+                "bookmark"
+            },
+            title: bookmark.title.as_deref(),
+            url: bookmark.url.as_deref(),
+            children: bookmark.children.iter().map(Self::new).collect(),
+        }
+    }
+}
+
+/// Flat form of a bookmark for CSV files
+#[derive(Serialize)]
+struct BookmarkCsvRecord<'a> {
+    /// Titles of the folders holding the bookmark, joined with `/`
+    folder: String,
+    title: Option<&'a str>,
+    url: Option<&'a str>,
+}
+
+/// Serialized form of a tab
+#[derive(Serialize)]
+struct TabRecord<'a> {
+    device: Option<&'a str>,
+    title: Option<&'a str>,
+    url: Option<&'a str>,
+    last_viewed: Option<String>,
+    private: bool,
+}
+
+impl<'a> TabRecord<'a> {
+    fn new(tab: &'a BrowserTab) -> Self {
+        Self {
+            device: tab.device.as_deref(),
+            title: tab.title.as_deref(),
+            url: tab.url.as_deref(),
+            last_viewed: tab.last_viewed.map(|time| time.to_rfc3339()),
+            private: tab.private,
+        }
+    }
+}
+
+/// Service for exporting the browsing data of Safari
+#[derive(Debug, Default)]
+#[non_exhaustive]
+pub struct SafariService {
+    /// Format of the exported files
+    format: SafariFormat,
+    /// Whether to also write the bookmarks in the Netscape bookmark file format
+    netscape_bookmarks: bool,
+}
+
+impl SafariService {
+    /// Creates a new `SafariService` writing JSON files
+    #[must_use]
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            format: SafariFormat::Json,
+            netscape_bookmarks: false,
+        }
+    }
+
+    /// Sets the format of the exported files
+    #[must_use]
+    #[inline]
+    pub const fn with_format(mut self, format: SafariFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Sets whether to also write the bookmarks in the Netscape bookmark file format
+    #[must_use]
+    #[inline]
+    pub const fn with_netscape_bookmarks(mut self, netscape_bookmarks: bool) -> Self {
+        self.netscape_bookmarks = netscape_bookmarks;
+        self
+    }
+
+    /// Exports the history, bookmarks and tabs of Safari into the output directory
+    ///
+    /// They are written to `history.<ext>`, `bookmarks.<ext>` and `tabs.<ext>`, and the
+    /// bookmarks also to `bookmarks-netscape.html` if requested, which browsers can import.
+    /// Existing files are overwritten.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the repository fails or an output file cannot be written
+    #[allow(
+        clippy::future_not_send,
+        reason = "Repository trait doesn't guarantee Send futures"
+    )]
+    #[inline]
+    pub async fn export<S: SafariRepository>(
+        &self,
+        safari: &S,
+        output_dir: &Path,
+    ) -> Result<Vec<ExportedSafariFile>> {
+        let history = safari.find_history().await?;
+        let bookmarks = safari.find_bookmarks().await?;
+        let tabs = safari.find_tabs().await?;
+        fs::create_dir_all(output_dir)
+            .with_context(|| format!("Failed to create directory: {}", output_dir.display()))?;
+
+        let (history_content, bookmarks_content, tabs_content) = match self.format {
+            SafariFormat::Json => (
+                to_json(history.iter().map(HistoryRecord::new))?,
+                to_json(bookmarks.iter().map(BookmarkRecord::new))?,
+                to_json(tabs.iter().map(TabRecord::new))?,
+            ),
+            SafariFormat::Csv => (
+                to_csv(history.iter().map(HistoryRecord::new))?,
+                to_csv(flatten_bookmarks(&bookmarks))?,
+                to_csv(tabs.iter().map(TabRecord::new))?,
+            ),
+            SafariFormat::Html => (
+                render_history_html(&history),
+                render_bookmarks_html(&bookmarks),
+                render_tabs_html(&tabs),
+            ),
+        };
+        let bookmark_count = bookmarks.iter().map(Bookmark::count).sum();
+
+        let extension = self.format.extension();
+        let mut exported = vec![
+            write(
+                output_dir,
+                SafariContent::History,
+                history.len(),
+                &format!("history.{extension}"),
+                &history_content,
+            )?,
+            write(
+                output_dir,
+                SafariContent::Bookmarks,
+                bookmark_count,
+                &format!("bookmarks.{extension}"),
+                &bookmarks_content,
+            )?,
+            write(
+                output_dir,
+                SafariContent::Tabs,
+                tabs.len(),
+                &format!("tabs.{extension}"),
+                &tabs_content,
+            )?,
+        ];
+        if self.netscape_bookmarks {
+            exported.push(write(
+                output_dir,
+                SafariContent::NetscapeBookmarks,
+                bookmark_count,
+                NETSCAPE_FILE_NAME,
+                &render_netscape_bookmarks(&bookmarks),
+            )?);
+        }
+
+        Ok(exported)
+    }
+}
+
+/// Writes an output file
+fn write(
+    output_dir: &Path,
+    content: SafariContent,
+    count: usize,
+    name: &str,
+    data: &str,
+) -> Result<ExportedSafariFile> {
+    let path = output_dir.join(name);
+    fs::write(&path, data).with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(ExportedSafariFile {
+        content,
+        count,
+        path,
+    })
+}
+
+/// Serializes records as a JSON array
+fn to_json<T: Serialize>(records: impl Iterator<Item = T>) -> Result<String> {
+    Ok(serde_json::to_string_pretty(&records.collect::<Vec<_>>())?)
+}
+
+/// Serializes records as CSV with a header row
+fn to_csv<T: Serialize>(records: impl Iterator<Item = T>) -> Result<String> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    for record in records {
+        writer.serialize(record)?;
+    }
+    Ok(String::from_utf8(writer.into_inner()?)?)
+}
+
+/// Lists the bookmarks of a tree with the path of their folder
+fn flatten_bookmarks(bookmarks: &[Bookmark]) -> impl Iterator<Item = BookmarkCsvRecord<'_>> {
+    fn visit<'a>(bookmark: &'a Bookmark, folder: &str, records: &mut Vec<BookmarkCsvRecord<'a>>) {
+        if bookmark.is_folder {
+            let title = bookmark.title.as_deref().unwrap_or_default();
+            let path = if folder.is_empty() {
+                title.to_owned()
+            } else {
+                format!("{folder}/{title}")
+            };
+            for child in &bookmark.children {
+                visit(child, &path, records);
+            }
+        } else {
+            records.push(BookmarkCsvRecord {
+                folder: folder.to_owned(),
+                title: bookmark.title.as_deref(),
+                url: bookmark.url.as_deref(),
+            });
+        }
+    }
+
+    let mut records = Vec::new();
+    for bookmark in bookmarks {
+        visit(bookmark, "", &mut records);
+    }
+    records.into_iter()
+}
+
+/// Starts a web page
+fn html_header(title: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n\
+         <style>\ntable {{ border-collapse: collapse; }}\n\
+         td, th {{ padding: 0.2em 0.5em; text-align: left; }}\n\
+         .meta {{ color: #888; font-size: 0.8em; }}\n</style>\n\
+         </head>\n<body>\n<h1>{title}</h1>\n"
+    )
+}
+
+/// Returns a link to an address, or the escaped title if there is no address
+fn html_link(title: Option<&str>, url: Option<&str>) -> String {
+    let text = escape_html(title.or(url).unwrap_or_default());
+    url.map_or_else(
+        || text.clone(),
+        |url| format!("<a href=\"{}\">{text}</a>", escape_html(url)),
+    )
+}
+
+/// Renders the browsing history as a web page
+fn render_history_html(history: &[HistoryVisit]) -> String {
+    let mut output = html_header("Safari History");
+    output.push_str("<table>\n<tr><th>Time</th><th>Page</th><th>Visits</th></tr>\n");
+    for visit in history {
+        let time = visit
+            .visit_time
+            .map(|time| time.to_string())
+            .unwrap_or_default();
+        let _ = writeln!(
+            output,
+            "<tr><td class=\"meta\">{}</td><td>{}</td><td>{}</td></tr>",
+            escape_html(&time),
+            html_link(visit.title.as_deref(), Some(&visit.url)),
+            visit.visit_count
+        );
+    }
+    output.push_str("</table>\n</body>\n</html>\n");
+    output
+}
+
+/// Renders the bookmark tree as a web page of nested lists
+fn render_bookmarks_html(bookmarks: &[Bookmark]) -> String {
+    fn render_list(bookmarks: &[Bookmark], output: &mut String) {
+        output.push_str("<ul>\n");
+        for bookmark in bookmarks {
+            if bookmark.is_folder {
+                let _ = writeln!(
+                    output,
+                    "<li><strong>{}</strong>",
+                    escape_html(bookmark.title.as_deref().unwrap_or_default())
+                );
+                render_list(&bookmark.children, output);
+                output.push_str("</li>\n");
+            } else {
+                let _ = writeln!(
+                    output,
+                    "<li>{}</li>",
+                    html_link(bookmark.title.as_deref(), bookmark.url.as_deref())
+                );
+            }
+        }
+        output.push_str("</ul>\n");
+    }
+
+    let mut output = html_header("Safari Bookmarks");
+    render_list(bookmarks, &mut output);
+    output.push_str("</body>\n</html>\n");
+    output
+}
+
+/// Renders the open tabs as a web page
+fn render_tabs_html(tabs: &[BrowserTab]) -> String {
+    let mut output = html_header("Safari Tabs");
+    output.push_str(
+        "<table>\n<tr><th>Device</th><th>Page</th><th>Last viewed</th><th>Private</th></tr>\n",
+    );
+    for tab in tabs {
+        let last_viewed = tab
+            .last_viewed
+            .map(|time| time.to_string())
+            .unwrap_or_default();
+        let _ = writeln!(
+            output,
+            "<tr><td>{}</td><td>{}</td><td class=\"meta\">{}</td><td>{}</td></tr>",
+            escape_html(tab.device.as_deref().unwrap_or("This device")),
+            html_link(tab.title.as_deref(), tab.url.as_deref()),
+            escape_html(&last_viewed),
+            if tab.private { "yes" } else { "no" }
+        );
+    }
+    output.push_str("</table>\n</body>\n</html>\n");
+    output
+}
+
+/// Renders the bookmark tree in the Netscape bookmark file format
+///
+/// This is the format browsers use to import and export bookmarks.
+fn render_netscape_bookmarks(bookmarks: &[Bookmark]) -> String {
+    fn render_list(bookmarks: &[Bookmark], depth: usize, output: &mut String) {
+        let indent = "    ".repeat(depth);
+        let _ = writeln!(output, "{indent}<DL><p>");
+        for bookmark in bookmarks {
+            let title = escape_html(bookmark.title.as_deref().unwrap_or_default());
+            if bookmark.is_folder {
+                let _ = writeln!(output, "{indent}    <DT><H3>{title}</H3>");
+                render_list(&bookmark.children, depth.saturating_add(1), output);
+            } else if let Some(url) = &bookmark.url {
+                let _ = writeln!(
+                    output,
+                    "{indent}    <DT><A HREF=\"{}\">{title}</A>",
+                    escape_html(url)
+                );
+            }
+        }
+        let _ = writeln!(output, "{indent}</DL><p>");
+    }
+
+    let mut output = "<!DOCTYPE NETSCAPE-Bookmark-file-1>\n\
+                      <META HTTP-EQUIV=\"Content-Type\" CONTENT=\"text/html; charset=UTF-8\">\n\
+                      <TITLE>Bookmarks</TITLE>\n<H1>Bookmarks</H1>\n"
+        .to_owned();
+    render_list(bookmarks, 0, &mut output);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_fs::TempDir;
+    use assert_fs::prelude::*;
+    use predicates::prelude::*;
+    use pretty_assertions::assert_eq;
+
+    struct MockSafariRepository;
+
+    impl SafariRepository for MockSafariRepository {
+        async fn find_history(&self) -> Result<Vec<HistoryVisit>> {
+            Ok(vec![HistoryVisit {
+                id: 1,
+                url: "https://example.com/?a=1&b=2".to_owned(),
+                title: Some("Example".to_owned()),
+                visit_time: Some("2024-01-15T02:30:00Z".parse()?),
+                visit_count: 3,
+            }])
+        }
+
+        async fn find_bookmarks(&self) -> Result<Vec<Bookmark>> {
+            Ok(vec![Bookmark::folder(
+                1,
+                Some("Favorites".to_owned()),
+                vec![
+                    Bookmark::new(
+                        2,
+                        Some("Apple".to_owned()),
+                        Some("https://www.apple.com/".to_owned()),
+                    ),
+                    Bookmark::folder(
+                        3,
+                        Some("News".to_owned()),
+                        vec![Bookmark::new(
+                            4,
+                            Some("<Example>".to_owned()),
+                            Some("https://example.com/".to_owned()),
+                        )],
+                    ),
+                ],
+            )])
+        }
+
+        async fn find_tabs(&self) -> Result<Vec<BrowserTab>> {
+            Ok(vec![BrowserTab {
+                title: Some("Tab".to_owned()),
+                url: Some("https://example.org/".to_owned()),
+                last_viewed: None,
+                private: true,
+                device: None,
+            }])
+        }
+    }
+
+    #[tokio::test]
+    async fn test_export_json() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+
+        let exported = SafariService::new()
+            .export(&MockSafariRepository, temp_dir.path())
+            .await?;
+
+        let counts: Vec<_> = exported
+            .iter()
+            .map(|file| (file.content, file.count))
+            .collect();
+        assert_eq!(
+            counts,
+            vec![
+                (SafariContent::History, 1),
+                (SafariContent::Bookmarks, 2),
+                (SafariContent::Tabs, 1),
+            ]
+        );
+        let bookmarks: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(temp_dir.path().join("bookmarks.json"))?)?;
+        assert_eq!(bookmarks[0]["type"], "folder");
+        assert_eq!(
+            bookmarks[0]["children"][1]["children"][0]["title"],
+            "<Example>"
+        );
+        temp_dir
+            .child("history.json")
+            .assert(predicate::str::contains(
+                "\"visit_time\": \"2024-01-15T02:30:00+00:00\"",
+            ));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_export_csv() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+
+        SafariService::new()
+            .with_format(SafariFormat::Csv)
+            .export(&MockSafariRepository, temp_dir.path())
+            .await?;
+
+        temp_dir.child("bookmarks.csv").assert(
+            "folder,title,url\n\
+             Favorites,Apple,https://www.apple.com/\n\
+             Favorites/News,<Example>,https://example.com/\n",
+        );
+        temp_dir
+            .child("tabs.csv")
+            .assert("device,title,url,last_viewed,private\n,Tab,https://example.org/,,true\n");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_export_html_and_netscape_bookmarks() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+
+        let exported = SafariService::new()
+            .with_format(SafariFormat::Html)
+            .with_netscape_bookmarks(true)
+            .export(&MockSafariRepository, temp_dir.path())
+            .await?;
+
+        assert_eq!(
+            exported.last().map(|file| file.path.clone()),
+            Some(temp_dir.path().join("bookmarks-netscape.html"))
+        );
+        temp_dir
+            .child("history.html")
+            .assert(predicate::str::contains(
+                "<a href=\"https://example.com/?a=1&amp;b=2\">Example</a>",
+            ));
+        temp_dir
+            .child("bookmarks.html")
+            .assert(predicate::str::contains("<li><strong>News</strong>"));
+        temp_dir.child("bookmarks-netscape.html").assert(
+            "<!DOCTYPE NETSCAPE-Bookmark-file-1>\n\
+             <META HTTP-EQUIV=\"Content-Type\" CONTENT=\"text/html; charset=UTF-8\">\n\
+             <TITLE>Bookmarks</TITLE>\n\
+             <H1>Bookmarks</H1>\n\
+             <DL><p>\n    \
+             <DT><H3>Favorites</H3>\n    \
+             <DL><p>\n        \
+             <DT><A HREF=\"https://www.apple.com/\">Apple</A>\n        \
+             <DT><H3>News</H3>\n        \
+             <DL><p>\n            \
+             <DT><A HREF=\"https://example.com/\">&lt;Example&gt;</A>\n        \
+             </DL><p>\n    \
+             </DL><p>\n\
+             </DL><p>\n",
+        );
+        Ok(())
+    }
+}
@@ -1,12 +1,60 @@
 //! Search service for handling file search operations
 
+use crate::domain::queries::BasicQuery;
+use crate::domain::value_objects::{
+    ByteSize, DomainKind, FileFlags, GlobPattern, PointInTime, RegexPattern,
+};
 use crate::{File, FileQuery, FileRepository};
 use anyhow::Result;
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
 
 /// Service for handling file search operations
 #[non_exhaustive]
 pub struct SearchService;
 
+/// File type accepted by the `--type` filter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum FileType {
+    /// Regular file
+    File,
+    /// Directory
+    Dir,
+    /// Symbolic link
+    Symlink,
+}
+
+impl FileType {
+    /// Returns the type of a file
+    ///
+    /// Entries that are neither directories nor symbolic links count as regular files.
+    #[must_use]
+    #[inline]
+    pub const fn of(file: &File) -> Self {
+        if file.flags().is_directory() {
+            Self::Dir
+        } else if file.flags().is_symbolic_link() {
+            Self::Symlink
+        } else {
+            Self::File
+        }
+    }
+
+    /// Returns the flag of this file type
+    #[must_use]
+    #[inline]
+    pub const fn flags(self) -> FileFlags {
+        match self {
+            Self::File => FileFlags::REGULAR_FILE,
+            Self::Dir => FileFlags::DIRECTORY,
+            Self::Symlink => FileFlags::SYMBOLIC_LINK,
+        }
+    }
+}
+
 /// Search parameters for file queries
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[non_exhaustive]
@@ -16,6 +64,23 @@ pub struct SearchParams {
     pub path_exact: Option<String>,
     pub path_contains: Option<String>,
     pub use_or: bool,
+    pub query: Option<String>,
+    pub domain_glob: Option<String>,
+    pub domain_regex: Option<String>,
+    pub path_glob: Option<String>,
+    pub path_regex: Option<String>,
+    pub ignore_case: bool,
+    pub file_types: Vec<FileType>,
+    pub domain_kinds: Vec<DomainKind>,
+    pub bundle_id: Option<String>,
+    pub min_size: Option<String>,
+    pub max_size: Option<String>,
+    pub modified_after: Option<String>,
+    pub modified_before: Option<String>,
+    pub created_after: Option<String>,
+    pub protection_class: Option<u32>,
+    /// Reference for relative times such as `48h`; the current time if unset
+    pub reference_time: Option<DateTime<Utc>>,
 }
 
 impl SearchParams {
@@ -35,62 +100,314 @@ impl SearchParams {
             path_exact,
             path_contains,
             use_or,
+            query: None,
+            domain_glob: None,
+            domain_regex: None,
+            path_glob: None,
+            path_regex: None,
+            ignore_case: false,
+            file_types: Vec::new(),
+            domain_kinds: Vec::new(),
+            bundle_id: None,
+            min_size: None,
+            max_size: None,
+            modified_after: None,
+            modified_before: None,
+            created_after: None,
+            protection_class: None,
+            reference_time: None,
         }
     }
 
+    /// Sets a query expression, combined with the other conditions using AND
+    ///
+    /// See `FileQuery::parse` for the syntax.
+    #[must_use]
+    #[inline]
+    pub fn with_query(mut self, query: Option<String>) -> Self {
+        self.query = query;
+        self
+    }
+
+    /// Sets a glob pattern the domain must match, e.g. `AppDomain-*`
+    #[must_use]
+    #[inline]
+    pub fn with_domain_glob(mut self, domain_glob: Option<String>) -> Self {
+        self.domain_glob = domain_glob;
+        self
+    }
+
+    /// Sets a regular expression the domain must match
+    #[must_use]
+    #[inline]
+    pub fn with_domain_regex(mut self, domain_regex: Option<String>) -> Self {
+        self.domain_regex = domain_regex;
+        self
+    }
+
+    /// Sets a glob pattern the path must match, e.g. `Library/SMS/**/*.heic`
+    #[must_use]
+    #[inline]
+    pub fn with_path_glob(mut self, path_glob: Option<String>) -> Self {
+        self.path_glob = path_glob;
+        self
+    }
+
+    /// Sets a regular expression the path must match
+    #[must_use]
+    #[inline]
+    pub fn with_path_regex(mut self, path_regex: Option<String>) -> Self {
+        self.path_regex = path_regex;
+        self
+    }
+
+    /// Makes all conditions, including the query expression, case-insensitive
+    #[must_use]
+    #[inline]
+    pub const fn with_ignore_case(mut self, ignore_case: bool) -> Self {
+        self.ignore_case = ignore_case;
+        self
+    }
+
+    /// Restricts the results to the given file types
+    #[must_use]
+    #[inline]
+    pub fn with_file_types(mut self, file_types: Vec<FileType>) -> Self {
+        self.file_types = file_types;
+        self
+    }
+
+    /// Restricts the results to domains of the given kinds
+    #[must_use]
+    #[inline]
+    pub fn with_domain_kinds(mut self, domain_kinds: Vec<DomainKind>) -> Self {
+        self.domain_kinds = domain_kinds;
+        self
+    }
+
+    /// Sets the bundle or group identifier of the container domain, e.g.
+    /// `net.whatsapp.WhatsApp`
+    #[must_use]
+    #[inline]
+    pub fn with_bundle_id(mut self, bundle_id: Option<String>) -> Self {
+        self.bundle_id = bundle_id;
+        self
+    }
+
+    /// Restricts the file size, e.g. `10KB` or `1.5MiB`
+    ///
+    /// Both bounds are inclusive. See `ByteSize` for the supported units.
+    #[must_use]
+    #[inline]
+    pub fn with_size_range(mut self, min_size: Option<String>, max_size: Option<String>) -> Self {
+        self.min_size = min_size;
+        self.max_size = max_size;
+        self
+    }
+
+    /// Restricts the modification time, e.g. `2024-01-15` or `48h`
+    ///
+    /// See `PointInTime` for the supported formats.
+    #[must_use]
+    #[inline]
+    pub fn with_modified_range(
+        mut self,
+        modified_after: Option<String>,
+        modified_before: Option<String>,
+    ) -> Self {
+        self.modified_after = modified_after;
+        self.modified_before = modified_before;
+        self
+    }
+
+    /// Restricts the creation time to the given time or later
+    #[must_use]
+    #[inline]
+    pub fn with_created_after(mut self, created_after: Option<String>) -> Self {
+        self.created_after = created_after;
+        self
+    }
+
+    /// Restricts the data protection class
+    #[must_use]
+    #[inline]
+    pub const fn with_protection_class(mut self, protection_class: Option<u32>) -> Self {
+        self.protection_class = protection_class;
+        self
+    }
+
+    /// Sets the time relative times are counted back from, usually the backup date
+    #[must_use]
+    #[inline]
+    pub const fn with_reference_time(mut self, reference_time: Option<DateTime<Utc>>) -> Self {
+        self.reference_time = reference_time;
+        self
+    }
+
     /// Build a `FileQuery` from search parameters
     ///
     /// # Errors
     ///
-    /// Returns an error if no search conditions are provided
+    /// Returns an error if:
+    /// - No search conditions are provided
+    /// - The query expression cannot be parsed
+    /// - A glob or regex pattern is invalid
+    /// - A size or time is invalid
     #[inline]
     pub fn build_query(self) -> Result<FileQuery, anyhow::Error> {
-        use crate::domain::queries::{BasicQuery, FileQuery};
+        self.build_optional_query()?
+            .ok_or_else(|| anyhow::anyhow!("At least one search condition must be specified"))
+    }
+
+    /// Build a `FileQuery` that matches every file when no search conditions are provided
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The query expression cannot be parsed
+    /// - A glob or regex pattern is invalid
+    /// - A size or time is invalid
+    #[inline]
+    pub fn build_query_or_all(self) -> Result<FileQuery, anyhow::Error> {
+        Ok(self.build_optional_query()?.unwrap_or_else(FileQuery::all))
+    }
+
+    fn build_optional_query(&self) -> Result<Option<FileQuery>, anyhow::Error> {
+        let query = self.build_case_sensitive_query()?;
+        Ok(if self.ignore_case {
+            query.map(FileQuery::ignore_case)
+        } else {
+            query
+        })
+    }
+
+    fn build_case_sensitive_query(&self) -> Result<Option<FileQuery>, anyhow::Error> {
+        let expression = self.query.as_deref().map(FileQuery::parse).transpose()?;
 
         let mut conditions = Vec::new();
 
-        if let Some(domain) = self.domain_exact {
-            conditions.push(BasicQuery::DomainExact(domain));
+        if let Some(domain) = &self.domain_exact {
+            conditions.push(BasicQuery::DomainExact(domain.clone()));
+        }
+
+        if let Some(domain) = &self.domain_contains {
+            conditions.push(BasicQuery::DomainContains(domain.clone()));
+        }
+
+        if let Some(pattern) = &self.domain_glob {
+            conditions.push(BasicQuery::DomainGlob(GlobPattern::new(pattern)?));
         }
 
-        if let Some(domain) = self.domain_contains {
-            conditions.push(BasicQuery::DomainContains(domain));
+        if let Some(pattern) = &self.domain_regex {
+            conditions.push(BasicQuery::DomainRegex(RegexPattern::new(pattern)?));
         }
 
-        if let Some(path) = self.path_exact {
-            conditions.push(BasicQuery::PathExact(path));
+        if let Some(path) = &self.path_exact {
+            conditions.push(BasicQuery::PathExact(path.clone()));
         }
 
-        if let Some(path) = self.path_contains {
-            conditions.push(BasicQuery::PathContains(path));
+        if let Some(path) = &self.path_contains {
+            conditions.push(BasicQuery::PathContains(path.clone()));
         }
 
-        if conditions.is_empty() {
-            return Err(anyhow::anyhow!(
-                "At least one search condition must be specified"
-            ));
+        if let Some(pattern) = &self.path_glob {
+            conditions.push(BasicQuery::PathGlob(GlobPattern::new(pattern)?));
+        }
+
+        if let Some(pattern) = &self.path_regex {
+            conditions.push(BasicQuery::PathRegex(RegexPattern::new(pattern)?));
+        }
+
+        let filters = self.build_filters()?;
+
+        if conditions.is_empty() && filters.is_empty() {
+            return Ok(expression);
         }
 
         // Build query based on logic type
-        let query = if conditions.len() == 1 {
+        let query = if conditions.is_empty() {
+            None
+        } else if conditions.len() == 1 {
             // Single condition - use Basic query
-            // We know conditions has exactly one element at this point
-            if let Some(condition) = conditions.into_iter().next() {
-                FileQuery::Basic(condition)
-            } else {
-                return Err(anyhow::anyhow!(
-                    "Internal error: expected exactly one condition"
-                ));
-            }
+            conditions.into_iter().next().map(FileQuery::Basic)
         } else if self.use_or {
             // Multiple conditions with OR logic
-            FileQuery::any_of(conditions)
+            Some(FileQuery::any_of(conditions))
         } else {
             // Multiple conditions with AND logic (default)
-            FileQuery::all_of(conditions)
+            Some(FileQuery::all_of(conditions))
+        };
+
+        // Filters always narrow down the results, even with OR logic
+        let filter_query = match filters.len() {
+            0 => None,
+            1 => filters.into_iter().next().map(FileQuery::Basic),
+            _ => Some(FileQuery::all_of(filters)),
+        };
+
+        let mut parts: Vec<_> = [expression, query, filter_query]
+            .into_iter()
+            .flatten()
+            .collect();
+        Ok(Some(if parts.len() == 1 {
+            parts.remove(0)
+        } else {
+            FileQuery::and(parts)
+        }))
+    }
+
+    /// Builds the type, domain kind, bundle ID, size, time and protection class filters
+    fn build_filters(&self) -> Result<Vec<BasicQuery>> {
+        let reference_time = self.reference_time.unwrap_or_else(Utc::now);
+        let time = |value: &String| -> Result<DateTime<Utc>> {
+            Ok(PointInTime::new(value)?.resolve(reference_time))
         };
 
-        Ok(query)
+        let mut filters = Vec::new();
+
+        if !self.file_types.is_empty() {
+            let flags = self
+                .file_types
+                .iter()
+                .fold(FileFlags::empty(), |flags, file_type| {
+                    flags | file_type.flags()
+                });
+            filters.push(BasicQuery::FileType(flags));
+        }
+
+        if !self.domain_kinds.is_empty() {
+            filters.push(BasicQuery::DomainKind(self.domain_kinds.clone()));
+        }
+
+        if let Some(bundle_id) = &self.bundle_id {
+            filters.push(BasicQuery::BundleId(bundle_id.clone()));
+        }
+
+        if let Some(size) = &self.min_size {
+            filters.push(BasicQuery::MinSize(ByteSize::new(size)?.value()));
+        }
+
+        if let Some(size) = &self.max_size {
+            filters.push(BasicQuery::MaxSize(ByteSize::new(size)?.value()));
+        }
+
+        if let Some(value) = &self.modified_after {
+            filters.push(BasicQuery::ModifiedAfter(time(value)?));
+        }
+
+        if let Some(value) = &self.modified_before {
+            filters.push(BasicQuery::ModifiedBefore(time(value)?));
+        }
+
+        if let Some(value) = &self.created_after {
+            filters.push(BasicQuery::CreatedAfter(time(value)?));
+        }
+
+        if let Some(class) = self.protection_class {
+            filters.push(BasicQuery::ProtectionClass(class));
+        }
+
+        Ok(filters)
     }
 }
 
@@ -134,7 +451,149 @@ impl Default for SearchService {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::domain::value_objects::{Domain, FileId, FileMetadata, RelativePath};
     use crate::{BasicQuery, FileQuery};
+    use pretty_assertions::assert_eq;
+
+    // Mock repository evaluating queries in memory
+    struct MockFileRepository {
+        files: Vec<File>,
+    }
+
+    impl MockFileRepository {
+        fn new() -> Result<Self> {
+            let files = [
+                ("HomeDomain", "Library/SMS/sms.db"),
+                ("MediaDomain", "Library/SMS/Attachments/0a/00/IMG_0001.HEIC"),
+                ("MediaDomain", "Library/SMS/Attachments/0b/01/IMG_0002.jpeg"),
+                (
+                    "AppDomain-net.whatsapp.WhatsApp",
+                    "Documents/ChatStorage.sqlite",
+                ),
+                ("CameraRollDomain", "Media/DCIM/100APPLE/IMG_0003.HEIC"),
+            ]
+            .iter()
+            .map(|(domain, path)| {
+                Ok(File::new(
+                    FileId::new("da39a3ee5e6b4b0d3255bfef95601890afd80709")?,
+                    Domain::new((*domain).to_owned())?,
+                    RelativePath::new((*path).to_owned())?,
+                    FileFlags::REGULAR_FILE,
+                    vec![],
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+            Ok(Self { files })
+        }
+    }
+
+    impl FileRepository for MockFileRepository {
+        async fn search(&self, query: FileQuery) -> Result<Vec<File>> {
+            Ok(self
+                .files
+                .iter()
+                .filter(|file| query.matches(file))
+                .cloned()
+                .collect())
+        }
+    }
+
+    async fn search_paths(params: SearchParams) -> Result<Vec<String>> {
+        let files = SearchService::new()
+            .search(&MockFileRepository::new()?, params)
+            .await?;
+        Ok(files
+            .iter()
+            .map(|file| file.relative_path().value().to_owned())
+            .collect())
+    }
+
+    fn empty_params() -> SearchParams {
+        SearchParams::new(None, None, None, None, false)
+    }
+
+    fn create_file_with_metadata(
+        path: &str,
+        flags: FileFlags,
+        size: u64,
+        last_modified: &str,
+        birth: &str,
+        protection_class: u32,
+    ) -> Result<File> {
+        let last_modified: DateTime<Utc> = last_modified.parse()?;
+        let metadata = FileMetadata::new(size, 0o100_644)
+            .with_timestamps(last_modified, last_modified, birth.parse()?)
+            .with_protection_class(protection_class);
+
+        Ok(File::new(
+            FileId::new("da39a3ee5e6b4b0d3255bfef95601890afd80709")?,
+            Domain::new("HomeDomain".to_owned())?,
+            RelativePath::new(path.to_owned())?,
+            flags,
+            vec![],
+        )
+        .with_file_metadata(metadata))
+    }
+
+    async fn filter_paths(params: SearchParams) -> Result<Vec<String>> {
+        let repository = MockFileRepository {
+            files: vec![
+                create_file_with_metadata(
+                    "Library/SMS",
+                    FileFlags::DIRECTORY,
+                    0,
+                    "2024-01-10T00:00:00Z",
+                    "2020-01-01T00:00:00Z",
+                    0,
+                )?,
+                create_file_with_metadata(
+                    "Library/SMS/sms.db",
+                    FileFlags::REGULAR_FILE,
+                    40_960,
+                    "2024-01-15T08:00:00Z",
+                    "2023-01-01T00:00:00Z",
+                    3,
+                )?,
+                create_file_with_metadata(
+                    "Library/SMS/link",
+                    FileFlags::SYMBOLIC_LINK,
+                    0,
+                    "2023-06-01T00:00:00Z",
+                    "2023-06-01T00:00:00Z",
+                    0,
+                )?,
+                create_file_with_metadata(
+                    "Media/DCIM/100APPLE/IMG_0003.HEIC",
+                    FileFlags::REGULAR_FILE,
+                    2_097_152,
+                    "2024-01-14T12:00:00Z",
+                    "2024-01-14T12:00:00Z",
+                    2,
+                )?,
+                // Files without decoded metadata never match size or time filters
+                File::new(
+                    FileId::new("da39a3ee5e6b4b0d3255bfef95601890afd80709")?,
+                    Domain::new("HomeDomain".to_owned())?,
+                    RelativePath::new("Library/SMS/unknown".to_owned())?,
+                    FileFlags::REGULAR_FILE,
+                    vec![],
+                ),
+            ],
+        };
+
+        let reference_time = "2024-01-15T10:30:00Z".parse()?;
+        let files = SearchService::new()
+            .search(
+                &repository,
+                params.with_reference_time(Some(reference_time)),
+            )
+            .await?;
+        Ok(files
+            .iter()
+            .map(|file| file.relative_path().value().to_owned())
+            .collect())
+    }
 
     #[test]
     fn test_build_query_single_condition() {
@@ -182,6 +641,19 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_build_query_or_all() -> Result<()> {
+        assert_eq!(empty_params().build_query_or_all()?, FileQuery::all());
+
+        let params = SearchParams::new(None, None, None, Some("Documents".to_owned()), false)
+            .with_ignore_case(true);
+        assert_eq!(
+            params.build_query_or_all()?,
+            FileQuery::Basic(BasicQuery::PathContains("Documents".to_owned())).ignore_case()
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_build_query_multiple_conditions_or() {
         let params = SearchParams::new(
@@ -201,4 +673,303 @@ mod tests {
             panic!("Expected Composite query for multiple conditions");
         }
     }
+
+    #[test]
+    fn test_build_query_expression_only() -> Result<()> {
+        let params = SearchParams::new(None, None, None, None, false).with_query(Some(
+            "domain ~ whatsapp and not path contains Caches".to_owned(),
+        ));
+
+        assert_eq!(
+            params.build_query()?,
+            FileQuery::and(vec![
+                FileQuery::domain_contains("whatsapp"),
+                FileQuery::negate(FileQuery::path_contains("Caches")),
+            ])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_build_query_expression_with_flags() -> Result<()> {
+        let params = SearchParams::new(Some("HomeDomain".to_owned()), None, None, None, false)
+            .with_query(Some("path ~ SMS or path ~ Voicemail".to_owned()));
+
+        assert_eq!(
+            params.build_query()?,
+            FileQuery::and(vec![
+                FileQuery::or(vec![
+                    FileQuery::path_contains("SMS"),
+                    FileQuery::path_contains("Voicemail"),
+                ]),
+                FileQuery::domain_exact("HomeDomain"),
+            ])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_build_query_invalid_expression() {
+        let params =
+            SearchParams::new(None, None, None, None, false).with_query(Some("path ~".to_owned()));
+
+        let result = params.build_query();
+
+        assert!(result.is_err());
+        if let Err(err) = result {
+            assert!(err.to_string().contains("Expected a value"));
+            assert!(err.to_string().contains("        ^"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_search_path_glob() -> Result<()> {
+        let params = empty_params().with_path_glob(Some("Library/SMS/**/*.HEIC".to_owned()));
+
+        assert_eq!(
+            search_paths(params).await?,
+            vec!["Library/SMS/Attachments/0a/00/IMG_0001.HEIC"]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_search_path_regex() -> Result<()> {
+        let params = empty_params().with_path_regex(Some(r"IMG_\d{4}\.(HEIC|jpeg)$".to_owned()));
+
+        assert_eq!(
+            search_paths(params).await?,
+            vec![
+                "Library/SMS/Attachments/0a/00/IMG_0001.HEIC",
+                "Library/SMS/Attachments/0b/01/IMG_0002.jpeg",
+                "Media/DCIM/100APPLE/IMG_0003.HEIC",
+            ]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_search_domain_glob() -> Result<()> {
+        let params = empty_params().with_domain_glob(Some("AppDomain-*".to_owned()));
+
+        assert_eq!(
+            search_paths(params).await?,
+            vec!["Documents/ChatStorage.sqlite"]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_search_domain_regex() -> Result<()> {
+        let params = empty_params().with_domain_regex(Some("^(Home|CameraRoll)Domain$".to_owned()));
+
+        assert_eq!(
+            search_paths(params).await?,
+            vec!["Library/SMS/sms.db", "Media/DCIM/100APPLE/IMG_0003.HEIC"]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_search_domain_kind_and_bundle_id() -> Result<()> {
+        let kinds = empty_params().with_domain_kinds(vec![DomainKind::Home, DomainKind::App]);
+        assert_eq!(
+            search_paths(kinds).await?,
+            vec!["Library/SMS/sms.db", "Documents/ChatStorage.sqlite"]
+        );
+
+        let bundle_id = empty_params().with_bundle_id(Some("net.whatsapp.WhatsApp".to_owned()));
+        assert_eq!(
+            search_paths(bundle_id).await?,
+            vec!["Documents/ChatStorage.sqlite"]
+        );
+
+        // Domain kinds and bundle IDs narrow down the other conditions, even with OR logic
+        let kinds_with_or = SearchParams::new(
+            None,
+            Some("Home".to_owned()),
+            None,
+            Some("IMG".to_owned()),
+            true,
+        )
+        .with_domain_kinds(vec![DomainKind::Media]);
+        assert_eq!(
+            search_paths(kinds_with_or).await?,
+            vec![
+                "Library/SMS/Attachments/0a/00/IMG_0001.HEIC",
+                "Library/SMS/Attachments/0b/01/IMG_0002.jpeg"
+            ]
+        );
+
+        let bundle_id_with_or = SearchParams::new(
+            None,
+            Some("Home".to_owned()),
+            None,
+            Some("ChatStorage".to_owned()),
+            true,
+        )
+        .with_bundle_id(Some("net.whatsapp.WhatsApp".to_owned()));
+        assert_eq!(
+            search_paths(bundle_id_with_or).await?,
+            vec!["Documents/ChatStorage.sqlite"]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_search_ignore_case() -> Result<()> {
+        let exact = SearchParams::new(
+            None,
+            None,
+            Some("library/sms/SMS.DB".to_owned()),
+            None,
+            false,
+        )
+        .with_ignore_case(true);
+        assert_eq!(search_paths(exact).await?, vec!["Library/SMS/sms.db"]);
+
+        let contains = SearchParams::new(None, Some("whatsapp".to_owned()), None, None, false)
+            .with_ignore_case(true);
+        assert_eq!(
+            search_paths(contains).await?,
+            vec!["Documents/ChatStorage.sqlite"]
+        );
+
+        let glob = empty_params()
+            .with_path_glob(Some("**/*.heic".to_owned()))
+            .with_ignore_case(true);
+        assert_eq!(
+            search_paths(glob).await?,
+            vec![
+                "Library/SMS/Attachments/0a/00/IMG_0001.HEIC",
+                "Media/DCIM/100APPLE/IMG_0003.HEIC",
+            ]
+        );
+
+        let regex = empty_params()
+            .with_domain_regex(Some("^mediadomain$".to_owned()))
+            .with_query(Some("path ~ IMG_0002".to_owned()))
+            .with_ignore_case(true);
+        assert_eq!(
+            search_paths(regex).await?,
+            vec!["Library/SMS/Attachments/0b/01/IMG_0002.jpeg"]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_search_is_case_sensitive_by_default() -> Result<()> {
+        let params = empty_params().with_path_glob(Some("**/*.heic".to_owned()));
+
+        assert_eq!(search_paths(params).await?, Vec::<String>::new());
+        Ok(())
+    }
+
+    #[test]
+    fn test_build_query_invalid_patterns() {
+        let glob = empty_params().with_path_glob(Some("Library/[SMS".to_owned()));
+        let glob_result = glob.build_query();
+        assert!(glob_result.is_err());
+        if let Err(err) = glob_result {
+            assert!(err.to_string().contains("Invalid glob pattern"));
+        }
+
+        let regex = empty_params().with_domain_regex(Some("(Home".to_owned()));
+        let regex_result = regex.build_query();
+        assert!(regex_result.is_err());
+        if let Err(err) = regex_result {
+            assert!(err.to_string().contains("Invalid regex pattern"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_search_file_type() -> Result<()> {
+        let params = empty_params().with_file_types(vec![FileType::Dir, FileType::Symlink]);
+
+        assert_eq!(
+            filter_paths(params).await?,
+            vec!["Library/SMS", "Library/SMS/link"]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_search_size_range() -> Result<()> {
+        let min = empty_params().with_size_range(Some("1MB".to_owned()), None);
+        assert_eq!(
+            filter_paths(min).await?,
+            vec!["Media/DCIM/100APPLE/IMG_0003.HEIC"]
+        );
+
+        let range = empty_params()
+            .with_file_types(vec![FileType::File])
+            .with_size_range(Some("1K".to_owned()), Some("40KiB".to_owned()));
+        assert_eq!(filter_paths(range).await?, vec!["Library/SMS/sms.db"]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_search_modified_range() -> Result<()> {
+        // 48 hours before the backup date
+        let after = empty_params().with_modified_range(Some("48h".to_owned()), None);
+        assert_eq!(
+            filter_paths(after).await?,
+            vec!["Library/SMS/sms.db", "Media/DCIM/100APPLE/IMG_0003.HEIC"]
+        );
+
+        let before =
+            empty_params().with_modified_range(None, Some("2024-01-14T12:00:00Z".to_owned()));
+        assert_eq!(
+            filter_paths(before).await?,
+            vec!["Library/SMS", "Library/SMS/link"]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_search_created_after() -> Result<()> {
+        let params = empty_params().with_created_after(Some("2024-01-01".to_owned()));
+
+        assert_eq!(
+            filter_paths(params).await?,
+            vec!["Media/DCIM/100APPLE/IMG_0003.HEIC"]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_search_protection_class() -> Result<()> {
+        let params = empty_params().with_protection_class(Some(3));
+
+        assert_eq!(filter_paths(params).await?, vec!["Library/SMS/sms.db"]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_search_filters_narrow_or_conditions() -> Result<()> {
+        let params = SearchParams::new(None, None, None, Some("DCIM".to_owned()), true)
+            .with_path_glob(Some("Library/SMS/*".to_owned()))
+            .with_file_types(vec![FileType::File])
+            .with_modified_range(Some("2024-01-15".to_owned()), None);
+
+        assert_eq!(filter_paths(params).await?, vec!["Library/SMS/sms.db"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_build_query_invalid_filters() {
+        let size = empty_params().with_size_range(None, Some("10 parsecs".to_owned()));
+        let size_result = size.build_query();
+        assert!(size_result.is_err());
+        if let Err(err) = size_result {
+            assert!(err.to_string().contains("Invalid size"));
+        }
+
+        let time = empty_params().with_created_after(Some("yesterday".to_owned()));
+        let time_result = time.build_query();
+        assert!(time_result.is_err());
+        if let Err(err) = time_result {
+            assert!(err.to_string().contains("Invalid time"));
+        }
+    }
 }
@@ -0,0 +1,397 @@
+//! vCard rendering of address book contacts
+
+use crate::domain::entities::contact::is_year_omitted;
+use crate::domain::entities::{Contact, Labeled};
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD;
+use chrono::NaiveDate;
+use clap::ValueEnum;
+
+/// Maximum length of a vCard line in octets, excluding the line break
+const MAX_LINE_LENGTH: usize = 75;
+
+/// Version of the vCard format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+#[non_exhaustive]
+pub enum VCardVersion {
+    /// vCard 3.0 (RFC 2426), understood by most address books
+    #[default]
+    #[value(name = "3.0")]
+    V3,
+    /// vCard 4.0 (RFC 6350)
+    #[value(name = "4.0")]
+    V4,
+}
+
+/// Kind of a labeled property, deciding which labels map to standard types
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PropertyKind {
+    Phone,
+    Email,
+    Address,
+    Url,
+    RelatedName,
+}
+
+/// Writes the lines of one vCard
+struct VCardWriter {
+    version: VCardVersion,
+    output: String,
+    /// Number of the last property group, used for custom labels
+    group: usize,
+}
+
+impl VCardWriter {
+    const fn new(version: VCardVersion) -> Self {
+        Self {
+            version,
+            output: String::new(),
+            group: 0,
+        }
+    }
+
+    /// Writes a property, folding it into lines of at most 75 octets
+    fn line(&mut self, name: &str, value: &str) {
+        let line = format!("{name}:{value}");
+        let mut length: usize = 0;
+        for c in line.chars() {
+            if length.saturating_add(c.len_utf8()) > MAX_LINE_LENGTH {
+                self.output.push_str("\r\n ");
+                // The leading space of the continuation counts towards its length
+                length = 1;
+            }
+            self.output.push(c);
+            length = length.saturating_add(c.len_utf8());
+        }
+        self.output.push_str("\r\n");
+    }
+
+    /// Writes a text property, skipping empty values
+    fn text(&mut self, name: &str, value: Option<&str>) {
+        if let Some(value) = value.filter(|value| !value.is_empty()) {
+            self.line(name, &escape(value));
+        }
+    }
+
+    /// Writes a property with a label
+    ///
+    /// Labels with a standard vCard type become a `TYPE` parameter. Other labels are
+    /// attached through a property group with an `X-ABLabel`, as the Contacts app does.
+    fn labeled(&mut self, kind: PropertyKind, name: &str, label: Option<&str>, value: &str) {
+        let mut types: Vec<&str> = label
+            .and_then(|label| standard_types(kind, label))
+            .unwrap_or_default()
+            .to_vec();
+        if kind == PropertyKind::Email && self.version == VCardVersion::V3 {
+            types.insert(0, "internet");
+        }
+
+        let mut property = name.to_owned();
+        if kind == PropertyKind::RelatedName && self.version == VCardVersion::V4 {
+            property.push_str(";VALUE=text");
+        }
+        if !types.is_empty() {
+            let types = types.join(",");
+            property = match self.version {
+                VCardVersion::V3 => format!("{property};TYPE={}", types.to_uppercase()),
+                VCardVersion::V4 => format!("{property};TYPE={types}"),
+            };
+        }
+
+        let custom_label =
+            label.filter(|label| standard_types(kind, label).is_none() && !label.is_empty());
+        match custom_label {
+            Some(custom_label) => self.grouped(&property, value, custom_label),
+            None => self.line(&property, value),
+        }
+    }
+
+    /// Writes a property in a new group together with an `X-ABLabel`
+    fn grouped(&mut self, property: &str, value: &str, label: &str) {
+        self.group = self.group.saturating_add(1);
+        let group = format!("item{}", self.group);
+        self.line(&format!("{group}.{property}"), value);
+        self.line(&format!("{group}.X-ABLabel"), &escape(label));
+    }
+
+    /// Formats a date, leaving out the year where it was not entered
+    fn date(&self, date: NaiveDate) -> String {
+        match self.version {
+            VCardVersion::V3 => date.format("%Y-%m-%d").to_string(),
+            VCardVersion::V4 if is_year_omitted(date) => date.format("--%m%d").to_string(),
+            VCardVersion::V4 => date.format("%Y%m%d").to_string(),
+        }
+    }
+
+    /// Writes the `BDAY` property
+    fn birthday(&mut self, date: NaiveDate) {
+        let value = self.date(date);
+        if self.version == VCardVersion::V3 && is_year_omitted(date) {
+            self.line("BDAY;X-APPLE-OMIT-YEAR=1604", &value);
+        } else {
+            self.line("BDAY", &value);
+        }
+    }
+
+    /// Writes a date other than the birthday
+    fn other_date(&mut self, date: &Labeled<NaiveDate>) {
+        let value = self.date(date.value);
+        let label = date.label.as_deref().unwrap_or("Other");
+        if self.version == VCardVersion::V4 && label == "Anniversary" {
+            self.line("ANNIVERSARY", &value);
+        } else {
+            self.grouped("X-ABDATE", &value, label);
+        }
+    }
+
+    /// Writes the `PHOTO` property with the image embedded
+    fn photo(&mut self, photo: &[u8]) {
+        let (media_type, v3_type) = if photo.starts_with(b"\x89PNG") {
+            ("image/png", "PNG")
+        } else {
+            ("image/jpeg", "JPEG")
+        };
+        let data = STANDARD.encode(photo);
+        match self.version {
+            VCardVersion::V3 => self.line(&format!("PHOTO;ENCODING=b;TYPE={v3_type}"), &data),
+            VCardVersion::V4 => self.line("PHOTO", &format!("data:{media_type};base64,{data}")),
+        }
+    }
+}
+
+/// Returns the vCard types of a label of the Contacts app, or `None` for other labels
+fn standard_types(kind: PropertyKind, label: &str) -> Option<&'static [&'static str]> {
+    match (kind, label) {
+        (PropertyKind::Phone, "Mobile" | "iPhone") => Some(&["cell"]),
+        (PropertyKind::Phone, "HomeFAX") => Some(&["home", "fax"]),
+        (PropertyKind::Phone, "WorkFAX") => Some(&["work", "fax"]),
+        (PropertyKind::Phone, "OtherFAX") => Some(&["fax"]),
+        (PropertyKind::Phone, "Pager") => Some(&["pager"]),
+        (
+            PropertyKind::Phone | PropertyKind::Email | PropertyKind::Address | PropertyKind::Url,
+            "Home",
+        ) => Some(&["home"]),
+        (
+            PropertyKind::Phone | PropertyKind::Email | PropertyKind::Address | PropertyKind::Url,
+            "Work",
+        ) => Some(&["work"]),
+        (PropertyKind::RelatedName, "Spouse") => Some(&["spouse"]),
+        (PropertyKind::RelatedName, "Child") => Some(&["child"]),
+        (PropertyKind::RelatedName, "Parent" | "Mother" | "Father") => Some(&["parent"]),
+        (PropertyKind::RelatedName, "Sibling" | "Brother" | "Sister") => Some(&["sibling"]),
+        (PropertyKind::RelatedName, "Friend") => Some(&["friend"]),
+        _ => None,
+    }
+}
+
+/// Escapes a text value
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ',' => escaped.push_str("\\,"),
+            ';' => escaped.push_str("\\;"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Escapes the components of a structured value, such as `N` or `ADR`, and joins them
+fn structured(components: &[Option<&str>]) -> String {
+    components
+        .iter()
+        .map(|component| escape(component.unwrap_or_default()))
+        .collect::<Vec<_>>()
+        .join(";")
+}
+
+/// Renders a contact as a vCard, with CRLF line breaks
+pub(crate) fn to_vcard(contact: &Contact, version: VCardVersion) -> String {
+    let mut writer = VCardWriter::new(version);
+    writer.line("BEGIN", "VCARD");
+    writer.line(
+        "VERSION",
+        match version {
+            VCardVersion::V3 => "3.0",
+            VCardVersion::V4 => "4.0",
+        },
+    );
+
+    writer.line(
+        "N",
+        &structured(&[
+            contact.last_name.as_deref(),
+            contact.first_name.as_deref(),
+            contact.middle_name.as_deref(),
+            contact.prefix.as_deref(),
+            contact.suffix.as_deref(),
+        ]),
+    );
+    writer.line("FN", &escape(&contact.display_name()));
+    writer.text("NICKNAME", contact.nickname.as_deref());
+    if contact.organization.is_some() || contact.department.is_some() {
+        let mut components = vec![contact.organization.as_deref()];
+        if contact.department.is_some() {
+            components.push(contact.department.as_deref());
+        }
+        writer.line("ORG", &structured(&components));
+    }
+    writer.text("TITLE", contact.job_title.as_deref());
+    if let Some(birthday) = contact.birthday {
+        writer.birthday(birthday);
+    }
+
+    for phone in &contact.phones {
+        writer.labeled(
+            PropertyKind::Phone,
+            "TEL",
+            phone.label.as_deref(),
+            &escape(&phone.value),
+        );
+    }
+    for email in &contact.emails {
+        writer.labeled(
+            PropertyKind::Email,
+            "EMAIL",
+            email.label.as_deref(),
+            &escape(&email.value),
+        );
+    }
+    for address in &contact.addresses {
+        let value = &address.value;
+        let value = structured(&[
+            None,
+            None,
+            value.street.as_deref(),
+            value.city.as_deref(),
+            value.state.as_deref(),
+            value.postal_code.as_deref(),
+            value.country.as_deref(),
+        ]);
+        writer.labeled(
+            PropertyKind::Address,
+            "ADR",
+            address.label.as_deref(),
+            &value,
+        );
+    }
+    for url in &contact.urls {
+        writer.labeled(
+            PropertyKind::Url,
+            "URL",
+            url.label.as_deref(),
+            &escape(&url.value),
+        );
+    }
+    for date in &contact.dates {
+        writer.other_date(date);
+    }
+    for related_name in &contact.related_names {
+        let name = match version {
+            VCardVersion::V3 => "X-ABRELATEDNAMES",
+            VCardVersion::V4 => "RELATED",
+        };
+        writer.labeled(
+            PropertyKind::RelatedName,
+            name,
+            related_name.label.as_deref(),
+            &escape(&related_name.value),
+        );
+    }
+
+    writer.text("NOTE", contact.note.as_deref());
+    if let Some(photo) = &contact.photo {
+        writer.photo(photo);
+    }
+    writer.line("END", "VCARD");
+    writer.output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entities::PostalAddress;
+    use pretty_assertions::assert_eq;
+
+    fn create_contact() -> Contact {
+        let mut contact = Contact::new(1);
+        contact.first_name = Some("Jane".to_owned());
+        contact.last_name = Some("Appleseed".to_owned());
+        contact.organization = Some("Apple, Inc.".to_owned());
+        contact.birthday = NaiveDate::from_ymd_opt(1604, 3, 15);
+        contact.phones = vec![
+            Labeled::new(Some("Mobile".to_owned()), "+1 555 0001".to_owned()),
+            Labeled::new(Some("Cabin".to_owned()), "+1 555 0002".to_owned()),
+        ];
+        contact.emails = vec![Labeled::new(
+            Some("Work".to_owned()),
+            "jane@example.com".to_owned(),
+        )];
+        contact.addresses = vec![Labeled::new(
+            Some("Home".to_owned()),
+            PostalAddress {
+                street: Some("1 Infinite Loop".to_owned()),
+                city: Some("Cupertino".to_owned()),
+                ..PostalAddress::default()
+            },
+        )];
+        contact.related_names = vec![Labeled::new(Some("Spouse".to_owned()), "John".to_owned())];
+        contact.note = Some("Line 1\nLine 2; more".to_owned());
+        contact
+    }
+
+    #[test]
+    fn test_to_vcard_v3() {
+        assert_eq!(
+            to_vcard(&create_contact(), VCardVersion::V3),
+            "BEGIN:VCARD\r\n\
+             VERSION:3.0\r\n\
+             N:Appleseed;Jane;;;\r\n\
+             FN:Jane Appleseed\r\n\
+             ORG:Apple\\, Inc.\r\n\
+             BDAY;X-APPLE-OMIT-YEAR=1604:1604-03-15\r\n\
+             TEL;TYPE=CELL:+1 555 0001\r\n\
+             item1.TEL:+1 555 0002\r\n\
+             item1.X-ABLabel:Cabin\r\n\
+             EMAIL;TYPE=INTERNET,WORK:jane@example.com\r\n\
+             ADR;TYPE=HOME:;;1 Infinite Loop;Cupertino;;;\r\n\
+             X-ABRELATEDNAMES;TYPE=SPOUSE:John\r\n\
+             NOTE:Line 1\\nLine 2\\; more\r\n\
+             END:VCARD\r\n"
+        );
+    }
+
+    #[test]
+    fn test_to_vcard_v4() {
+        let output = to_vcard(&create_contact(), VCardVersion::V4);
+
+        assert!(output.contains("VERSION:4.0\r\n"));
+        assert!(output.contains("BDAY:--0315\r\n"));
+        assert!(output.contains("EMAIL;TYPE=work:jane@example.com\r\n"));
+        assert!(output.contains("RELATED;VALUE=text;TYPE=spouse:John\r\n"));
+    }
+
+    #[test]
+    fn test_to_vcard_folds_photo() {
+        let mut contact = Contact::new(1);
+        contact.photo = Some(vec![0xFF; 100]);
+
+        let output = to_vcard(&contact, VCardVersion::V4);
+
+        assert!(output.contains("PHOTO:data:image/jpeg;base64,"));
+        assert!(
+            output
+                .split("\r\n")
+                .all(|line| line.len() <= MAX_LINE_LENGTH)
+        );
+        let unfolded = output.replace("\r\n ", "");
+        assert!(unfolded.contains(&format!(
+            "PHOTO:data:image/jpeg;base64,{}\r\n",
+            STANDARD.encode([0xFF; 100])
+        )));
+    }
+}
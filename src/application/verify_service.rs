@@ -0,0 +1,594 @@
+//! Verify service for checking the integrity of iPhone backups
+
+use crate::application::hash_manifest::{FileHashes, HashManifest, HashManifestEntry, hex};
+use crate::application::search_service::FileType;
+use crate::domain::entities::File;
+use crate::domain::queries::FileQuery;
+use crate::domain::repositories::{BlobRepository, FileRepository};
+use crate::domain::value_objects::FileMetadata;
+use anyhow::{Context as _, Result};
+use sha1::{Digest as _, Sha1};
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+/// Size of an AES block, which encrypted blobs are padded to
+const BLOCK_SIZE: u64 = 16;
+
+/// Kind of an integrity problem
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum IssueKind {
+    /// A regular file has no blob
+    Missing,
+    /// The size of a blob differs from the size recorded in the manifest
+    SizeMismatch,
+    /// The SHA1 digest of the content differs from the digest recorded in the manifest
+    DigestMismatch,
+    /// A blob exists but cannot be read or decrypted
+    Unreadable,
+    /// A blob has no row in the manifest
+    Orphaned,
+}
+
+impl IssueKind {
+    /// Returns the name used in output
+    #[must_use]
+    #[inline]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Missing => "missing",
+            Self::SizeMismatch => "size_mismatch",
+            Self::DigestMismatch => "digest_mismatch",
+            Self::Unreadable => "unreadable",
+            Self::Orphaned => "orphaned",
+        }
+    }
+}
+
+/// An integrity problem found in a backup
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct VerifyIssue {
+    /// Kind of the problem
+    pub kind: IssueKind,
+    /// File ID in the backup
+    pub file_id: String,
+    /// Domain of the file, or empty for orphaned blobs
+    pub domain: String,
+    /// Relative path of the file, or empty for orphaned blobs
+    pub relative_path: String,
+    /// Location of the blob or extracted file, if it exists
+    pub path: Option<PathBuf>,
+    /// Description of the problem
+    pub detail: String,
+}
+
+impl VerifyIssue {
+    /// Creates an issue about a file of the manifest
+    fn new(kind: IssueKind, file: &File, path: Option<PathBuf>, detail: String) -> Self {
+        Self {
+            kind,
+            file_id: file.id().to_string(),
+            domain: file.domain().to_string(),
+            relative_path: file.relative_path().to_string(),
+            path,
+            detail,
+        }
+    }
+}
+
+/// Result of verifying a backup
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub struct VerifyReport {
+    /// Number of rows in the backup or hash manifest
+    pub file_count: usize,
+    /// Number of regular files checked against their blobs or digests
+    pub checked_count: usize,
+    /// Number of blobs in the backup directory, or 0 when checking an extraction
+    pub blob_count: usize,
+    /// Problems found, in manifest order followed by orphaned blobs
+    pub issues: Vec<VerifyIssue>,
+}
+
+impl VerifyReport {
+    /// Checks if no problems were found
+    #[must_use]
+    #[inline]
+    pub const fn is_healthy(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Returns the number of problems of the given kind
+    #[must_use]
+    #[inline]
+    pub fn issue_count(&self, kind: IssueKind) -> usize {
+        self.issues
+            .iter()
+            .filter(|issue| issue.kind == kind)
+            .count()
+    }
+}
+
+/// Service for verifying backups against their manifest, and extracted files against a
+/// hash manifest
+///
+/// Every regular file of the manifest is checked to have a blob of the recorded size, and
+/// its content is checked against the SHA1 digest recorded by legacy `Manifest.mbdb`
+/// backups. Blobs that no manifest row refers to are reported as orphaned.
+#[derive(Debug, Default)]
+#[non_exhaustive]
+pub struct VerifyService;
+
+impl VerifyService {
+    /// Creates a new `VerifyService`
+    #[must_use]
+    #[inline]
+    pub const fn new() -> Self {
+        Self
+    }
+
+    /// Verifies a backup
+    ///
+    /// Problems with single files are collected in the report rather than failing the
+    /// verification.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The manifest cannot be read
+    /// - The backup directory cannot be listed
+    #[allow(
+        clippy::future_not_send,
+        reason = "Repository trait doesn't guarantee Send futures"
+    )]
+    #[inline]
+    pub async fn verify<R: FileRepository, B: BlobRepository>(
+        &self,
+        repository: &R,
+        blobs: &B,
+    ) -> Result<VerifyReport> {
+        let files = repository
+            .search(FileQuery::all())
+            .await
+            .context("Failed to read the manifest")?;
+
+        let mut report = VerifyReport {
+            file_count: files.len(),
+            ..VerifyReport::default()
+        };
+
+        for file in files
+            .iter()
+            .filter(|file| FileType::of(file) == FileType::File)
+        {
+            report.checked_count = report.checked_count.saturating_add(1);
+            if let Some(issue) = Self::verify_file(file, blobs) {
+                report.issues.push(issue);
+            }
+        }
+
+        let file_ids: HashSet<_> = files.iter().map(File::id).collect();
+        for (file_id, path) in blobs.list()? {
+            report.blob_count = report.blob_count.saturating_add(1);
+            if !file_ids.contains(&file_id) {
+                report.issues.push(VerifyIssue {
+                    kind: IssueKind::Orphaned,
+                    file_id: file_id.to_string(),
+                    domain: String::new(),
+                    relative_path: String::new(),
+                    path: Some(path),
+                    detail: "Blob has no row in the manifest".to_owned(),
+                });
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Verifies extracted files against a hash manifest
+    ///
+    /// Every file of the manifest is checked to exist below `output_dir` with the recorded
+    /// size and digests. Files that are not in the manifest are ignored.
+    #[must_use]
+    #[inline]
+    pub fn verify_extraction(&self, output_dir: &Path, manifest: &HashManifest) -> VerifyReport {
+        let mut report = VerifyReport {
+            file_count: manifest.entries.len(),
+            ..VerifyReport::default()
+        };
+
+        for entry in &manifest.entries {
+            report.checked_count = report.checked_count.saturating_add(1);
+            if let Some(issue) = Self::verify_extracted_file(output_dir, entry) {
+                report.issues.push(issue);
+            }
+        }
+
+        report
+    }
+
+    /// Checks an extracted file against its manifest entry
+    fn verify_extracted_file(output_dir: &Path, entry: &HashManifestEntry) -> Option<VerifyIssue> {
+        let issue = |kind, path, detail| VerifyIssue {
+            kind,
+            file_id: entry.file_id.clone(),
+            domain: entry.domain.clone(),
+            relative_path: entry.relative_path.clone(),
+            path,
+            detail,
+        };
+
+        // Manifests may have been edited, so only paths below the output directory are read
+        if !entry
+            .destination
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+        {
+            return Some(issue(
+                IssueKind::Unreadable,
+                None,
+                format!(
+                    "Destination must be a relative path without '..': {}",
+                    entry.destination.display()
+                ),
+            ));
+        }
+
+        let path = output_dir.join(&entry.destination);
+        if !fs::symlink_metadata(&path).is_ok_and(|metadata| metadata.is_file()) {
+            return Some(issue(
+                IssueKind::Missing,
+                None,
+                "Extracted file does not exist".to_owned(),
+            ));
+        }
+
+        let algorithms = entry.algorithms();
+        let hashes = match FileHashes::of_file(&path, &algorithms) {
+            Ok(hashes) => hashes,
+            Err(e) => return Some(issue(IssueKind::Unreadable, Some(path), format!("{e:#}"))),
+        };
+
+        if hashes.size != entry.size {
+            return Some(issue(
+                IssueKind::SizeMismatch,
+                Some(path),
+                format!("Expected {} bytes, found {}", entry.size, hashes.size),
+            ));
+        }
+
+        algorithms.into_iter().find_map(|algorithm| {
+            let expected = entry.digest(algorithm).unwrap_or_default();
+            let found = hashes
+                .digests
+                .get(&algorithm)
+                .map(String::as_str)
+                .unwrap_or_default();
+            (!expected.eq_ignore_ascii_case(found)).then(|| {
+                issue(
+                    IssueKind::DigestMismatch,
+                    Some(path.clone()),
+                    format!("Expected {} {expected}, found {found}", algorithm.name()),
+                )
+            })
+        })
+    }
+
+    /// Checks the blob of a regular file
+    fn verify_file<B: BlobRepository>(file: &File, blobs: &B) -> Option<VerifyIssue> {
+        let metadata = file.file_metadata();
+
+        let Some(path) = blobs.locate(file) else {
+            // Empty files are not required to have a blob
+            if file.size() == Some(0) {
+                return None;
+            }
+            return Some(VerifyIssue::new(
+                IssueKind::Missing,
+                file,
+                None,
+                "Blob does not exist".to_owned(),
+            ));
+        };
+
+        if let Some(expected) = metadata.map(Self::stored_size) {
+            match fs::metadata(&path) {
+                Ok(stored) if stored.len() != expected => {
+                    return Some(VerifyIssue::new(
+                        IssueKind::SizeMismatch,
+                        file,
+                        Some(path),
+                        format!("Expected {expected} bytes, found {}", stored.len()),
+                    ));
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    return Some(VerifyIssue::new(
+                        IssueKind::Unreadable,
+                        file,
+                        Some(path),
+                        e.to_string(),
+                    ));
+                }
+            }
+        }
+
+        let expected_digest = metadata.and_then(FileMetadata::digest)?;
+        match Self::digest(file, blobs) {
+            Ok(digest) if digest == expected_digest => None,
+            Ok(digest) => Some(VerifyIssue::new(
+                IssueKind::DigestMismatch,
+                file,
+                Some(path),
+                format!(
+                    "Expected SHA1 {}, found {}",
+                    hex(expected_digest),
+                    hex(&digest)
+                ),
+            )),
+            Err(e) => Some(VerifyIssue::new(
+                IssueKind::Unreadable,
+                file,
+                Some(path),
+                format!("{e:#}"),
+            )),
+        }
+    }
+
+    /// Returns the size of the blob of a file with the given metadata
+    ///
+    /// Encrypted blobs are padded with PKCS#7 to the next whole AES block, so they are
+    /// always longer than the content.
+    fn stored_size(metadata: &FileMetadata) -> u64 {
+        if metadata.encryption_key().is_some() {
+            (metadata.size() | (BLOCK_SIZE - 1)).saturating_add(1)
+        } else {
+            metadata.size()
+        }
+    }
+
+    /// Computes the SHA1 digest of the content of a file, decrypting it if needed
+    fn digest<B: BlobRepository>(file: &File, blobs: &B) -> Result<Vec<u8>> {
+        let mut content = blobs.open(file)?.context("Blob does not exist")?;
+        let mut hasher = Sha1::new();
+        io::copy(&mut content, &mut hasher).context("Failed to read blob")?;
+        Ok(hasher.finalize().to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::value_objects::{Domain, FileFlags, FileId, RelativePath};
+    use crate::infrastructure::crypto::Keybag;
+    use crate::infrastructure::repositories::BlobRepositoryImpl;
+    use assert_fs::TempDir;
+    use assert_fs::prelude::*;
+    use pretty_assertions::assert_eq;
+
+    struct MockFileRepository {
+        files: Vec<File>,
+    }
+
+    impl FileRepository for MockFileRepository {
+        async fn search(&self, _query: FileQuery) -> Result<Vec<File>> {
+            Ok(self.files.clone())
+        }
+    }
+
+    fn create_test_file(id: &str, path: &str, flags: FileFlags, size: u64) -> Result<File> {
+        Ok(File::new(
+            FileId::new(id)?,
+            Domain::new("HomeDomain".to_owned())?,
+            RelativePath::new(path.to_owned())?,
+            flags,
+            vec![],
+        )
+        .with_file_metadata(FileMetadata::new(size, 0o100_644)))
+    }
+
+    fn write_blob(temp_backup: &TempDir, id: &str, content: &[u8]) -> Result<()> {
+        temp_backup
+            .child(&id[0..2])
+            .child(id)
+            .write_binary(content)?;
+        Ok(())
+    }
+
+    async fn verify(temp_backup: &TempDir, files: Vec<File>) -> Result<VerifyReport> {
+        VerifyService::new()
+            .verify(
+                &MockFileRepository { files },
+                &BlobRepositoryImpl::new(temp_backup.path()),
+            )
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_verify_healthy_backup() -> Result<()> {
+        let temp_backup = TempDir::new()?;
+        let id = "3d0d7e5fb2ce288813306e4d4636395e047a3d28";
+        write_blob(&temp_backup, id, b"content")?;
+        temp_backup.child("Manifest.db").touch()?;
+        let files = vec![
+            create_test_file(id, "Library/SMS/sms.db", FileFlags::REGULAR_FILE, 7)?,
+            create_test_file(
+                "da39a3ee5e6b4b0d3255bfef95601890afd80709",
+                "Library/SMS",
+                FileFlags::DIRECTORY,
+                0,
+            )?,
+            // Empty files may have no blob
+            create_test_file(
+                "356a192b7913b04c54574d18c28d46e6395428ab",
+                "Library/empty.txt",
+                FileFlags::REGULAR_FILE,
+                0,
+            )?,
+        ];
+
+        let report = verify(&temp_backup, files).await?;
+
+        assert!(report.is_healthy());
+        assert_eq!(report.file_count, 3);
+        assert_eq!(report.checked_count, 2);
+        assert_eq!(report.blob_count, 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_verify_reports_problems() -> Result<()> {
+        let temp_backup = TempDir::new()?;
+        let truncated = "3d0d7e5fb2ce288813306e4d4636395e047a3d28";
+        let orphan = "77de68daecd823babbb58edb1c8e14d7106e83bb";
+        write_blob(&temp_backup, truncated, b"cont")?;
+        write_blob(&temp_backup, orphan, b"left over")?;
+        let files = vec![
+            create_test_file(truncated, "Library/SMS/sms.db", FileFlags::REGULAR_FILE, 7)?,
+            create_test_file(
+                "da39a3ee5e6b4b0d3255bfef95601890afd80709",
+                "Library/Notes/notes.sqlite",
+                FileFlags::REGULAR_FILE,
+                3,
+            )?,
+        ];
+
+        let report = verify(&temp_backup, files).await?;
+
+        assert!(!report.is_healthy());
+        let issues: Vec<_> = report
+            .issues
+            .iter()
+            .map(|issue| (issue.kind, issue.file_id.as_str()))
+            .collect();
+        assert_eq!(
+            issues,
+            vec![
+                (IssueKind::SizeMismatch, truncated),
+                (
+                    IssueKind::Missing,
+                    "da39a3ee5e6b4b0d3255bfef95601890afd80709"
+                ),
+                (IssueKind::Orphaned, orphan),
+            ]
+        );
+        assert_eq!(report.issues[0].detail, "Expected 7 bytes, found 4");
+        assert_eq!(
+            report.issues[2].path,
+            Some(temp_backup.path().join("77").join(orphan))
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_verify_legacy_digests() -> Result<()> {
+        let temp_backup = TempDir::new()?;
+        let intact = "3d0d7e5fb2ce288813306e4d4636395e047a3d28";
+        let altered = "da39a3ee5e6b4b0d3255bfef95601890afd80709";
+        // Legacy backups store blobs directly in the backup directory
+        temp_backup.child(intact).write_str("content")?;
+        temp_backup.child(altered).write_str("CONTENT")?;
+        let digest = Sha1::digest(b"content").to_vec();
+        let files = [intact, altered]
+            .into_iter()
+            .map(|id| {
+                let file = create_test_file(id, id, FileFlags::REGULAR_FILE, 7)?;
+                Ok(file.with_file_metadata(
+                    FileMetadata::new(7, 0o100_644).with_digest(Some(digest.clone())),
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let report = verify(&temp_backup, files).await?;
+
+        assert_eq!(report.blob_count, 2);
+        assert_eq!(report.issues.len(), 1);
+        assert_eq!(report.issues[0].kind, IssueKind::DigestMismatch);
+        assert_eq!(report.issues[0].file_id, altered);
+        assert!(report.issues[0].detail.contains(&hex(&digest)));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_verify_encrypted_blob_size() -> Result<()> {
+        let temp_backup = TempDir::new()?;
+        let id = "3d0d7e5fb2ce288813306e4d4636395e047a3d28";
+        // 16 bytes of content take two blocks with their padding
+        write_blob(&temp_backup, id, &[0; 32])?;
+        let file = create_test_file(id, "Library/SMS/sms.db", FileFlags::REGULAR_FILE, 16)?
+            .with_file_metadata(
+                FileMetadata::new(16, 0o100_644)
+                    .with_encryption_key(Some(Keybag::wrap_for_test(3, [3; 32], &[9; 32])?)),
+            );
+
+        let report = verify(&temp_backup, vec![file]).await?;
+
+        assert!(report.is_healthy());
+        Ok(())
+    }
+
+    #[test]
+    fn test_verify_extraction() -> Result<()> {
+        let temp_output = TempDir::new()?;
+        temp_output.child("Documents/intact.txt").write_str("abc")?;
+        temp_output
+            .child("Documents/modified.txt")
+            .write_str("abd")?;
+        temp_output
+            .child("Documents/truncated.txt")
+            .write_str("ab")?;
+        let entry = |file_id: &str, destination: &str| HashManifestEntry {
+            file_id: file_id.to_owned(),
+            domain: "HomeDomain".to_owned(),
+            relative_path: destination.to_owned(),
+            destination: PathBuf::from(destination),
+            size: 3,
+            sha256: None,
+            sha1: Some("a9993e364706816aba3e25717850c26c9cd0d89d".to_owned()),
+            // Digests are compared case-insensitively
+            md5: Some("900150983CD24FB0D6963F7D28E17F72".to_owned()),
+        };
+        let manifest = HashManifest {
+            entries: vec![
+                entry("1", "Documents/intact.txt"),
+                entry("2", "Documents/modified.txt"),
+                entry("3", "Documents/truncated.txt"),
+                entry("4", "Documents/missing.txt"),
+                entry("5", "../Documents/intact.txt"),
+            ],
+        };
+
+        let report = VerifyService::new().verify_extraction(temp_output.path(), &manifest);
+
+        assert_eq!(report.file_count, 5);
+        assert_eq!(report.checked_count, 5);
+        assert_eq!(report.blob_count, 0);
+        let issues: Vec<_> = report
+            .issues
+            .iter()
+            .map(|issue| (issue.kind, issue.file_id.as_str()))
+            .collect();
+        assert_eq!(
+            issues,
+            vec![
+                (IssueKind::DigestMismatch, "2"),
+                (IssueKind::SizeMismatch, "3"),
+                (IssueKind::Missing, "4"),
+                (IssueKind::Unreadable, "5"),
+            ]
+        );
+        assert!(
+            report.issues[0]
+                .detail
+                .starts_with("Expected sha1 a9993e364706816aba3e25717850c26c9cd0d89d, found ")
+        );
+        assert_eq!(
+            report.issues[0].path,
+            Some(temp_output.path().join("Documents/modified.txt"))
+        );
+        Ok(())
+    }
+}
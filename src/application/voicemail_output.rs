@@ -0,0 +1,151 @@
+//! Rows and records of voicemail messages
+
+use serde::Serialize;
+use tabled::Tabled;
+
+use crate::application::display_service::{Listing, table_duration, table_time};
+use crate::application::voicemail_service::VoicemailSummary;
+
+/// Represents a voicemail for table display
+#[derive(Tabled)]
+pub struct VoicemailRow {
+    #[tabled(rename = "Date")]
+    date: String,
+    #[tabled(rename = "Sender")]
+    sender: String,
+    #[tabled(rename = "Callback")]
+    callback_number: String,
+    #[tabled(rename = "Duration")]
+    duration: String,
+    #[tabled(rename = "Deleted")]
+    trashed: &'static str,
+    #[tabled(rename = "Audio")]
+    audio: String,
+}
+
+impl VoicemailRow {
+    fn new(summary: &VoicemailSummary) -> Self {
+        let voicemail = &summary.voicemail;
+        let audio = match (&summary.audio, summary.has_audio) {
+            (Some(path), _) => path.display().to_string(),
+            (None, true) => "yes".to_owned(),
+            (None, false) => "missing".to_owned(),
+        };
+        Self {
+            date: table_time(voicemail.date),
+            sender: voicemail.sender.clone().unwrap_or_default(),
+            callback_number: voicemail.callback_number.clone().unwrap_or_default(),
+            duration: table_duration(voicemail.duration),
+            trashed: if voicemail.trashed_date.is_some() {
+                "yes"
+            } else {
+                "no"
+            },
+            audio,
+        }
+    }
+}
+
+/// Serialized form of a voicemail
+#[derive(Serialize)]
+struct VoicemailRecord<'a> {
+    id: i64,
+    date: Option<String>,
+    sender: Option<&'a str>,
+    callback_number: Option<&'a str>,
+    duration_seconds: u64,
+    trashed_date: Option<String>,
+    has_audio: bool,
+    audio: Option<String>,
+}
+
+impl<'a> VoicemailRecord<'a> {
+    fn new(summary: &'a VoicemailSummary) -> Self {
+        let voicemail = &summary.voicemail;
+        Self {
+            id: voicemail.id,
+            date: voicemail.date.map(|date| date.to_rfc3339()),
+            sender: voicemail.sender.as_deref(),
+            callback_number: voicemail.callback_number.as_deref(),
+            duration_seconds: voicemail.duration.as_secs(),
+            trashed_date: voicemail.trashed_date.map(|date| date.to_rfc3339()),
+            has_audio: summary.has_audio,
+            audio: summary
+                .audio
+                .as_ref()
+                .map(|path| path.display().to_string()),
+        }
+    }
+}
+
+impl Listing for VoicemailSummary {
+    type Row = VoicemailRow;
+
+    const EMPTY: &'static str = "No voicemails found.";
+
+    fn row(&self) -> Self::Row {
+        VoicemailRow::new(self)
+    }
+
+    fn record(&self) -> impl Serialize {
+        VoicemailRecord::new(self)
+    }
+
+    fn summary(voicemails: &[Self]) -> String {
+        format!("Found {} voicemail(s)", voicemails.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use chrono::DateTime;
+    use serde_json::Value;
+    use std::time::Duration;
+
+    use super::*;
+    use crate::application::{JsonFormatter, OutputFormatter as _, TableFormatter};
+    use crate::domain::entities::Voicemail;
+
+    fn create_voicemail_summary() -> VoicemailSummary {
+        VoicemailSummary {
+            voicemail: Voicemail {
+                id: 3,
+                sender: Some("+15550001".to_owned()),
+                callback_number: None,
+                date: DateTime::from_timestamp(1_700_000_000, 0),
+                duration: Duration::from_secs(23),
+                trashed_date: None,
+            },
+            has_audio: true,
+            audio: Some("voicemail/20231114-221320_+15550001.amr".into()),
+        }
+    }
+
+    #[test]
+    fn test_format_voicemails() -> Result<()> {
+        let formatter = TableFormatter::new();
+
+        let output = formatter.format_voicemails(&[create_voicemail_summary()])?;
+
+        assert!(output.contains("2023-11-14 22:13:20"));
+        assert!(output.contains("0:23"));
+        assert!(output.contains("voicemail/20231114-221320_+15550001.amr"));
+        assert!(output.ends_with("Found 1 voicemail(s)"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_json_format_voicemails() -> Result<()> {
+        let formatter = JsonFormatter::new();
+
+        let output = formatter.format_voicemails(&[create_voicemail_summary()])?;
+
+        let value: Value = serde_json::from_str(&output)?;
+        assert_eq!(value[0]["date"], "2023-11-14T22:13:20+00:00");
+        assert_eq!(value[0]["duration_seconds"], 23);
+        assert_eq!(value[0]["trashed_date"], Value::Null);
+        assert_eq!(value[0]["audio"], "voicemail/20231114-221320_+15550001.amr");
+        Ok(())
+    }
+}
@@ -0,0 +1,268 @@
+//! Voicemail service for listing voicemail messages and extracting their recordings
+
+use crate::application::export_util::{UniqueNames, file_name};
+use crate::domain::entities::{File, Voicemail};
+use crate::domain::queries::FileQuery;
+use crate::domain::repositories::{BlobRepository, FileRepository, VoicemailRepository};
+use anyhow::{Context as _, Result};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Domain holding the voicemail recordings
+const AUDIO_DOMAIN: &str = "HomeDomain";
+
+/// Directory of the voicemail recordings in their domain
+const AUDIO_DIR: &str = "Library/Voicemail/";
+
+/// A voicemail with the state of its recording
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct VoicemailSummary {
+    /// The voicemail
+    pub voicemail: Voicemail,
+    /// Whether the backup holds the recording
+    pub has_audio: bool,
+    /// Location of the extracted recording, if recordings were extracted
+    pub audio: Option<PathBuf>,
+}
+
+/// Service for listing the voicemail messages of the Phone app
+#[derive(Debug, Default)]
+#[non_exhaustive]
+pub struct VoicemailService {
+    /// Directory to extract the recordings to
+    audio_dir: Option<PathBuf>,
+}
+
+impl VoicemailService {
+    /// Creates a new `VoicemailService` that does not extract recordings
+    #[must_use]
+    #[inline]
+    pub const fn new() -> Self {
+        Self { audio_dir: None }
+    }
+
+    /// Sets the directory to extract the recordings to
+    #[must_use]
+    #[inline]
+    pub fn with_audio_dir(mut self, audio_dir: Option<PathBuf>) -> Self {
+        self.audio_dir = audio_dir;
+        self
+    }
+
+    /// Lists every voicemail, most recent first, pairing it with its `.amr` recording
+    ///
+    /// If an audio directory is set, the recordings are copied into it as
+    /// `<date>_<sender>.amr`, e.g. `20240115-023000_+15550001.amr`. Existing files are
+    /// overwritten.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a repository fails or a recording cannot be written
+    #[allow(
+        clippy::future_not_send,
+        reason = "Repository trait doesn't guarantee Send futures"
+    )]
+    #[inline]
+    pub async fn list<V: VoicemailRepository, F: FileRepository, B: BlobRepository>(
+        &self,
+        voicemails: &V,
+        files: &F,
+        blobs: &B,
+    ) -> Result<Vec<VoicemailSummary>> {
+        let voicemails = voicemails.find_all().await?;
+
+        let query = FileQuery::and(vec![
+            FileQuery::domain_exact(AUDIO_DOMAIN),
+            FileQuery::path_contains(AUDIO_DIR),
+        ]);
+        let audio_files: HashMap<String, File> = files
+            .search(query)
+            .await?
+            .into_iter()
+            .map(|file| (file.relative_path().value().to_owned(), file))
+            .collect();
+
+        if let Some(audio_dir) = &self.audio_dir {
+            fs::create_dir_all(audio_dir)
+                .with_context(|| format!("Failed to create directory: {}", audio_dir.display()))?;
+        }
+
+        let mut names = UniqueNames::default();
+        let mut summaries = Vec::with_capacity(voicemails.len());
+        for voicemail in voicemails {
+            let file = audio_files.get(&voicemail.audio_path());
+            let audio = match (file, &self.audio_dir) {
+                (Some(file), Some(audio_dir)) => {
+                    let name = names.claim(audio_name(&voicemail), voicemail.id);
+                    let path = audio_dir.join(format!("{name}.amr"));
+                    copy_audio(blobs, file, &path)?.then_some(path)
+                }
+                _ => None,
+            };
+
+            summaries.push(VoicemailSummary {
+                voicemail,
+                has_audio: file.is_some(),
+                audio,
+            });
+        }
+
+        Ok(summaries)
+    }
+}
+
+/// Returns the file name of a recording, without extension
+fn audio_name(voicemail: &Voicemail) -> String {
+    let date = voicemail.date.map_or_else(
+        || "unknown-date".to_owned(),
+        |date| date.format("%Y%m%d-%H%M%S").to_string(),
+    );
+    let sender = voicemail
+        .sender
+        .as_deref()
+        .and_then(file_name)
+        .unwrap_or_else(|| "unknown".to_owned());
+    format!("{date}_{sender}")
+}
+
+/// Copies a recording out of the backup, returning `false` if it has no content
+fn copy_audio<B: BlobRepository>(blobs: &B, file: &File, path: &Path) -> Result<bool> {
+    let Some(mut source) = blobs.open(file)? else {
+        return Ok(false);
+    };
+    let mut destination = fs::File::create(path)
+        .with_context(|| format!("Failed to create recording: {}", path.display()))?;
+    io::copy(&mut source, &mut destination)
+        .with_context(|| format!("Failed to copy recording: {}", path.display()))?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::value_objects::{Domain, FileFlags, FileId, RelativePath};
+    use crate::infrastructure::repositories::BlobRepositoryImpl;
+    use assert_fs::TempDir;
+    use assert_fs::prelude::*;
+    use chrono::DateTime;
+    use pretty_assertions::assert_eq;
+    use std::time::Duration;
+
+    struct MockVoicemailRepository {
+        voicemails: Vec<Voicemail>,
+    }
+
+    impl VoicemailRepository for MockVoicemailRepository {
+        async fn find_all(&self) -> Result<Vec<Voicemail>> {
+            Ok(self.voicemails.clone())
+        }
+    }
+
+    // Mock repository returning every file
+    struct MockFileRepository {
+        files: Vec<File>,
+    }
+
+    impl FileRepository for MockFileRepository {
+        async fn search(&self, _query: FileQuery) -> Result<Vec<File>> {
+            Ok(self.files.clone())
+        }
+    }
+
+    fn create_voicemail(id: i64, sender: Option<&str>) -> Voicemail {
+        Voicemail {
+            id,
+            sender: sender.map(str::to_owned),
+            callback_number: None,
+            date: DateTime::from_timestamp(1_700_000_000, 0),
+            duration: Duration::from_secs(10),
+            trashed_date: None,
+        }
+    }
+
+    async fn list(audio_dir: Option<PathBuf>) -> Result<(TempDir, Vec<VoicemailSummary>)> {
+        let temp_backup = TempDir::new()?;
+        let mut audio_files = Vec::new();
+        for (id, hash) in [
+            (1, "1111111111111111111111111111111111111111"),
+            (2, "2222222222222222222222222222222222222222"),
+        ] {
+            let file = File::new(
+                FileId::new(hash)?,
+                Domain::new(AUDIO_DOMAIN.to_owned())?,
+                RelativePath::new(format!("{AUDIO_DIR}{id}.amr"))?,
+                FileFlags::REGULAR_FILE,
+                vec![],
+            );
+            temp_backup
+                .child(&hash[..2])
+                .child(hash)
+                .write_str(&format!("audio {id}"))?;
+            audio_files.push(file);
+        }
+
+        let summaries = VoicemailService::new()
+            .with_audio_dir(audio_dir)
+            .list(
+                &MockVoicemailRepository {
+                    voicemails: vec![
+                        create_voicemail(1, Some("+15550001")),
+                        create_voicemail(2, Some("+15550001")),
+                        create_voicemail(3, None),
+                    ],
+                },
+                &MockFileRepository { files: audio_files },
+                &BlobRepositoryImpl::new(temp_backup.path()),
+            )
+            .await?;
+        Ok((temp_backup, summaries))
+    }
+
+    #[tokio::test]
+    async fn test_list_pairs_recordings() -> Result<()> {
+        let (_temp_backup, summaries) = list(None).await?;
+
+        let audio: Vec<_> = summaries
+            .iter()
+            .map(|summary| {
+                (
+                    summary.voicemail.id,
+                    summary.has_audio,
+                    summary.audio.clone(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            audio,
+            vec![(1, true, None), (2, true, None), (3, false, None)]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_list_extracts_recordings() -> Result<()> {
+        let temp_output = TempDir::new()?;
+
+        let (_temp_backup, summaries) = list(Some(temp_output.path().to_path_buf())).await?;
+
+        let audio: Vec<_> = summaries
+            .iter()
+            .map(|summary| summary.audio.clone())
+            .collect();
+        assert_eq!(
+            audio,
+            vec![
+                Some(temp_output.path().join("20231114-221320_+15550001.amr")),
+                Some(temp_output.path().join("20231114-221320_+15550001-2.amr")),
+                None,
+            ]
+        );
+        temp_output
+            .child("20231114-221320_+15550001-2.amr")
+            .assert("audio 2");
+        Ok(())
+    }
+}
@@ -1,6 +1,13 @@
 //! CLI configuration and command definitions
 
-use clap::{Parser, Subcommand, ValueHint};
+use crate::application::{
+    AlbumExport, ConflictPolicy, FileType, HashAlgorithm, ManifestFormat, MetadataColumn,
+    OutputFormat, OutputLayout, SafariFormat, TranscriptFormat, VCardVersion,
+};
+use crate::domain::value_objects::DomainKind;
+use clap::builder::PossibleValue;
+use clap::{Args, Parser, Subcommand, ValueEnum, ValueHint};
+use std::num::NonZeroUsize;
 use std::path::PathBuf;
 
 #[derive(Parser)]
@@ -11,6 +18,10 @@ use std::path::PathBuf;
 pub struct Cli {
     #[command(subcommand)]
     pub command: Commands,
+
+    /// Output format
+    #[arg(long, global = true, value_enum, default_value_t)]
+    pub format: OutputFormat,
 }
 
 #[derive(Subcommand)]
@@ -25,6 +36,23 @@ pub enum Commands {
         /// Root directory containing multiple backup folders
         #[arg(long, value_hint = ValueHint::DirPath, default_value="~/Library/Application Support/MobileSync/Backup")]
         backups_root: PathBuf,
+
+        /// Columns to show
+        #[arg(
+            long,
+            value_name = "COLUMN",
+            value_enum,
+            value_delimiter = ',',
+            default_values_t = MetadataColumn::DEFAULT
+        )]
+        columns: Vec<MetadataColumn>,
+    },
+
+    /// Show every detail of a backup
+    Info {
+        /// iPhone backup directory path (containing Info.plist)
+        #[arg(value_hint = ValueHint::DirPath)]
+        backup_dir: PathBuf,
     },
 
     /// Search for files based on various criteria
@@ -33,54 +61,374 @@ pub enum Commands {
         #[arg(short = 'b', long, value_hint = ValueHint::DirPath)]
         backup_dir: PathBuf,
 
-        /// Exact domain match
-        #[arg(long)]
-        domain_exact: Option<String>,
+        #[command(flatten)]
+        search: SearchArgs,
 
-        /// Partial domain match
+        /// Password of an encrypted backup (prompted for if omitted)
         #[arg(long)]
-        domain_contains: Option<String>,
+        password: Option<String>,
+    },
 
-        /// Exact path match
-        #[arg(long)]
-        path_exact: Option<String>,
+    /// Extract files based on search criteria
+    Extract(ExtractArgs),
 
-        /// Partial path match
-        #[arg(long)]
-        path_contains: Option<String>,
+    /// Verify that the files of a backup match its manifest
+    Verify {
+        /// iPhone backup directory path (containing Manifest.db)
+        #[arg(short = 'b', long, value_hint = ValueHint::DirPath)]
+        backup_dir: PathBuf,
 
-        /// Use OR logic instead of AND (default is AND)
+        /// Password of an encrypted backup (prompted for if omitted)
         #[arg(long)]
-        or: bool,
+        password: Option<String>,
     },
-    /// Extract files based on search criteria
-    Extract {
+
+    /// List the applications installed on the device and the files kept for them
+    Apps {
         /// iPhone backup directory path (containing Manifest.db)
         #[arg(short = 'b', long, value_hint = ValueHint::DirPath)]
         backup_dir: PathBuf,
 
-        /// Output directory for extracted files
-        #[arg(short, long, value_hint = ValueHint::DirPath)]
-        output: String,
-
-        /// Exact domain match
+        /// Password of an encrypted backup (prompted for if omitted)
         #[arg(long)]
-        domain_exact: Option<String>,
+        password: Option<String>,
+    },
 
-        /// Partial domain match
-        #[arg(long)]
-        domain_contains: Option<String>,
+    /// Export iMessage and SMS conversations with their attachments
+    Messages(MessagesArgs),
 
-        /// Exact path match
-        #[arg(long)]
-        path_exact: Option<String>,
+    /// Export the address book as vCards and CSV
+    Contacts(ContactsArgs),
+
+    /// List the call history, including video and app calls
+    Calls(CallsArgs),
+
+    /// List voicemail messages and optionally extract their recordings
+    Voicemail(VoicemailArgs),
 
-        /// Partial path match
+    /// Export the photos and videos of the camera roll with their capture dates
+    Photos(PhotosArgs),
+
+    /// Export the browsing history, bookmarks and open tabs of Safari
+    Safari(SafariArgs),
+
+    /// Compare two backups of the same device
+    Diff {
+        /// Older backup directory (containing Manifest.db)
+        #[arg(long, value_hint = ValueHint::DirPath)]
+        from: PathBuf,
+
+        /// Newer backup directory (containing Manifest.db)
+        #[arg(long, value_hint = ValueHint::DirPath)]
+        to: PathBuf,
+
+        #[command(flatten)]
+        search: SearchArgs,
+
+        /// Password of the older backup if encrypted (prompted for if omitted)
         #[arg(long)]
-        path_contains: Option<String>,
+        from_password: Option<String>,
 
-        /// Use OR logic instead of AND (default is AND)
+        /// Password of the newer backup if encrypted (prompted for if omitted)
         #[arg(long)]
-        or: bool,
+        to_password: Option<String>,
     },
+
+    /// Verify extracted files against a hash manifest written by `extract --hash`
+    VerifyExtraction {
+        /// Directory the files were extracted to
+        #[arg(value_hint = ValueHint::DirPath)]
+        dir: PathBuf,
+
+        /// Hash manifest saved as CSV or JSON
+        #[arg(value_hint = ValueHint::FilePath)]
+        manifest: PathBuf,
+    },
+}
+
+/// Options of the extract command
+#[derive(Args)]
+#[non_exhaustive]
+pub struct ExtractArgs {
+    /// iPhone backup directory path (containing Manifest.db)
+    #[arg(short = 'b', long, value_hint = ValueHint::DirPath)]
+    pub backup_dir: PathBuf,
+
+    /// Output directory for extracted files
+    #[arg(short, long, value_hint = ValueHint::DirPath, required_unless_present = "plan")]
+    pub output: Option<String>,
+
+    #[command(flatten)]
+    pub search: SearchArgs,
+
+    /// Directory layout of extracted files
+    #[arg(long, value_enum, default_value_t)]
+    pub layout: OutputLayout,
+
+    /// What to do when an extracted file already exists
+    #[arg(long, value_enum, default_value_t)]
+    pub on_conflict: ConflictPolicy,
+
+    /// Print the planned operations without writing anything
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Execute a plan saved from `--dry-run --format json` instead of searching
+    #[arg(
+        long,
+        value_hint = ValueHint::FilePath,
+        conflicts_with_all = ["output", "dry_run", "layout", "on_conflict", "SearchArgs"]
+    )]
+    pub plan: Option<PathBuf>,
+
+    /// Number of files to copy at the same time [default: number of CPUs]
+    #[arg(short, long)]
+    pub jobs: Option<NonZeroUsize>,
+
+    /// Restore the owner of extracted files (usually requires root)
+    #[arg(long)]
+    pub preserve_owner: bool,
+
+    /// Hash extracted files while copying them and write a manifest of the digests
+    #[arg(long, value_name = "ALGORITHM", value_enum, value_delimiter = ',')]
+    pub hash: Vec<HashAlgorithm>,
+
+    /// Path of the hash manifest [default: idig-hashes.csv, .json or .xml in the output]
+    #[arg(long, value_hint = ValueHint::FilePath, requires = "hash")]
+    pub hash_manifest: Option<PathBuf>,
+
+    /// Format of the hash manifest
+    #[arg(long, value_enum, default_value_t)]
+    pub hash_format: ManifestFormat,
+
+    /// Password of an encrypted backup (prompted for if omitted)
+    #[arg(long)]
+    pub password: Option<String>,
+}
+
+/// Options of the messages command
+#[derive(Args)]
+#[non_exhaustive]
+pub struct MessagesArgs {
+    /// iPhone backup directory path (containing Manifest.db)
+    #[arg(short = 'b', long, value_hint = ValueHint::DirPath)]
+    pub backup_dir: PathBuf,
+
+    /// Output directory, receiving one directory per conversation
+    #[arg(short = 'o', long, value_hint = ValueHint::DirPath)]
+    pub output: PathBuf,
+
+    /// Format of the transcripts
+    #[arg(long, value_enum, default_value_t)]
+    pub transcript_format: TranscriptFormat,
+
+    /// Write transcripts only, without copying attachments
+    #[arg(long)]
+    pub no_attachments: bool,
+
+    /// Password of an encrypted backup (prompted for if omitted)
+    #[arg(long)]
+    pub password: Option<String>,
+}
+
+/// Options of the contacts command
+#[derive(Args)]
+#[non_exhaustive]
+pub struct ContactsArgs {
+    /// iPhone backup directory path (containing Manifest.db)
+    #[arg(short = 'b', long, value_hint = ValueHint::DirPath)]
+    pub backup_dir: PathBuf,
+
+    /// Output directory, receiving the vCards and contacts.csv
+    #[arg(short = 'o', long, value_hint = ValueHint::DirPath)]
+    pub output: PathBuf,
+
+    /// Version of the vCards
+    #[arg(long, value_enum, default_value_t)]
+    pub vcard_version: VCardVersion,
+
+    /// Write one vCard file per contact instead of a combined contacts.vcf
+    #[arg(long)]
+    pub split: bool,
+
+    /// Password of an encrypted backup (prompted for if omitted)
+    #[arg(long)]
+    pub password: Option<String>,
+}
+
+/// Options of the calls command
+#[derive(Args)]
+#[non_exhaustive]
+pub struct CallsArgs {
+    /// iPhone backup directory path (containing Manifest.db)
+    #[arg(short = 'b', long, value_hint = ValueHint::DirPath)]
+    pub backup_dir: PathBuf,
+
+    /// Password of an encrypted backup (prompted for if omitted)
+    #[arg(long)]
+    pub password: Option<String>,
+}
+
+/// Options of the voicemail command
+#[derive(Args)]
+#[non_exhaustive]
+pub struct VoicemailArgs {
+    /// iPhone backup directory path (containing Manifest.db)
+    #[arg(short = 'b', long, value_hint = ValueHint::DirPath)]
+    pub backup_dir: PathBuf,
+
+    /// Copy the recordings into this directory as <date>_<sender>.amr
+    #[arg(long, value_name = "DIR", value_hint = ValueHint::DirPath)]
+    pub extract_audio: Option<PathBuf>,
+
+    /// Password of an encrypted backup (prompted for if omitted)
+    #[arg(long)]
+    pub password: Option<String>,
+}
+
+/// Options of the photos command
+#[derive(Args)]
+#[non_exhaustive]
+pub struct PhotosArgs {
+    /// iPhone backup directory path (containing Manifest.db)
+    #[arg(short = 'b', long, value_hint = ValueHint::DirPath)]
+    pub backup_dir: PathBuf,
+
+    /// Output directory for the originals
+    #[arg(short = 'o', long, value_hint = ValueHint::DirPath)]
+    pub output: PathBuf,
+
+    /// Sort the originals into YYYY/MM directories by capture date
+    #[arg(long)]
+    pub by_date: bool,
+
+    /// Record album membership as album folders or JSON sidecar files
+    #[arg(long, value_name = "MODE", value_enum)]
+    pub albums: Option<AlbumExport>,
+
+    /// Password of an encrypted backup (prompted for if omitted)
+    #[arg(long)]
+    pub password: Option<String>,
+}
+
+/// Options of the safari command
+#[derive(Args)]
+#[non_exhaustive]
+pub struct SafariArgs {
+    /// iPhone backup directory path (containing Manifest.db)
+    #[arg(short = 'b', long, value_hint = ValueHint::DirPath)]
+    pub backup_dir: PathBuf,
+
+    /// Output directory, receiving the history, bookmarks and tabs
+    #[arg(short = 'o', long, value_hint = ValueHint::DirPath)]
+    pub output: PathBuf,
+
+    /// Format of the exported files
+    #[arg(long, value_enum, default_value_t)]
+    pub file_format: SafariFormat,
+
+    /// Also write the bookmarks as bookmarks-netscape.html, which browsers can import
+    #[arg(long)]
+    pub netscape_bookmarks: bool,
+
+    /// Password of an encrypted backup (prompted for if omitted)
+    #[arg(long)]
+    pub password: Option<String>,
+}
+
+/// Search criteria shared by the search, extract and diff commands
+#[derive(Args)]
+#[non_exhaustive]
+pub struct SearchArgs {
+    /// Exact domain match
+    #[arg(long)]
+    pub domain_exact: Option<String>,
+
+    /// Partial domain match
+    #[arg(long)]
+    pub domain_contains: Option<String>,
+
+    /// Domain glob pattern
+    #[arg(long)]
+    pub domain_glob: Option<String>,
+
+    /// Domain regular expression
+    #[arg(long)]
+    pub domain_regex: Option<String>,
+
+    /// Bundle or group identifier of an app domain, e.g. net.whatsapp.WhatsApp
+    #[arg(long)]
+    pub bundle_id: Option<String>,
+
+    /// Exact path match
+    #[arg(long)]
+    pub path_exact: Option<String>,
+
+    /// Partial path match
+    #[arg(long)]
+    pub path_contains: Option<String>,
+
+    /// Path glob pattern, e.g. 'Library/SMS/**/*.heic'
+    #[arg(long)]
+    pub path_glob: Option<String>,
+
+    /// Path regular expression
+    #[arg(long)]
+    pub path_regex: Option<String>,
+
+    /// Use OR logic instead of AND (default is AND)
+    #[arg(long)]
+    pub or: bool,
+
+    /// Match all conditions case-insensitively
+    #[arg(short, long)]
+    pub ignore_case: bool,
+
+    /// File types to include
+    #[arg(long = "type", value_name = "TYPE", value_enum, value_delimiter = ',')]
+    pub file_type: Vec<FileType>,
+
+    /// Domain kinds to include
+    #[arg(long, value_name = "KIND", value_enum, value_delimiter = ',')]
+    pub domain_kind: Vec<DomainKind>,
+
+    /// Minimum file size, e.g. 10KB or 1.5MiB
+    #[arg(long)]
+    pub min_size: Option<String>,
+
+    /// Maximum file size, e.g. 10KB or 1.5MiB
+    #[arg(long)]
+    pub max_size: Option<String>,
+
+    /// Modified at or after, e.g. 2024-01-15 or 48h (before the backup)
+    #[arg(long)]
+    pub modified_after: Option<String>,
+
+    /// Modified before, e.g. 2024-01-15T10:30:00Z or 7d (before the backup)
+    #[arg(long)]
+    pub modified_before: Option<String>,
+
+    /// Created at or after, e.g. 2024-01-15 or 48h (before the backup)
+    #[arg(long)]
+    pub created_after: Option<String>,
+
+    /// Data protection class
+    #[arg(long)]
+    pub protection_class: Option<u32>,
+
+    /// Query expression, e.g. 'domain ~ "whatsapp" and not path contains "Caches"'
+    #[arg(short, long)]
+    pub query: Option<String>,
+}
+
+impl ValueEnum for DomainKind {
+    #[inline]
+    fn value_variants<'a>() -> &'a [Self] {
+        &Self::ALL
+    }
+
+    #[inline]
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(PossibleValue::new(self.name()))
+    }
 }
@@ -0,0 +1,60 @@
+/// An application installed on the device a backup was taken from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Application {
+    /// Bundle identifier, e.g. `net.whatsapp.WhatsApp`
+    bundle_id: String,
+    /// Marketing version, e.g. `24.1.78`
+    version: Option<String>,
+    /// Name shown on the home screen
+    display_name: Option<String>,
+}
+
+impl Application {
+    /// Creates a new `Application` known only by its bundle identifier
+    #[must_use]
+    #[inline]
+    pub const fn new(bundle_id: String) -> Self {
+        Self {
+            bundle_id,
+            version: None,
+            display_name: None,
+        }
+    }
+
+    /// Sets the version
+    #[must_use]
+    #[inline]
+    pub fn with_version(mut self, version: Option<String>) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Sets the display name
+    #[must_use]
+    #[inline]
+    pub fn with_display_name(mut self, display_name: Option<String>) -> Self {
+        self.display_name = display_name;
+        self
+    }
+
+    /// Gets the bundle identifier
+    #[must_use]
+    #[inline]
+    pub fn bundle_id(&self) -> &str {
+        &self.bundle_id
+    }
+
+    /// Gets the version
+    #[must_use]
+    #[inline]
+    pub fn version(&self) -> Option<&str> {
+        self.version.as_deref()
+    }
+
+    /// Gets the display name
+    #[must_use]
+    #[inline]
+    pub fn display_name(&self) -> Option<&str> {
+        self.display_name.as_deref()
+    }
+}
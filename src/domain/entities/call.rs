@@ -0,0 +1,90 @@
+use chrono::{DateTime, Utc};
+use std::time::Duration;
+
+/// Direction of a call
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CallDirection {
+    /// Call received by the device
+    Incoming,
+    /// Call made from the device
+    Outgoing,
+}
+
+impl CallDirection {
+    /// Returns the lowercase name of the direction
+    #[must_use]
+    #[inline]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Incoming => "incoming",
+            Self::Outgoing => "outgoing",
+        }
+    }
+}
+
+/// A call of the call history, as recorded in `CallHistory.storedata`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Call {
+    /// Primary key of the call record
+    pub id: i64,
+    /// Phone number or handle of the other party
+    pub address: Option<String>,
+    /// Name of the other party, as the call history recorded it
+    pub name: Option<String>,
+    /// Time the call started
+    pub date: Option<DateTime<Utc>>,
+    /// Length of the call
+    pub duration: Duration,
+    /// Whether the call was incoming or outgoing
+    pub direction: CallDirection,
+    /// Whether the call was answered
+    pub answered: bool,
+    /// Bundle ID of the app that placed the call, e.g. `com.apple.Telephony`
+    pub service_provider: Option<String>,
+}
+
+impl Call {
+    /// Returns the name of the service the call was made with
+    ///
+    /// Phone and `FaceTime` calls are named as such. Calls of other apps are named after the
+    /// last component of the app's bundle ID, e.g. `WhatsApp` for `net.whatsapp.WhatsApp`.
+    #[must_use]
+    #[inline]
+    pub fn service(&self) -> Option<&str> {
+        let provider = self.service_provider.as_deref()?;
+        Some(match provider {
+            "com.apple.Telephony" => "Phone",
+            "com.apple.FaceTime" => "FaceTime",
+            _ => provider.rsplit('.').next().unwrap_or(provider),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_service() {
+        let call = |provider: Option<&str>| Call {
+            id: 1,
+            address: None,
+            name: None,
+            date: None,
+            duration: Duration::ZERO,
+            direction: CallDirection::Incoming,
+            answered: false,
+            service_provider: provider.map(str::to_owned),
+        };
+
+        assert_eq!(call(Some("com.apple.Telephony")).service(), Some("Phone"));
+        assert_eq!(call(Some("com.apple.FaceTime")).service(), Some("FaceTime"));
+        assert_eq!(
+            call(Some("net.whatsapp.WhatsApp")).service(),
+            Some("WhatsApp")
+        );
+        assert_eq!(call(None).service(), None);
+    }
+}
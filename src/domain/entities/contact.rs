@@ -0,0 +1,195 @@
+use chrono::{Datelike as _, NaiveDate};
+
+/// Year the Contacts app stores for dates entered without a year
+pub const OMITTED_YEAR: i32 = 1604;
+
+/// A person or organization of the address book, as recorded in `AddressBook.sqlitedb`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Contact {
+    /// Row ID of the person
+    pub id: i64,
+    /// Given name
+    pub first_name: Option<String>,
+    /// Middle name
+    pub middle_name: Option<String>,
+    /// Family name
+    pub last_name: Option<String>,
+    /// Honorific prefix, e.g. `Dr.`
+    pub prefix: Option<String>,
+    /// Honorific suffix, e.g. `Jr.`
+    pub suffix: Option<String>,
+    /// Nickname
+    pub nickname: Option<String>,
+    /// Company
+    pub organization: Option<String>,
+    /// Department in the company
+    pub department: Option<String>,
+    /// Job title
+    pub job_title: Option<String>,
+    /// Free-form notes
+    pub note: Option<String>,
+    /// Birthday, in year [`OMITTED_YEAR`] if it was entered without a year
+    pub birthday: Option<NaiveDate>,
+    /// Phone numbers
+    pub phones: Vec<Labeled<String>>,
+    /// Email addresses
+    pub emails: Vec<Labeled<String>>,
+    /// Postal addresses
+    pub addresses: Vec<Labeled<PostalAddress>>,
+    /// Web pages
+    pub urls: Vec<Labeled<String>>,
+    /// Other dates, such as anniversaries
+    pub dates: Vec<Labeled<NaiveDate>>,
+    /// Names of related people, such as a spouse or assistant
+    pub related_names: Vec<Labeled<String>>,
+    /// Picture of the contact, as JPEG or PNG
+    pub photo: Option<Vec<u8>>,
+}
+
+impl Contact {
+    /// Creates a contact without any details
+    #[must_use]
+    #[inline]
+    pub fn new(id: i64) -> Self {
+        Self {
+            id,
+            ..Self::default()
+        }
+    }
+
+    /// Returns the name the Contacts app shows for the contact
+    ///
+    /// This is the full name, or else the organization, nickname, first email address or
+    /// first phone number. Returns an empty string for a contact without any of these.
+    #[must_use]
+    #[inline]
+    pub fn display_name(&self) -> String {
+        let full_name = [
+            &self.prefix,
+            &self.first_name,
+            &self.middle_name,
+            &self.last_name,
+            &self.suffix,
+        ]
+        .into_iter()
+        .filter_map(|part| part.as_deref().filter(|part| !part.is_empty()))
+        .collect::<Vec<_>>()
+        .join(" ");
+        if !full_name.is_empty() {
+            return full_name;
+        }
+
+        [&self.organization, &self.nickname]
+            .into_iter()
+            .find_map(|name| name.as_deref().filter(|name| !name.is_empty()))
+            .or_else(|| self.emails.first().map(|email| email.value.as_str()))
+            .or_else(|| self.phones.first().map(|phone| phone.value.as_str()))
+            .unwrap_or_default()
+            .to_owned()
+    }
+}
+
+/// A value of a contact with the label given to it, e.g. a phone number labeled `Mobile`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Labeled<T> {
+    /// Label, e.g. `Home`, `Work` or a custom label
+    pub label: Option<String>,
+    /// The value
+    pub value: T,
+}
+
+impl<T> Labeled<T> {
+    /// Creates a labeled value
+    #[must_use]
+    #[inline]
+    pub const fn new(label: Option<String>, value: T) -> Self {
+        Self { label, value }
+    }
+}
+
+/// A postal address of a contact
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct PostalAddress {
+    /// Street and house number, possibly over several lines
+    pub street: Option<String>,
+    /// City
+    pub city: Option<String>,
+    /// State or province
+    pub state: Option<String>,
+    /// Postal code
+    pub postal_code: Option<String>,
+    /// Country name
+    pub country: Option<String>,
+    /// ISO country code, e.g. `us`
+    pub country_code: Option<String>,
+}
+
+impl PostalAddress {
+    /// Returns the address on one line, e.g. `1 Infinite Loop, Cupertino, CA, 95014, USA`
+    #[must_use]
+    #[inline]
+    pub fn to_single_line(&self) -> String {
+        [
+            &self.street,
+            &self.city,
+            &self.state,
+            &self.postal_code,
+            &self.country,
+        ]
+        .into_iter()
+        .filter_map(|part| part.as_deref().filter(|part| !part.is_empty()))
+        .map(|part| part.replace('\n', ", "))
+        .collect::<Vec<_>>()
+        .join(", ")
+    }
+}
+
+/// Returns whether a date was entered without a year
+#[must_use]
+#[inline]
+pub fn is_year_omitted(date: NaiveDate) -> bool {
+    date.year() == OMITTED_YEAR
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_name() {
+        let mut contact = Contact::new(1);
+        contact
+            .phones
+            .push(Labeled::new(None, "+15550001".to_owned()));
+        assert_eq!(contact.display_name(), "+15550001");
+
+        contact.organization = Some("Apple".to_owned());
+        assert_eq!(contact.display_name(), "Apple");
+
+        contact.prefix = Some("Dr.".to_owned());
+        contact.first_name = Some("Jane".to_owned());
+        contact.last_name = Some("Appleseed".to_owned());
+        contact.middle_name = Some(String::new());
+        assert_eq!(contact.display_name(), "Dr. Jane Appleseed");
+    }
+
+    #[test]
+    fn test_postal_address_to_single_line() {
+        let address = PostalAddress {
+            street: Some("1 Infinite Loop\nBuilding 2".to_owned()),
+            city: Some("Cupertino".to_owned()),
+            state: Some("CA".to_owned()),
+            postal_code: Some("95014".to_owned()),
+            country: None,
+            country_code: Some("us".to_owned()),
+        };
+
+        assert_eq!(
+            address.to_single_line(),
+            "1 Infinite Loop, Building 2, Cupertino, CA, 95014"
+        );
+    }
+}
@@ -1,4 +1,6 @@
-use crate::domain::value_objects::{Domain, FileFlags, FileId, RelativePath};
+use chrono::{DateTime, Utc};
+
+use crate::domain::value_objects::{Domain, FileFlags, FileId, FileMetadata, RelativePath};
 
 /// File Entity - Represents a file in a backup
 ///
@@ -16,6 +18,8 @@ pub struct File {
     flags: FileFlags,
     /// File metadata in plist format
     metadata: Vec<u8>,
+    /// Decoded file metadata, if the plist could be decoded
+    decoded_metadata: Option<FileMetadata>,
 }
 
 impl File {
@@ -40,6 +44,7 @@ impl File {
             relative_path,
             flags,
             metadata,
+            decoded_metadata: None,
         }
     }
 
@@ -63,9 +68,18 @@ impl File {
             relative_path,
             flags,
             metadata,
+            decoded_metadata: None,
         }
     }
 
+    /// Attaches the decoded file metadata
+    #[must_use]
+    #[inline]
+    pub fn with_file_metadata(mut self, file_metadata: FileMetadata) -> Self {
+        self.decoded_metadata = Some(file_metadata);
+        self
+    }
+
     // Getters
     /// Returns the file ID
     #[must_use]
@@ -102,6 +116,61 @@ impl File {
         &self.metadata
     }
 
+    /// Returns the decoded file metadata
+    #[must_use]
+    #[inline]
+    pub const fn file_metadata(&self) -> Option<&FileMetadata> {
+        self.decoded_metadata.as_ref()
+    }
+
+    /// Returns the file size in bytes
+    #[must_use]
+    #[inline]
+    pub fn size(&self) -> Option<u64> {
+        self.decoded_metadata.as_ref().map(FileMetadata::size)
+    }
+
+    /// Returns the Unix mode
+    #[must_use]
+    #[inline]
+    pub fn mode(&self) -> Option<u32> {
+        self.decoded_metadata.as_ref().map(FileMetadata::mode)
+    }
+
+    /// Returns the last modification time
+    #[must_use]
+    #[inline]
+    pub fn last_modified(&self) -> Option<&DateTime<Utc>> {
+        self.decoded_metadata
+            .as_ref()
+            .map(FileMetadata::last_modified)
+    }
+
+    /// Returns the creation time
+    #[must_use]
+    #[inline]
+    pub fn birth(&self) -> Option<&DateTime<Utc>> {
+        self.decoded_metadata.as_ref().map(FileMetadata::birth)
+    }
+
+    /// Returns the data protection class
+    #[must_use]
+    #[inline]
+    pub fn protection_class(&self) -> Option<u32> {
+        self.decoded_metadata
+            .as_ref()
+            .map(FileMetadata::protection_class)
+    }
+
+    /// Returns the symbolic link target
+    #[must_use]
+    #[inline]
+    pub fn symlink_target(&self) -> Option<&str> {
+        self.decoded_metadata
+            .as_ref()
+            .and_then(FileMetadata::target)
+    }
+
     // Business logic methods
     /// Updates the file flags
     #[inline]
@@ -212,4 +281,41 @@ mod tests {
         assert_eq!(file.metadata(), &new_metadata);
         Ok(())
     }
+
+    #[test]
+    fn test_file_entity_without_file_metadata() -> Result<()> {
+        let id = FileId::new("a1b2c3d4e5f6789012345678901234567890abcd")?;
+        let domain = Domain::new("AppDomain-com.apple.news".to_owned())?;
+        let relative_path = RelativePath::new("Documents/test.txt".to_owned())?;
+
+        let file = File::new(id, domain, relative_path, FileFlags::REGULAR_FILE, vec![]);
+
+        assert_eq!(file.file_metadata(), None);
+        assert_eq!(file.size(), None);
+        assert_eq!(file.mode(), None);
+        assert_eq!(file.last_modified(), None);
+        assert_eq!(file.symlink_target(), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_entity_with_file_metadata() -> Result<()> {
+        let id = FileId::new("a1b2c3d4e5f6789012345678901234567890abcd")?;
+        let domain = Domain::new("HomeDomain".to_owned())?;
+        let relative_path = RelativePath::new("Library/link".to_owned())?;
+        let file_metadata = FileMetadata::new(12, 0o120_755)
+            .with_protection_class(4)
+            .with_target(Some("../Media".to_owned()));
+
+        let file = File::reconstruct(id, domain, relative_path, FileFlags::SYMBOLIC_LINK, vec![])
+            .with_file_metadata(file_metadata.clone());
+
+        assert_eq!(file.file_metadata(), Some(&file_metadata));
+        assert_eq!(file.size(), Some(12));
+        assert_eq!(file.mode(), Some(0o120_755));
+        assert_eq!(file.protection_class(), Some(4));
+        assert_eq!(file.symlink_target(), Some("../Media"));
+        assert_eq!(file.last_modified(), Some(&DateTime::<Utc>::default()));
+        Ok(())
+    }
 }
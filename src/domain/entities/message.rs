@@ -0,0 +1,123 @@
+use chrono::{DateTime, Utc};
+
+/// A conversation of the Messages app, as recorded in `sms.db`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Conversation {
+    /// Row ID of the chat
+    pub id: i64,
+    /// Identifier of the chat, e.g. a phone number, an email address or `chat<digits>`
+    pub chat_identifier: String,
+    /// Name given to a group chat
+    pub display_name: Option<String>,
+    /// Service of the chat, e.g. `iMessage` or `SMS`
+    pub service: Option<String>,
+    /// Handles of the other participants
+    pub participants: Vec<String>,
+    /// Messages, ordered by date
+    pub messages: Vec<Message>,
+}
+
+/// A message of a conversation
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Message {
+    /// Row ID of the message
+    pub id: i64,
+    /// Globally unique identifier of the message
+    pub guid: String,
+    /// Time the message was sent or received
+    pub date: Option<DateTime<Utc>>,
+    /// Handle of the sender, or `None` for messages sent from the device
+    pub sender: Option<String>,
+    /// Whether the message was sent from the device
+    pub is_from_me: bool,
+    /// Plain text of the message
+    pub text: Option<String>,
+    /// Service the message was sent with, e.g. `iMessage` or `SMS`
+    pub service: Option<String>,
+    /// Attached files
+    pub attachments: Vec<Attachment>,
+}
+
+/// A file attached to a message
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Attachment {
+    /// Row ID of the attachment
+    pub id: i64,
+    /// Location on the device, e.g. `~/Library/SMS/Attachments/ab/11/<GUID>/IMG_0001.heic`
+    pub filename: Option<String>,
+    /// MIME type, e.g. `image/heic`
+    pub mime_type: Option<String>,
+    /// Name of the file when it was sent
+    pub transfer_name: Option<String>,
+    /// Size in bytes
+    pub total_bytes: u64,
+}
+
+impl Attachment {
+    /// Returns the relative path of the attachment in `MediaDomain`
+    ///
+    /// Attachments are stored below the home directory of the mobile user, which
+    /// `MediaDomain` mirrors. Returns `None` if the file is stored elsewhere.
+    #[must_use]
+    #[inline]
+    pub fn media_path(&self) -> Option<&str> {
+        let filename = self.filename.as_deref()?;
+        ["~/", "/var/mobile/", "/private/var/mobile/"]
+            .into_iter()
+            .find_map(|home| filename.strip_prefix(home))
+    }
+
+    /// Returns the name of the file, preferring the name it was sent with
+    #[must_use]
+    #[inline]
+    pub fn name(&self) -> Option<&str> {
+        self.transfer_name
+            .as_deref()
+            .filter(|name| !name.is_empty())
+            .or_else(|| self.filename.as_deref()?.rsplit('/').next())
+            .filter(|name| !name.is_empty())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attachment(filename: &str, transfer_name: Option<&str>) -> Attachment {
+        Attachment {
+            id: 1,
+            filename: Some(filename.to_owned()),
+            mime_type: None,
+            transfer_name: transfer_name.map(str::to_owned),
+            total_bytes: 0,
+        }
+    }
+
+    #[test]
+    fn test_attachment_media_path() {
+        assert_eq!(
+            attachment("~/Library/SMS/Attachments/ab/11/X/IMG_0001.heic", None).media_path(),
+            Some("Library/SMS/Attachments/ab/11/X/IMG_0001.heic")
+        );
+        assert_eq!(
+            attachment("/var/mobile/Library/SMS/Attachments/ab/11/X/a.jpg", None).media_path(),
+            Some("Library/SMS/Attachments/ab/11/X/a.jpg")
+        );
+        assert_eq!(attachment("/tmp/a.jpg", None).media_path(), None);
+    }
+
+    #[test]
+    fn test_attachment_name() {
+        assert_eq!(
+            attachment("~/Library/SMS/Attachments/ab/11/X/IMG_0001.heic", None).name(),
+            Some("IMG_0001.heic")
+        );
+        assert_eq!(
+            attachment("~/Library/SMS/Attachments/ab/11/X/a.jpg", Some("photo.jpg")).name(),
+            Some("photo.jpg")
+        );
+    }
+}
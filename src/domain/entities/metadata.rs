@@ -12,13 +12,71 @@ pub struct Metadata {
     product_name: String,
     /// Last backup datetime with timezone
     last_backup_date: DateTime<Utc>,
+    /// Further details of the device
+    device: DeviceDetails,
+    /// Details of the backup itself
+    manifest: ManifestDetails,
+    /// State of the last backup operation
+    status: StatusDetails,
+}
+
+/// Details of the device a backup was taken from, as recorded in `Info.plist`
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub struct DeviceDetails {
+    /// iOS version, e.g. `17.2.1`
+    pub product_version: Option<String>,
+    /// iOS build, e.g. `21C66`
+    pub build_version: Option<String>,
+    /// Serial number
+    pub serial_number: Option<String>,
+    /// IMEI of a GSM device
+    pub imei: Option<String>,
+    /// MEID of a CDMA device
+    pub meid: Option<String>,
+    /// ICCID of the SIM card
+    pub iccid: Option<String>,
+    /// Phone number of the SIM card
+    pub phone_number: Option<String>,
+    /// GUID of the device
+    pub guid: Option<String>,
+    /// Kind of device, e.g. `Device`
+    pub target_type: Option<String>,
+    /// Version of iTunes or Finder that took the backup
+    pub itunes_version: Option<String>,
+}
+
+/// Details of a backup, as recorded in `Manifest.plist`
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub struct ManifestDetails {
+    /// Whether the backup is encrypted
+    pub is_encrypted: Option<bool>,
+    /// Version of the backup format
+    pub version: Option<String>,
+    /// Whether the device had a passcode
+    pub was_passcode_set: Option<bool>,
+    /// Date the backup was taken
+    pub date: Option<DateTime<Utc>>,
+}
+
+/// State of the last backup operation, as recorded in `Status.plist`
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub struct StatusDetails {
+    /// State of the snapshot, e.g. `finished`
+    pub snapshot_state: Option<String>,
+    /// Whether the last backup was a full backup
+    pub is_full_backup: Option<bool>,
+    /// State of the backup, e.g. `new`
+    pub backup_state: Option<String>,
 }
 
 impl Metadata {
     /// Creates a new `Metadata` instance
     #[must_use]
     #[inline]
-    pub const fn new(
+    pub fn new(
         id: MetadataId,
         device_name: String,
         product_name: String,
@@ -29,9 +87,36 @@ impl Metadata {
             device_name,
             product_name,
             last_backup_date,
+            device: DeviceDetails::default(),
+            manifest: ManifestDetails::default(),
+            status: StatusDetails::default(),
         }
     }
 
+    /// Sets the further details of the device
+    #[must_use]
+    #[inline]
+    pub fn with_device(mut self, device: DeviceDetails) -> Self {
+        self.device = device;
+        self
+    }
+
+    /// Sets the details of the backup itself
+    #[must_use]
+    #[inline]
+    pub fn with_manifest(mut self, manifest: ManifestDetails) -> Self {
+        self.manifest = manifest;
+        self
+    }
+
+    /// Sets the state of the last backup operation
+    #[must_use]
+    #[inline]
+    pub fn with_status(mut self, status: StatusDetails) -> Self {
+        self.status = status;
+        self
+    }
+
     /// Gets the unique identifier
     #[must_use]
     #[inline]
@@ -59,4 +144,25 @@ impl Metadata {
     pub const fn last_backup_date(&self) -> &DateTime<Utc> {
         &self.last_backup_date
     }
+
+    /// Gets the further details of the device
+    #[must_use]
+    #[inline]
+    pub const fn device(&self) -> &DeviceDetails {
+        &self.device
+    }
+
+    /// Gets the details of the backup itself
+    #[must_use]
+    #[inline]
+    pub const fn manifest(&self) -> &ManifestDetails {
+        &self.manifest
+    }
+
+    /// Gets the state of the last backup operation
+    #[must_use]
+    #[inline]
+    pub const fn status(&self) -> &StatusDetails {
+        &self.status
+    }
 }
@@ -1,5 +1,19 @@
+pub mod application;
+pub mod call;
+pub mod contact;
 pub mod file;
+pub mod message;
 pub mod metadata;
+pub mod photo_asset;
+pub mod safari;
+pub mod voicemail;
 
+pub use application::Application;
+pub use call::{Call, CallDirection};
+pub use contact::{Contact, Labeled, PostalAddress};
 pub use file::File;
-pub use metadata::Metadata;
+pub use message::{Attachment, Conversation, Message};
+pub use metadata::{DeviceDetails, ManifestDetails, Metadata, StatusDetails};
+pub use photo_asset::{AssetKind, PhotoAsset};
+pub use safari::{Bookmark, BrowserTab, HistoryVisit};
+pub use voicemail::Voicemail;
@@ -0,0 +1,115 @@
+use chrono::{DateTime, Utc};
+
+/// Kind of a photo library asset
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum AssetKind {
+    /// Still image, possibly the still of a Live Photo
+    Photo,
+    /// Video
+    Video,
+}
+
+impl AssetKind {
+    /// Returns the lowercase name of the kind
+    #[must_use]
+    #[inline]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Photo => "photo",
+            Self::Video => "video",
+        }
+    }
+}
+
+/// A photo or video of the photo library, as recorded in `Photos.sqlite`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct PhotoAsset {
+    /// Primary key of the asset
+    pub id: i64,
+    /// Directory of the original below `Media`, e.g. `DCIM/100APPLE`
+    pub directory: String,
+    /// Name of the original in the camera roll, e.g. `IMG_0001.HEIC`
+    pub filename: String,
+    /// Name of the file the asset was captured or imported as
+    pub original_filename: Option<String>,
+    /// Whether the asset is a photo or a video
+    pub kind: AssetKind,
+    /// Time the photo or video was taken
+    pub date_created: Option<DateTime<Utc>>,
+    /// Whether the asset is in the hidden album
+    pub hidden: bool,
+    /// Time the asset was moved to the recently deleted album, if it is there
+    pub trashed_date: Option<DateTime<Utc>>,
+    /// Whether the asset is in the recently deleted album
+    pub trashed: bool,
+    /// Titles of the user albums holding the asset
+    pub albums: Vec<String>,
+}
+
+impl PhotoAsset {
+    /// Returns the relative path of the original in `CameraRollDomain`
+    #[must_use]
+    #[inline]
+    pub fn relative_path(&self) -> String {
+        format!("Media/{}/{}", self.directory, self.filename)
+    }
+
+    /// Returns the relative path the video of a Live Photo would have in `CameraRollDomain`
+    ///
+    /// The camera stores the video next to the still, with the same name and a `.MOV`
+    /// extension. Returns `None` for videos.
+    #[must_use]
+    #[inline]
+    pub fn live_photo_video_path(&self) -> Option<String> {
+        if self.kind != AssetKind::Photo {
+            return None;
+        }
+        let (stem, _) = self.filename.rsplit_once('.')?;
+        Some(format!("Media/{}/{stem}.MOV", self.directory))
+    }
+
+    /// Returns the name to export the original as, preferring the original file name
+    #[must_use]
+    #[inline]
+    pub fn export_name(&self) -> &str {
+        self.original_filename
+            .as_deref()
+            .filter(|name| !name.is_empty())
+            .unwrap_or(&self.filename)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paths() {
+        let mut asset = PhotoAsset {
+            id: 1,
+            directory: "DCIM/100APPLE".to_owned(),
+            filename: "IMG_0001.HEIC".to_owned(),
+            original_filename: None,
+            kind: AssetKind::Photo,
+            date_created: None,
+            hidden: false,
+            trashed_date: None,
+            trashed: false,
+            albums: vec![],
+        };
+
+        assert_eq!(asset.relative_path(), "Media/DCIM/100APPLE/IMG_0001.HEIC");
+        assert_eq!(
+            asset.live_photo_video_path().as_deref(),
+            Some("Media/DCIM/100APPLE/IMG_0001.MOV")
+        );
+        assert_eq!(asset.export_name(), "IMG_0001.HEIC");
+
+        asset.kind = AssetKind::Video;
+        asset.original_filename = Some("holiday.mov".to_owned());
+        assert_eq!(asset.live_photo_video_path(), None);
+        assert_eq!(asset.export_name(), "holiday.mov");
+    }
+}
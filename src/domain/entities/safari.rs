@@ -0,0 +1,112 @@
+use chrono::{DateTime, Utc};
+
+/// A visit of a web page, as recorded in Safari's `History.db`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct HistoryVisit {
+    /// Row ID of the visit
+    pub id: i64,
+    /// Address of the visited page
+    pub url: String,
+    /// Title of the page at the time of the visit
+    pub title: Option<String>,
+    /// Time of the visit
+    pub visit_time: Option<DateTime<Utc>>,
+    /// Number of visits of the address over the whole history
+    pub visit_count: i64,
+}
+
+/// A bookmark or bookmark folder, as recorded in Safari's `Bookmarks.db`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Bookmark {
+    /// Row ID of the bookmark
+    pub id: i64,
+    /// Title shown for the bookmark or folder
+    pub title: Option<String>,
+    /// Address of a bookmark, `None` for folders
+    pub url: Option<String>,
+    /// Whether this is a folder
+    pub is_folder: bool,
+    /// Bookmarks and folders in a folder, in their order
+    pub children: Vec<Bookmark>,
+}
+
+impl Bookmark {
+    /// Creates a bookmark of an address
+    #[must_use]
+    #[inline]
+    pub const fn new(id: i64, title: Option<String>, url: Option<String>) -> Self {
+        Self {
+            id,
+            title,
+            url,
+            is_folder: false,
+            children: Vec::new(),
+        }
+    }
+
+    /// Creates a bookmark folder
+    #[must_use]
+    #[inline]
+    pub const fn folder(id: i64, title: Option<String>, children: Vec<Self>) -> Self {
+        Self {
+            id,
+            title,
+            url: None,
+            is_folder: true,
+            children,
+        }
+    }
+
+    /// Returns the number of bookmarks in the tree below this one, folders excluded
+    #[must_use]
+    #[inline]
+    pub fn count(&self) -> usize {
+        if self.is_folder {
+            self.children.iter().map(Self::count).sum()
+        } else {
+            1
+        }
+    }
+}
+
+/// A tab open in Safari on the device or on another device signed in to iCloud
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct BrowserTab {
+    /// Title of the page shown in the tab
+    pub title: Option<String>,
+    /// Address of the page shown in the tab
+    pub url: Option<String>,
+    /// Time the tab was last viewed, if recorded
+    pub last_viewed: Option<DateTime<Utc>>,
+    /// Whether the tab is a private browsing tab
+    pub private: bool,
+    /// Name of the device the tab is open on, `None` for tabs of the backed up device
+    pub device: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bookmark_count() {
+        let tree = Bookmark::folder(
+            1,
+            Some("Favorites".to_owned()),
+            vec![
+                Bookmark::new(2, None, Some("https://example.com/".to_owned())),
+                Bookmark::folder(
+                    3,
+                    Some("News".to_owned()),
+                    vec![Bookmark::new(4, None, None)],
+                ),
+                Bookmark::folder(5, Some("Empty".to_owned()), vec![]),
+            ],
+        );
+
+        assert_eq!(tree.count(), 2);
+    }
+}
@@ -0,0 +1,29 @@
+use chrono::{DateTime, Utc};
+use std::time::Duration;
+
+/// A voicemail message, as recorded in `voicemail.db`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Voicemail {
+    /// Row ID of the voicemail
+    pub id: i64,
+    /// Phone number of the caller
+    pub sender: Option<String>,
+    /// Number to call back, if it differs from the sender
+    pub callback_number: Option<String>,
+    /// Time the voicemail was received
+    pub date: Option<DateTime<Utc>>,
+    /// Length of the recording
+    pub duration: Duration,
+    /// Time the voicemail was deleted, if it is in the deleted messages
+    pub trashed_date: Option<DateTime<Utc>>,
+}
+
+impl Voicemail {
+    /// Returns the relative path of the recording in `HomeDomain`
+    #[must_use]
+    #[inline]
+    pub fn audio_path(&self) -> String {
+        format!("Library/Voicemail/{}.amr", self.id)
+    }
+}
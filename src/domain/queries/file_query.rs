@@ -1,19 +1,53 @@
+use super::query_parser::{QueryParseError, QueryParser};
+use crate::domain::entities::File;
+use crate::domain::value_objects::{DomainKind, FileFlags, FileId, GlobPattern, RegexPattern};
+use chrono::{DateTime, Utc};
+
 /// File query for searching files based on various criteria
+///
+/// Queries form a tree: `Not`, `And` and `Or` nodes combine other queries, so any
+/// boolean expression over basic conditions can be represented.
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[non_exhaustive]
 pub enum FileQuery {
     Basic(BasicQuery),
     Composite(CompositeQuery),
+    Not(Box<FileQuery>),
+    And(Vec<FileQuery>),
+    Or(Vec<FileQuery>),
 }
 
 /// Basic query conditions for file search
+///
+/// Conditions are case-sensitive unless wrapped in `IgnoreCase`. Size, time and protection
+/// class conditions are evaluated against the decoded file metadata, so files without
+/// metadata never match them. Lower time bounds are inclusive and upper bounds exclusive.
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[non_exhaustive]
 pub enum BasicQuery {
+    /// Matches files with any of the given IDs
+    FileId(Vec<FileId>),
     DomainExact(String),
     DomainContains(String),
+    DomainGlob(GlobPattern),
+    DomainRegex(RegexPattern),
+    /// Matches domains of any of the given kinds
+    DomainKind(Vec<DomainKind>),
+    /// Matches container domains with the given bundle or group identifier
+    BundleId(String),
     PathExact(String),
     PathContains(String),
+    PathGlob(GlobPattern),
+    PathRegex(RegexPattern),
+    /// Matches files having any of the given type flags
+    FileType(FileFlags),
+    MinSize(u64),
+    MaxSize(u64),
+    ModifiedAfter(DateTime<Utc>),
+    ModifiedBefore(DateTime<Utc>),
+    CreatedAfter(DateTime<Utc>),
+    ProtectionClass(u32),
+    IgnoreCase(Box<BasicQuery>),
 }
 
 /// Composite query conditions for combining multiple basic queries
@@ -25,6 +59,13 @@ pub enum CompositeQuery {
 }
 
 impl FileQuery {
+    /// Create a query that matches every file
+    #[must_use]
+    #[inline]
+    pub const fn all() -> Self {
+        Self::And(Vec::new())
+    }
+
     /// Create a query for exact domain match
     #[must_use]
     #[inline]
@@ -66,11 +107,180 @@ impl FileQuery {
     pub const fn all_of(queries: Vec<BasicQuery>) -> Self {
         Self::Composite(CompositeQuery::AllOf(queries))
     }
+
+    /// Create a query that matches files not matching the given query
+    #[must_use]
+    #[inline]
+    pub fn negate(query: Self) -> Self {
+        Self::Not(Box::new(query))
+    }
+
+    /// Create a query that matches files matching all of the given queries
+    #[must_use]
+    #[inline]
+    pub const fn and(queries: Vec<Self>) -> Self {
+        Self::And(queries)
+    }
+
+    /// Create a query that matches files matching any of the given queries
+    #[must_use]
+    #[inline]
+    pub const fn or(queries: Vec<Self>) -> Self {
+        Self::Or(queries)
+    }
+
+    /// Makes every condition of the query case-insensitive
+    #[must_use]
+    #[inline]
+    pub fn ignore_case(self) -> Self {
+        match self {
+            Self::Basic(query) => Self::Basic(query.ignore_case()),
+            Self::Composite(CompositeQuery::AnyOf(queries)) => {
+                Self::any_of(queries.into_iter().map(BasicQuery::ignore_case).collect())
+            }
+            Self::Composite(CompositeQuery::AllOf(queries)) => {
+                Self::all_of(queries.into_iter().map(BasicQuery::ignore_case).collect())
+            }
+            Self::Not(query) => Self::negate(query.ignore_case()),
+            Self::And(queries) => Self::and(queries.into_iter().map(Self::ignore_case).collect()),
+            Self::Or(queries) => Self::or(queries.into_iter().map(Self::ignore_case).collect()),
+        }
+    }
+
+    /// Parses a query expression
+    ///
+    /// The syntax combines conditions of the form `<field> <operator> <value>` with
+    /// `and`, `or`, `not` and parentheses, e.g.
+    /// `domain ~ "whatsapp" and (path glob "*.sqlite" or path regex "Media/.*\\.jpg$")`.
+    ///
+    /// - Fields: `domain`, `path`
+    /// - Operators: `=` (exact), `~` or `contains`, `glob`, `regex`
+    /// - Values: double- or single-quoted strings, or bare words
+    ///
+    /// # Errors
+    ///
+    /// Returns an error pointing at the offending position if the expression is invalid
+    #[inline]
+    pub fn parse(input: &str) -> Result<Self, QueryParseError> {
+        QueryParser::new(input).parse()
+    }
+
+    /// Checks if a file satisfies the query
+    ///
+    /// Used by repositories that cannot push (part of) the query down to a database.
+    #[must_use]
+    #[inline]
+    pub fn matches(&self, file: &File) -> bool {
+        match self {
+            Self::Basic(basic_query) => basic_query.matches(file),
+            Self::Composite(composite_query) => composite_query.matches(file),
+            Self::Not(query) => !query.matches(file),
+            Self::And(queries) => queries.iter().all(|query| query.matches(file)),
+            Self::Or(queries) => queries.iter().any(|query| query.matches(file)),
+        }
+    }
+}
+
+impl BasicQuery {
+    /// Makes the condition case-insensitive
+    #[must_use]
+    #[inline]
+    pub fn ignore_case(self) -> Self {
+        match self {
+            Self::IgnoreCase(_) => self,
+            query => Self::IgnoreCase(Box::new(query)),
+        }
+    }
+
+    /// Checks if a file satisfies the condition
+    #[must_use]
+    #[inline]
+    pub fn matches(&self, file: &File) -> bool {
+        self.matches_with_case(file, false)
+    }
+
+    fn matches_with_case(&self, file: &File, ignore_case: bool) -> bool {
+        let domain = file.domain().value();
+        let path = file.relative_path().value();
+
+        match self {
+            Self::FileId(ids) => ids.contains(file.id()),
+            Self::DomainExact(expected) => Self::equals(domain, expected, ignore_case),
+            Self::DomainContains(needle) => Self::contains(domain, needle, ignore_case),
+            Self::DomainGlob(pattern) if ignore_case => pattern.is_match_ignore_case(domain),
+            Self::DomainGlob(pattern) => pattern.is_match(domain),
+            Self::DomainRegex(pattern) if ignore_case => pattern.is_match_ignore_case(domain),
+            Self::DomainRegex(pattern) => pattern.is_match(domain),
+            Self::DomainKind(kinds) => kinds.contains(&file.domain().kind()),
+            Self::BundleId(expected) => file
+                .domain()
+                .bundle_id()
+                .is_some_and(|bundle_id| Self::equals(bundle_id, expected, ignore_case)),
+            Self::PathExact(expected) => Self::equals(path, expected, ignore_case),
+            Self::PathContains(needle) => Self::contains(path, needle, ignore_case),
+            Self::PathGlob(pattern) if ignore_case => pattern.is_match_ignore_case(path),
+            Self::PathGlob(pattern) => pattern.is_match(path),
+            Self::PathRegex(pattern) if ignore_case => pattern.is_match_ignore_case(path),
+            Self::PathRegex(pattern) => pattern.is_match(path),
+            Self::FileType(flags) => file.flags().intersects(flags.clone()),
+            Self::MinSize(size) => file.size().is_some_and(|value| value >= *size),
+            Self::MaxSize(size) => file.size().is_some_and(|value| value <= *size),
+            Self::ModifiedAfter(time) => file.last_modified().is_some_and(|value| value >= time),
+            Self::ModifiedBefore(time) => file.last_modified().is_some_and(|value| value < time),
+            Self::CreatedAfter(time) => file.birth().is_some_and(|value| value >= time),
+            Self::ProtectionClass(class) => file.protection_class() == Some(*class),
+            Self::IgnoreCase(query) => query.matches_with_case(file, true),
+        }
+    }
+
+    fn equals(value: &str, expected: &str, ignore_case: bool) -> bool {
+        if ignore_case {
+            value.to_lowercase() == expected.to_lowercase()
+        } else {
+            value == expected
+        }
+    }
+
+    fn contains(value: &str, needle: &str, ignore_case: bool) -> bool {
+        if ignore_case {
+            value.to_lowercase().contains(&needle.to_lowercase())
+        } else {
+            value.contains(needle)
+        }
+    }
+}
+
+impl CompositeQuery {
+    /// Checks if a file satisfies the combined conditions
+    ///
+    /// An empty list matches every file, as it does in the database repository.
+    #[must_use]
+    #[inline]
+    pub fn matches(&self, file: &File) -> bool {
+        match self {
+            Self::AnyOf(queries) => {
+                queries.is_empty() || queries.iter().any(|query| query.matches(file))
+            }
+            Self::AllOf(queries) => queries.iter().all(|query| query.matches(file)),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::domain::value_objects::{Domain, FileFlags, FileId, RelativePath};
+    use anyhow::Result;
+
+    fn create_test_file(domain: &str, relative_path: &str) -> Result<File> {
+        Ok(File::new(
+            FileId::new("da39a3ee5e6b4b0d3255bfef95601890afd80709")?,
+            Domain::new(domain.to_owned())?,
+            RelativePath::new(relative_path.to_owned())?,
+            FileFlags::REGULAR_FILE,
+            vec![],
+        ))
+    }
 
     #[test]
     fn test_domain_exact_query() {
@@ -122,4 +332,152 @@ mod tests {
             FileQuery::Composite(CompositeQuery::AnyOf(basic_queries))
         );
     }
+
+    #[test]
+    fn test_basic_query_matches() -> Result<()> {
+        let file = create_test_file("AppDomain-com.apple.news", "Documents/file.txt")?;
+
+        assert!(FileQuery::domain_exact("AppDomain-com.apple.news").matches(&file));
+        assert!(!FileQuery::domain_exact("AppDomain").matches(&file));
+        assert!(FileQuery::domain_contains("apple").matches(&file));
+        assert!(FileQuery::path_exact("Documents/file.txt").matches(&file));
+        assert!(!FileQuery::path_exact("Documents").matches(&file));
+        assert!(FileQuery::path_contains("file").matches(&file));
+        assert!(!FileQuery::path_contains("Library").matches(&file));
+        Ok(())
+    }
+
+    #[test]
+    fn test_composite_query_matches() -> Result<()> {
+        let file = create_test_file("HomeDomain", "Library/SMS/sms.db")?;
+
+        let any_of = FileQuery::any_of(vec![
+            BasicQuery::DomainExact("CameraRollDomain".to_owned()),
+            BasicQuery::PathContains("sms.db".to_owned()),
+        ]);
+        assert!(any_of.matches(&file));
+
+        let all_of = FileQuery::all_of(vec![
+            BasicQuery::DomainExact("HomeDomain".to_owned()),
+            BasicQuery::PathContains("Photos".to_owned()),
+        ]);
+        assert!(!all_of.matches(&file));
+
+        assert!(FileQuery::any_of(vec![]).matches(&file));
+        assert!(FileQuery::all_of(vec![]).matches(&file));
+        Ok(())
+    }
+
+    #[test]
+    fn test_pattern_queries_match() -> Result<()> {
+        let file = create_test_file(
+            "AppDomain-net.whatsapp.WhatsApp",
+            "Documents/ChatStorage.sqlite",
+        )?;
+
+        let glob = FileQuery::Basic(BasicQuery::PathGlob(GlobPattern::new("*.sqlite")?));
+        assert!(glob.matches(&file));
+
+        let regex = FileQuery::Basic(BasicQuery::DomainRegex(RegexPattern::new(
+            r"^AppDomain-net\.whatsapp\.",
+        )?));
+        assert!(regex.matches(&file));
+
+        let domain_glob = FileQuery::Basic(BasicQuery::DomainGlob(GlobPattern::new("HomeDomain")?));
+        assert!(!domain_glob.matches(&file));
+        Ok(())
+    }
+
+    #[test]
+    fn test_domain_model_queries_match() -> Result<()> {
+        let app = create_test_file("AppDomain-net.whatsapp.WhatsApp", "Documents/a.sqlite")?;
+        let group = create_test_file(
+            "AppDomainGroup-group.net.whatsapp.WhatsApp.shared",
+            "ChatStorage.sqlite",
+        )?;
+        let home = create_test_file("HomeDomain", "Library/SMS/sms.db")?;
+
+        let kinds = FileQuery::Basic(BasicQuery::DomainKind(vec![
+            DomainKind::App,
+            DomainKind::Home,
+        ]));
+        assert!(kinds.matches(&app));
+        assert!(!kinds.matches(&group));
+        assert!(kinds.matches(&home));
+
+        let bundle_id = FileQuery::Basic(BasicQuery::BundleId("net.whatsapp.WhatsApp".to_owned()));
+        assert!(bundle_id.matches(&app));
+        assert!(!bundle_id.matches(&group));
+        assert!(!bundle_id.matches(&home));
+
+        let lowercase = BasicQuery::BundleId("net.whatsapp.whatsapp".to_owned());
+        assert!(!lowercase.matches(&app));
+        assert!(lowercase.ignore_case().matches(&app));
+
+        let ids = FileQuery::Basic(BasicQuery::FileId(vec![app.id().clone()]));
+        let other_ids = FileQuery::Basic(BasicQuery::FileId(vec![FileId::new(
+            "356a192b7913b04c54574d18c28d46e6395428ab",
+        )?]));
+        assert!(ids.matches(&app));
+        assert!(!other_ids.matches(&app));
+        Ok(())
+    }
+
+    #[test]
+    fn test_ignore_case_queries_match() -> Result<()> {
+        let file = create_test_file("CameraRollDomain", "Media/DCIM/100APPLE/IMG_0001.HEIC")?;
+
+        let contains = FileQuery::path_contains("img_0001.heic");
+        assert!(!contains.matches(&file));
+        assert!(contains.ignore_case().matches(&file));
+
+        let and = FileQuery::and(vec![
+            FileQuery::domain_exact("cameraroll domain"),
+            FileQuery::Basic(BasicQuery::PathGlob(GlobPattern::new("media/**/*.heic")?)),
+        ])
+        .ignore_case();
+        assert!(!and.matches(&file));
+
+        let not = FileQuery::negate(FileQuery::all_of(vec![
+            BasicQuery::DomainExact("cameraRollDomain".to_owned()),
+            BasicQuery::PathRegex(RegexPattern::new(r"\.heic$")?),
+        ]))
+        .ignore_case();
+        assert!(!not.matches(&file));
+        Ok(())
+    }
+
+    #[test]
+    fn test_ignore_case_is_idempotent() {
+        let query = BasicQuery::PathExact("sms.db".to_owned()).ignore_case();
+        assert_eq!(
+            query,
+            BasicQuery::IgnoreCase(Box::new(BasicQuery::PathExact("sms.db".to_owned())))
+        );
+        assert_eq!(query.clone().ignore_case(), query);
+    }
+
+    #[test]
+    fn test_boolean_queries_match() -> Result<()> {
+        let file = create_test_file("HomeDomain", "Library/Caches/cache.db")?;
+
+        assert!(!FileQuery::negate(FileQuery::path_contains("Caches")).matches(&file));
+        assert!(
+            FileQuery::and(vec![
+                FileQuery::domain_exact("HomeDomain"),
+                FileQuery::negate(FileQuery::path_contains("SMS")),
+            ])
+            .matches(&file)
+        );
+        assert!(
+            FileQuery::or(vec![
+                FileQuery::domain_exact("CameraRollDomain"),
+                FileQuery::path_exact("Library/Caches/cache.db"),
+            ])
+            .matches(&file)
+        );
+        assert!(!FileQuery::or(vec![]).matches(&file));
+        assert!(FileQuery::all().matches(&file));
+        Ok(())
+    }
 }
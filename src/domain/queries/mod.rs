@@ -1,3 +1,5 @@
 pub mod file_query;
+pub mod query_parser;
 
 pub use file_query::{BasicQuery, CompositeQuery, FileQuery};
+pub use query_parser::QueryParseError;
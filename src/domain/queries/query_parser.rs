@@ -0,0 +1,602 @@
+use std::error::Error;
+use std::fmt;
+
+use super::file_query::{BasicQuery, FileQuery};
+use crate::domain::value_objects::{GlobPattern, RegexPattern};
+
+/// Error raised when a query expression cannot be parsed
+///
+/// Its `Display` output repeats the expression with a caret under the offending position.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct QueryParseError {
+    /// Description of the problem
+    pub message: String,
+    /// The expression that failed to parse
+    pub input: String,
+    /// Character offset of the problem within the expression
+    pub position: usize,
+}
+
+impl fmt::Display for QueryParseError {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} at position {}",
+            self.message,
+            self.position.saturating_add(1)
+        )?;
+        writeln!(f, "  {}", self.input)?;
+        write!(f, "  {}^", " ".repeat(self.position))
+    }
+}
+
+impl Error for QueryParseError {}
+
+/// Token of a query expression
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TokenKind {
+    LeftParen,
+    RightParen,
+    /// `=` or `==`
+    Equals,
+    /// `~`
+    Tilde,
+    /// Unquoted word: a keyword, field name or bare value
+    Word(String),
+    /// Quoted string with escapes resolved
+    Quoted(String),
+    End,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    /// Character offset of the token
+    position: usize,
+}
+
+impl Token {
+    /// Checks if the token is the given keyword, ignoring case
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(&self.kind, TokenKind::Word(word) if word.eq_ignore_ascii_case(keyword))
+    }
+
+    /// Describes the token for error messages
+    fn describe(&self) -> String {
+        match &self.kind {
+            TokenKind::LeftParen => "'('".to_owned(),
+            TokenKind::RightParen => "')'".to_owned(),
+            TokenKind::Equals => "'='".to_owned(),
+            TokenKind::Tilde => "'~'".to_owned(),
+            TokenKind::Word(word) => format!("\"{word}\""),
+            TokenKind::Quoted(value) => format!("string \"{value}\""),
+            TokenKind::End => "end of query".to_owned(),
+        }
+    }
+}
+
+/// Field a condition applies to
+#[derive(Debug, Clone, Copy)]
+enum Field {
+    Domain,
+    Path,
+}
+
+/// Comparison of a condition
+#[derive(Debug, Clone, Copy)]
+enum Operator {
+    Exact,
+    Contains,
+    Glob,
+    Regex,
+}
+
+/// Deepest nesting of `not` and parentheses accepted, keeping the recursion off the end of
+/// the stack
+const MAX_DEPTH: usize = 128;
+
+/// Recursive-descent parser for query expressions
+///
+/// Grammar, from lowest to highest precedence:
+///
+/// ```text
+/// or        := and ("or" and)*
+/// and       := unary ("and" unary)*
+/// unary     := "not" unary | primary
+/// primary   := "(" or ")" | condition
+/// condition := field operator value
+/// ```
+pub(crate) struct QueryParser<'a> {
+    input: &'a str,
+    tokens: Vec<Token>,
+    index: usize,
+    /// Number of `not` and parentheses enclosing the current position
+    depth: usize,
+}
+
+impl<'a> QueryParser<'a> {
+    /// Creates a parser for the given expression
+    pub(crate) const fn new(input: &'a str) -> Self {
+        Self {
+            input,
+            tokens: Vec::new(),
+            index: 0,
+            depth: 0,
+        }
+    }
+
+    /// Parses the whole expression
+    pub(crate) fn parse(mut self) -> Result<FileQuery, QueryParseError> {
+        self.tokens = self.tokenize()?;
+
+        if self.peek().kind == TokenKind::End {
+            return Err(self.error("Query is empty", self.peek().position));
+        }
+
+        let query = self.parse_or()?;
+
+        let token = self.peek();
+        if token.kind != TokenKind::End {
+            let message = if token.kind == TokenKind::RightParen {
+                "Unmatched ')'".to_owned()
+            } else {
+                format!(
+                    "Unexpected {}; expected 'and', 'or' or end of query",
+                    token.describe()
+                )
+            };
+            return Err(self.error(&message, token.position));
+        }
+
+        Ok(query)
+    }
+
+    fn parse_or(&mut self) -> Result<FileQuery, QueryParseError> {
+        let mut queries = vec![self.parse_and()?];
+        while self.peek().is_keyword("or") {
+            self.advance();
+            queries.push(self.parse_and()?);
+        }
+
+        Ok(Self::combine(queries, FileQuery::or))
+    }
+
+    fn parse_and(&mut self) -> Result<FileQuery, QueryParseError> {
+        let mut queries = vec![self.parse_unary()?];
+        while self.peek().is_keyword("and") {
+            self.advance();
+            queries.push(self.parse_unary()?);
+        }
+
+        Ok(Self::combine(queries, FileQuery::and))
+    }
+
+    fn parse_unary(&mut self) -> Result<FileQuery, QueryParseError> {
+        if self.peek().is_keyword("not") {
+            let token = self.advance();
+            self.enter(token.position)?;
+            let query = self.parse_unary()?;
+            self.leave();
+            return Ok(FileQuery::negate(query));
+        }
+
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<FileQuery, QueryParseError> {
+        let token = self.advance();
+        match token.kind {
+            TokenKind::LeftParen => {
+                self.enter(token.position)?;
+                let query = self.parse_or()?;
+                self.leave();
+                let closing = self.advance();
+                if closing.kind != TokenKind::RightParen {
+                    return Err(self.error(
+                        &format!(
+                            "Expected ')' to close '(' at position {}, found {}",
+                            token.position.saturating_add(1),
+                            closing.describe()
+                        ),
+                        closing.position,
+                    ));
+                }
+                Ok(query)
+            }
+            TokenKind::Word(ref word) => {
+                let field = if word.eq_ignore_ascii_case("domain") {
+                    Field::Domain
+                } else if word.eq_ignore_ascii_case("path") {
+                    Field::Path
+                } else {
+                    return Err(self.error(
+                        &format!("Unknown field \"{word}\"; expected 'domain' or 'path'"),
+                        token.position,
+                    ));
+                };
+                self.parse_condition(field)
+            }
+            _ => Err(self.error(
+                &format!(
+                    "Expected a condition such as 'path contains \"...\"', found {}",
+                    token.describe()
+                ),
+                token.position,
+            )),
+        }
+    }
+
+    fn parse_condition(&mut self, field: Field) -> Result<FileQuery, QueryParseError> {
+        let operator_token = self.advance();
+        let operator = match &operator_token.kind {
+            TokenKind::Equals => Operator::Exact,
+            TokenKind::Tilde => Operator::Contains,
+            TokenKind::Word(word) if word.eq_ignore_ascii_case("contains") => Operator::Contains,
+            TokenKind::Word(word) if word.eq_ignore_ascii_case("glob") => Operator::Glob,
+            TokenKind::Word(word) if word.eq_ignore_ascii_case("regex") => Operator::Regex,
+            _ => {
+                return Err(self.error(
+                    &format!(
+                        "Expected an operator ('=', '~', 'contains', 'glob' or 'regex'), found {}",
+                        operator_token.describe()
+                    ),
+                    operator_token.position,
+                ));
+            }
+        };
+
+        let token = self.advance();
+        let (TokenKind::Word(value) | TokenKind::Quoted(value)) = token.kind else {
+            return Err(self.error(
+                &format!("Expected a value, found {}", token.describe()),
+                token.position,
+            ));
+        };
+
+        let basic_query = match (field, operator) {
+            (Field::Domain, Operator::Exact) => BasicQuery::DomainExact(value),
+            (Field::Domain, Operator::Contains) => BasicQuery::DomainContains(value),
+            (Field::Domain, Operator::Glob) => BasicQuery::DomainGlob(
+                GlobPattern::new(&value).map_err(|e| self.error(&e.to_string(), token.position))?,
+            ),
+            (Field::Domain, Operator::Regex) => BasicQuery::DomainRegex(
+                RegexPattern::new(&value)
+                    .map_err(|e| self.error(&e.to_string(), token.position))?,
+            ),
+            (Field::Path, Operator::Exact) => BasicQuery::PathExact(value),
+            (Field::Path, Operator::Contains) => BasicQuery::PathContains(value),
+            (Field::Path, Operator::Glob) => BasicQuery::PathGlob(
+                GlobPattern::new(&value).map_err(|e| self.error(&e.to_string(), token.position))?,
+            ),
+            (Field::Path, Operator::Regex) => BasicQuery::PathRegex(
+                RegexPattern::new(&value)
+                    .map_err(|e| self.error(&e.to_string(), token.position))?,
+            ),
+        };
+
+        Ok(FileQuery::Basic(basic_query))
+    }
+
+    /// Wraps several queries in a node, leaving a single query as is
+    fn combine(mut queries: Vec<FileQuery>, node: fn(Vec<FileQuery>) -> FileQuery) -> FileQuery {
+        if queries.len() == 1
+            && let Some(query) = queries.pop()
+        {
+            return query;
+        }
+        node(queries)
+    }
+
+    /// Descends into a `not` or parenthesis, failing if the expression is nested too deeply
+    fn enter(&mut self, position: usize) -> Result<(), QueryParseError> {
+        self.depth = self.depth.saturating_add(1);
+        if self.depth > MAX_DEPTH {
+            return Err(self.error(
+                &format!(
+                    "Query is nested too deeply; at most {MAX_DEPTH} levels of 'not' and \
+                     parentheses are allowed"
+                ),
+                position,
+            ));
+        }
+        Ok(())
+    }
+
+    /// Returns from a `not` or parenthesis
+    const fn leave(&mut self) {
+        self.depth = self.depth.saturating_sub(1);
+    }
+
+    fn peek(&self) -> &Token {
+        // The token list always ends with an `End` token, which is never consumed
+        let last = self.tokens.len().saturating_sub(1);
+        &self.tokens[self.index.min(last)]
+    }
+
+    fn advance(&mut self) -> Token {
+        let token = self.peek().clone();
+        if token.kind != TokenKind::End {
+            self.index = self.index.saturating_add(1);
+        }
+        token
+    }
+
+    fn error(&self, message: &str, position: usize) -> QueryParseError {
+        QueryParseError {
+            message: message.to_owned(),
+            input: self.input.to_owned(),
+            position,
+        }
+    }
+
+    /// Splits the expression into tokens
+    fn tokenize(&self) -> Result<Vec<Token>, QueryParseError> {
+        let mut tokens = Vec::new();
+        let mut chars = self.input.chars().enumerate().peekable();
+
+        while let Some((position, c)) = chars.next() {
+            let kind = match c {
+                c if c.is_whitespace() => continue,
+                '(' => TokenKind::LeftParen,
+                ')' => TokenKind::RightParen,
+                '~' => TokenKind::Tilde,
+                '=' => {
+                    chars.next_if(|&(_, next)| next == '=');
+                    TokenKind::Equals
+                }
+                '"' | '\'' => {
+                    let mut value = String::new();
+                    let mut terminated = false;
+                    while let Some((_, next)) = chars.next() {
+                        match next {
+                            quote if quote == c => {
+                                terminated = true;
+                                break;
+                            }
+                            // Only quotes and backslashes are escaped, so regex escapes
+                            // such as `\d` can be written as is
+                            '\\' => match chars
+                                .next_if(|&(_, escaped)| escaped == c || escaped == '\\')
+                            {
+                                Some((_, escaped)) => value.push(escaped),
+                                None => value.push('\\'),
+                            },
+                            _ => value.push(next),
+                        }
+                    }
+                    if !terminated {
+                        return Err(self.error("Unterminated string", position));
+                    }
+                    TokenKind::Quoted(value)
+                }
+                _ => {
+                    let mut word = c.to_string();
+                    while let Some((_, next)) = chars.next_if(|&(_, next)| {
+                        !next.is_whitespace() && !matches!(next, '(' | ')' | '=' | '~' | '"' | '\'')
+                    }) {
+                        word.push(next);
+                    }
+                    TokenKind::Word(word)
+                }
+            };
+            tokens.push(Token { kind, position });
+        }
+
+        tokens.push(Token {
+            kind: TokenKind::End,
+            position: self.input.chars().count(),
+        });
+        Ok(tokens)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::queries::CompositeQuery;
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+
+    fn parse_error(input: &str) -> QueryParseError {
+        match FileQuery::parse(input) {
+            Ok(query) => QueryParseError {
+                message: format!("Unexpectedly parsed: {query:?}"),
+                input: input.to_owned(),
+                position: usize::MAX,
+            },
+            Err(error) => error,
+        }
+    }
+
+    #[test]
+    fn test_parse_single_condition() -> Result<()> {
+        assert_eq!(
+            FileQuery::parse("domain = HomeDomain")?,
+            FileQuery::domain_exact("HomeDomain")
+        );
+        assert_eq!(
+            FileQuery::parse("path == 'Library/SMS/sms.db'")?,
+            FileQuery::path_exact("Library/SMS/sms.db")
+        );
+        assert_eq!(
+            FileQuery::parse("domain ~ \"whatsapp\"")?,
+            FileQuery::domain_contains("whatsapp")
+        );
+        assert_eq!(
+            FileQuery::parse("PATH CONTAINS Caches")?,
+            FileQuery::path_contains("Caches")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_full_expression() -> Result<()> {
+        let query = FileQuery::parse(
+            r#"domain ~ "whatsapp" and (path glob "*.sqlite" or path regex "Media/.*\\.jpg$") and not path contains "Caches""#,
+        )?;
+
+        assert_eq!(
+            query,
+            FileQuery::and(vec![
+                FileQuery::domain_contains("whatsapp"),
+                FileQuery::or(vec![
+                    FileQuery::Basic(BasicQuery::PathGlob(GlobPattern::new("*.sqlite")?)),
+                    FileQuery::Basic(BasicQuery::PathRegex(RegexPattern::new(r"Media/.*\.jpg$")?)),
+                ]),
+                FileQuery::negate(FileQuery::path_contains("Caches")),
+            ])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_precedence() -> Result<()> {
+        // `and` binds tighter than `or`, and `not` tighter than both
+        let query = FileQuery::parse("not domain = A or domain = B and path = C")?;
+
+        assert_eq!(
+            query,
+            FileQuery::or(vec![
+                FileQuery::negate(FileQuery::domain_exact("A")),
+                FileQuery::and(vec![
+                    FileQuery::domain_exact("B"),
+                    FileQuery::path_exact("C"),
+                ]),
+            ])
+        );
+        assert!(!matches!(
+            query,
+            FileQuery::Composite(CompositeQuery::AnyOf(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_escapes() -> Result<()> {
+        assert_eq!(
+            FileQuery::parse(r#"path = "say \"hi\" \\ \d""#)?,
+            FileQuery::path_exact(r#"say "hi" \ \d"#)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_error_unknown_field() {
+        let error = parse_error("name = foo");
+
+        assert_eq!(error.position, 0);
+        assert!(error.message.contains("Unknown field \"name\""));
+    }
+
+    #[test]
+    fn test_parse_error_missing_value() {
+        let error = parse_error("domain ~ \"a\" and path glob");
+
+        assert_eq!(error.position, 26);
+        assert!(error.message.contains("Expected a value"));
+        assert_eq!(
+            error.to_string(),
+            "Expected a value, found end of query at position 27\n  domain ~ \"a\" and path glob\n                            ^"
+        );
+    }
+
+    #[test]
+    fn test_parse_error_missing_operator() {
+        let error = parse_error("path \"Caches\"");
+
+        assert_eq!(error.position, 5);
+        assert!(error.message.contains("Expected an operator"));
+    }
+
+    #[test]
+    fn test_parse_error_unclosed_paren() {
+        let error = parse_error("(domain = a or path = b");
+
+        assert_eq!(error.position, 23);
+        assert!(
+            error
+                .message
+                .contains("Expected ')' to close '(' at position 1")
+        );
+    }
+
+    #[test]
+    fn test_parse_error_unmatched_paren() {
+        let error = parse_error("domain = a)");
+
+        assert_eq!(error.position, 10);
+        assert!(error.message.contains("Unmatched ')'"));
+    }
+
+    #[test]
+    fn test_parse_error_trailing_tokens() {
+        let error = parse_error("domain = a path = b");
+
+        assert_eq!(error.position, 11);
+        assert!(
+            error
+                .message
+                .contains("expected 'and', 'or' or end of query")
+        );
+    }
+
+    #[test]
+    fn test_parse_error_unterminated_string() {
+        let error = parse_error("path = \"Library");
+
+        assert_eq!(error.position, 7);
+        assert!(error.message.contains("Unterminated string"));
+    }
+
+    #[test]
+    fn test_parse_error_invalid_regex() {
+        let error = parse_error("path regex \"Media/(\"");
+
+        assert_eq!(error.position, 11);
+        assert!(error.message.contains("Invalid regex pattern"));
+    }
+
+    #[test]
+    fn test_parse_nesting_limit() -> Result<()> {
+        let nested = format!(
+            "{}domain = a{}",
+            "(".repeat(MAX_DEPTH),
+            ")".repeat(MAX_DEPTH)
+        );
+        assert_eq!(FileQuery::parse(&nested)?, FileQuery::domain_exact("a"));
+
+        let negated = format!("{}domain = a", "not ".repeat(MAX_DEPTH));
+        let expected = (0..MAX_DEPTH).fold(FileQuery::domain_exact("a"), |query, _| {
+            FileQuery::negate(query)
+        });
+        assert_eq!(FileQuery::parse(&negated)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_error_nested_too_deeply() {
+        let negated = parse_error(&format!("{}domain = a", "not ".repeat(100_000)));
+
+        assert_eq!(negated.position, MAX_DEPTH.saturating_mul(4));
+        assert!(negated.message.contains("nested too deeply"));
+
+        let nested = parse_error(&format!(
+            "{}domain = a{}",
+            "(".repeat(100_000),
+            ")".repeat(100_000)
+        ));
+
+        assert_eq!(nested.position, MAX_DEPTH);
+        assert!(nested.message.contains("nested too deeply"));
+    }
+
+    #[test]
+    fn test_parse_error_empty() {
+        let error = parse_error("   ");
+
+        assert_eq!(error.position, 3);
+        assert!(error.message.contains("Query is empty"));
+    }
+}
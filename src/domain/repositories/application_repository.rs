@@ -0,0 +1,15 @@
+use crate::domain::entities::Application;
+use anyhow::Result;
+
+/// `ApplicationRepository` trait - Interface for the applications recorded in a backup
+#[allow(
+    async_fn_in_trait,
+    reason = "Using native async fn in trait for better ergonomics"
+)]
+pub trait ApplicationRepository: Send + Sync {
+    /// Lists the installed applications, sorted by bundle identifier
+    ///
+    /// # Errors
+    /// Returns an error if the application records cannot be read
+    async fn find_all(&self) -> Result<Vec<Application>>;
+}
@@ -0,0 +1,29 @@
+use crate::domain::entities::File;
+use crate::domain::value_objects::FileId;
+use anyhow::Result;
+use std::io::Read;
+use std::path::PathBuf;
+
+/// `BlobRepository` trait - Interface for accessing the stored content of backup files
+///
+/// Backups keep the content of each file in a separate blob named after its file ID.
+/// Implementations hide where the blob lives and whether it has to be decrypted.
+pub trait BlobRepository: Send + Sync {
+    /// Returns the location of the stored blob for the file, if it exists
+    fn locate(&self, file: &File) -> Option<PathBuf>;
+
+    /// Opens the plaintext content of the file
+    ///
+    /// # Returns
+    /// * `Result<Option<...>>` - A reader over the content, or `None` if the backup holds no blob
+    ///
+    /// # Errors
+    /// Returns an error if the blob exists but cannot be opened or decrypted
+    fn open(&self, file: &File) -> Result<Option<Box<dyn Read + Send>>>;
+
+    /// Lists every stored blob with the ID of the file it is named after, sorted by ID
+    ///
+    /// # Errors
+    /// Returns an error if the storage cannot be listed
+    fn list(&self) -> Result<Vec<(FileId, PathBuf)>>;
+}
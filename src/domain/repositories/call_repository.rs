@@ -0,0 +1,15 @@
+use crate::domain::entities::Call;
+use anyhow::Result;
+
+/// `CallRepository` trait - Interface for the call history of the Phone app
+#[allow(
+    async_fn_in_trait,
+    reason = "Using native async fn in trait for better ergonomics"
+)]
+pub trait CallRepository: Send + Sync {
+    /// Lists every call, most recent first
+    ///
+    /// # Errors
+    /// Returns an error if the call history cannot be read
+    async fn find_all(&self) -> Result<Vec<Call>>;
+}
@@ -0,0 +1,15 @@
+use crate::domain::entities::Contact;
+use anyhow::Result;
+
+/// `ContactRepository` trait - Interface for the address book of the Contacts app
+#[allow(
+    async_fn_in_trait,
+    reason = "Using native async fn in trait for better ergonomics"
+)]
+pub trait ContactRepository: Send + Sync {
+    /// Lists every contact with its labeled values and photo, ordered by row ID
+    ///
+    /// # Errors
+    /// Returns an error if the address book cannot be read
+    async fn find_all(&self) -> Result<Vec<Contact>>;
+}
@@ -0,0 +1,15 @@
+use crate::domain::entities::Conversation;
+use anyhow::Result;
+
+/// `MessageRepository` trait - Interface for the conversations of the Messages app
+#[allow(
+    async_fn_in_trait,
+    reason = "Using native async fn in trait for better ergonomics"
+)]
+pub trait MessageRepository: Send + Sync {
+    /// Lists every conversation with its messages, ordered by chat
+    ///
+    /// # Errors
+    /// Returns an error if the messages cannot be read
+    async fn find_conversations(&self) -> Result<Vec<Conversation>>;
+}
@@ -1,5 +1,21 @@
+pub mod application_repository;
+pub mod blob_repository;
+pub mod call_repository;
+pub mod contact_repository;
 pub mod file_repository;
+pub mod message_repository;
 pub mod metadata_repository;
+pub mod photo_repository;
+pub mod safari_repository;
+pub mod voicemail_repository;
 
+pub use application_repository::ApplicationRepository;
+pub use blob_repository::BlobRepository;
+pub use call_repository::CallRepository;
+pub use contact_repository::ContactRepository;
 pub use file_repository::FileRepository;
+pub use message_repository::MessageRepository;
 pub use metadata_repository::MetadataRepository;
+pub use photo_repository::PhotoRepository;
+pub use safari_repository::SafariRepository;
+pub use voicemail_repository::VoicemailRepository;
@@ -0,0 +1,15 @@
+use crate::domain::entities::PhotoAsset;
+use anyhow::Result;
+
+/// `PhotoRepository` trait - Interface for the assets of the photo library
+#[allow(
+    async_fn_in_trait,
+    reason = "Using native async fn in trait for better ergonomics"
+)]
+pub trait PhotoRepository: Send + Sync {
+    /// Lists every photo and video, oldest first, with the user albums holding it
+    ///
+    /// # Errors
+    /// Returns an error if the photo library cannot be read
+    async fn find_all(&self) -> Result<Vec<PhotoAsset>>;
+}
@@ -0,0 +1,27 @@
+use crate::domain::entities::{Bookmark, BrowserTab, HistoryVisit};
+use anyhow::Result;
+
+/// `SafariRepository` trait - Interface for the browsing data of Safari
+#[allow(
+    async_fn_in_trait,
+    reason = "Using native async fn in trait for better ergonomics"
+)]
+pub trait SafariRepository: Send + Sync {
+    /// Lists every visit of the browsing history, most recent first
+    ///
+    /// # Errors
+    /// Returns an error if the history cannot be read
+    async fn find_history(&self) -> Result<Vec<HistoryVisit>>;
+
+    /// Lists the top-level bookmark folders, such as Favorites, with the bookmarks in them
+    ///
+    /// # Errors
+    /// Returns an error if the bookmarks cannot be read
+    async fn find_bookmarks(&self) -> Result<Vec<Bookmark>>;
+
+    /// Lists the tabs open on the device, followed by the tabs open on other devices
+    ///
+    /// # Errors
+    /// Returns an error if the tabs cannot be read
+    async fn find_tabs(&self) -> Result<Vec<BrowserTab>>;
+}
@@ -0,0 +1,15 @@
+use crate::domain::entities::Voicemail;
+use anyhow::Result;
+
+/// `VoicemailRepository` trait - Interface for the voicemail messages of the Phone app
+#[allow(
+    async_fn_in_trait,
+    reason = "Using native async fn in trait for better ergonomics"
+)]
+pub trait VoicemailRepository: Send + Sync {
+    /// Lists every voicemail, most recent first
+    ///
+    /// # Errors
+    /// Returns an error if the voicemail messages cannot be read
+    async fn find_all(&self) -> Result<Vec<Voicemail>>;
+}
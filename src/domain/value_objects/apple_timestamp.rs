@@ -0,0 +1,136 @@
+use chrono::{DateTime, TimeDelta, Utc};
+
+/// Seconds between the Unix epoch and the Apple epoch, 2001-01-01T00:00:00Z
+const APPLE_EPOCH_OFFSET: i64 = 978_307_200;
+
+/// Values larger than this are nanoseconds rather than seconds (about 3170 years)
+const NANOSECONDS_THRESHOLD: i64 = 100_000_000_000;
+
+/// `AppleTimestamp` - Value Object representing a time counted from the Apple epoch
+///
+/// Apple databases count time from 2001-01-01T00:00:00Z, in seconds (Core Data stores,
+/// Safari) or, since iOS 11, in nanoseconds (Messages).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct AppleTimestamp(DateTime<Utc>);
+
+impl AppleTimestamp {
+    /// Creates an `AppleTimestamp` from whole seconds since the Apple epoch
+    #[must_use]
+    #[inline]
+    pub fn from_seconds(seconds: i64) -> Option<Self> {
+        DateTime::from_timestamp(seconds.checked_add(APPLE_EPOCH_OFFSET)?, 0).map(Self)
+    }
+
+    /// Creates an `AppleTimestamp` from fractional seconds since the Apple epoch
+    ///
+    /// The time is rounded to microseconds. Returns `None` for values that are not finite
+    /// or out of range.
+    #[must_use]
+    #[inline]
+    pub fn from_fractional_seconds(seconds: f64) -> Option<Self> {
+        let microseconds = (seconds * 1_000_000.0).round();
+        // Beyond ±2^62 microseconds (146,000 years) the conversion below could overflow
+        if !microseconds.is_finite() || microseconds.abs() > 4.611_686_018_427_388e18 {
+            return None;
+        }
+        #[allow(
+            clippy::cast_possible_truncation,
+            reason = "The value is rounded and checked to be in range"
+        )]
+        let microseconds = microseconds as i64;
+
+        Self::from_seconds(0)?
+            .0
+            .checked_add_signed(TimeDelta::microseconds(microseconds))
+            .map(Self)
+    }
+
+    /// Creates an `AppleTimestamp` from nanoseconds since the Apple epoch
+    #[must_use]
+    #[inline]
+    pub fn from_nanoseconds(nanoseconds: i64) -> Option<Self> {
+        Self::from_seconds(0)?
+            .0
+            .checked_add_signed(TimeDelta::nanoseconds(nanoseconds))
+            .map(Self)
+    }
+
+    /// Creates an `AppleTimestamp` from a value that is either seconds or nanoseconds
+    ///
+    /// Messages stores seconds before iOS 11 and nanoseconds since, in the same columns.
+    /// Values too large to be seconds are taken as nanoseconds.
+    #[must_use]
+    #[inline]
+    pub fn from_seconds_or_nanoseconds(value: i64) -> Option<Self> {
+        if value
+            .checked_abs()
+            .is_none_or(|abs| abs > NANOSECONDS_THRESHOLD)
+        {
+            Self::from_nanoseconds(value)
+        } else {
+            Self::from_seconds(value)
+        }
+    }
+
+    /// Returns the time in UTC
+    #[must_use]
+    #[inline]
+    pub const fn to_datetime(self) -> DateTime<Utc> {
+        self.0
+    }
+}
+
+impl From<AppleTimestamp> for DateTime<Utc> {
+    #[inline]
+    fn from(timestamp: AppleTimestamp) -> Self {
+        timestamp.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+
+    #[test]
+    fn test_from_seconds() -> Result<()> {
+        assert_eq!(
+            AppleTimestamp::from_seconds(0).map(AppleTimestamp::to_datetime),
+            Some("2001-01-01T00:00:00Z".parse()?)
+        );
+        assert_eq!(
+            AppleTimestamp::from_seconds(726_978_600).map(AppleTimestamp::to_datetime),
+            Some("2024-01-15T02:30:00Z".parse()?)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_fractional_seconds() -> Result<()> {
+        assert_eq!(
+            AppleTimestamp::from_fractional_seconds(726_978_600.25)
+                .map(AppleTimestamp::to_datetime),
+            Some("2024-01-15T02:30:00.25Z".parse()?)
+        );
+        assert_eq!(AppleTimestamp::from_fractional_seconds(f64::NAN), None);
+        assert_eq!(AppleTimestamp::from_fractional_seconds(1e300), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_seconds_or_nanoseconds() -> Result<()> {
+        let expected = Some("2024-01-15T02:30:00Z".parse()?);
+
+        assert_eq!(
+            AppleTimestamp::from_seconds_or_nanoseconds(726_978_600)
+                .map(AppleTimestamp::to_datetime),
+            expected
+        );
+        assert_eq!(
+            AppleTimestamp::from_seconds_or_nanoseconds(726_978_600_000_000_000)
+                .map(AppleTimestamp::to_datetime),
+            expected
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,139 @@
+use std::fmt;
+
+/// `ByteSize` - Value Object representing a file size given in human-readable units
+///
+/// Sizes are written as a number with an optional unit, e.g. `512`, `10KB`, `1.5MiB` or
+/// `2G`. `kB`, `MB`, `GB` and `TB` are decimal units; `KiB`, `MiB`, `GiB` and `TiB` as
+/// well as the single-letter `K`, `M`, `G` and `T` are binary units.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ByteSize(u64);
+
+impl ByteSize {
+    /// Parses a `ByteSize`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The number or the unit is invalid
+    /// - The size does not fit into 64 bits
+    #[inline]
+    pub fn new(size: &str) -> anyhow::Result<Self> {
+        let invalid =
+            || anyhow::anyhow!("Invalid size: '{size}' (expected e.g. 512, 10KB or 1.5MiB)");
+
+        let trimmed = size.trim();
+        let unit_start = trimmed
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(trimmed.len());
+        let (number, unit) = trimmed.split_at(unit_start);
+
+        let multiplier: u128 = match unit.trim().to_ascii_lowercase().as_str() {
+            "" | "b" => 1,
+            "kb" => 1_000,
+            "mb" => 1_000_000,
+            "gb" => 1_000_000_000,
+            "tb" => 1_000_000_000_000,
+            "k" | "kib" => 1 << 10,
+            "m" | "mib" => 1 << 20,
+            "g" | "gib" => 1 << 30,
+            "t" | "tib" => 1 << 40,
+            _ => return Err(invalid()),
+        };
+
+        let (whole, fraction) = number.split_once('.').unwrap_or((number, ""));
+        if whole.is_empty() && fraction.is_empty() {
+            return Err(invalid());
+        }
+
+        let whole: u128 = if whole.is_empty() {
+            0
+        } else {
+            whole.parse().map_err(|_| invalid())?
+        };
+        let scale = 10_u128
+            .checked_pow(u32::try_from(fraction.len())?)
+            .ok_or_else(invalid)?;
+        let fraction: u128 = if fraction.is_empty() {
+            0
+        } else {
+            fraction.parse().map_err(|_| invalid())?
+        };
+
+        // Fractional bytes are truncated
+        let bytes = whole
+            .checked_mul(multiplier)
+            .zip(fraction.checked_mul(multiplier))
+            .and_then(|(whole, fraction)| whole.checked_add(fraction.checked_div(scale)?))
+            .ok_or_else(invalid)?;
+
+        Ok(Self(u64::try_from(bytes).map_err(|_| {
+            anyhow::anyhow!("Size is too large: '{size}'")
+        })?))
+    }
+
+    /// Returns the size in bytes
+    #[must_use]
+    #[inline]
+    pub const fn value(&self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for ByteSize {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} bytes", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use super::*;
+
+    #[test]
+    fn test_byte_size_units() -> Result<()> {
+        assert_eq!(ByteSize::new("512")?.value(), 512);
+        assert_eq!(ByteSize::new("512B")?.value(), 512);
+        assert_eq!(ByteSize::new("10KB")?.value(), 10_000);
+        assert_eq!(ByteSize::new("10kb")?.value(), 10_000);
+        assert_eq!(ByteSize::new("10K")?.value(), 10_240);
+        assert_eq!(ByteSize::new("10 KiB")?.value(), 10_240);
+        assert_eq!(ByteSize::new("2MB")?.value(), 2_000_000);
+        assert_eq!(ByteSize::new("2MiB")?.value(), 2_097_152);
+        assert_eq!(ByteSize::new("1G")?.value(), 1_073_741_824);
+        assert_eq!(ByteSize::new("1TB")?.value(), 1_000_000_000_000);
+        Ok(())
+    }
+
+    #[test]
+    fn test_byte_size_fractions() -> Result<()> {
+        assert_eq!(ByteSize::new("1.5MiB")?.value(), 1_572_864);
+        assert_eq!(ByteSize::new("0.5k")?.value(), 512);
+        assert_eq!(ByteSize::new(".25KB")?.value(), 250);
+        assert_eq!(ByteSize::new("1.0001K")?.value(), 1024);
+        Ok(())
+    }
+
+    #[test]
+    fn test_byte_size_invalid() {
+        for size in ["", "MB", "1.2.3K", "10 parsecs", "-1", "1e3"] {
+            let result = ByteSize::new(size);
+            assert!(result.is_err(), "{size} should be invalid");
+            if let Err(error) = result {
+                assert!(error.to_string().contains("Invalid size"));
+            }
+        }
+    }
+
+    #[test]
+    fn test_byte_size_too_large() {
+        let result = ByteSize::new("20000000TiB");
+
+        assert!(result.is_err());
+        if let Err(error) = result {
+            assert!(error.to_string().contains("too large"));
+        }
+    }
+}
@@ -1,8 +1,203 @@
+use super::UnsafePathError;
+use super::relative_path::validate_component;
 use std::fmt;
+use std::path::PathBuf;
+
+/// Locations of other known domains on the device, relative to the file system root
+const OTHER_DEVICE_PATHS: [(&str, &str); 4] = [
+    ("BooksDomain", "private/var/mobile/Media/Books"),
+    ("MobileDeviceDomain", "private/var/MobileDevice"),
+    ("NetworkDomain", "private/var/networkd"),
+    ("ProtectedDomain", "private/var/protected"),
+];
+
+/// Directory for domains whose location on the device is unknown
+const UNKNOWN_DEVICE_PATH: &str = "Domains";
+
+/// Kind of a backup domain
+///
+/// Container domains are named after a bundle or group identifier following their prefix,
+/// e.g. `AppDomain-net.whatsapp.WhatsApp`. The other domains have a fixed name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum DomainKind {
+    /// `AppDomain-<bundle ID>`: data container of an app
+    App,
+    /// `AppDomainGroup-<group ID>`: container shared by an app group
+    AppGroup,
+    /// `AppDomainPlugin-<bundle ID>`: data container of an app extension
+    AppPlugin,
+    /// `SysContainerDomain-<bundle ID>`: data container of a system app
+    SysContainer,
+    /// `SysSharedContainerDomain-<group ID>`: container shared by system apps
+    SysSharedContainer,
+    /// `HomeDomain`: home directory of the mobile user
+    Home,
+    /// `CameraRollDomain`: photos and videos of the camera roll
+    CameraRoll,
+    /// `MediaDomain`: other media, e.g. message attachments and recordings
+    Media,
+    /// `KeychainDomain`: keychain data
+    Keychain,
+    /// `RootDomain`: home directory of the root user
+    Root,
+    /// `WirelessDomain`: cellular and call data
+    Wireless,
+    /// `ManagedPreferencesDomain`: preferences installed by device management
+    ManagedPreferences,
+    /// `DatabaseDomain`: system databases
+    Database,
+    /// `SystemPreferencesDomain`: system preferences
+    SystemPreferences,
+    /// `TonesDomain`: ringtones
+    Tones,
+    /// `HealthDomain`: Health data
+    Health,
+    /// `InstallDomain`: app installation data
+    Install,
+    /// Any other domain
+    Unknown,
+}
+
+impl DomainKind {
+    /// All domain kinds
+    pub const ALL: [Self; 18] = [
+        Self::App,
+        Self::AppGroup,
+        Self::AppPlugin,
+        Self::SysContainer,
+        Self::SysSharedContainer,
+        Self::Home,
+        Self::CameraRoll,
+        Self::Media,
+        Self::Keychain,
+        Self::Root,
+        Self::Wireless,
+        Self::ManagedPreferences,
+        Self::Database,
+        Self::SystemPreferences,
+        Self::Tones,
+        Self::Health,
+        Self::Install,
+        Self::Unknown,
+    ];
+
+    /// Returns the kebab-case name of the kind, e.g. `app-group` or `home`
+    #[must_use]
+    #[inline]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::App => "app",
+            Self::AppGroup => "app-group",
+            Self::AppPlugin => "app-plugin",
+            Self::SysContainer => "sys-container",
+            Self::SysSharedContainer => "sys-shared-container",
+            Self::Home => "home",
+            Self::CameraRoll => "camera-roll",
+            Self::Media => "media",
+            Self::Keychain => "keychain",
+            Self::Root => "root",
+            Self::Wireless => "wireless",
+            Self::ManagedPreferences => "managed-preferences",
+            Self::Database => "database",
+            Self::SystemPreferences => "system-preferences",
+            Self::Tones => "tones",
+            Self::Health => "health",
+            Self::Install => "install",
+            Self::Unknown => "unknown",
+        }
+    }
+
+    /// Returns the domain prefix of a container kind, e.g. `AppDomain-`
+    #[must_use]
+    #[inline]
+    pub const fn prefix(self) -> Option<&'static str> {
+        match self {
+            Self::App => Some("AppDomain-"),
+            Self::AppGroup => Some("AppDomainGroup-"),
+            Self::AppPlugin => Some("AppDomainPlugin-"),
+            Self::SysContainer => Some("SysContainerDomain-"),
+            Self::SysSharedContainer => Some("SysSharedContainerDomain-"),
+            _ => None,
+        }
+    }
+
+    /// Returns the domain name of a kind without identifier, e.g. `HomeDomain`
+    #[must_use]
+    #[inline]
+    pub const fn domain_name(self) -> Option<&'static str> {
+        match self {
+            Self::Home => Some("HomeDomain"),
+            Self::CameraRoll => Some("CameraRollDomain"),
+            Self::Media => Some("MediaDomain"),
+            Self::Keychain => Some("KeychainDomain"),
+            Self::Root => Some("RootDomain"),
+            Self::Wireless => Some("WirelessDomain"),
+            Self::ManagedPreferences => Some("ManagedPreferencesDomain"),
+            Self::Database => Some("DatabaseDomain"),
+            Self::SystemPreferences => Some("SystemPreferencesDomain"),
+            Self::Tones => Some("TonesDomain"),
+            Self::Health => Some("HealthDomain"),
+            Self::Install => Some("InstallDomain"),
+            _ => None,
+        }
+    }
+
+    /// Returns where domains of this kind are located on the device
+    ///
+    /// Container kinds return the directory holding one container per identifier. Relative
+    /// paths of camera roll and health domains already start with `Media` and `Library`.
+    const fn device_path(self) -> Option<&'static str> {
+        match self {
+            Self::App => Some("private/var/mobile/Containers/Data/Application"),
+            Self::AppGroup => Some("private/var/mobile/Containers/Shared/AppGroup"),
+            Self::AppPlugin => Some("private/var/mobile/Containers/Data/PluginKitPlugin"),
+            Self::SysContainer => Some("private/var/containers/Data/System"),
+            Self::SysSharedContainer => Some("private/var/containers/Shared/SystemGroup"),
+            Self::Home | Self::CameraRoll | Self::Health | Self::Media | Self::Tones => {
+                Some("private/var/mobile")
+            }
+            Self::Keychain => Some("private/var/Keychains"),
+            Self::Root => Some("private/var/root"),
+            Self::Wireless => Some("private/var/wireless"),
+            Self::ManagedPreferences => Some("private/var/Managed Preferences"),
+            Self::Database => Some("private/var/db"),
+            Self::SystemPreferences => Some("private/var/preferences"),
+            Self::Install => Some("private/var/installd"),
+            Self::Unknown => None,
+        }
+    }
+
+    /// Determines the kind of a domain string
+    fn parse(domain: &str) -> Self {
+        Self::ALL
+            .into_iter()
+            .find(|kind| {
+                kind.domain_name() == Some(domain)
+                    || kind
+                        .prefix()
+                        .is_some_and(|prefix| domain.starts_with(prefix))
+            })
+            .unwrap_or(Self::Unknown)
+    }
+}
+
+impl fmt::Display for DomainKind {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
 
 /// Domain - Value Object representing an application identifier
+///
+/// The domain string is parsed into its [`DomainKind`] and, for container domains, the
+/// bundle or group identifier.
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct Domain(String);
+pub struct Domain {
+    value: String,
+    kind: DomainKind,
+}
 
 impl Domain {
     /// Creates a new Domain
@@ -24,36 +219,114 @@ impl Domain {
             ));
         }
 
-        Ok(Self(domain))
+        let kind = DomainKind::parse(&domain);
+        Ok(Self {
+            value: domain,
+            kind,
+        })
     }
 
     /// Returns the string value of the Domain
     #[must_use]
     #[inline]
     pub fn value(&self) -> &str {
-        &self.0
+        &self.value
+    }
+
+    /// Returns the kind of the domain
+    #[must_use]
+    #[inline]
+    pub const fn kind(&self) -> DomainKind {
+        self.kind
     }
+
+    /// Returns the bundle or group identifier of a container domain
+    ///
+    /// e.g. `net.whatsapp.WhatsApp` for `AppDomain-net.whatsapp.WhatsApp`
+    #[must_use]
+    #[inline]
+    pub fn bundle_id(&self) -> Option<&str> {
+        self.kind
+            .prefix()
+            .and_then(|prefix| self.value.strip_prefix(prefix))
+    }
+
+    /// Returns the domain as a single path component
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the domain contains a `/` or cannot be used as a file name
+    #[inline]
+    pub fn to_path(&self) -> Result<PathBuf, UnsafePathError> {
+        validate_name(&self.value)?;
+        Ok(PathBuf::from(&self.value))
+    }
+
+    /// Returns where the files of the domain are located on the device
+    ///
+    /// The path is relative to the root of the device file system, e.g.
+    /// `private/var/mobile` for `HomeDomain`. Domains with an unknown location are
+    /// placed under `Domains/<domain>`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the domain or its container name cannot be used as a file name
+    #[inline]
+    pub fn device_path(&self) -> Result<PathBuf, UnsafePathError> {
+        if let Some(path) = self.kind.device_path() {
+            return match self.bundle_id() {
+                Some(bundle_id) => {
+                    validate_name(bundle_id)?;
+                    Ok(PathBuf::from(path).join(bundle_id))
+                }
+                None => Ok(PathBuf::from(path)),
+            };
+        }
+
+        if let Some((_, path)) = OTHER_DEVICE_PATHS
+            .iter()
+            .find(|(domain, _)| *domain == self.value)
+        {
+            return Ok(PathBuf::from(path));
+        }
+
+        Ok(PathBuf::from(UNKNOWN_DEVICE_PATH).join(self.to_path()?))
+    }
+}
+
+/// Checks that a name can be used as a single path component
+fn validate_name(name: &str) -> Result<(), UnsafePathError> {
+    if matches!(name, "" | "." | "..") {
+        return Err(UnsafePathError::ReservedName(name.to_owned()));
+    }
+    if name.contains('/') {
+        return Err(UnsafePathError::InvalidCharacter('/'));
+    }
+
+    validate_component(name)
 }
 
 impl fmt::Display for Domain {
     #[inline]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.0)
+        write!(f, "{}", self.value)
     }
 }
 
 impl From<Domain> for String {
     #[inline]
     fn from(domain: Domain) -> Self {
-        domain.0
+        domain.value
     }
 }
 
 #[cfg(test)]
 mod tests {
     use anyhow::Result;
+    use std::path::Path;
 
     use super::*;
+    use crate::domain::value_objects::RelativePath;
 
     #[test]
     fn test_domain_creation() -> Result<()> {
@@ -79,4 +352,154 @@ mod tests {
         let max_domain = "a".repeat(255);
         assert!(Domain::new(max_domain).is_ok());
     }
+
+    #[test]
+    fn test_domain_kind() -> Result<()> {
+        let cases = [
+            (
+                "AppDomain-net.whatsapp.WhatsApp",
+                DomainKind::App,
+                Some("net.whatsapp.WhatsApp"),
+            ),
+            (
+                "AppDomainGroup-group.net.whatsapp.WhatsApp.shared",
+                DomainKind::AppGroup,
+                Some("group.net.whatsapp.WhatsApp.shared"),
+            ),
+            (
+                "AppDomainPlugin-net.whatsapp.WhatsApp.ShareExtension",
+                DomainKind::AppPlugin,
+                Some("net.whatsapp.WhatsApp.ShareExtension"),
+            ),
+            (
+                "SysContainerDomain-com.apple.mobilesafari",
+                DomainKind::SysContainer,
+                Some("com.apple.mobilesafari"),
+            ),
+            (
+                "SysSharedContainerDomain-systemgroup.com.apple.configurationprofiles",
+                DomainKind::SysSharedContainer,
+                Some("systemgroup.com.apple.configurationprofiles"),
+            ),
+            ("HomeDomain", DomainKind::Home, None),
+            ("CameraRollDomain", DomainKind::CameraRoll, None),
+            ("HealthDomain", DomainKind::Health, None),
+            ("BooksDomain", DomainKind::Unknown, None),
+            ("AppDomain", DomainKind::Unknown, None),
+            ("homedomain", DomainKind::Unknown, None),
+        ];
+
+        for (value, kind, bundle_id) in cases {
+            let domain = Domain::new(value.to_owned())?;
+            assert_eq!(domain.kind(), kind, "{value}");
+            assert_eq!(domain.bundle_id(), bundle_id, "{value}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_domain_kind_names() {
+        for kind in DomainKind::ALL {
+            // Names and domain strings are unique
+            assert_eq!(
+                DomainKind::ALL
+                    .iter()
+                    .filter(|other| other.name() == kind.name())
+                    .count(),
+                1
+            );
+            if let Some(name) = kind.domain_name() {
+                assert_eq!(DomainKind::parse(name), kind);
+            }
+        }
+        assert_eq!(DomainKind::AppGroup.to_string(), "app-group");
+    }
+
+    #[test]
+    fn test_domain_to_path() -> Result<()> {
+        let domain = Domain::new("AppDomain-com.example.app".to_owned())?;
+        assert_eq!(domain.to_path()?, Path::new("AppDomain-com.example.app"));
+
+        for unsafe_domain in ["..", "AppDomain-x/../../evil", "evil\0"] {
+            assert!(
+                Domain::new(unsafe_domain.to_owned())?.to_path().is_err(),
+                "{unsafe_domain} should be rejected"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_domain_device_path() -> Result<()> {
+        let cases = [
+            ("HomeDomain", "private/var/mobile"),
+            ("CameraRollDomain", "private/var/mobile"),
+            ("HealthDomain", "private/var/mobile"),
+            ("KeychainDomain", "private/var/Keychains"),
+            (
+                "AppDomain-net.whatsapp.WhatsApp",
+                "private/var/mobile/Containers/Data/Application/net.whatsapp.WhatsApp",
+            ),
+            (
+                "AppDomainGroup-group.net.whatsapp.WhatsApp.shared",
+                "private/var/mobile/Containers/Shared/AppGroup/group.net.whatsapp.WhatsApp.shared",
+            ),
+            (
+                "AppDomainPlugin-com.apple.share",
+                "private/var/mobile/Containers/Data/PluginKitPlugin/com.apple.share",
+            ),
+            ("SomeFutureDomain", "Domains/SomeFutureDomain"),
+        ];
+
+        for (domain, path) in cases {
+            assert_eq!(
+                Domain::new(domain.to_owned())?.device_path()?,
+                Path::new(path)
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_domain_device_path_with_relative_path() -> Result<()> {
+        let cases = [
+            (
+                "CameraRollDomain",
+                "Media/DCIM/100APPLE/IMG_0001.HEIC",
+                "private/var/mobile/Media/DCIM/100APPLE/IMG_0001.HEIC",
+            ),
+            (
+                "HealthDomain",
+                "Library/Health/healthdb_secure.sqlite",
+                "private/var/mobile/Library/Health/healthdb_secure.sqlite",
+            ),
+            (
+                "HomeDomain",
+                "Library/SMS/sms.db",
+                "private/var/mobile/Library/SMS/sms.db",
+            ),
+        ];
+
+        for (domain, relative_path, path) in cases {
+            let relative_path = RelativePath::new(relative_path.to_owned())?;
+            assert_eq!(
+                Domain::new(domain.to_owned())?
+                    .device_path()?
+                    .join(relative_path.to_path()?),
+                Path::new(path)
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_domain_device_path_unsafe_container() -> Result<()> {
+        for domain in ["AppDomain-..", "AppDomain-", "AppDomainGroup-a/../../b"] {
+            assert!(
+                Domain::new(domain.to_owned())?.device_path().is_err(),
+                "{domain} should be rejected"
+            );
+        }
+        Ok(())
+    }
 }
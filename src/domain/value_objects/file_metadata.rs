@@ -0,0 +1,334 @@
+use chrono::{DateTime, Utc};
+
+/// `FileMetadata` - Value Object representing the decoded `MBFile` attributes of a file
+///
+/// Every backup row carries an archived `MBFile` object describing the original file on the
+/// device. This value object holds its decoded form.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct FileMetadata {
+    /// File size in bytes
+    size: u64,
+    /// Unix mode (file type and permission bits)
+    mode: u32,
+    /// Owner user ID
+    user_id: u32,
+    /// Owner group ID
+    group_id: u32,
+    /// Inode number on the device
+    inode: u64,
+    /// Last modification time
+    last_modified: DateTime<Utc>,
+    /// Last status change time
+    last_status_change: DateTime<Utc>,
+    /// Last access time (legacy `Manifest.mbdb` backups only)
+    last_accessed: Option<DateTime<Utc>>,
+    /// Creation time
+    birth: DateTime<Utc>,
+    /// Data protection class
+    protection_class: u32,
+    /// `MBFile` flags
+    flags: u64,
+    /// Symbolic link target
+    target: Option<String>,
+    /// Wrapped per-file encryption key (encrypted backups only)
+    encryption_key: Option<Vec<u8>>,
+    /// SHA1 digest of the content (legacy `Manifest.mbdb` backups only)
+    digest: Option<Vec<u8>>,
+}
+
+impl FileMetadata {
+    /// Unix file type mask
+    pub const TYPE_MASK: u32 = 0o170_000;
+    /// Unix file type for directories
+    pub const TYPE_DIRECTORY: u32 = 0o040_000;
+    /// Unix file type for regular files
+    pub const TYPE_REGULAR_FILE: u32 = 0o100_000;
+    /// Unix file type for symbolic links
+    pub const TYPE_SYMBOLIC_LINK: u32 = 0o120_000;
+
+    /// Creates a new `FileMetadata` with the given size and mode
+    ///
+    /// The remaining attributes default to zero (or the Unix epoch) and can be set with
+    /// the `with_*` methods.
+    #[must_use]
+    #[inline]
+    pub fn new(size: u64, mode: u32) -> Self {
+        Self {
+            size,
+            mode,
+            ..Self::default()
+        }
+    }
+
+    /// Sets the owner user and group IDs
+    #[must_use]
+    #[inline]
+    pub const fn with_owner(mut self, user_id: u32, group_id: u32) -> Self {
+        self.user_id = user_id;
+        self.group_id = group_id;
+        self
+    }
+
+    /// Sets the inode number
+    #[must_use]
+    #[inline]
+    pub const fn with_inode(mut self, inode: u64) -> Self {
+        self.inode = inode;
+        self
+    }
+
+    /// Sets the modification, status change and creation times
+    #[must_use]
+    #[inline]
+    pub const fn with_timestamps(
+        mut self,
+        last_modified: DateTime<Utc>,
+        last_status_change: DateTime<Utc>,
+        birth: DateTime<Utc>,
+    ) -> Self {
+        self.last_modified = last_modified;
+        self.last_status_change = last_status_change;
+        self.birth = birth;
+        self
+    }
+
+    /// Sets the last access time
+    #[must_use]
+    #[inline]
+    pub const fn with_last_accessed(mut self, last_accessed: Option<DateTime<Utc>>) -> Self {
+        self.last_accessed = last_accessed;
+        self
+    }
+
+    /// Sets the data protection class
+    #[must_use]
+    #[inline]
+    pub const fn with_protection_class(mut self, protection_class: u32) -> Self {
+        self.protection_class = protection_class;
+        self
+    }
+
+    /// Sets the `MBFile` flags
+    #[must_use]
+    #[inline]
+    pub const fn with_flags(mut self, flags: u64) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    /// Sets the symbolic link target
+    #[must_use]
+    #[inline]
+    pub fn with_target(mut self, target: Option<String>) -> Self {
+        self.target = target;
+        self
+    }
+
+    /// Sets the wrapped per-file encryption key
+    #[must_use]
+    #[inline]
+    pub fn with_encryption_key(mut self, encryption_key: Option<Vec<u8>>) -> Self {
+        self.encryption_key = encryption_key;
+        self
+    }
+
+    /// Sets the SHA1 digest of the content
+    #[must_use]
+    #[inline]
+    pub fn with_digest(mut self, digest: Option<Vec<u8>>) -> Self {
+        self.digest = digest;
+        self
+    }
+
+    /// Returns the file size in bytes
+    #[must_use]
+    #[inline]
+    pub const fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Returns the Unix mode
+    #[must_use]
+    #[inline]
+    pub const fn mode(&self) -> u32 {
+        self.mode
+    }
+
+    /// Returns the permission bits of the Unix mode
+    #[must_use]
+    #[inline]
+    pub const fn permissions(&self) -> u32 {
+        self.mode & 0o7777
+    }
+
+    /// Returns the owner user ID
+    #[must_use]
+    #[inline]
+    pub const fn user_id(&self) -> u32 {
+        self.user_id
+    }
+
+    /// Returns the owner group ID
+    #[must_use]
+    #[inline]
+    pub const fn group_id(&self) -> u32 {
+        self.group_id
+    }
+
+    /// Returns the inode number
+    #[must_use]
+    #[inline]
+    pub const fn inode(&self) -> u64 {
+        self.inode
+    }
+
+    /// Returns the last modification time
+    #[must_use]
+    #[inline]
+    pub const fn last_modified(&self) -> &DateTime<Utc> {
+        &self.last_modified
+    }
+
+    /// Returns the last status change time
+    #[must_use]
+    #[inline]
+    pub const fn last_status_change(&self) -> &DateTime<Utc> {
+        &self.last_status_change
+    }
+
+    /// Returns the last access time
+    #[must_use]
+    #[inline]
+    pub const fn last_accessed(&self) -> Option<&DateTime<Utc>> {
+        self.last_accessed.as_ref()
+    }
+
+    /// Returns the creation time
+    #[must_use]
+    #[inline]
+    pub const fn birth(&self) -> &DateTime<Utc> {
+        &self.birth
+    }
+
+    /// Returns the data protection class
+    #[must_use]
+    #[inline]
+    pub const fn protection_class(&self) -> u32 {
+        self.protection_class
+    }
+
+    /// Returns the `MBFile` flags
+    #[must_use]
+    #[inline]
+    pub const fn flags(&self) -> u64 {
+        self.flags
+    }
+
+    /// Returns the symbolic link target
+    #[must_use]
+    #[inline]
+    pub fn target(&self) -> Option<&str> {
+        self.target.as_deref()
+    }
+
+    /// Returns the wrapped per-file encryption key
+    #[must_use]
+    #[inline]
+    pub fn encryption_key(&self) -> Option<&[u8]> {
+        self.encryption_key.as_deref()
+    }
+
+    /// Returns the SHA1 digest of the content
+    #[must_use]
+    #[inline]
+    pub fn digest(&self) -> Option<&[u8]> {
+        self.digest.as_deref()
+    }
+
+    /// Checks if the mode describes a directory
+    #[must_use]
+    #[inline]
+    pub const fn is_directory(&self) -> bool {
+        self.mode & Self::TYPE_MASK == Self::TYPE_DIRECTORY
+    }
+
+    /// Checks if the mode describes a regular file
+    #[must_use]
+    #[inline]
+    pub const fn is_regular_file(&self) -> bool {
+        self.mode & Self::TYPE_MASK == Self::TYPE_REGULAR_FILE
+    }
+
+    /// Checks if the mode describes a symbolic link
+    #[must_use]
+    #[inline]
+    pub const fn is_symbolic_link(&self) -> bool {
+        self.mode & Self::TYPE_MASK == Self::TYPE_SYMBOLIC_LINK
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::{Context as _, Result};
+
+    use super::*;
+
+    #[test]
+    fn test_file_metadata_creation() {
+        let metadata = FileMetadata::new(1024, 0o100_644);
+
+        assert_eq!(metadata.size(), 1024);
+        assert_eq!(metadata.mode(), 0o100_644);
+        assert_eq!(metadata.permissions(), 0o644);
+        assert_eq!(metadata.user_id(), 0);
+        assert_eq!(metadata.target(), None);
+        assert_eq!(metadata.encryption_key(), None);
+        assert_eq!(*metadata.last_modified(), DateTime::<Utc>::default());
+    }
+
+    #[test]
+    fn test_file_metadata_with_attributes() -> Result<()> {
+        let modified = DateTime::from_timestamp(1_700_000_000, 0).context("invalid timestamp")?;
+        let changed = DateTime::from_timestamp(1_700_000_100, 0).context("invalid timestamp")?;
+        let birth = DateTime::from_timestamp(1_600_000_000, 0).context("invalid timestamp")?;
+
+        let metadata = FileMetadata::new(42, 0o120_755)
+            .with_owner(501, 20)
+            .with_inode(123_456)
+            .with_timestamps(modified, changed, birth)
+            .with_protection_class(3)
+            .with_flags(1)
+            .with_target(Some("../target".to_owned()))
+            .with_encryption_key(Some(vec![1, 2, 3]))
+            .with_digest(Some(vec![4, 5, 6]));
+
+        assert_eq!(metadata.user_id(), 501);
+        assert_eq!(metadata.group_id(), 20);
+        assert_eq!(metadata.inode(), 123_456);
+        assert_eq!(*metadata.last_modified(), modified);
+        assert_eq!(*metadata.last_status_change(), changed);
+        assert_eq!(*metadata.birth(), birth);
+        assert_eq!(metadata.protection_class(), 3);
+        assert_eq!(metadata.flags(), 1);
+        assert_eq!(metadata.target(), Some("../target"));
+        assert_eq!(metadata.encryption_key(), Some([1, 2, 3].as_slice()));
+        assert_eq!(metadata.digest(), Some([4, 5, 6].as_slice()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_metadata_file_types() {
+        let directory = FileMetadata::new(0, 0o040_755);
+        assert!(directory.is_directory());
+        assert!(!directory.is_regular_file());
+        assert!(!directory.is_symbolic_link());
+
+        let regular_file = FileMetadata::new(0, 0o100_644);
+        assert!(regular_file.is_regular_file());
+        assert!(!regular_file.is_directory());
+
+        let symlink = FileMetadata::new(0, 0o120_644);
+        assert!(symlink.is_symbolic_link());
+        assert!(!symlink.is_regular_file());
+    }
+}
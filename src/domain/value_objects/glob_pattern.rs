@@ -0,0 +1,158 @@
+use globset::{GlobBuilder, GlobMatcher};
+use std::fmt;
+
+/// `GlobPattern` - Value Object representing a validated shell-style glob
+///
+/// `*` and `?` also match `/`, as they do in `SQLite` `GLOB`, and `**/` matches zero or
+/// more directories.
+#[derive(Debug, Clone)]
+pub struct GlobPattern {
+    /// Pattern as written by the user
+    pattern: String,
+    /// Compiled matcher
+    matcher: GlobMatcher,
+    /// Compiled matcher ignoring case
+    case_insensitive_matcher: GlobMatcher,
+}
+
+impl GlobPattern {
+    /// Creates a new `GlobPattern`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pattern is not a valid glob
+    #[inline]
+    pub fn new(pattern: &str) -> anyhow::Result<Self> {
+        let compile = |case_insensitive| {
+            GlobBuilder::new(pattern)
+                .literal_separator(false)
+                .case_insensitive(case_insensitive)
+                .build()
+                .map(|glob| glob.compile_matcher())
+                .map_err(|e| anyhow::anyhow!("Invalid glob pattern: {e}"))
+        };
+
+        Ok(Self {
+            pattern: pattern.to_owned(),
+            matcher: compile(false)?,
+            case_insensitive_matcher: compile(true)?,
+        })
+    }
+
+    /// Returns the pattern as written by the user
+    #[must_use]
+    #[inline]
+    pub fn value(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Checks if the value matches the pattern
+    #[must_use]
+    #[inline]
+    pub fn is_match(&self, value: &str) -> bool {
+        self.matcher.is_match(value)
+    }
+
+    /// Checks if the value matches the pattern, ignoring case
+    #[must_use]
+    #[inline]
+    pub fn is_match_ignore_case(&self, value: &str) -> bool {
+        self.case_insensitive_matcher.is_match(value)
+    }
+
+    /// Returns the leading part of the pattern that contains no wildcards
+    ///
+    /// Every matching value starts with this prefix.
+    #[must_use]
+    #[inline]
+    pub fn literal_prefix(&self) -> &str {
+        self.pattern
+            .split(['*', '?', '[', '{', '\\'])
+            .next()
+            .unwrap_or_default()
+    }
+}
+
+impl PartialEq for GlobPattern {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.pattern == other.pattern
+    }
+}
+
+impl Eq for GlobPattern {}
+
+impl fmt::Display for GlobPattern {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.pattern)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use super::*;
+
+    #[test]
+    fn test_glob_pattern_matching() -> Result<()> {
+        let pattern = GlobPattern::new("*.sqlite")?;
+
+        assert_eq!(pattern.value(), "*.sqlite");
+        assert!(pattern.is_match("Library/Notes/notes.sqlite"));
+        assert!(!pattern.is_match("Library/Notes/notes.sqlite-wal"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_glob_pattern_recursive_wildcard() -> Result<()> {
+        let pattern = GlobPattern::new("Library/SMS/**/*.heic")?;
+
+        assert!(pattern.is_match("Library/SMS/Attachments/0a/00/IMG_0001.heic"));
+        assert!(pattern.is_match("Library/SMS/IMG_0001.heic"));
+        assert!(!pattern.is_match("Media/DCIM/IMG_0001.heic"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_glob_pattern_ignore_case() -> Result<()> {
+        let pattern = GlobPattern::new("Media/DCIM/*.HEIC")?;
+
+        assert!(!pattern.is_match("Media/DCIM/IMG_0001.heic"));
+        assert!(pattern.is_match_ignore_case("media/dcim/IMG_0001.heic"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_glob_pattern_literal_prefix() -> Result<()> {
+        assert_eq!(
+            GlobPattern::new("Library/SMS/**/*.heic")?.literal_prefix(),
+            "Library/SMS/"
+        );
+        assert_eq!(
+            GlobPattern::new("Library/{SMS,Notes}")?.literal_prefix(),
+            "Library/"
+        );
+        assert_eq!(GlobPattern::new("*.db")?.literal_prefix(), "");
+        assert_eq!(GlobPattern::new("sms.db")?.literal_prefix(), "sms.db");
+        Ok(())
+    }
+
+    #[test]
+    fn test_glob_pattern_invalid() {
+        let result = GlobPattern::new("Library/[abc");
+
+        assert!(result.is_err());
+        if let Err(error) = result {
+            assert!(error.to_string().contains("Invalid glob pattern"));
+        }
+    }
+
+    #[test]
+    fn test_glob_pattern_equality() -> Result<()> {
+        assert_eq!(GlobPattern::new("*.db")?, GlobPattern::new("*.db")?);
+        assert_ne!(GlobPattern::new("*.db")?, GlobPattern::new("*.sqlite")?);
+        Ok(())
+    }
+}
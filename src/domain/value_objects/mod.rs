@@ -1,11 +1,23 @@
+pub mod apple_timestamp;
+pub mod byte_size;
 pub mod domain;
 pub mod file_flags;
 pub mod file_id;
+pub mod file_metadata;
+pub mod glob_pattern;
 pub mod metadata_id;
+pub mod point_in_time;
+pub mod regex_pattern;
 pub mod relative_path;
 
-pub use domain::Domain;
+pub use apple_timestamp::AppleTimestamp;
+pub use byte_size::ByteSize;
+pub use domain::{Domain, DomainKind};
 pub use file_flags::FileFlags;
 pub use file_id::FileId;
+pub use file_metadata::FileMetadata;
+pub use glob_pattern::GlobPattern;
 pub use metadata_id::MetadataId;
-pub use relative_path::RelativePath;
+pub use point_in_time::PointInTime;
+pub use regex_pattern::RegexPattern;
+pub use relative_path::{RelativePath, UnsafePathError};
@@ -0,0 +1,162 @@
+use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeDelta, Utc};
+use std::fmt;
+
+/// `PointInTime` - Value Object representing a time bound of a search
+///
+/// It is either an absolute time (`2024-01-15`, `2024-01-15 10:30:00` or RFC 3339) in UTC
+/// unless an offset is given, or a duration such as `48h` or `7d` that is counted back from
+/// a reference time, usually the date of the backup.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PointInTime {
+    Absolute(DateTime<Utc>),
+    Ago(TimeDelta),
+}
+
+impl PointInTime {
+    /// Parses a `PointInTime`
+    ///
+    /// Durations use the units `s`, `m`, `h`, `d` and `w`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value is neither a supported date format nor a duration
+    #[inline]
+    pub fn new(value: &str) -> anyhow::Result<Self> {
+        let value = value.trim();
+
+        if let Some(delta) = Self::parse_duration(value) {
+            return Ok(Self::Ago(delta));
+        }
+
+        if let Ok(datetime) = DateTime::parse_from_rfc3339(value) {
+            return Ok(Self::Absolute(datetime.to_utc()));
+        }
+
+        for format in ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M"] {
+            if let Ok(datetime) = NaiveDateTime::parse_from_str(value, format) {
+                return Ok(Self::Absolute(datetime.and_utc()));
+            }
+        }
+
+        if let Some(datetime) = NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .ok()
+            .and_then(|date| date.and_hms_opt(0, 0, 0))
+        {
+            return Ok(Self::Absolute(datetime.and_utc()));
+        }
+
+        Err(anyhow::anyhow!(
+            "Invalid time: '{value}' (expected e.g. 2024-01-15, 2024-01-15T10:30:00Z or 48h)"
+        ))
+    }
+
+    fn parse_duration(value: &str) -> Option<TimeDelta> {
+        let unit_start = value.find(|c: char| !c.is_ascii_digit())?;
+        let (amount, unit) = value.split_at(unit_start);
+        let amount: i64 = amount.parse().ok()?;
+
+        match unit {
+            "s" => TimeDelta::try_seconds(amount),
+            "m" => TimeDelta::try_minutes(amount),
+            "h" => TimeDelta::try_hours(amount),
+            "d" => TimeDelta::try_days(amount),
+            "w" => TimeDelta::try_weeks(amount),
+            _ => None,
+        }
+    }
+
+    /// Returns the absolute time, counting durations back from the reference time
+    #[must_use]
+    #[inline]
+    pub fn resolve(&self, reference: DateTime<Utc>) -> DateTime<Utc> {
+        match self {
+            Self::Absolute(datetime) => *datetime,
+            Self::Ago(delta) => reference
+                .checked_sub_signed(*delta)
+                .unwrap_or(DateTime::<Utc>::MIN_UTC),
+        }
+    }
+}
+
+impl fmt::Display for PointInTime {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Absolute(datetime) => write!(f, "{}", datetime.to_rfc3339()),
+            Self::Ago(delta) => write!(f, "{}s ago", delta.num_seconds()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_point_in_time_absolute() -> Result<()> {
+        let expected: DateTime<Utc> = "2024-01-15T10:30:00Z".parse()?;
+
+        assert_eq!(
+            PointInTime::new("2024-01-15T10:30:00Z")?,
+            PointInTime::Absolute(expected)
+        );
+        assert_eq!(
+            PointInTime::new("2024-01-15T19:30:00+09:00")?,
+            PointInTime::Absolute(expected)
+        );
+        assert_eq!(
+            PointInTime::new("2024-01-15 10:30:00")?,
+            PointInTime::Absolute(expected)
+        );
+        assert_eq!(
+            PointInTime::new("2024-01-15")?,
+            PointInTime::Absolute("2024-01-15T00:00:00Z".parse()?)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_point_in_time_relative() -> Result<()> {
+        let reference: DateTime<Utc> = "2024-01-15T10:30:00Z".parse()?;
+
+        assert_eq!(
+            PointInTime::new("48h")?.resolve(reference),
+            "2024-01-13T10:30:00Z".parse::<DateTime<Utc>>()?
+        );
+        assert_eq!(
+            PointInTime::new("1w")?.resolve(reference),
+            "2024-01-08T10:30:00Z".parse::<DateTime<Utc>>()?
+        );
+        assert_eq!(
+            PointInTime::new("90m")?.resolve(reference),
+            "2024-01-15T09:00:00Z".parse::<DateTime<Utc>>()?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_point_in_time_absolute_ignores_reference() -> Result<()> {
+        let time = PointInTime::new("2020-06-01")?;
+
+        assert_eq!(
+            time.resolve(Utc::now()),
+            "2020-06-01T00:00:00Z".parse::<DateTime<Utc>>()?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_point_in_time_invalid() {
+        for value in ["", "yesterday", "48", "48y", "2024-13-01", "h"] {
+            let result = PointInTime::new(value);
+            assert!(result.is_err(), "{value} should be invalid");
+            if let Err(error) = result {
+                assert!(error.to_string().contains("Invalid time"));
+            }
+        }
+    }
+}
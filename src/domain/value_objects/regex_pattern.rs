@@ -0,0 +1,108 @@
+use regex::{Regex, RegexBuilder};
+use std::fmt;
+
+/// `RegexPattern` - Value Object representing a validated regular expression
+///
+/// Matching is unanchored, so use `^` and `$` to match a whole value.
+#[derive(Debug, Clone)]
+pub struct RegexPattern {
+    /// Compiled expression
+    regex: Regex,
+    /// Compiled expression ignoring case
+    case_insensitive_regex: Regex,
+}
+
+impl RegexPattern {
+    /// Creates a new `RegexPattern`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pattern is not a valid regular expression
+    #[inline]
+    pub fn new(pattern: &str) -> anyhow::Result<Self> {
+        let compile = |case_insensitive| {
+            RegexBuilder::new(pattern)
+                .case_insensitive(case_insensitive)
+                .build()
+                .map_err(|e| anyhow::anyhow!("Invalid regex pattern: {e}"))
+        };
+
+        Ok(Self {
+            regex: compile(false)?,
+            case_insensitive_regex: compile(true)?,
+        })
+    }
+
+    /// Returns the pattern as written by the user
+    #[must_use]
+    #[inline]
+    pub fn value(&self) -> &str {
+        self.regex.as_str()
+    }
+
+    /// Checks if the value matches the pattern
+    #[must_use]
+    #[inline]
+    pub fn is_match(&self, value: &str) -> bool {
+        self.regex.is_match(value)
+    }
+
+    /// Checks if the value matches the pattern, ignoring case
+    #[must_use]
+    #[inline]
+    pub fn is_match_ignore_case(&self, value: &str) -> bool {
+        self.case_insensitive_regex.is_match(value)
+    }
+}
+
+impl PartialEq for RegexPattern {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.value() == other.value()
+    }
+}
+
+impl Eq for RegexPattern {}
+
+impl fmt::Display for RegexPattern {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.regex)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use super::*;
+
+    #[test]
+    fn test_regex_pattern_matching() -> Result<()> {
+        let pattern = RegexPattern::new(r"Media/.*\.jpg$")?;
+
+        assert_eq!(pattern.value(), r"Media/.*\.jpg$");
+        assert!(pattern.is_match("Media/DCIM/100APPLE/IMG_0001.jpg"));
+        assert!(!pattern.is_match("Media/DCIM/100APPLE/IMG_0001.jpg.xmp"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_regex_pattern_ignore_case() -> Result<()> {
+        let pattern = RegexPattern::new(r"IMG_\d+\.JPG$")?;
+
+        assert!(!pattern.is_match("Media/DCIM/100APPLE/IMG_0001.jpg"));
+        assert!(pattern.is_match_ignore_case("Media/DCIM/100APPLE/IMG_0001.jpg"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_regex_pattern_invalid() {
+        let result = RegexPattern::new("Media/(");
+
+        assert!(result.is_err());
+        if let Err(error) = result {
+            assert!(error.to_string().contains("Invalid regex pattern"));
+        }
+    }
+}
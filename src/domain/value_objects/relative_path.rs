@@ -1,24 +1,83 @@
+use std::error::Error;
 use std::fmt;
+use std::path::PathBuf;
+
+/// Device names reserved by Windows, with or without an extension
+const WINDOWS_RESERVED_NAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Characters Windows does not allow in file names
+const WINDOWS_INVALID_CHARACTERS: [char; 8] = ['<', '>', ':', '"', '\\', '|', '?', '*'];
+
+/// Error raised when a `RelativePath` cannot safely be used as a file system path
+///
+/// Backups may come from untrusted devices, so a crafted or corrupted manifest must not
+/// be able to write outside the output directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum UnsafePathError {
+    /// The path is absolute, i.e. starts with `/` or `\`
+    AbsolutePath,
+    /// The path contains a `..` component
+    ParentDirectory,
+    /// The path contains a NUL byte
+    NulByte,
+    /// The path contains a name reserved by the platform, e.g. `CON` on Windows
+    ReservedName(String),
+    /// The path contains a character the platform does not allow in file names
+    InvalidCharacter(char),
+    /// The target of a symbolic link leads outside the output directory
+    EscapingLinkTarget(String),
+}
+
+impl fmt::Display for UnsafePathError {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AbsolutePath => write!(f, "Unsafe path: is an absolute path"),
+            Self::ParentDirectory => write!(f, "Unsafe path: contains a '..' component"),
+            Self::NulByte => write!(f, "Unsafe path: contains a NUL byte"),
+            Self::ReservedName(name) => {
+                write!(f, "Unsafe path: '{name}' is a reserved file name")
+            }
+            Self::InvalidCharacter(character) => write!(
+                f,
+                "Unsafe path: '{}' is not allowed in file names",
+                character.escape_default()
+            ),
+            Self::EscapingLinkTarget(target) => write!(
+                f,
+                "Unsafe path: symbolic link target '{target}' escapes the output directory"
+            ),
+        }
+    }
+}
+
+impl Error for UnsafePathError {}
 
 /// `RelativePath` - Value Object representing a relative path within a backup
+///
+/// The path is kept as recorded in the backup, so that it can be searched and displayed.
+/// Use [`RelativePath::to_path`] to turn it into a file system path.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct RelativePath(String);
 
 impl RelativePath {
     /// Creates a new `RelativePath`
     ///
-    /// # Errors
-    ///
-    /// Returns an error if:
-    /// - The path is an absolute path (starts with '/' or '\')
+    /// Any path recorded in a backup is accepted, so that a single crafted manifest record
+    /// does not prevent the others from being read. Unsafe paths, including absolute ones,
+    /// are rejected by [`RelativePath::to_path`].
     ///
     /// Note: Empty paths are allowed as they represent files at the root level
+    ///
+    /// # Errors
+    ///
+    /// Currently never returns an error
     #[inline]
-    pub fn new(path: String) -> anyhow::Result<Self> {
-        if path.starts_with('/') || path.starts_with('\\') {
-            return Err(anyhow::anyhow!("RelativePath cannot be an absolute path"));
-        }
-
+    pub const fn new(path: String) -> anyhow::Result<Self> {
         Ok(Self(path))
     }
 
@@ -27,6 +86,73 @@ impl RelativePath {
     pub fn value(&self) -> &str {
         &self.0
     }
+
+    /// Converts the path to a file system path that stays below the directory it is joined to
+    ///
+    /// Empty and `.` components are dropped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The path is an absolute path (starts with '/' or '\')
+    /// - The path contains a `..` component
+    /// - The path contains a NUL byte
+    /// - The path contains a name or character reserved by the platform
+    #[inline]
+    pub fn to_path(&self) -> Result<PathBuf, UnsafePathError> {
+        if self.0.starts_with('/') || self.0.starts_with('\\') {
+            return Err(UnsafePathError::AbsolutePath);
+        }
+
+        let mut path = PathBuf::new();
+        for component in self.0.split('/') {
+            match component {
+                "" | "." => {}
+                ".." => return Err(UnsafePathError::ParentDirectory),
+                _ => {
+                    validate_component(component)?;
+                    path.push(component);
+                }
+            }
+        }
+
+        Ok(path)
+    }
+}
+
+/// Checks a single path component against the rules of the current platform
+pub(crate) fn validate_component(component: &str) -> Result<(), UnsafePathError> {
+    if component.contains('\0') {
+        return Err(UnsafePathError::NulByte);
+    }
+
+    if cfg!(windows) {
+        if let Some(character) = component
+            .chars()
+            .find(|c| c.is_ascii_control() || WINDOWS_INVALID_CHARACTERS.contains(c))
+        {
+            return Err(UnsafePathError::InvalidCharacter(character));
+        }
+
+        if is_windows_reserved_name(component) {
+            return Err(UnsafePathError::ReservedName(component.to_owned()));
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns whether a path component is a Windows device name such as `CON` or `com1.txt`
+fn is_windows_reserved_name(component: &str) -> bool {
+    let stem = component
+        .split('.')
+        .next()
+        .unwrap_or_default()
+        .trim_end_matches(' ');
+
+    WINDOWS_RESERVED_NAMES
+        .iter()
+        .any(|name| stem.eq_ignore_ascii_case(name))
 }
 
 impl fmt::Display for RelativePath {
@@ -46,6 +172,7 @@ impl From<RelativePath> for String {
 #[cfg(test)]
 mod tests {
     use anyhow::Result;
+    use std::path::Path;
 
     use super::*;
 
@@ -57,15 +184,21 @@ mod tests {
     }
 
     #[test]
-    fn test_relative_path_absolute_path_rejected() {
-        let absolute_path = "/absolute/path".to_owned();
-        assert!(RelativePath::new(absolute_path).is_err());
+    fn test_relative_path_absolute_path_rejected() -> Result<()> {
+        let path = RelativePath::new("/absolute/path".to_owned())?;
+        assert_eq!(path.value(), "/absolute/path");
+        assert_eq!(path.to_path(), Err(UnsafePathError::AbsolutePath));
+        Ok(())
     }
 
     #[test]
-    fn test_relative_path_windows_absolute_rejected() {
-        let windows_absolute = "\\absolute\\path".to_owned();
-        assert!(RelativePath::new(windows_absolute).is_err());
+    fn test_relative_path_windows_absolute_rejected() -> Result<()> {
+        let windows_absolute = RelativePath::new("\\absolute\\path".to_owned())?;
+        assert_eq!(
+            windows_absolute.to_path(),
+            Err(UnsafePathError::AbsolutePath)
+        );
+        Ok(())
     }
 
     #[test]
@@ -75,4 +208,87 @@ mod tests {
         assert_eq!(path.value(), "");
         Ok(())
     }
+
+    #[test]
+    fn test_to_path_normalizes() -> Result<()> {
+        let path = RelativePath::new("./Documents//notes/./file.txt/".to_owned())?;
+        assert_eq!(path.to_path()?, Path::new("Documents/notes/file.txt"));
+
+        let dots = RelativePath::new("Documents/.../..file".to_owned())?;
+        assert_eq!(dots.to_path()?, Path::new("Documents/.../..file"));
+
+        assert_eq!(RelativePath::new(String::new())?.to_path()?, PathBuf::new());
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_path_rejects_parent_directory() -> Result<()> {
+        for path in [
+            "..",
+            "../evil.txt",
+            "Documents/../../evil.txt",
+            "Documents/..",
+        ] {
+            assert_eq!(
+                RelativePath::new(path.to_owned())?.to_path(),
+                Err(UnsafePathError::ParentDirectory),
+                "{path} should be rejected"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_path_rejects_nul_byte() -> Result<()> {
+        let path = RelativePath::new("Documents/evil\0.txt".to_owned())?;
+        assert_eq!(path.to_path(), Err(UnsafePathError::NulByte));
+        Ok(())
+    }
+
+    #[test]
+    fn test_windows_reserved_names() {
+        for name in ["CON", "con", "nul.txt", "Com1.tar.gz", "LPT9 ", "aux"] {
+            assert!(is_windows_reserved_name(name), "{name} should be reserved");
+        }
+        for name in ["CONSOLE", "COM10", "null", "file.con", "LPT"] {
+            assert!(!is_windows_reserved_name(name), "{name} should be allowed");
+        }
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_to_path_rejects_windows_names() -> Result<()> {
+        let reserved = RelativePath::new("Documents/CON.txt".to_owned())?;
+        assert_eq!(
+            reserved.to_path(),
+            Err(UnsafePathError::ReservedName("CON.txt".to_owned()))
+        );
+
+        let backslash = RelativePath::new("Documents\\..\\..\\evil.txt".to_owned())?;
+        assert_eq!(
+            backslash.to_path(),
+            Err(UnsafePathError::InvalidCharacter('\\'))
+        );
+        Ok(())
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn test_to_path_allows_windows_names_elsewhere() -> Result<()> {
+        let path = RelativePath::new("Documents/CON.txt".to_owned())?;
+        assert_eq!(path.to_path()?, Path::new("Documents/CON.txt"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_unsafe_path_error_display() {
+        assert_eq!(
+            UnsafePathError::ParentDirectory.to_string(),
+            "Unsafe path: contains a '..' component"
+        );
+        assert_eq!(
+            UnsafePathError::InvalidCharacter('\u{1}').to_string(),
+            "Unsafe path: '\\u{1}' is not allowed in file names"
+        );
+    }
 }
@@ -0,0 +1,182 @@
+use anyhow::{Context as _, Result};
+use std::io::Read as _;
+
+use crate::domain::entities::File;
+use crate::domain::queries::FileQuery;
+use crate::domain::repositories::{BlobRepository, FileRepository};
+use crate::infrastructure::database::DatabaseConnection;
+
+/// Opens an `SQLite` database stored in a backup, such as `HomeDomain/Library/SMS/sms.db`
+///
+/// The database is located through the file repository and read through the blob
+/// repository, so that it is decrypted if needed. The connection works on a private
+/// read-only copy that includes the `-wal` file next to the database, if any.
+///
+/// # Errors
+///
+/// Returns an error if the database is not in the backup or cannot be read or opened
+#[allow(
+    clippy::future_not_send,
+    reason = "Repository trait doesn't guarantee Send futures"
+)]
+#[inline]
+pub async fn open_database<F: FileRepository, B: BlobRepository>(
+    files: &F,
+    blobs: &B,
+    domain: &str,
+    relative_path: &str,
+) -> Result<DatabaseConnection> {
+    open_database_if_exists(files, blobs, domain, relative_path)
+        .await?
+        .with_context(|| format!("{domain}/{relative_path} was not found in the backup"))
+}
+
+/// Opens an `SQLite` database stored in a backup, or returns `None` if it is not there
+///
+/// Use this for optional databases, such as `AddressBookImages.sqlitedb` next to the
+/// address book. See [`open_database`] for details.
+///
+/// # Errors
+///
+/// Returns an error if the database is in the backup but cannot be read or opened
+#[allow(
+    clippy::future_not_send,
+    reason = "Repository trait doesn't guarantee Send futures"
+)]
+#[inline]
+pub async fn open_database_if_exists<F: FileRepository, B: BlobRepository>(
+    files: &F,
+    blobs: &B,
+    domain: &str,
+    relative_path: &str,
+) -> Result<Option<DatabaseConnection>> {
+    let wal_path = format!("{relative_path}-wal");
+    let query = FileQuery::and(vec![
+        FileQuery::domain_exact(domain),
+        FileQuery::or(vec![
+            FileQuery::path_exact(relative_path),
+            FileQuery::path_exact(&wal_path),
+        ]),
+    ]);
+    let found = files.search(query).await?;
+    let find = |path: &str| {
+        found
+            .iter()
+            .find(|file| file.relative_path().value() == path)
+    };
+
+    let Some(database) = find(relative_path) else {
+        return Ok(None);
+    };
+    let bytes = read_blob(blobs, database)?
+        .with_context(|| format!("{domain}/{relative_path} has no content in the backup"))?;
+    let wal = find(&wal_path)
+        .map(|wal| read_blob(blobs, wal))
+        .transpose()?
+        .flatten();
+
+    DatabaseConnection::from_bytes_with_wal(&bytes, wal.as_deref())
+        .await
+        .map(Some)
+        .with_context(|| format!("Failed to open {domain}/{relative_path}"))
+}
+
+/// Reads the whole content of a file of the backup, or `None` if it has no blob
+fn read_blob<B: BlobRepository>(blobs: &B, file: &File) -> Result<Option<Vec<u8>>> {
+    let Some(mut reader) = blobs.open(file)? else {
+        return Ok(None);
+    };
+
+    let mut bytes = Vec::new();
+    reader
+        .read_to_end(&mut bytes)
+        .with_context(|| format!("Failed to read {}/{}", file.domain(), file.relative_path()))?;
+    Ok(Some(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::value_objects::{Domain, FileFlags, FileId, RelativePath};
+    use crate::infrastructure::repositories::BlobRepositoryImpl;
+    use assert_fs::TempDir;
+    use assert_fs::prelude::*;
+    use sea_orm::{ConnectionTrait as _, Database, Statement};
+    use std::fs;
+
+    struct MockFileRepository {
+        files: Vec<File>,
+    }
+
+    impl FileRepository for MockFileRepository {
+        async fn search(&self, query: FileQuery) -> Result<Vec<File>> {
+            Ok(self
+                .files
+                .iter()
+                .filter(|file| query.matches(file))
+                .cloned()
+                .collect())
+        }
+    }
+
+    /// Creates an `SQLite` database with one row and returns its content
+    async fn create_database(temp_dir: &TempDir) -> Result<Vec<u8>> {
+        let path = temp_dir.child("source.db");
+        let db = Database::connect(format!("sqlite://{}?mode=rwc", path.display())).await?;
+        db.execute_unprepared("CREATE TABLE t (value TEXT); INSERT INTO t VALUES ('hello');")
+            .await?;
+        db.close().await?;
+        Ok(fs::read(path.path())?)
+    }
+
+    #[tokio::test]
+    async fn test_open_database() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let file = File::new(
+            FileId::new("3d0d7e5fb2ce288813306e4d4636395e047a3d28")?,
+            Domain::new("HomeDomain".to_owned())?,
+            RelativePath::new("Library/SMS/sms.db".to_owned())?,
+            FileFlags::REGULAR_FILE,
+            vec![],
+        );
+        temp_dir
+            .child("3d")
+            .child(file.id().value())
+            .write_binary(&create_database(&temp_dir).await?)?;
+        let files = MockFileRepository { files: vec![file] };
+        let blobs = BlobRepositoryImpl::new(temp_dir.path());
+
+        let db = open_database(&files, &blobs, "HomeDomain", "Library/SMS/sms.db").await?;
+        let row = db
+            .get_connection()
+            .query_one(Statement::from_string(
+                db.get_connection().get_database_backend(),
+                "SELECT value FROM t",
+            ))
+            .await?
+            .context("no row")?;
+
+        assert_eq!(row.try_get::<String>("", "value")?, "hello");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_open_database_missing() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let files = MockFileRepository { files: vec![] };
+        let blobs = BlobRepositoryImpl::new(temp_dir.path());
+
+        let result = open_database(&files, &blobs, "HomeDomain", "Library/SMS/sms.db").await;
+
+        assert!(result.is_err_and(|e| {
+            e.to_string()
+                .contains("HomeDomain/Library/SMS/sms.db was not found")
+        }));
+        assert!(
+            open_database_if_exists(&files, &blobs, "HomeDomain", "Library/SMS/sms.db")
+                .await?
+                .is_none()
+        );
+        Ok(())
+    }
+}
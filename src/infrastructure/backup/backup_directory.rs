@@ -0,0 +1,616 @@
+use anyhow::{Context as _, Result};
+use std::path::{Path, PathBuf};
+use tokio::fs;
+
+use crate::domain::entities::Metadata;
+use crate::infrastructure::crypto::{Keybag, decrypt_cbc};
+use crate::infrastructure::database::DatabaseConnection;
+use crate::infrastructure::plist::entities::ManifestInfo;
+use crate::infrastructure::repositories::{
+    ApplicationRepositoryImpl, BlobRepositoryImpl, CallRepositoryImpl, ContactRepositoryImpl,
+    FileRepositoryImpl, MbdbFileRepositoryImpl, MessageRepositoryImpl, MetadataRepositoryImpl,
+    PhotoRepositoryImpl, SafariRepositoryImpl, VoicemailRepositoryImpl,
+};
+
+use super::{BackupFileRepository, open_database, open_database_if_exists};
+
+/// Format of the file manifest of a backup
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum BackupFormat {
+    /// `SQLite` Manifest.db, written by iOS 10 and later
+    ManifestDb,
+    /// Binary Manifest.mbdb, written by iOS 9 and earlier
+    ManifestMbdb,
+}
+
+/// A single backup directory, as written by Finder or iTunes
+///
+/// This is the entry point for opening the repositories of a backup. It reads
+/// Manifest.plist to find out whether the backup is encrypted and, once unlocked
+/// with the backup password, decrypts Manifest.db and the file blobs transparently.
+pub struct BackupDirectory {
+    /// Backup directory path
+    path: PathBuf,
+    /// Contents of Manifest.plist
+    manifest: ManifestInfo,
+    /// Unlocked keybag of an encrypted backup
+    keybag: Option<Keybag>,
+}
+
+impl BackupDirectory {
+    /// Opens a backup directory
+    ///
+    /// # Errors
+    /// Returns an error if Manifest.plist exists but cannot be read or parsed
+    #[inline]
+    pub async fn open<P: AsRef<Path> + Send>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let manifest_plist_path = path.join("Manifest.plist");
+
+        let manifest = if manifest_plist_path.exists() {
+            let content = fs::read(&manifest_plist_path).await.with_context(|| {
+                format!(
+                    "Failed to read Manifest.plist file: {}",
+                    manifest_plist_path.display()
+                )
+            })?;
+            plist::from_bytes(&content).with_context(|| {
+                format!(
+                    "Failed to parse Manifest.plist file: {}",
+                    manifest_plist_path.display()
+                )
+            })?
+        } else {
+            ManifestInfo::default()
+        };
+
+        Ok(Self {
+            path,
+            manifest,
+            keybag: None,
+        })
+    }
+
+    /// Returns the backup directory path
+    #[must_use]
+    #[inline]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Checks if the backup is encrypted
+    #[must_use]
+    #[inline]
+    pub const fn is_encrypted(&self) -> bool {
+        self.manifest.is_encrypted
+    }
+
+    /// Unlocks an encrypted backup with its password
+    ///
+    /// # Errors
+    /// Returns an error if the backup has no keybag or the password is wrong
+    #[inline]
+    pub fn unlock(&mut self, password: &str) -> Result<()> {
+        let keybag_bytes = self
+            .manifest
+            .backup_key_bag
+            .as_ref()
+            .context("Manifest.plist has no BackupKeyBag")?;
+
+        let mut keybag = Keybag::parse(keybag_bytes.as_ref())?;
+        keybag.unlock(password)?;
+        self.keybag = Some(keybag);
+
+        Ok(())
+    }
+
+    /// Reads the backup metadata from Info.plist, Manifest.plist and Status.plist
+    ///
+    /// # Errors
+    /// Returns an error if Info.plist is missing or cannot be read or parsed
+    #[inline]
+    pub async fn metadata(&self) -> Result<Metadata> {
+        MetadataRepositoryImpl::read_backup_directory(&self.path).await
+    }
+
+    /// Detects the format of the backup from the manifest file it contains
+    #[must_use]
+    #[inline]
+    pub fn format(&self) -> Option<BackupFormat> {
+        if self.path.join("Manifest.db").exists() {
+            Some(BackupFormat::ManifestDb)
+        } else if self.path.join("Manifest.mbdb").exists() {
+            Some(BackupFormat::ManifestMbdb)
+        } else {
+            None
+        }
+    }
+
+    /// Opens the file repository matching the format of the backup
+    ///
+    /// # Errors
+    /// Returns an error if no manifest is found, it cannot be decrypted or parsed,
+    /// or the connection fails
+    #[inline]
+    pub async fn file_repository(&self) -> Result<BackupFileRepository> {
+        match self.format() {
+            Some(BackupFormat::ManifestDb) => Ok(BackupFileRepository::Database(
+                self.database_file_repository().await?,
+            )),
+            Some(BackupFormat::ManifestMbdb) => Ok(BackupFileRepository::Mbdb(
+                MbdbFileRepositoryImpl::open(self.path.join("Manifest.mbdb")).await?,
+            )),
+            None => Err(anyhow::anyhow!(
+                "Neither Manifest.db nor Manifest.mbdb found in backup directory: {}",
+                self.path.display()
+            )),
+        }
+    }
+
+    /// Opens the file repository backed by Manifest.db, decrypting it if needed
+    async fn database_file_repository(&self) -> Result<FileRepositoryImpl> {
+        let manifest_path = self.path.join("Manifest.db");
+
+        let db = match (&self.keybag, &self.manifest.manifest_key) {
+            (Some(keybag), Some(manifest_key)) => {
+                let key = keybag
+                    .unwrap_class_prefixed_key(manifest_key.as_ref())
+                    .context("Failed to unwrap ManifestKey")?;
+                let encrypted = fs::read(&manifest_path).await.with_context(|| {
+                    format!("Failed to read Manifest.db: {}", manifest_path.display())
+                })?;
+                let decrypted =
+                    decrypt_cbc(&key, encrypted).context("Failed to decrypt Manifest.db")?;
+
+                DatabaseConnection::from_bytes(&decrypted).await?
+            }
+            (None, _) if self.is_encrypted() => {
+                return Err(anyhow::anyhow!(
+                    "Backup is encrypted; a password is required: {}",
+                    self.path.display()
+                ));
+            }
+            _ => {
+                let db_url = format!("sqlite://{}", manifest_path.display());
+                DatabaseConnection::new(&db_url).await?
+            }
+        };
+
+        Ok(FileRepositoryImpl::new(db))
+    }
+
+    /// Creates the repository for the applications recorded in the backup
+    #[must_use]
+    #[inline]
+    pub fn application_repository(&self) -> ApplicationRepositoryImpl {
+        ApplicationRepositoryImpl::new(&self.path)
+    }
+
+    /// Opens the repository for the conversations of the Messages app
+    ///
+    /// # Errors
+    /// Returns an error if `sms.db` is not in the backup or cannot be read or opened
+    #[inline]
+    pub async fn message_repository(
+        &self,
+        files: &BackupFileRepository,
+    ) -> Result<MessageRepositoryImpl> {
+        let db = open_database(
+            files,
+            &self.blob_repository(),
+            MessageRepositoryImpl::DOMAIN,
+            MessageRepositoryImpl::RELATIVE_PATH,
+        )
+        .await?;
+        Ok(MessageRepositoryImpl::new(db))
+    }
+
+    /// Opens the repository for the address book of the Contacts app
+    ///
+    /// Photos are read from `AddressBookImages.sqlitedb` if the backup has it.
+    ///
+    /// # Errors
+    /// Returns an error if `AddressBook.sqlitedb` is not in the backup, or either
+    /// database cannot be read or opened
+    #[inline]
+    pub async fn contact_repository(
+        &self,
+        files: &BackupFileRepository,
+    ) -> Result<ContactRepositoryImpl> {
+        let blobs = self.blob_repository();
+        let db = open_database(
+            files,
+            &blobs,
+            ContactRepositoryImpl::DOMAIN,
+            ContactRepositoryImpl::RELATIVE_PATH,
+        )
+        .await?;
+        let images = open_database_if_exists(
+            files,
+            &blobs,
+            ContactRepositoryImpl::DOMAIN,
+            ContactRepositoryImpl::IMAGES_RELATIVE_PATH,
+        )
+        .await?;
+        Ok(ContactRepositoryImpl::new(db).with_images(images))
+    }
+
+    /// Opens the repository for the call history of the Phone app
+    ///
+    /// # Errors
+    /// Returns an error if `CallHistory.storedata` is not in the backup or cannot be read
+    /// or opened
+    #[inline]
+    pub async fn call_repository(
+        &self,
+        files: &BackupFileRepository,
+    ) -> Result<CallRepositoryImpl> {
+        let db = open_database(
+            files,
+            &self.blob_repository(),
+            CallRepositoryImpl::DOMAIN,
+            CallRepositoryImpl::RELATIVE_PATH,
+        )
+        .await?;
+        Ok(CallRepositoryImpl::new(db))
+    }
+
+    /// Opens the repository for the voicemail messages of the Phone app
+    ///
+    /// # Errors
+    /// Returns an error if `voicemail.db` is not in the backup or cannot be read or opened
+    #[inline]
+    pub async fn voicemail_repository(
+        &self,
+        files: &BackupFileRepository,
+    ) -> Result<VoicemailRepositoryImpl> {
+        let db = open_database(
+            files,
+            &self.blob_repository(),
+            VoicemailRepositoryImpl::DOMAIN,
+            VoicemailRepositoryImpl::RELATIVE_PATH,
+        )
+        .await?;
+        Ok(VoicemailRepositoryImpl::new(db))
+    }
+
+    /// Opens the repository for the photo library of the Photos app
+    ///
+    /// # Errors
+    /// Returns an error if `Photos.sqlite` is not in the backup or cannot be read or opened
+    #[inline]
+    pub async fn photo_repository(
+        &self,
+        files: &BackupFileRepository,
+    ) -> Result<PhotoRepositoryImpl> {
+        let db = open_database(
+            files,
+            &self.blob_repository(),
+            PhotoRepositoryImpl::DOMAIN,
+            PhotoRepositoryImpl::RELATIVE_PATH,
+        )
+        .await?;
+        Ok(PhotoRepositoryImpl::new(db))
+    }
+
+    /// Opens the repository for the history, bookmarks and tabs of Safari
+    ///
+    /// The bookmarks and tabs are left empty if their databases are not in the backup.
+    ///
+    /// # Errors
+    /// Returns an error if `History.db` is not in the backup, or if a database cannot be
+    /// read or opened
+    #[inline]
+    pub async fn safari_repository(
+        &self,
+        files: &BackupFileRepository,
+    ) -> Result<SafariRepositoryImpl> {
+        let blobs = self.blob_repository();
+        let history = open_database(
+            files,
+            &blobs,
+            SafariRepositoryImpl::DOMAIN,
+            SafariRepositoryImpl::HISTORY_RELATIVE_PATH,
+        )
+        .await?;
+        let bookmarks = open_database_if_exists(
+            files,
+            &blobs,
+            SafariRepositoryImpl::DOMAIN,
+            SafariRepositoryImpl::BOOKMARKS_RELATIVE_PATH,
+        )
+        .await?;
+        let browser_state = open_database_if_exists(
+            files,
+            &blobs,
+            SafariRepositoryImpl::APP_DOMAIN,
+            SafariRepositoryImpl::BROWSER_STATE_RELATIVE_PATH,
+        )
+        .await?;
+        let mut cloud_tabs = None;
+        for domain in [
+            SafariRepositoryImpl::APP_DOMAIN,
+            SafariRepositoryImpl::DOMAIN,
+        ] {
+            cloud_tabs = open_database_if_exists(
+                files,
+                &blobs,
+                domain,
+                SafariRepositoryImpl::CLOUD_TABS_RELATIVE_PATH,
+            )
+            .await?;
+            if cloud_tabs.is_some() {
+                break;
+            }
+        }
+
+        Ok(SafariRepositoryImpl::new(history)
+            .with_bookmarks(bookmarks)
+            .with_browser_state(browser_state)
+            .with_cloud_tabs(cloud_tabs))
+    }
+
+    /// Creates the blob repository for the files of the backup
+    #[must_use]
+    #[inline]
+    pub fn blob_repository(&self) -> BlobRepositoryImpl {
+        let repository = BlobRepositoryImpl::new(&self.path);
+        match &self.keybag {
+            Some(keybag) => repository.with_keybag(keybag.clone()),
+            None => repository,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::queries::FileQuery;
+    use crate::domain::repositories::FileRepository as _;
+    use crate::infrastructure::crypto::cipher::encrypt_cbc_for_test;
+    use crate::infrastructure::database::entities::files::{ActiveModel, Entity};
+    use crate::infrastructure::mbdb::{ManifestMbdb, MbdbRecord};
+    use assert_fs::TempDir;
+    use assert_fs::prelude::*;
+    use plist::Data;
+    use sea_orm::{ActiveModelTrait as _, ConnectionTrait as _, Database, Set};
+
+    const CLASS_KEY: [u8; 32] = [3; 32];
+    const MANIFEST_KEY: [u8; 32] = [5; 32];
+
+    // Creates a Manifest.db with a single row and returns its bytes
+    async fn create_manifest_db(temp_dir: &TempDir) -> Result<Vec<u8>> {
+        let path = temp_dir.child("plain-Manifest.db");
+        let db = Database::connect(format!("sqlite://{}?mode=rwc", path.path().display())).await?;
+
+        let schema = sea_orm::Schema::new(sea_orm::DatabaseBackend::Sqlite);
+        let stmt = schema.create_table_from_entity(Entity);
+        db.execute(db.get_database_backend().build(&stmt)).await?;
+
+        ActiveModel {
+            file_id: Set("356a192b7913b04c54574d18c28d46e6395428ab".to_owned()),
+            domain: Set("HomeDomain".to_owned()),
+            relative_path: Set("Library/SMS/sms.db".to_owned()),
+            flags: Set(1),
+            file: Set(vec![]),
+        }
+        .insert(&db)
+        .await?;
+        db.close().await?;
+
+        Ok(fs::read(path.path()).await?)
+    }
+
+    fn write_manifest_plist(temp_dir: &TempDir, manifest: &ManifestInfo) -> Result<()> {
+        let mut content = Vec::new();
+        plist::to_writer_xml(&mut content, manifest)?;
+        temp_dir.child("Manifest.plist").write_binary(&content)?;
+        Ok(())
+    }
+
+    async fn create_encrypted_backup(temp_dir: &TempDir) -> Result<()> {
+        let manifest_db = create_manifest_db(temp_dir).await?;
+        temp_dir
+            .child("Manifest.db")
+            .write_binary(&encrypt_cbc_for_test(&MANIFEST_KEY, &manifest_db))?;
+
+        write_manifest_plist(
+            temp_dir,
+            &ManifestInfo {
+                is_encrypted: true,
+                backup_key_bag: Some(Data::new(Keybag::create_for_test(
+                    "secret",
+                    &[(3, CLASS_KEY)],
+                )?)),
+                manifest_key: Some(Data::new(Keybag::wrap_for_test(
+                    3,
+                    CLASS_KEY,
+                    &MANIFEST_KEY,
+                )?)),
+                ..ManifestInfo::default()
+            },
+        )
+    }
+
+    #[tokio::test]
+    async fn test_open_unencrypted_backup() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let manifest_db = create_manifest_db(&temp_dir).await?;
+        temp_dir.child("Manifest.db").write_binary(&manifest_db)?;
+
+        let backup = BackupDirectory::open(temp_dir.path()).await?;
+        assert!(!backup.is_encrypted());
+
+        let files = backup
+            .file_repository()
+            .await?
+            .search(FileQuery::domain_exact("HomeDomain"))
+            .await?;
+        assert_eq!(files.len(), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_open_encrypted_backup_with_password() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        create_encrypted_backup(&temp_dir).await?;
+
+        let mut backup = BackupDirectory::open(temp_dir.path()).await?;
+        assert!(backup.is_encrypted());
+        backup.unlock("secret")?;
+
+        let files = backup
+            .file_repository()
+            .await?
+            .search(FileQuery::domain_exact("HomeDomain"))
+            .await?;
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].relative_path().value(), "Library/SMS/sms.db");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_open_encrypted_backup_without_password() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        create_encrypted_backup(&temp_dir).await?;
+
+        let backup = BackupDirectory::open(temp_dir.path()).await?;
+        let result = backup.file_repository().await;
+
+        assert!(result.is_err());
+        if let Err(error) = result {
+            assert!(error.to_string().contains("a password is required"));
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_unlock_with_wrong_password() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        create_encrypted_backup(&temp_dir).await?;
+
+        let mut backup = BackupDirectory::open(temp_dir.path()).await?;
+        let result = backup.unlock("wrong");
+
+        assert!(result.is_err());
+        if let Err(error) = result {
+            assert!(error.to_string().contains("Invalid backup password"));
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_open_legacy_mbdb_backup() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let manifest = ManifestMbdb {
+            records: vec![MbdbRecord {
+                domain: "HomeDomain".to_owned(),
+                path: "Library/SMS/sms.db".to_owned(),
+                mode: 0o100_644,
+                ..MbdbRecord::default()
+            }],
+        };
+        temp_dir
+            .child("Manifest.mbdb")
+            .write_binary(&manifest.to_bytes()?)?;
+
+        let backup = BackupDirectory::open(temp_dir.path()).await?;
+        assert_eq!(backup.format(), Some(BackupFormat::ManifestMbdb));
+
+        let repository = backup.file_repository().await?;
+        assert!(matches!(repository, BackupFileRepository::Mbdb(_)));
+
+        let files = repository
+            .search(FileQuery::domain_exact("HomeDomain"))
+            .await?;
+        assert_eq!(files.len(), 1);
+        assert_eq!(
+            files[0].id().value(),
+            "3d0d7e5fb2ce288813306e4d4636395e047a3d28"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_manifest_db_takes_precedence() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let manifest_db = create_manifest_db(&temp_dir).await?;
+        temp_dir.child("Manifest.db").write_binary(&manifest_db)?;
+        temp_dir
+            .child("Manifest.mbdb")
+            .write_binary(&ManifestMbdb::default().to_bytes()?)?;
+
+        let backup = BackupDirectory::open(temp_dir.path()).await?;
+
+        assert_eq!(backup.format(), Some(BackupFormat::ManifestDb));
+        assert!(matches!(
+            backup.file_repository().await?,
+            BackupFileRepository::Database(_)
+        ));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_missing_manifest_db() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+
+        let backup = BackupDirectory::open(temp_dir.path()).await?;
+        let result = backup.file_repository().await;
+
+        assert!(result.is_err());
+        if let Err(error) = result {
+            assert!(
+                error
+                    .to_string()
+                    .contains("Neither Manifest.db nor Manifest.mbdb found")
+            );
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_metadata() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        temp_dir.child("Info.plist").write_str(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Device Name</key>
+    <string>iPhone 15 Pro</string>
+    <key>Last Backup Date</key>
+    <string>2024-01-15T10:30:00Z</string>
+    <key>Product Name</key>
+    <string>iPhone16,1</string>
+    <key>Unique Identifier</key>
+    <string>a1b2c3d4e5f67890123456789</string>
+</dict>
+</plist>"#,
+        )?;
+
+        let backup = BackupDirectory::open(temp_dir.path()).await?;
+        let metadata = backup.metadata().await?;
+
+        assert_eq!(metadata.device_name(), "iPhone 15 Pro");
+        assert_eq!(
+            metadata.last_backup_date().to_rfc3339(),
+            "2024-01-15T10:30:00+00:00"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_metadata_missing_info_plist() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+
+        let backup = BackupDirectory::open(temp_dir.path()).await?;
+        let result = backup.metadata().await;
+
+        assert!(result.is_err());
+        if let Err(error) = result {
+            assert!(error.to_string().contains("Failed to read Info.plist"));
+        }
+        Ok(())
+    }
+}
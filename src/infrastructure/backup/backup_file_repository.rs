@@ -0,0 +1,37 @@
+use anyhow::Result;
+
+use crate::domain::entities::File;
+use crate::domain::queries::FileQuery;
+use crate::domain::repositories::FileRepository;
+use crate::infrastructure::repositories::{FileRepositoryImpl, MbdbFileRepositoryImpl};
+
+/// File repository of a backup, chosen by the format of its manifest
+#[non_exhaustive]
+pub enum BackupFileRepository {
+    /// iOS 10 and later, backed by Manifest.db
+    Database(FileRepositoryImpl),
+    /// iOS 9 and earlier, backed by Manifest.mbdb
+    Mbdb(MbdbFileRepositoryImpl),
+}
+
+impl BackupFileRepository {
+    /// Returns descriptions of the manifest records that were skipped because they are invalid
+    #[must_use]
+    #[inline]
+    pub fn skipped_records(&self) -> &[String] {
+        match self {
+            Self::Database(_) => &[],
+            Self::Mbdb(repository) => repository.skipped_records(),
+        }
+    }
+}
+
+impl FileRepository for BackupFileRepository {
+    #[inline]
+    async fn search(&self, query: FileQuery) -> Result<Vec<File>> {
+        match self {
+            Self::Database(repository) => repository.search(query).await,
+            Self::Mbdb(repository) => repository.search(query).await,
+        }
+    }
+}
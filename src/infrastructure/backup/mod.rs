@@ -0,0 +1,7 @@
+pub mod backup_database;
+pub mod backup_directory;
+pub mod backup_file_repository;
+
+pub use backup_database::{open_database, open_database_if_exists};
+pub use backup_directory::{BackupDirectory, BackupFormat};
+pub use backup_file_repository::BackupFileRepository;
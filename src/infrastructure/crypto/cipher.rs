@@ -0,0 +1,228 @@
+use aes::Aes256;
+use aes::cipher::{BlockDecryptMut as _, KeyIvInit as _, generic_array::GenericArray};
+use anyhow::{Context as _, Result};
+use std::io::{self, Read};
+
+use super::keybag::KEY_LENGTH;
+
+/// AES block size in bytes
+const BLOCK_SIZE: usize = 16;
+
+/// Number of bytes decrypted at once by `DecryptingReader`
+const CHUNK_SIZE: usize = 64 * 1024;
+
+type Aes256CbcDec = cbc::Decryptor<Aes256>;
+
+/// Decrypts AES-256-CBC data with a zero IV and strips its PKCS#7 padding
+///
+/// Backups encrypt Manifest.db and every file this way.
+///
+/// # Errors
+/// Returns an error if the data is not a whole number of blocks
+#[inline]
+pub fn decrypt_cbc(key: &[u8; KEY_LENGTH], mut data: Vec<u8>) -> Result<Vec<u8>> {
+    let mut decryptor = Aes256CbcDec::new(key.into(), &[0; BLOCK_SIZE].into());
+    decrypt_blocks(&mut decryptor, &mut data)?;
+
+    let length = unpadded_length(&data);
+    data.truncate(length);
+    Ok(data)
+}
+
+/// Decrypts whole blocks in place
+fn decrypt_blocks(decryptor: &mut Aes256CbcDec, data: &mut [u8]) -> Result<()> {
+    if !data.len().is_multiple_of(BLOCK_SIZE) {
+        return Err(anyhow::anyhow!(
+            "Encrypted data is not a multiple of the block size"
+        ));
+    }
+
+    for block in data.chunks_exact_mut(BLOCK_SIZE) {
+        decryptor.decrypt_block_mut(GenericArray::from_mut_slice(block));
+    }
+
+    Ok(())
+}
+
+/// Returns the length of the data without a valid PKCS#7 padding
+fn unpadded_length(data: &[u8]) -> usize {
+    let Some(&padding) = data.last() else {
+        return 0;
+    };
+
+    let padding = usize::from(padding);
+    if padding == 0 || padding > BLOCK_SIZE || padding > data.len() {
+        return data.len();
+    }
+
+    let start = data.len().saturating_sub(padding);
+    if data
+        .get(start..)
+        .is_some_and(|tail| tail.iter().all(|&byte| usize::from(byte) == padding))
+    {
+        start
+    } else {
+        data.len()
+    }
+}
+
+/// Reader that decrypts an AES-256-CBC stream on the fly
+///
+/// The plaintext is truncated to the expected size recorded in the file metadata,
+/// which also discards the padding of the last block.
+pub struct DecryptingReader<R> {
+    /// Encrypted source
+    inner: R,
+    /// CBC decryptor carrying the chaining state between chunks
+    decryptor: Aes256CbcDec,
+    /// Decrypted bytes not yet returned
+    buffer: Vec<u8>,
+    /// Read position within `buffer`
+    position: usize,
+    /// Plaintext bytes still to be returned
+    remaining: u64,
+}
+
+impl<R: Read> DecryptingReader<R> {
+    /// Creates a reader returning `size` bytes of plaintext
+    #[must_use]
+    #[inline]
+    pub fn new(inner: R, key: &[u8; KEY_LENGTH], size: u64) -> Self {
+        Self {
+            inner,
+            decryptor: Aes256CbcDec::new(key.into(), &[0; BLOCK_SIZE].into()),
+            buffer: Vec::new(),
+            position: 0,
+            remaining: size,
+        }
+    }
+
+    /// Reads and decrypts the next chunk into the buffer
+    fn fill_buffer(&mut self) -> Result<()> {
+        self.buffer.resize(CHUNK_SIZE, 0);
+        self.position = 0;
+
+        let mut filled = 0;
+        while filled < CHUNK_SIZE {
+            let slice = self.buffer.get_mut(filled..).unwrap_or_default();
+            let read = self.inner.read(slice)?;
+            if read == 0 {
+                break;
+            }
+            filled = filled.saturating_add(read);
+        }
+
+        self.buffer.truncate(filled);
+        if self.buffer.is_empty() {
+            return Err(anyhow::anyhow!(
+                "Encrypted file is shorter than its recorded size"
+            ));
+        }
+
+        decrypt_blocks(&mut self.decryptor, &mut self.buffer).context("Encrypted file is truncated")
+    }
+}
+
+impl<R: Read> Read for DecryptingReader<R> {
+    #[inline]
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        if self.remaining == 0 || out.is_empty() {
+            return Ok(0);
+        }
+
+        if self.position >= self.buffer.len() {
+            self.fill_buffer().map_err(io::Error::other)?;
+        }
+
+        let available = self.buffer.get(self.position..).unwrap_or_default();
+        let limit = usize::try_from(self.remaining).unwrap_or(usize::MAX);
+        let count = available.len().min(out.len()).min(limit);
+
+        if let (Some(target), Some(source)) = (out.get_mut(..count), available.get(..count)) {
+            target.copy_from_slice(source);
+        }
+
+        self.position = self.position.saturating_add(count);
+        self.remaining = self
+            .remaining
+            .saturating_sub(u64::try_from(count).unwrap_or(u64::MAX));
+        Ok(count)
+    }
+}
+
+/// Encrypts data with AES-256-CBC, a zero IV and PKCS#7 padding
+#[cfg(test)]
+pub(crate) fn encrypt_cbc_for_test(key: &[u8; KEY_LENGTH], data: &[u8]) -> Vec<u8> {
+    use aes::cipher::BlockEncryptMut as _;
+
+    let padding = BLOCK_SIZE.saturating_sub(data.len().checked_rem(BLOCK_SIZE).unwrap_or(0));
+    let mut encrypted = data.to_vec();
+    encrypted.resize(
+        data.len().saturating_add(padding),
+        u8::try_from(padding).unwrap_or_default(),
+    );
+
+    let mut encryptor = cbc::Encryptor::<Aes256>::new(key.into(), &[0; BLOCK_SIZE].into());
+    for block in encrypted.chunks_exact_mut(BLOCK_SIZE) {
+        encryptor.encrypt_block_mut(GenericArray::from_mut_slice(block));
+    }
+    encrypted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const KEY: [u8; KEY_LENGTH] = [7; KEY_LENGTH];
+
+    #[test]
+    fn test_decrypt_cbc_strips_padding() -> Result<()> {
+        let encrypted = encrypt_cbc_for_test(&KEY, b"SQLite format 3\0 and more");
+
+        assert_eq!(decrypt_cbc(&KEY, encrypted)?, b"SQLite format 3\0 and more");
+        Ok(())
+    }
+
+    #[test]
+    fn test_decrypt_cbc_rejects_partial_block() {
+        let result = decrypt_cbc(&KEY, vec![0; 17]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_decrypting_reader_streams_large_data() -> Result<()> {
+        let plaintext: Vec<u8> = (0..200_000_u32).flat_map(u32::to_le_bytes).collect();
+        let encrypted = encrypt_cbc_for_test(&KEY, &plaintext);
+
+        let mut reader =
+            DecryptingReader::new(encrypted.as_slice(), &KEY, u64::try_from(plaintext.len())?);
+        let mut decrypted = Vec::new();
+        reader.read_to_end(&mut decrypted)?;
+
+        assert_eq!(decrypted, plaintext);
+        Ok(())
+    }
+
+    #[test]
+    fn test_decrypting_reader_truncates_to_size() -> Result<()> {
+        let encrypted = encrypt_cbc_for_test(&KEY, b"hello world");
+
+        let mut reader = DecryptingReader::new(encrypted.as_slice(), &KEY, 5);
+        let mut decrypted = Vec::new();
+        reader.read_to_end(&mut decrypted)?;
+
+        assert_eq!(decrypted, b"hello");
+        Ok(())
+    }
+
+    #[test]
+    fn test_decrypting_reader_detects_short_file() {
+        let encrypted = encrypt_cbc_for_test(&KEY, b"hello");
+
+        let mut reader = DecryptingReader::new(encrypted.as_slice(), &KEY, 100);
+        let mut decrypted = Vec::new();
+
+        assert!(reader.read_to_end(&mut decrypted).is_err());
+    }
+}
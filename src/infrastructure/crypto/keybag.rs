@@ -0,0 +1,408 @@
+use aes_kw::KekAes256;
+use anyhow::{Context as _, Result};
+use pbkdf2::pbkdf2_hmac;
+use sha1::Sha1;
+use sha2::Sha256;
+use std::collections::HashMap;
+
+/// Length of an unwrapped AES-256 key
+pub const KEY_LENGTH: usize = 32;
+
+/// Length of an RFC 3394 wrapped AES-256 key
+pub const WRAPPED_KEY_LENGTH: usize = 40;
+
+/// Class key is wrapped with the key derived from the backup password
+const WRAP_PASSCODE: u32 = 2;
+
+/// A class key entry of a keybag
+#[derive(Clone)]
+struct ClassKey {
+    /// Wrapping flags
+    wrap: u32,
+    /// Wrapped key material
+    wrapped_key: Vec<u8>,
+    /// Unwrapped key, available once the keybag has been unlocked
+    key: Option<[u8; KEY_LENGTH]>,
+}
+
+/// Backup keybag stored in the `BackupKeyBag` entry of Manifest.plist
+///
+/// The keybag is a sequence of tag-length-value records. The header holds the
+/// password derivation parameters, followed by one record group per protection
+/// class holding the wrapped class key.
+#[derive(Clone)]
+pub struct Keybag {
+    /// Salt of the PBKDF2-SHA1 round
+    salt: Vec<u8>,
+    /// Iterations of the PBKDF2-SHA1 round
+    iterations: u32,
+    /// Salt of the PBKDF2-SHA256 round (iOS 10.2+)
+    double_protection_salt: Option<Vec<u8>>,
+    /// Iterations of the PBKDF2-SHA256 round (iOS 10.2+)
+    double_protection_iterations: Option<u32>,
+    /// Class keys indexed by protection class
+    class_keys: HashMap<u32, ClassKey>,
+}
+
+impl Keybag {
+    /// Parses a keybag from its binary TLV representation
+    ///
+    /// # Errors
+    /// Returns an error if the keybag is truncated or lacks the derivation parameters
+    #[inline]
+    pub fn parse(bytes: &[u8]) -> Result<Self> {
+        let mut salt = None;
+        let mut iterations = None;
+        let mut double_protection_salt = None;
+        let mut double_protection_iterations = None;
+        let mut class_keys = HashMap::new();
+
+        // Class key records start at the second UUID record
+        let mut seen_uuid = false;
+        let mut current: Option<(Option<u32>, ClassKey)> = None;
+
+        let mut rest = bytes;
+        while !rest.is_empty() {
+            let (tag, value, remaining) = Self::next_record(rest)?;
+            rest = remaining;
+
+            match tag {
+                b"UUID" if !seen_uuid => seen_uuid = true,
+                b"UUID" => {
+                    if let Some((Some(class), class_key)) = current.take() {
+                        class_keys.insert(class, class_key);
+                    }
+                    current = Some((
+                        None,
+                        ClassKey {
+                            wrap: 0,
+                            wrapped_key: Vec::new(),
+                            key: None,
+                        },
+                    ));
+                }
+                b"CLAS" => {
+                    if let Some((class, _)) = current.as_mut() {
+                        *class = Some(Self::read_u32(value)?);
+                    }
+                }
+                b"WRAP" => {
+                    if let Some((_, class_key)) = current.as_mut() {
+                        class_key.wrap = Self::read_u32(value)?;
+                    }
+                }
+                b"WPKY" => {
+                    if let Some((_, class_key)) = current.as_mut() {
+                        class_key.wrapped_key = value.to_vec();
+                    }
+                }
+                b"SALT" => salt = Some(value.to_vec()),
+                b"ITER" => iterations = Some(Self::read_u32(value)?),
+                b"DPSL" => double_protection_salt = Some(value.to_vec()),
+                b"DPIC" => double_protection_iterations = Some(Self::read_u32(value)?),
+                _ => {}
+            }
+        }
+
+        if let Some((Some(class), class_key)) = current {
+            class_keys.insert(class, class_key);
+        }
+
+        Ok(Self {
+            salt: salt.context("Keybag has no SALT record")?,
+            iterations: iterations.context("Keybag has no ITER record")?,
+            double_protection_salt,
+            double_protection_iterations,
+            class_keys,
+        })
+    }
+
+    /// Unlocks the class keys with the backup password
+    ///
+    /// # Errors
+    /// Returns an error if the password is wrong or a class key cannot be unwrapped
+    #[inline]
+    pub fn unlock(&mut self, password: &str) -> Result<()> {
+        let password_key = self.derive_password_key(password);
+        let kek = KekAes256::from(password_key);
+
+        for class_key in self.class_keys.values_mut() {
+            if class_key.wrap & WRAP_PASSCODE == 0 {
+                continue;
+            }
+
+            let mut key = [0_u8; KEY_LENGTH];
+            kek.unwrap(&class_key.wrapped_key, &mut key)
+                .map_err(|_| anyhow::anyhow!("Invalid backup password"))?;
+            class_key.key = Some(key);
+        }
+
+        Ok(())
+    }
+
+    /// Checks if the class keys have been unlocked
+    #[must_use]
+    #[inline]
+    pub fn is_unlocked(&self) -> bool {
+        self.class_keys
+            .values()
+            .any(|class_key| class_key.key.is_some())
+    }
+
+    /// Unwraps a key protected by the given protection class
+    ///
+    /// # Errors
+    /// Returns an error if the class is unknown or locked, or if the key cannot be unwrapped
+    #[inline]
+    pub fn unwrap_key(
+        &self,
+        protection_class: u32,
+        wrapped_key: &[u8],
+    ) -> Result<[u8; KEY_LENGTH]> {
+        let class_key = self
+            .class_keys
+            .get(&protection_class)
+            .and_then(|class_key| class_key.key)
+            .with_context(|| {
+                format!("No unlocked class key for protection class {protection_class}")
+            })?;
+
+        let mut key = [0_u8; KEY_LENGTH];
+        KekAes256::from(class_key)
+            .unwrap(wrapped_key, &mut key)
+            .map_err(|_| {
+                anyhow::anyhow!("Failed to unwrap key for protection class {protection_class}")
+            })?;
+
+        Ok(key)
+    }
+
+    /// Unwraps a key prefixed with its little-endian protection class
+    ///
+    /// This is the layout of both `ManifestKey` and the per-file `EncryptionKey`.
+    ///
+    /// # Errors
+    /// Returns an error if the key is too short or cannot be unwrapped
+    #[inline]
+    pub fn unwrap_class_prefixed_key(&self, prefixed_key: &[u8]) -> Result<[u8; KEY_LENGTH]> {
+        let (class, wrapped_key) = prefixed_key
+            .split_first_chunk::<4>()
+            .context("Wrapped key is too short")?;
+
+        self.unwrap_key(u32::from_le_bytes(*class), wrapped_key)
+    }
+
+    /// Derives the key that wraps the class keys from the backup password
+    fn derive_password_key(&self, password: &str) -> [u8; KEY_LENGTH] {
+        // iOS 10.2+ runs PBKDF2-SHA256 over the password before the PBKDF2-SHA1 round
+        let mut intermediate = [0_u8; KEY_LENGTH];
+        let password = match (
+            &self.double_protection_salt,
+            self.double_protection_iterations,
+        ) {
+            (Some(salt), Some(iterations)) => {
+                pbkdf2_hmac::<Sha256>(password.as_bytes(), salt, iterations, &mut intermediate);
+                intermediate.as_slice()
+            }
+            _ => password.as_bytes(),
+        };
+
+        let mut key = [0_u8; KEY_LENGTH];
+        pbkdf2_hmac::<Sha1>(password, &self.salt, self.iterations, &mut key);
+        key
+    }
+
+    /// Splits the next tag-length-value record off the input
+    fn next_record(bytes: &[u8]) -> Result<(&[u8], &[u8], &[u8])> {
+        let (tag, rest) = bytes
+            .split_at_checked(4)
+            .context("Keybag record tag is truncated")?;
+        let (length, rest) = rest
+            .split_first_chunk::<4>()
+            .context("Keybag record length is truncated")?;
+        let length = usize::try_from(u32::from_be_bytes(*length))?;
+        let (value, rest) = rest
+            .split_at_checked(length)
+            .context("Keybag record value is truncated")?;
+
+        Ok((tag, value, rest))
+    }
+
+    /// Reads a big-endian 32-bit integer record value
+    fn read_u32(value: &[u8]) -> Result<u32> {
+        let bytes: [u8; 4] = value
+            .try_into()
+            .context("Keybag integer record must be 4 bytes")?;
+        Ok(u32::from_be_bytes(bytes))
+    }
+
+    /// Builds a keybag whose class keys are wrapped with the given password
+    #[cfg(test)]
+    pub(crate) fn create_for_test(
+        password: &str,
+        class_keys: &[(u32, [u8; KEY_LENGTH])],
+    ) -> Result<Vec<u8>> {
+        let keybag = Self {
+            salt: b"test-salt-sha1".to_vec(),
+            iterations: 2,
+            double_protection_salt: Some(b"test-salt-sha256".to_vec()),
+            double_protection_iterations: Some(3),
+            class_keys: HashMap::new(),
+        };
+        let kek = KekAes256::from(keybag.derive_password_key(password));
+
+        let mut bytes = Vec::new();
+        let mut record = |tag: &[u8; 4], value: &[u8]| -> Result<()> {
+            bytes.extend_from_slice(tag);
+            bytes.extend_from_slice(&u32::try_from(value.len())?.to_be_bytes());
+            bytes.extend_from_slice(value);
+            Ok(())
+        };
+
+        record(b"VERS", &4_u32.to_be_bytes())?;
+        record(b"TYPE", &1_u32.to_be_bytes())?;
+        record(b"UUID", &[0; 16])?;
+        record(b"WRAP", &0_u32.to_be_bytes())?;
+        record(b"SALT", &keybag.salt)?;
+        record(b"ITER", &keybag.iterations.to_be_bytes())?;
+        record(b"DPWT", &1_u32.to_be_bytes())?;
+        record(b"DPIC", &3_u32.to_be_bytes())?;
+        record(b"DPSL", b"test-salt-sha256")?;
+
+        for (class, key) in class_keys {
+            let mut wrapped_key = [0_u8; WRAPPED_KEY_LENGTH];
+            kek.wrap(key, &mut wrapped_key)
+                .map_err(|e| anyhow::anyhow!("Failed to wrap class key: {e}"))?;
+
+            record(b"UUID", &[1; 16])?;
+            record(b"CLAS", &class.to_be_bytes())?;
+            record(b"WRAP", &WRAP_PASSCODE.to_be_bytes())?;
+            record(b"KTYP", &0_u32.to_be_bytes())?;
+            record(b"WPKY", &wrapped_key)?;
+        }
+
+        Ok(bytes)
+    }
+
+    /// Wraps a key with a class key and prefixes it with the protection class
+    #[cfg(test)]
+    pub(crate) fn wrap_for_test(
+        protection_class: u32,
+        class_key: [u8; KEY_LENGTH],
+        key: &[u8; KEY_LENGTH],
+    ) -> Result<Vec<u8>> {
+        let mut wrapped_key = [0_u8; WRAPPED_KEY_LENGTH];
+        KekAes256::from(class_key)
+            .wrap(key, &mut wrapped_key)
+            .map_err(|e| anyhow::anyhow!("Failed to wrap key: {e}"))?;
+
+        let mut prefixed_key = protection_class.to_le_bytes().to_vec();
+        prefixed_key.extend_from_slice(&wrapped_key);
+        Ok(prefixed_key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CLASS_KEY_3: [u8; KEY_LENGTH] = [3; KEY_LENGTH];
+    const CLASS_KEY_4: [u8; KEY_LENGTH] = [4; KEY_LENGTH];
+
+    #[test]
+    fn test_parse_keybag() -> Result<()> {
+        let bytes = Keybag::create_for_test("secret", &[(3, CLASS_KEY_3), (4, CLASS_KEY_4)])?;
+        let keybag = Keybag::parse(&bytes)?;
+
+        assert_eq!(keybag.salt, b"test-salt-sha1");
+        assert_eq!(keybag.iterations, 2);
+        assert_eq!(keybag.double_protection_iterations, Some(3));
+        assert_eq!(keybag.class_keys.len(), 2);
+        assert!(!keybag.is_unlocked());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_truncated_keybag() -> Result<()> {
+        let bytes = Keybag::create_for_test("secret", &[(3, CLASS_KEY_3)])?;
+        let truncated = bytes
+            .get(..bytes.len().saturating_sub(5))
+            .unwrap_or_default();
+
+        let result = Keybag::parse(truncated);
+        assert!(result.is_err());
+        if let Err(error) = result {
+            assert!(error.to_string().contains("truncated"));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_unlock_with_correct_password() -> Result<()> {
+        let bytes = Keybag::create_for_test("secret", &[(3, CLASS_KEY_3), (4, CLASS_KEY_4)])?;
+        let mut keybag = Keybag::parse(&bytes)?;
+
+        keybag.unlock("secret")?;
+
+        assert!(keybag.is_unlocked());
+        assert_eq!(
+            keybag
+                .class_keys
+                .get(&3)
+                .and_then(|class_key| class_key.key),
+            Some(CLASS_KEY_3)
+        );
+        assert_eq!(
+            keybag
+                .class_keys
+                .get(&4)
+                .and_then(|class_key| class_key.key),
+            Some(CLASS_KEY_4)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_unlock_with_wrong_password() -> Result<()> {
+        let bytes = Keybag::create_for_test("secret", &[(3, CLASS_KEY_3)])?;
+        let mut keybag = Keybag::parse(&bytes)?;
+
+        let result = keybag.unlock("wrong");
+
+        assert!(result.is_err());
+        if let Err(error) = result {
+            assert!(error.to_string().contains("Invalid backup password"));
+        }
+        assert!(!keybag.is_unlocked());
+        Ok(())
+    }
+
+    #[test]
+    fn test_unwrap_class_prefixed_key() -> Result<()> {
+        let bytes = Keybag::create_for_test("secret", &[(3, CLASS_KEY_3)])?;
+        let mut keybag = Keybag::parse(&bytes)?;
+        keybag.unlock("secret")?;
+
+        let file_key = [9; KEY_LENGTH];
+        let prefixed_key = Keybag::wrap_for_test(3, CLASS_KEY_3, &file_key)?;
+
+        assert_eq!(keybag.unwrap_class_prefixed_key(&prefixed_key)?, file_key);
+        Ok(())
+    }
+
+    #[test]
+    fn test_unwrap_key_unknown_class() -> Result<()> {
+        let bytes = Keybag::create_for_test("secret", &[(3, CLASS_KEY_3)])?;
+        let mut keybag = Keybag::parse(&bytes)?;
+        keybag.unlock("secret")?;
+
+        let prefixed_key = Keybag::wrap_for_test(7, CLASS_KEY_3, &[9; KEY_LENGTH])?;
+        let result = keybag.unwrap_class_prefixed_key(&prefixed_key);
+
+        assert!(result.is_err());
+        if let Err(error) = result {
+            assert!(error.to_string().contains("protection class 7"));
+        }
+        Ok(())
+    }
+}
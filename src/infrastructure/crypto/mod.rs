@@ -0,0 +1,5 @@
+pub mod cipher;
+pub mod keybag;
+
+pub use cipher::{DecryptingReader, decrypt_cbc};
+pub use keybag::Keybag;
@@ -1,7 +1,12 @@
+use anyhow::{Context as _, Result};
 use sea_orm::{Database, DatabaseConnection as SeaOrmConnection, DbErr};
+use std::fs;
+use tempfile::TempDir;
 
 pub struct DatabaseConnection {
     connection: SeaOrmConnection,
+    /// Temporary directory of the database file, removed when the connection is dropped
+    _temporary_dir: Option<TempDir>,
 }
 
 impl DatabaseConnection {
@@ -13,14 +18,63 @@ impl DatabaseConnection {
     #[inline]
     pub async fn new(database_url: &str) -> Result<Self, DbErr> {
         let connection = Database::connect(database_url).await?;
-        Ok(Self { connection })
+        Ok(Self {
+            connection,
+            _temporary_dir: None,
+        })
+    }
+
+    /// Opens a read-only connection to a database held in memory
+    ///
+    /// The bytes are written to a private temporary file that lives as long as the
+    /// connection. This is used for databases decrypted from encrypted backups.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the temporary file cannot be written or the connection fails
+    #[inline]
+    pub async fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        Self::from_bytes_with_wal(bytes, None).await
+    }
+
+    /// Opens a read-only connection to a database and its write-ahead log held in memory
+    ///
+    /// Databases in WAL mode keep recent changes in a separate `-wal` file, which has to be
+    /// placed next to the database for `SQLite` to see them.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the temporary files cannot be written or the connection fails
+    #[inline]
+    pub async fn from_bytes_with_wal(bytes: &[u8], wal: Option<&[u8]>) -> Result<Self> {
+        let temporary_dir = tempfile::Builder::new()
+            .prefix("idig-")
+            .tempdir()
+            .context("Failed to create temporary database directory")?;
+        let path = temporary_dir.path().join("database.db");
+        fs::write(&path, bytes).context("Failed to write temporary database file")?;
+        if let Some(wal) = wal {
+            fs::write(temporary_dir.path().join("database.db-wal"), wal)
+                .context("Failed to write temporary write-ahead log")?;
+        }
+
+        let db_url = format!("sqlite://{}?mode=ro", path.display());
+        let connection = Database::connect(&db_url).await?;
+
+        Ok(Self {
+            connection,
+            _temporary_dir: Some(temporary_dir),
+        })
     }
 
     #[cfg(test)]
     #[must_use]
     #[inline]
     pub const fn new_from_connection(connection: SeaOrmConnection) -> Self {
-        Self { connection }
+        Self {
+            connection,
+            _temporary_dir: None,
+        }
     }
 
     #[must_use]
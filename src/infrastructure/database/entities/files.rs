@@ -7,6 +7,7 @@
 
 use crate::domain::entities::File;
 use crate::domain::value_objects::{Domain, FileFlags, FileId, RelativePath};
+use crate::infrastructure::plist::entities::MbFile;
 use anyhow::Result;
 use sea_orm::entity::prelude::*;
 
@@ -42,12 +43,15 @@ impl Model {
             .map_err(|e| anyhow::anyhow!("Invalid RelativePath: {e}"))?;
         let flags = FileFlags::from_bits_truncate(self.flags);
 
-        Ok(File::reconstruct(
-            file_id,
-            domain,
-            relative_path,
-            flags,
-            self.file,
-        ))
+        // A malformed MBFile blob should not prevent the file from being listed
+        let file_metadata = MbFile::from_bytes(&self.file)
+            .and_then(MbFile::to_domain)
+            .ok();
+
+        let file = File::reconstruct(file_id, domain, relative_path, flags, self.file);
+        Ok(match file_metadata {
+            Some(file_metadata) => file.with_file_metadata(file_metadata),
+            None => file,
+        })
     }
 }
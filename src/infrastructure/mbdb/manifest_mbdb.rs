@@ -0,0 +1,232 @@
+use super::MbdbRecord;
+use anyhow::{Context as _, Result};
+
+/// Magic bytes at the start of a Manifest.mbdb file
+const HEADER: &[u8] = b"mbdb\x05\x00";
+
+/// Length marker of an absent string
+const NULL_STRING: u16 = 0xffff;
+
+/// Represents a legacy Manifest.mbdb file
+///
+/// The file starts with `mbdb\x05\x00` and is followed by file records. All integers are
+/// big-endian and strings are prefixed with their 16-bit length.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub struct ManifestMbdb {
+    pub records: Vec<MbdbRecord>,
+}
+
+impl ManifestMbdb {
+    /// Parses a Manifest.mbdb file
+    ///
+    /// # Errors
+    /// Returns an error if the header is missing or a record is truncated or malformed
+    #[inline]
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let mut reader = MbdbReader::new(bytes);
+        if reader.take(HEADER.len())? != HEADER {
+            return Err(anyhow::anyhow!("Not a Manifest.mbdb file: invalid header"));
+        }
+
+        let mut records = Vec::new();
+        while !reader.is_empty() {
+            let offset = reader.offset;
+            let record = Self::read_record(&mut reader)
+                .with_context(|| format!("Invalid Manifest.mbdb record at offset {offset}"))?;
+            records.push(record);
+        }
+
+        Ok(Self { records })
+    }
+
+    fn read_record(reader: &mut MbdbReader<'_>) -> Result<MbdbRecord> {
+        let domain = reader.text()?.unwrap_or_default();
+        let path = reader.text()?.unwrap_or_default();
+        let link_target = reader.text()?;
+        let data_hash = reader.data()?;
+        let encryption_key = reader.data()?;
+        let mode = reader.u16()?;
+        let inode_number = reader.u64()?;
+        let user_id = reader.u32()?;
+        let group_id = reader.u32()?;
+        let last_modified = reader.u32()?;
+        let last_accessed = reader.u32()?;
+        let last_status_change = reader.u32()?;
+        let size = reader.u64()?;
+        let protection_class = reader.u8()?;
+
+        let property_count = reader.u8()?;
+        let mut properties = Vec::with_capacity(property_count.into());
+        for _ in 0..property_count {
+            let name = reader.text()?.unwrap_or_default();
+            let value = reader.data()?.unwrap_or_default();
+            properties.push((name, value));
+        }
+
+        Ok(MbdbRecord {
+            domain,
+            path,
+            link_target,
+            data_hash,
+            encryption_key,
+            mode,
+            inode_number,
+            user_id,
+            group_id,
+            last_modified,
+            last_accessed,
+            last_status_change,
+            size,
+            protection_class,
+            properties,
+        })
+    }
+
+    /// Encodes the file the way iOS writes it
+    #[cfg(test)]
+    pub(crate) fn to_bytes(&self) -> Result<Vec<u8>> {
+        let mut bytes = HEADER.to_vec();
+        for record in &self.records {
+            bytes.extend(record.to_bytes()?);
+        }
+        Ok(bytes)
+    }
+}
+
+/// Cursor over the big-endian fields of a Manifest.mbdb file
+struct MbdbReader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> MbdbReader<'a> {
+    const fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, offset: 0 }
+    }
+
+    const fn is_empty(&self) -> bool {
+        self.offset >= self.bytes.len()
+    }
+
+    fn take(&mut self, length: usize) -> Result<&'a [u8]> {
+        let end = self.offset.saturating_add(length);
+        let bytes = self
+            .bytes
+            .get(self.offset..end)
+            .context("Unexpected end of Manifest.mbdb")?;
+        self.offset = end;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        Ok(self.take(N)?.try_into()?)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(u8::from_be_bytes(self.array()?))
+    }
+
+    fn u16(&mut self) -> Result<u16> {
+        Ok(u16::from_be_bytes(self.array()?))
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_be_bytes(self.array()?))
+    }
+
+    fn u64(&mut self) -> Result<u64> {
+        Ok(u64::from_be_bytes(self.array()?))
+    }
+
+    fn data(&mut self) -> Result<Option<Vec<u8>>> {
+        let length = self.u16()?;
+        if length == NULL_STRING {
+            return Ok(None);
+        }
+        Ok(Some(self.take(length.into())?.to_vec()))
+    }
+
+    fn text(&mut self) -> Result<Option<String>> {
+        self.data()?
+            .map(|bytes| String::from_utf8(bytes).context("Manifest.mbdb string is not UTF-8"))
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn create_test_record() -> MbdbRecord {
+        MbdbRecord {
+            domain: "HomeDomain".to_owned(),
+            path: "Library/SMS/sms.db".to_owned(),
+            link_target: None,
+            data_hash: Some(vec![0xaa; 20]),
+            encryption_key: Some(vec![3, 0, 0, 0, 1, 2, 3]),
+            mode: 0o100_644,
+            inode_number: 42,
+            user_id: 501,
+            group_id: 501,
+            last_modified: 1_400_000_000,
+            last_accessed: 1_400_000_100,
+            last_status_change: 1_300_000_000,
+            size: 1024,
+            protection_class: 3,
+            properties: vec![("com.apple.backup.test".to_owned(), b"value".to_vec())],
+        }
+    }
+
+    #[test]
+    fn test_from_bytes_round_trip() -> Result<()> {
+        let directory = MbdbRecord {
+            domain: "HomeDomain".to_owned(),
+            mode: 0o040_755,
+            ..MbdbRecord::default()
+        };
+        let manifest = ManifestMbdb {
+            records: vec![directory, create_test_record()],
+        };
+
+        let decoded = ManifestMbdb::from_bytes(&manifest.to_bytes()?)?;
+
+        assert_eq!(decoded, manifest);
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_bytes_empty_manifest() -> Result<()> {
+        let decoded = ManifestMbdb::from_bytes(HEADER)?;
+        assert!(decoded.records.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_bytes_invalid_header() {
+        let result = ManifestMbdb::from_bytes(b"mbdx\x05\x00");
+
+        assert!(result.is_err());
+        if let Err(error) = result {
+            assert!(error.to_string().contains("invalid header"));
+        }
+    }
+
+    #[test]
+    fn test_from_bytes_truncated_record() -> Result<()> {
+        let manifest = ManifestMbdb {
+            records: vec![create_test_record()],
+        };
+        let mut bytes = manifest.to_bytes()?;
+        bytes.truncate(bytes.len().saturating_sub(3));
+
+        let result = ManifestMbdb::from_bytes(&bytes);
+
+        assert!(result.is_err());
+        if let Err(error) = result {
+            assert!(error.to_string().contains("offset 6"));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,225 @@
+use crate::domain::entities::File;
+use crate::domain::value_objects::{Domain, FileFlags, FileId, FileMetadata, RelativePath};
+use anyhow::{Context as _, Result};
+use chrono::{DateTime, Utc};
+use sha1::{Digest as _, Sha1};
+use std::mem;
+
+/// Represents a single file record of a legacy Manifest.mbdb file
+///
+/// iOS 5–9 backups describe every file with one of these records instead of a
+/// row in Manifest.db. Hashes and keys are kept as raw bytes.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub struct MbdbRecord {
+    pub domain: String,
+    pub path: String,
+    pub link_target: Option<String>,
+    pub data_hash: Option<Vec<u8>>,
+    pub encryption_key: Option<Vec<u8>>,
+    pub mode: u16,
+    pub inode_number: u64,
+    pub user_id: u32,
+    pub group_id: u32,
+    pub last_modified: u32,
+    pub last_accessed: u32,
+    pub last_status_change: u32,
+    pub size: u64,
+    pub protection_class: u8,
+    pub properties: Vec<(String, Vec<u8>)>,
+}
+
+impl MbdbRecord {
+    /// Computes the file ID, the SHA1 hash of `domain-path`
+    ///
+    /// Blobs of legacy backups are stored under this name.
+    #[must_use]
+    #[inline]
+    pub fn file_id(&self) -> String {
+        let digest = Sha1::new()
+            .chain_update(self.domain.as_bytes())
+            .chain_update(b"-")
+            .chain_update(self.path.as_bytes())
+            .finalize();
+
+        format!("{digest:x}")
+    }
+
+    /// Converts the mbdb record to a domain `File`
+    ///
+    /// # Errors
+    /// Returns an error if any of the value objects cannot be constructed from the record
+    #[inline]
+    pub fn to_domain(mut self) -> Result<File> {
+        let file_id =
+            FileId::new(&self.file_id()).map_err(|e| anyhow::anyhow!("Invalid FileId: {e}"))?;
+        let domain = Domain::new(mem::take(&mut self.domain))
+            .map_err(|e| anyhow::anyhow!("Invalid Domain: {e}"))?;
+        let relative_path = RelativePath::new(mem::take(&mut self.path))
+            .map_err(|e| anyhow::anyhow!("Invalid RelativePath: {e}"))?;
+
+        let file_metadata = self.into_file_metadata()?;
+        let flags = if file_metadata.is_directory() {
+            FileFlags::DIRECTORY
+        } else if file_metadata.is_symbolic_link() {
+            FileFlags::SYMBOLIC_LINK
+        } else {
+            FileFlags::REGULAR_FILE
+        };
+
+        Ok(
+            File::reconstruct(file_id, domain, relative_path, flags, Vec::new())
+                .with_file_metadata(file_metadata),
+        )
+    }
+
+    /// Converts the record attributes to domain `FileMetadata`
+    fn into_file_metadata(self) -> Result<FileMetadata> {
+        // mbdb records have no birth time; the status change time is the closest match
+        let last_status_change = Self::to_datetime(self.last_status_change)?;
+
+        Ok(FileMetadata::new(self.size, self.mode.into())
+            .with_owner(self.user_id, self.group_id)
+            .with_inode(self.inode_number)
+            .with_timestamps(
+                Self::to_datetime(self.last_modified)?,
+                last_status_change,
+                last_status_change,
+            )
+            .with_last_accessed(Some(Self::to_datetime(self.last_accessed)?))
+            .with_protection_class(self.protection_class.into())
+            .with_target(self.link_target)
+            .with_encryption_key(self.encryption_key)
+            .with_digest(self.data_hash))
+    }
+
+    /// Converts Unix seconds to a UTC datetime
+    fn to_datetime(seconds: u32) -> Result<DateTime<Utc>> {
+        DateTime::from_timestamp(seconds.into(), 0)
+            .with_context(|| format!("Invalid mbdb timestamp: {seconds}"))
+    }
+
+    /// Encodes the record the way iOS writes it
+    #[cfg(test)]
+    pub(crate) fn to_bytes(&self) -> Result<Vec<u8>> {
+        fn write_string(bytes: &mut Vec<u8>, value: Option<&[u8]>) -> Result<()> {
+            match value {
+                Some(value) => {
+                    bytes.extend_from_slice(&u16::try_from(value.len())?.to_be_bytes());
+                    bytes.extend_from_slice(value);
+                }
+                None => bytes.extend_from_slice(&[0xff, 0xff]),
+            }
+            Ok(())
+        }
+
+        let mut bytes = Vec::new();
+        write_string(&mut bytes, Some(self.domain.as_bytes()))?;
+        write_string(&mut bytes, Some(self.path.as_bytes()))?;
+        write_string(&mut bytes, self.link_target.as_deref().map(str::as_bytes))?;
+        write_string(&mut bytes, self.data_hash.as_deref())?;
+        write_string(&mut bytes, self.encryption_key.as_deref())?;
+        bytes.extend_from_slice(&self.mode.to_be_bytes());
+        bytes.extend_from_slice(&self.inode_number.to_be_bytes());
+        bytes.extend_from_slice(&self.user_id.to_be_bytes());
+        bytes.extend_from_slice(&self.group_id.to_be_bytes());
+        bytes.extend_from_slice(&self.last_modified.to_be_bytes());
+        bytes.extend_from_slice(&self.last_accessed.to_be_bytes());
+        bytes.extend_from_slice(&self.last_status_change.to_be_bytes());
+        bytes.extend_from_slice(&self.size.to_be_bytes());
+        bytes.push(self.protection_class);
+        bytes.push(u8::try_from(self.properties.len())?);
+        for (name, value) in &self.properties {
+            write_string(&mut bytes, Some(name.as_bytes()))?;
+            write_string(&mut bytes, Some(value))?;
+        }
+
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_file_id_is_sha1_of_domain_and_path() {
+        let record = MbdbRecord {
+            domain: "HomeDomain".to_owned(),
+            path: "Library/SMS/sms.db".to_owned(),
+            ..MbdbRecord::default()
+        };
+
+        assert_eq!(record.file_id(), "3d0d7e5fb2ce288813306e4d4636395e047a3d28");
+    }
+
+    #[test]
+    fn test_to_domain() -> Result<()> {
+        let record = MbdbRecord {
+            domain: "HomeDomain".to_owned(),
+            path: "Library/SMS/sms.db".to_owned(),
+            data_hash: Some(vec![0xaa; 20]),
+            mode: 0o100_644,
+            inode_number: 42,
+            user_id: 501,
+            group_id: 501,
+            last_modified: 1_400_000_000,
+            last_accessed: 1_400_000_100,
+            last_status_change: 1_300_000_000,
+            size: 1024,
+            protection_class: 3,
+            ..MbdbRecord::default()
+        };
+
+        let file = record.to_domain()?;
+
+        assert_eq!(
+            file.id().value(),
+            "3d0d7e5fb2ce288813306e4d4636395e047a3d28"
+        );
+        assert_eq!(file.domain().value(), "HomeDomain");
+        assert_eq!(file.relative_path().value(), "Library/SMS/sms.db");
+        assert!(file.flags().is_regular_file());
+        assert_eq!(file.size(), Some(1024));
+        assert_eq!(file.protection_class(), Some(3));
+        assert_eq!(
+            file.last_modified().map(DateTime::timestamp),
+            Some(1_400_000_000)
+        );
+        assert_eq!(
+            file.file_metadata()
+                .and_then(FileMetadata::last_accessed)
+                .map(DateTime::timestamp),
+            Some(1_400_000_100)
+        );
+        assert_eq!(
+            file.file_metadata().and_then(FileMetadata::digest),
+            Some([0xaa; 20].as_slice())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_domain_file_types() -> Result<()> {
+        let directory = MbdbRecord {
+            domain: "HomeDomain".to_owned(),
+            path: "Library".to_owned(),
+            mode: 0o040_755,
+            ..MbdbRecord::default()
+        };
+        assert!(directory.to_domain()?.flags().is_directory());
+
+        let symlink = MbdbRecord {
+            domain: "HomeDomain".to_owned(),
+            path: "Library/link".to_owned(),
+            link_target: Some("/var/mobile".to_owned()),
+            mode: 0o120_755,
+            ..MbdbRecord::default()
+        };
+        let file = symlink.to_domain()?;
+        assert!(file.flags().is_symbolic_link());
+        assert_eq!(file.symlink_target(), Some("/var/mobile"));
+        Ok(())
+    }
+}
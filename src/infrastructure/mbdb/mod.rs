@@ -0,0 +1,5 @@
+pub mod manifest_mbdb;
+pub mod mbdb_record;
+
+pub use manifest_mbdb::ManifestMbdb;
+pub use mbdb_record::MbdbRecord;
@@ -1,3 +1,6 @@
+pub mod backup;
+pub mod crypto;
 pub mod database;
+pub mod mbdb;
 pub mod plist;
 pub mod repositories;
@@ -0,0 +1,105 @@
+use crate::domain::entities::Application;
+use plist::Data;
+use serde::{Deserialize, Serialize};
+
+/// Represents an entry of the `Applications` dictionary in Manifest.plist
+///
+/// It contains only the fields relevant to `Application`.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct ApplicationInfo {
+    #[serde(rename = "CFBundleIdentifier", default)]
+    pub bundle_identifier: Option<String>,
+
+    #[serde(rename = "CFBundleVersion", default)]
+    pub bundle_version: Option<String>,
+
+    #[serde(rename = "iTunesMetadata", default)]
+    pub itunes_metadata: Option<Data>,
+}
+
+/// Represents the App Store metadata embedded in an `ApplicationInfo`
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct ITunesMetadata {
+    #[serde(rename = "itemName", default)]
+    pub item_name: Option<String>,
+
+    #[serde(rename = "bundleDisplayName", default)]
+    pub bundle_display_name: Option<String>,
+
+    #[serde(rename = "bundleShortVersionString", default)]
+    pub bundle_short_version_string: Option<String>,
+}
+
+impl ApplicationInfo {
+    /// Converts the plist representation to a domain `Application`
+    ///
+    /// The App Store metadata is optional, so it is ignored if it cannot be parsed.
+    #[must_use]
+    #[inline]
+    pub fn to_domain(self, bundle_id: String) -> Application {
+        let itunes_metadata = self
+            .itunes_metadata
+            .and_then(|data| plist::from_bytes::<ITunesMetadata>(data.as_ref()).ok())
+            .unwrap_or_default();
+
+        Application::new(bundle_id)
+            .with_version(
+                itunes_metadata
+                    .bundle_short_version_string
+                    .or(self.bundle_version),
+            )
+            .with_display_name(
+                itunes_metadata
+                    .bundle_display_name
+                    .or(itunes_metadata.item_name),
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_to_domain_with_itunes_metadata() -> Result<()> {
+        let mut itunes_metadata = Vec::new();
+        plist::to_writer_binary(
+            &mut itunes_metadata,
+            &ITunesMetadata {
+                item_name: Some("WhatsApp Messenger".to_owned()),
+                bundle_display_name: Some("WhatsApp".to_owned()),
+                bundle_short_version_string: Some("24.1.78".to_owned()),
+            },
+        )?;
+        let info = ApplicationInfo {
+            bundle_identifier: Some("net.whatsapp.WhatsApp".to_owned()),
+            bundle_version: Some("566".to_owned()),
+            itunes_metadata: Some(itunes_metadata.into()),
+        };
+
+        let application = info.to_domain("net.whatsapp.WhatsApp".to_owned());
+
+        assert_eq!(application.bundle_id(), "net.whatsapp.WhatsApp");
+        assert_eq!(application.version(), Some("24.1.78"));
+        assert_eq!(application.display_name(), Some("WhatsApp"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_domain_without_itunes_metadata() {
+        let info = ApplicationInfo {
+            bundle_version: Some("566".to_owned()),
+            itunes_metadata: Some(vec![0, 1, 2].into()),
+            ..ApplicationInfo::default()
+        };
+
+        let application = info.to_domain("net.whatsapp.WhatsApp".to_owned());
+
+        assert_eq!(application.version(), Some("566"));
+        assert_eq!(application.display_name(), None);
+    }
+}
@@ -1,4 +1,7 @@
-use crate::domain::{entities::Metadata, value_objects::MetadataId};
+use crate::domain::{
+    entities::{DeviceDetails, Metadata},
+    value_objects::MetadataId,
+};
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 
@@ -6,7 +9,7 @@ use serde::{Deserialize, Serialize};
 ///
 /// This struct maps to the plist file format used by iTunes/Finder backups.
 /// It contains only the fields relevant to `Metadata`.
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
 #[non_exhaustive]
 pub struct BackupInfo {
     #[serde(rename = "Unique Identifier")]
@@ -20,6 +23,39 @@ pub struct BackupInfo {
 
     #[serde(rename = "Last Backup Date")]
     pub last_backup_date: String,
+
+    #[serde(rename = "Product Version", default)]
+    pub product_version: Option<String>,
+
+    #[serde(rename = "Build Version", default)]
+    pub build_version: Option<String>,
+
+    #[serde(rename = "Serial Number", default)]
+    pub serial_number: Option<String>,
+
+    #[serde(rename = "IMEI", default)]
+    pub imei: Option<String>,
+
+    #[serde(rename = "MEID", default)]
+    pub meid: Option<String>,
+
+    #[serde(rename = "ICCID", default)]
+    pub iccid: Option<String>,
+
+    #[serde(rename = "Phone Number", default)]
+    pub phone_number: Option<String>,
+
+    #[serde(rename = "GUID", default)]
+    pub guid: Option<String>,
+
+    #[serde(rename = "Target Type", default)]
+    pub target_type: Option<String>,
+
+    #[serde(rename = "iTunes Version", default)]
+    pub itunes_version: Option<String>,
+
+    #[serde(rename = "Installed Applications", default)]
+    pub installed_applications: Vec<String>,
 }
 
 impl BackupInfo {
@@ -33,12 +69,26 @@ impl BackupInfo {
             .map_err(|e| anyhow::anyhow!("Invalid FileId: {e}"))?;
         let last_backup_date = self.last_backup_date.parse()?;
 
+        let device = DeviceDetails {
+            product_version: self.product_version,
+            build_version: self.build_version,
+            serial_number: self.serial_number,
+            imei: self.imei,
+            meid: self.meid,
+            iccid: self.iccid,
+            phone_number: self.phone_number,
+            guid: self.guid,
+            target_type: self.target_type,
+            itunes_version: self.itunes_version,
+        };
+
         Ok(Metadata::new(
             metadata_id,
             self.device_name,
             self.product_name,
             last_backup_date,
-        ))
+        )
+        .with_device(device))
     }
 }
 
@@ -55,6 +105,7 @@ mod tests {
             device_name: "iPhone 15 Pro".to_owned(),
             product_name: "iPhone16,1".to_owned(),
             last_backup_date: "2024-01-15T10:30:00Z".to_owned(),
+            ..BackupInfo::default()
         };
 
         let metadata = backup_info.to_domain()?;
@@ -77,6 +128,7 @@ mod tests {
             device_name: "iPhone 15 Pro".to_owned(),
             product_name: "iPhone16,1".to_owned(),
             last_backup_date: "2024-01-15T10:30:00Z".to_owned(),
+            ..BackupInfo::default()
         };
 
         let result = backup_info.to_domain();
@@ -96,6 +148,7 @@ mod tests {
             device_name: "iPhone 15 Pro".to_owned(),
             product_name: "iPhone16,1".to_owned(),
             last_backup_date: "not-a-date".to_owned(), // Invalid date format
+            ..BackupInfo::default()
         };
 
         let result = backup_info.to_domain();
@@ -128,6 +181,7 @@ mod tests {
                 device_name: "Test Device".to_owned(),
                 product_name: "TestProduct".to_owned(),
                 last_backup_date: date_str.to_owned(),
+                ..BackupInfo::default()
             };
 
             let metadata = backup_info
@@ -149,6 +203,7 @@ mod tests {
             device_name: "My iPhone's Name with Spaces & Symbols!".to_owned(),
             product_name: "iPhone16,1-Beta".to_owned(),
             last_backup_date: "2024-01-15T10:30:00Z".to_owned(),
+            ..BackupInfo::default()
         };
 
         let metadata = backup_info.to_domain()?;
@@ -161,4 +216,61 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_to_domain_device_details() -> Result<()> {
+        let content = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Build Version</key>
+    <string>21C66</string>
+    <key>Device Name</key>
+    <string>iPhone 15 Pro</string>
+    <key>GUID</key>
+    <string>0123456789ABCDEF0123456789ABCDEF</string>
+    <key>ICCID</key>
+    <string>8981100000000000000</string>
+    <key>IMEI</key>
+    <string>350000000000000</string>
+    <key>Last Backup Date</key>
+    <string>2024-01-15T10:30:00Z</string>
+    <key>Phone Number</key>
+    <string>+81 90-0000-0000</string>
+    <key>Product Name</key>
+    <string>iPhone16,1</string>
+    <key>Product Version</key>
+    <string>17.2.1</string>
+    <key>Serial Number</key>
+    <string>F2LXXXXXXXXX</string>
+    <key>Target Type</key>
+    <string>Device</string>
+    <key>Unique Identifier</key>
+    <string>a1b2c3d4e5f67890123456789</string>
+    <key>iTunes Version</key>
+    <string>12.13</string>
+</dict>
+</plist>"#;
+
+        let backup_info: BackupInfo = plist::from_bytes(content.as_bytes())?;
+        let metadata = backup_info.to_domain()?;
+
+        assert_eq!(
+            metadata.device(),
+            &DeviceDetails {
+                product_version: Some("17.2.1".to_owned()),
+                build_version: Some("21C66".to_owned()),
+                serial_number: Some("F2LXXXXXXXXX".to_owned()),
+                imei: Some("350000000000000".to_owned()),
+                meid: None,
+                iccid: Some("8981100000000000000".to_owned()),
+                phone_number: Some("+81 90-0000-0000".to_owned()),
+                guid: Some("0123456789ABCDEF0123456789ABCDEF".to_owned()),
+                target_type: Some("Device".to_owned()),
+                itunes_version: Some("12.13".to_owned()),
+            }
+        );
+
+        Ok(())
+    }
 }
@@ -0,0 +1,138 @@
+use super::ApplicationInfo;
+use crate::domain::entities::ManifestDetails;
+use chrono::{DateTime, Utc};
+use plist::{Data, Date};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::SystemTime;
+
+/// Represents the structure of an iPhone backup Manifest.plist file
+///
+/// It contains only the fields needed to open the backup and describe it.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct ManifestInfo {
+    #[serde(rename = "IsEncrypted", default)]
+    pub is_encrypted: bool,
+
+    #[serde(rename = "BackupKeyBag", default)]
+    pub backup_key_bag: Option<Data>,
+
+    #[serde(rename = "ManifestKey", default)]
+    pub manifest_key: Option<Data>,
+
+    #[serde(rename = "Version", default)]
+    pub version: Option<String>,
+
+    #[serde(rename = "WasPasscodeSet", default)]
+    pub was_passcode_set: Option<bool>,
+
+    #[serde(rename = "Date", default)]
+    pub date: Option<Date>,
+
+    #[serde(rename = "Applications", default)]
+    pub applications: BTreeMap<String, ApplicationInfo>,
+}
+
+impl ManifestInfo {
+    /// Converts the plist representation to domain `ManifestDetails`
+    #[must_use]
+    #[inline]
+    pub fn to_details(&self) -> ManifestDetails {
+        ManifestDetails {
+            is_encrypted: Some(self.is_encrypted),
+            version: self.version.clone(),
+            was_passcode_set: self.was_passcode_set,
+            date: self
+                .date
+                .map(|date| DateTime::<Utc>::from(SystemTime::from(date))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_deserialize_encrypted_manifest() -> Result<()> {
+        let content = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>BackupKeyBag</key>
+    <data>AQID</data>
+    <key>IsEncrypted</key>
+    <true/>
+    <key>ManifestKey</key>
+    <data>BAUG</data>
+    <key>Version</key>
+    <string>10.0</string>
+</dict>
+</plist>"#;
+
+        let manifest: ManifestInfo = plist::from_bytes(content.as_bytes())?;
+
+        assert!(manifest.is_encrypted);
+        assert_eq!(manifest.version.as_deref(), Some("10.0"));
+        assert_eq!(
+            manifest.backup_key_bag.as_ref().map(AsRef::as_ref),
+            Some([1, 2, 3].as_slice())
+        );
+        assert_eq!(
+            manifest.manifest_key.as_ref().map(AsRef::as_ref),
+            Some([4, 5, 6].as_slice())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_deserialize_unencrypted_manifest() -> Result<()> {
+        let content = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>IsEncrypted</key>
+    <false/>
+</dict>
+</plist>"#;
+
+        let manifest: ManifestInfo = plist::from_bytes(content.as_bytes())?;
+
+        assert_eq!(manifest, ManifestInfo::default());
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_details() -> Result<()> {
+        let content = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Date</key>
+    <date>2024-01-15T10:30:00Z</date>
+    <key>IsEncrypted</key>
+    <false/>
+    <key>Version</key>
+    <string>10.0</string>
+    <key>WasPasscodeSet</key>
+    <true/>
+</dict>
+</plist>"#;
+
+        let manifest: ManifestInfo = plist::from_bytes(content.as_bytes())?;
+
+        assert_eq!(
+            manifest.to_details(),
+            ManifestDetails {
+                is_encrypted: Some(false),
+                version: Some("10.0".to_owned()),
+                was_passcode_set: Some(true),
+                date: Some("2024-01-15T10:30:00Z".parse()?),
+            }
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,258 @@
+use crate::domain::value_objects::FileMetadata;
+use crate::infrastructure::plist::KeyedArchive;
+use anyhow::{Context as _, Result};
+use chrono::{DateTime, Utc};
+
+/// Represents the `MBFile` object archived in the `file` column of Manifest.db
+///
+/// The column holds an `NSKeyedArchiver` binary plist whose root object is an `MBFile`.
+/// Only the attributes relevant to `FileMetadata` are kept.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub struct MbFile {
+    pub size: u64,
+    pub mode: u32,
+    pub user_id: u32,
+    pub group_id: u32,
+    pub inode_number: u64,
+    pub last_modified: i64,
+    pub last_status_change: i64,
+    pub birth: i64,
+    pub protection_class: u32,
+    pub flags: u64,
+    pub target: Option<String>,
+    pub encryption_key: Option<Vec<u8>>,
+}
+
+impl MbFile {
+    /// Decodes an `MBFile` from keyed archive bytes
+    ///
+    /// # Errors
+    /// Returns an error if the bytes are not a keyed archive or a field has an unexpected type
+    #[inline]
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let archive = KeyedArchive::from_bytes(bytes)?;
+        let root = archive.root()?;
+
+        let unsigned = |key: &str| -> Result<u64> {
+            Ok(archive.unsigned_field(root, key)?.unwrap_or_default())
+        };
+        let narrow = |key: &str| -> Result<u32> {
+            u32::try_from(unsigned(key)?)
+                .with_context(|| format!("MBFile field is out of range: {key}"))
+        };
+        let signed =
+            |key: &str| -> Result<i64> { Ok(archive.signed_field(root, key)?.unwrap_or_default()) };
+
+        Ok(Self {
+            size: unsigned("Size")?,
+            mode: narrow("Mode")?,
+            user_id: narrow("UserID")?,
+            group_id: narrow("GroupID")?,
+            inode_number: unsigned("InodeNumber")?,
+            last_modified: signed("LastModified")?,
+            last_status_change: signed("LastStatusChange")?,
+            birth: signed("Birth")?,
+            protection_class: narrow("ProtectionClass")?,
+            flags: unsigned("Flags")?,
+            target: archive.string_field(root, "Target")?.map(str::to_owned),
+            encryption_key: archive
+                .data_field(root, "EncryptionKey")?
+                .map(<[u8]>::to_vec),
+        })
+    }
+
+    /// Converts the plist representation to domain `FileMetadata`
+    ///
+    /// # Errors
+    /// Returns an error if a timestamp is out of the representable range
+    #[inline]
+    pub fn to_domain(self) -> Result<FileMetadata> {
+        Ok(FileMetadata::new(self.size, self.mode)
+            .with_owner(self.user_id, self.group_id)
+            .with_inode(self.inode_number)
+            .with_timestamps(
+                Self::to_datetime(self.last_modified)?,
+                Self::to_datetime(self.last_status_change)?,
+                Self::to_datetime(self.birth)?,
+            )
+            .with_protection_class(self.protection_class)
+            .with_flags(self.flags)
+            .with_target(self.target)
+            .with_encryption_key(self.encryption_key))
+    }
+
+    /// Converts Unix seconds to a UTC datetime
+    fn to_datetime(seconds: i64) -> Result<DateTime<Utc>> {
+        DateTime::from_timestamp(seconds, 0)
+            .with_context(|| format!("Invalid MBFile timestamp: {seconds}"))
+    }
+
+    /// Encodes the `MBFile` as a keyed archive, the way iOS writes it
+    #[cfg(test)]
+    pub(crate) fn to_bytes(&self) -> Result<Vec<u8>> {
+        use plist::{Dictionary, Uid, Value};
+
+        let integer = |value: u64| Value::Integer(value.into());
+        let signed = |value: i64| Value::Integer(value.into());
+
+        let mut class = Dictionary::new();
+        class.insert("$classname".to_owned(), Value::String("MBFile".to_owned()));
+        class.insert(
+            "$classes".to_owned(),
+            Value::Array(vec![
+                Value::String("MBFile".to_owned()),
+                Value::String("NSObject".to_owned()),
+            ]),
+        );
+
+        let mut root = Dictionary::new();
+        root.insert("$class".to_owned(), Value::Uid(Uid::new(2)));
+        root.insert("Size".to_owned(), integer(self.size));
+        root.insert("Mode".to_owned(), integer(self.mode.into()));
+        root.insert("UserID".to_owned(), integer(self.user_id.into()));
+        root.insert("GroupID".to_owned(), integer(self.group_id.into()));
+        root.insert("InodeNumber".to_owned(), integer(self.inode_number));
+        root.insert("LastModified".to_owned(), signed(self.last_modified));
+        root.insert(
+            "LastStatusChange".to_owned(),
+            signed(self.last_status_change),
+        );
+        root.insert("Birth".to_owned(), signed(self.birth));
+        root.insert(
+            "ProtectionClass".to_owned(),
+            integer(self.protection_class.into()),
+        );
+        root.insert("Flags".to_owned(), integer(self.flags));
+
+        let mut objects = vec![Value::String("$null".to_owned()), Value::Boolean(false)];
+        objects.push(Value::Dictionary(class));
+
+        if let Some(target) = &self.target {
+            root.insert(
+                "Target".to_owned(),
+                Value::Uid(Uid::new(u64::try_from(objects.len())?)),
+            );
+            objects.push(Value::String(target.clone()));
+        }
+
+        if let Some(key) = &self.encryption_key {
+            let mut data = Dictionary::new();
+            data.insert("NS.data".to_owned(), Value::Data(key.clone()));
+            root.insert(
+                "EncryptionKey".to_owned(),
+                Value::Uid(Uid::new(u64::try_from(objects.len())?)),
+            );
+            objects.push(Value::Dictionary(data));
+        }
+
+        objects[1] = Value::Dictionary(root);
+
+        let mut top = Dictionary::new();
+        top.insert("root".to_owned(), Value::Uid(Uid::new(1)));
+
+        let mut archive = Dictionary::new();
+        archive.insert(
+            "$archiver".to_owned(),
+            Value::String("NSKeyedArchiver".to_owned()),
+        );
+        archive.insert("$version".to_owned(), Value::Integer(100_000.into()));
+        archive.insert("$objects".to_owned(), Value::Array(objects));
+        archive.insert("$top".to_owned(), Value::Dictionary(top));
+
+        let mut bytes = Vec::new();
+        Value::Dictionary(archive).to_writer_binary(&mut bytes)?;
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn create_test_mb_file() -> MbFile {
+        MbFile {
+            size: 1024,
+            mode: 0o100_644,
+            user_id: 501,
+            group_id: 501,
+            inode_number: 987_654,
+            last_modified: 1_700_000_000,
+            last_status_change: 1_700_000_100,
+            birth: 1_600_000_000,
+            protection_class: 3,
+            flags: 0,
+            target: None,
+            encryption_key: None,
+        }
+    }
+
+    #[test]
+    fn test_from_bytes_regular_file() -> Result<()> {
+        let bytes = create_test_mb_file().to_bytes()?;
+        let mb_file = MbFile::from_bytes(&bytes)?;
+
+        assert_eq!(mb_file, create_test_mb_file());
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_bytes_symlink_and_encryption_key() -> Result<()> {
+        let bytes = MbFile {
+            target: Some("../Media/link".to_owned()),
+            encryption_key: Some(vec![0xAA; 44]),
+            ..create_test_mb_file()
+        }
+        .to_bytes()?;
+        let mb_file = MbFile::from_bytes(&bytes)?;
+
+        assert_eq!(mb_file.target.as_deref(), Some("../Media/link"));
+        assert_eq!(mb_file.encryption_key, Some(vec![0xAA; 44]));
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_domain() -> Result<()> {
+        let bytes = MbFile {
+            target: Some("target".to_owned()),
+            encryption_key: Some(vec![1, 2, 3]),
+            ..create_test_mb_file()
+        }
+        .to_bytes()?;
+        let metadata = MbFile::from_bytes(&bytes)?.to_domain()?;
+
+        assert_eq!(metadata.size(), 1024);
+        assert_eq!(metadata.permissions(), 0o644);
+        assert!(metadata.is_regular_file());
+        assert_eq!(metadata.user_id(), 501);
+        assert_eq!(metadata.inode(), 987_654);
+        assert_eq!(metadata.last_modified().timestamp(), 1_700_000_000);
+        assert_eq!(metadata.last_status_change().timestamp(), 1_700_000_100);
+        assert_eq!(metadata.birth().timestamp(), 1_600_000_000);
+        assert_eq!(metadata.protection_class(), 3);
+        assert_eq!(metadata.target(), Some("target"));
+        assert_eq!(metadata.encryption_key(), Some([1, 2, 3].as_slice()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_bytes_invalid_archive() {
+        let result = MbFile::from_bytes(b"news content");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_to_domain_invalid_timestamp() {
+        let mb_file = MbFile {
+            last_modified: i64::MAX,
+            ..MbFile::default()
+        };
+
+        let result = mb_file.to_domain();
+        assert!(result.is_err());
+        if let Err(error) = result {
+            assert!(error.to_string().contains("Invalid MBFile timestamp"));
+        }
+    }
+}
@@ -1,3 +1,11 @@
+pub mod application_info;
 pub mod backup_info;
+pub mod manifest_info;
+pub mod mb_file;
+pub mod status_info;
 
+pub use application_info::{ApplicationInfo, ITunesMetadata};
 pub use backup_info::BackupInfo;
+pub use manifest_info::ManifestInfo;
+pub use mb_file::MbFile;
+pub use status_info::StatusInfo;
@@ -0,0 +1,72 @@
+use crate::domain::entities::StatusDetails;
+use serde::{Deserialize, Serialize};
+
+/// Represents the structure of an iPhone backup Status.plist file
+///
+/// It describes the state of the last backup operation.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct StatusInfo {
+    #[serde(rename = "SnapshotState", default)]
+    pub snapshot_state: Option<String>,
+
+    #[serde(rename = "IsFullBackup", default)]
+    pub is_full_backup: Option<bool>,
+
+    #[serde(rename = "BackupState", default)]
+    pub backup_state: Option<String>,
+}
+
+impl StatusInfo {
+    /// Converts the plist representation to domain `StatusDetails`
+    #[must_use]
+    #[inline]
+    pub fn to_details(self) -> StatusDetails {
+        StatusDetails {
+            snapshot_state: self.snapshot_state,
+            is_full_backup: self.is_full_backup,
+            backup_state: self.backup_state,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_to_details() -> Result<()> {
+        let content = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>BackupState</key>
+    <string>new</string>
+    <key>Date</key>
+    <date>2024-01-15T10:30:00Z</date>
+    <key>IsFullBackup</key>
+    <false/>
+    <key>SnapshotState</key>
+    <string>finished</string>
+    <key>UUID</key>
+    <string>0F1E2D3C-4B5A-6978-8796-A5B4C3D2E1F0</string>
+    <key>Version</key>
+    <string>3.3</string>
+</dict>
+</plist>"#;
+
+        let status: StatusInfo = plist::from_bytes(content.as_bytes())?;
+
+        assert_eq!(
+            status.to_details(),
+            StatusDetails {
+                snapshot_state: Some("finished".to_owned()),
+                is_full_backup: Some(false),
+                backup_state: Some("new".to_owned()),
+            }
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,263 @@
+use anyhow::{Context as _, Result};
+use plist::{Dictionary, Value};
+use std::io::Cursor;
+
+/// A decoded `NSKeyedArchiver` property list
+///
+/// Keyed archives store every object in a flat `$objects` array and refer to them
+/// through `UID` values. This type resolves those references so that callers can
+/// walk the object graph starting from `$top`.
+#[derive(Debug, Clone)]
+pub struct KeyedArchive {
+    /// Objects referenced by UID
+    objects: Vec<Value>,
+    /// Top-level entries of the archive
+    top: Dictionary,
+}
+
+impl KeyedArchive {
+    /// Parses a keyed archive from binary or XML plist bytes
+    ///
+    /// # Errors
+    /// Returns an error if the bytes are not a plist or lack `$objects`/`$top`
+    #[inline]
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let value = Value::from_reader(Cursor::new(bytes))
+            .with_context(|| "Failed to parse keyed archive")?;
+        let mut archive = value
+            .into_dictionary()
+            .context("Keyed archive is not a dictionary")?;
+
+        let objects = archive
+            .remove("$objects")
+            .and_then(Value::into_array)
+            .context("Keyed archive has no $objects array")?;
+        let top = archive
+            .remove("$top")
+            .and_then(Value::into_dictionary)
+            .context("Keyed archive has no $top dictionary")?;
+
+        Ok(Self { objects, top })
+    }
+
+    /// Returns the object referenced by the given `$top` key
+    ///
+    /// # Errors
+    /// Returns an error if the key is missing or refers to an unknown object
+    #[inline]
+    pub fn top(&self, key: &str) -> Result<&Value> {
+        let value = self
+            .top
+            .get(key)
+            .with_context(|| format!("Keyed archive has no $top entry: {key}"))?;
+        self.resolve(value)
+    }
+
+    /// Returns the root object of the archive as a dictionary
+    ///
+    /// # Errors
+    /// Returns an error if the root object is missing or is not a dictionary
+    #[inline]
+    pub fn root(&self) -> Result<&Dictionary> {
+        self.top("root")?
+            .as_dictionary()
+            .context("Keyed archive root is not a dictionary")
+    }
+
+    /// Follows a UID reference, returning non-UID values unchanged
+    ///
+    /// # Errors
+    /// Returns an error if the UID points outside of `$objects`
+    #[inline]
+    pub fn resolve<'a>(&'a self, value: &'a Value) -> Result<&'a Value> {
+        let Some(uid) = value.as_uid() else {
+            return Ok(value);
+        };
+
+        usize::try_from(uid.get())
+            .ok()
+            .and_then(|index| self.objects.get(index))
+            .with_context(|| format!("Keyed archive UID out of range: {}", uid.get()))
+    }
+
+    /// Resolves a dictionary entry, treating `$null` references as absent
+    ///
+    /// # Errors
+    /// Returns an error if the entry refers to an unknown object
+    #[inline]
+    pub fn field<'a>(&'a self, object: &'a Dictionary, key: &str) -> Result<Option<&'a Value>> {
+        let Some(value) = object.get(key) else {
+            return Ok(None);
+        };
+
+        let resolved = self.resolve(value)?;
+        if resolved.as_string() == Some("$null") {
+            return Ok(None);
+        }
+
+        Ok(Some(resolved))
+    }
+
+    /// Resolves a dictionary entry holding an `NSString`
+    ///
+    /// # Errors
+    /// Returns an error if the entry exists but is not a string
+    #[inline]
+    pub fn string_field<'a>(
+        &'a self,
+        object: &'a Dictionary,
+        key: &str,
+    ) -> Result<Option<&'a str>> {
+        self.field(object, key)?
+            .map(|value| {
+                value
+                    .as_string()
+                    .with_context(|| format!("Keyed archive field is not a string: {key}"))
+            })
+            .transpose()
+    }
+
+    /// Resolves a dictionary entry holding `NSData` or `NSMutableData`
+    ///
+    /// # Errors
+    /// Returns an error if the entry exists but holds no data
+    #[inline]
+    pub fn data_field<'a>(&'a self, object: &'a Dictionary, key: &str) -> Result<Option<&'a [u8]>> {
+        let Some(value) = self.field(object, key)? else {
+            return Ok(None);
+        };
+
+        if let Some(data) = value.as_data() {
+            return Ok(Some(data));
+        }
+
+        // NSMutableData is archived as a dictionary with an NS.data entry
+        let data = value
+            .as_dictionary()
+            .and_then(|dictionary| dictionary.get("NS.data"))
+            .map(|data| self.resolve(data))
+            .transpose()?
+            .and_then(Value::as_data)
+            .with_context(|| format!("Keyed archive field is not data: {key}"))?;
+
+        Ok(Some(data))
+    }
+
+    /// Reads an unsigned integer entry
+    ///
+    /// # Errors
+    /// Returns an error if the entry exists but is not a non-negative integer
+    #[inline]
+    pub fn unsigned_field(&self, object: &Dictionary, key: &str) -> Result<Option<u64>> {
+        self.field(object, key)?
+            .map(|value| {
+                value
+                    .as_unsigned_integer()
+                    .with_context(|| format!("Keyed archive field is not an integer: {key}"))
+            })
+            .transpose()
+    }
+
+    /// Reads a signed integer entry
+    ///
+    /// # Errors
+    /// Returns an error if the entry exists but is not an integer
+    #[inline]
+    pub fn signed_field(&self, object: &Dictionary, key: &str) -> Result<Option<i64>> {
+        self.field(object, key)?
+            .map(|value| {
+                value
+                    .as_signed_integer()
+                    .with_context(|| format!("Keyed archive field is not an integer: {key}"))
+            })
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use plist::Uid;
+
+    fn create_archive(root: Dictionary, mut objects: Vec<Value>) -> Result<Vec<u8>> {
+        objects.insert(0, Value::String("$null".to_owned()));
+        objects.insert(1, Value::Dictionary(root));
+
+        let mut top = Dictionary::new();
+        top.insert("root".to_owned(), Value::Uid(Uid::new(1)));
+
+        let mut archive = Dictionary::new();
+        archive.insert(
+            "$archiver".to_owned(),
+            Value::String("NSKeyedArchiver".to_owned()),
+        );
+        archive.insert("$objects".to_owned(), Value::Array(objects));
+        archive.insert("$top".to_owned(), Value::Dictionary(top));
+
+        let mut bytes = Vec::new();
+        Value::Dictionary(archive).to_writer_binary(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    #[test]
+    fn test_resolves_uid_references() -> Result<()> {
+        let mut root = Dictionary::new();
+        root.insert("Name".to_owned(), Value::Uid(Uid::new(2)));
+        root.insert("Missing".to_owned(), Value::Uid(Uid::new(0)));
+        root.insert("Size".to_owned(), Value::Integer(42.into()));
+        let bytes = create_archive(root, vec![Value::String("hello".to_owned())])?;
+
+        let archive = KeyedArchive::from_bytes(&bytes)?;
+        let decoded = archive.root()?;
+
+        assert_eq!(archive.string_field(decoded, "Name")?, Some("hello"));
+        assert_eq!(archive.string_field(decoded, "Missing")?, None);
+        assert_eq!(archive.string_field(decoded, "Absent")?, None);
+        assert_eq!(archive.unsigned_field(decoded, "Size")?, Some(42));
+        assert_eq!(archive.signed_field(decoded, "Size")?, Some(42));
+        Ok(())
+    }
+
+    #[test]
+    fn test_resolves_mutable_data() -> Result<()> {
+        let mut data = Dictionary::new();
+        data.insert("NS.data".to_owned(), Value::Data(vec![1, 2, 3]));
+
+        let mut root = Dictionary::new();
+        root.insert("Key".to_owned(), Value::Uid(Uid::new(2)));
+        root.insert("Raw".to_owned(), Value::Data(vec![4, 5]));
+        let bytes = create_archive(root, vec![Value::Dictionary(data)])?;
+
+        let archive = KeyedArchive::from_bytes(&bytes)?;
+        let decoded = archive.root()?;
+
+        assert_eq!(
+            archive.data_field(decoded, "Key")?,
+            Some([1, 2, 3].as_slice())
+        );
+        assert_eq!(archive.data_field(decoded, "Raw")?, Some([4, 5].as_slice()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_uid_out_of_range() -> Result<()> {
+        let mut root = Dictionary::new();
+        root.insert("Name".to_owned(), Value::Uid(Uid::new(99)));
+        let bytes = create_archive(root, vec![])?;
+
+        let archive = KeyedArchive::from_bytes(&bytes)?;
+        let result = archive.string_field(archive.root()?, "Name");
+
+        assert!(result.is_err());
+        if let Err(error) = result {
+            assert!(error.to_string().contains("UID out of range"));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_not_a_keyed_archive() {
+        let result = KeyedArchive::from_bytes(b"not a plist");
+        assert!(result.is_err());
+    }
+}
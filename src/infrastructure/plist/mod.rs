@@ -1 +1,4 @@
 pub mod entities;
+pub mod keyed_archive;
+
+pub use keyed_archive::KeyedArchive;
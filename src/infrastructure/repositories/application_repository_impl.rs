@@ -0,0 +1,163 @@
+use crate::domain::entities::Application;
+use crate::domain::repositories::ApplicationRepository;
+use crate::infrastructure::plist::entities::{BackupInfo, ManifestInfo};
+use anyhow::{Context as _, Result};
+use serde::de::DeserializeOwned;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tokio::fs;
+
+/// Implementation of `ApplicationRepository` reading the plist files of a backup
+///
+/// Applications are taken from the `Applications` dictionary of Manifest.plist, which
+/// holds their App Store metadata, and from `Installed Applications` in Info.plist, which
+/// only lists bundle identifiers.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct ApplicationRepositoryImpl {
+    /// Backup directory containing the plist files
+    backup_directory: PathBuf,
+}
+
+impl ApplicationRepositoryImpl {
+    /// Creates a new instance for a backup directory
+    #[must_use]
+    #[inline]
+    pub fn new<P: AsRef<Path>>(backup_directory: P) -> Self {
+        Self {
+            backup_directory: backup_directory.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Reads a plist file of the backup, returning `None` if it does not exist
+    async fn read_plist<T: DeserializeOwned>(&self, name: &str) -> Result<Option<T>> {
+        let path = self.backup_directory.join(name);
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let content = fs::read(&path)
+            .await
+            .with_context(|| format!("Failed to read {name} file: {}", path.display()))?;
+        let value = plist::from_bytes(&content)
+            .with_context(|| format!("Failed to parse {name} file: {}", path.display()))?;
+        Ok(Some(value))
+    }
+}
+
+impl ApplicationRepository for ApplicationRepositoryImpl {
+    #[inline]
+    async fn find_all(&self) -> Result<Vec<Application>> {
+        let mut applications: BTreeMap<String, Application> = self
+            .read_plist::<ManifestInfo>("Manifest.plist")
+            .await?
+            .map(|manifest| manifest.applications)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(bundle_id, info)| (bundle_id.clone(), info.to_domain(bundle_id)))
+            .collect();
+
+        let installed = self
+            .read_plist::<BackupInfo>("Info.plist")
+            .await?
+            .map(|info| info.installed_applications)
+            .unwrap_or_default();
+        for bundle_id in installed {
+            applications
+                .entry(bundle_id.clone())
+                .or_insert_with(|| Application::new(bundle_id));
+        }
+
+        Ok(applications.into_values().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use assert_fs::TempDir;
+    use assert_fs::prelude::*;
+    use pretty_assertions::assert_eq;
+
+    #[tokio::test]
+    async fn test_find_all_merges_manifest_and_info() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        temp_dir.child("Manifest.plist").write_str(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Applications</key>
+    <dict>
+        <key>net.whatsapp.WhatsApp</key>
+        <dict>
+            <key>CFBundleIdentifier</key>
+            <string>net.whatsapp.WhatsApp</string>
+            <key>CFBundleVersion</key>
+            <string>566</string>
+        </dict>
+    </dict>
+    <key>IsEncrypted</key>
+    <false/>
+</dict>
+</plist>"#,
+        )?;
+        temp_dir.child("Info.plist").write_str(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Device Name</key>
+    <string>iPhone 15 Pro</string>
+    <key>Installed Applications</key>
+    <array>
+        <string>net.whatsapp.WhatsApp</string>
+        <string>com.burbn.instagram</string>
+    </array>
+    <key>Last Backup Date</key>
+    <string>2024-01-15T10:30:00Z</string>
+    <key>Product Name</key>
+    <string>iPhone16,1</string>
+    <key>Unique Identifier</key>
+    <string>a1b2c3d4e5f67890123456789</string>
+</dict>
+</plist>"#,
+        )?;
+
+        let repo = ApplicationRepositoryImpl::new(temp_dir.path());
+        let applications = repo.find_all().await?;
+
+        assert_eq!(
+            applications,
+            vec![
+                Application::new("com.burbn.instagram".to_owned()),
+                Application::new("net.whatsapp.WhatsApp".to_owned())
+                    .with_version(Some("566".to_owned())),
+            ]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_find_all_without_plists() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+
+        let repo = ApplicationRepositoryImpl::new(temp_dir.path());
+
+        assert_eq!(repo.find_all().await?, Vec::new());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_find_all_malformed_manifest() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        temp_dir.child("Manifest.plist").write_str("not a plist")?;
+
+        let repo = ApplicationRepositoryImpl::new(temp_dir.path());
+        let result = repo.find_all().await;
+
+        assert!(result.is_err_and(|e| e.to_string().contains("Failed to parse Manifest.plist")));
+        Ok(())
+    }
+}
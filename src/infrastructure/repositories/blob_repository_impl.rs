@@ -0,0 +1,302 @@
+use anyhow::{Context as _, Result};
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use crate::domain::entities::File;
+use crate::domain::repositories::BlobRepository;
+use crate::domain::value_objects::{FileId, FileMetadata};
+use crate::infrastructure::crypto::{DecryptingReader, Keybag};
+
+/// Implementation of `BlobRepository` for a backup directory on disk
+///
+/// Blobs are stored as `XX/fileID`, where `XX` is the first two characters of the file ID.
+/// Legacy backups (iOS 9 and earlier) store them directly as `fileID` instead.
+/// When an unlocked keybag is attached, blobs of encrypted backups are decrypted on read.
+#[derive(Clone)]
+pub struct BlobRepositoryImpl {
+    /// Backup directory
+    backup_dir: PathBuf,
+    /// Unlocked keybag of an encrypted backup
+    keybag: Option<Keybag>,
+}
+
+impl BlobRepositoryImpl {
+    /// Creates a new `BlobRepositoryImpl` for an unencrypted backup
+    #[must_use]
+    #[inline]
+    pub fn new<P: AsRef<Path>>(backup_dir: P) -> Self {
+        Self {
+            backup_dir: backup_dir.as_ref().to_path_buf(),
+            keybag: None,
+        }
+    }
+
+    /// Attaches the unlocked keybag used to decrypt blobs
+    #[must_use]
+    #[inline]
+    pub fn with_keybag(mut self, keybag: Keybag) -> Self {
+        self.keybag = Some(keybag);
+        self
+    }
+
+    /// Returns the backup directory
+    #[must_use]
+    #[inline]
+    pub fn backup_dir(&self) -> &Path {
+        &self.backup_dir
+    }
+}
+
+impl BlobRepository for BlobRepositoryImpl {
+    #[inline]
+    fn locate(&self, file: &File) -> Option<PathBuf> {
+        let file_id = file.id().value();
+        let prefix = file_id.get(0..2)?;
+        let hashed_path = self.backup_dir.join(prefix).join(file_id);
+        if hashed_path.is_file() {
+            return Some(hashed_path);
+        }
+
+        let flat_path = self.backup_dir.join(file_id);
+        flat_path.is_file().then_some(flat_path)
+    }
+
+    #[inline]
+    fn open(&self, file: &File) -> Result<Option<Box<dyn Read + Send>>> {
+        let Some(path) = self.locate(file) else {
+            return Ok(None);
+        };
+
+        let blob = fs::File::open(&path)
+            .with_context(|| format!("Failed to open backup file: {}", path.display()))?;
+
+        let encryption_key = file.file_metadata().and_then(FileMetadata::encryption_key);
+
+        match (encryption_key, &self.keybag) {
+            (None, _) => Ok(Some(Box::new(blob))),
+            (Some(_), None) => Err(anyhow::anyhow!(
+                "File is encrypted but no backup password was supplied"
+            )),
+            (Some(encryption_key), Some(keybag)) => {
+                let key = keybag
+                    .unwrap_class_prefixed_key(encryption_key)
+                    .context("Failed to unwrap file encryption key")?;
+                let size = file.size().unwrap_or_default();
+
+                Ok(Some(Box::new(DecryptingReader::new(blob, &key, size))))
+            }
+        }
+    }
+
+    #[inline]
+    fn list(&self) -> Result<Vec<(FileId, PathBuf)>> {
+        let mut blobs = Vec::new();
+        for entry in read_dir(&self.backup_dir)? {
+            let path = entry.path();
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+
+            if entry.file_type()?.is_dir() {
+                // Hashed layout: `XX/fileID`
+                if name.len() != 2 {
+                    continue;
+                }
+                for blob in read_dir(&path)? {
+                    let blob_name = blob.file_name();
+                    if let Ok(file_id) = FileId::new(&blob_name.to_string_lossy())
+                        && file_id.value().starts_with(name.as_ref())
+                        && blob.file_type()?.is_file()
+                    {
+                        blobs.push((file_id, blob.path()));
+                    }
+                }
+            } else if let Ok(file_id) = FileId::new(&name) {
+                // Flat layout of legacy backups: `fileID`
+                blobs.push((file_id, path));
+            }
+        }
+
+        blobs.sort_by(|(a, _), (b, _)| a.value().cmp(b.value()));
+        Ok(blobs)
+    }
+}
+
+/// Reads the entries of a directory
+fn read_dir(path: &Path) -> Result<Vec<fs::DirEntry>> {
+    fs::read_dir(path)
+        .and_then(Iterator::collect)
+        .with_context(|| format!("Failed to list directory: {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::value_objects::{Domain, FileFlags, FileId, RelativePath};
+    use crate::infrastructure::crypto::cipher::encrypt_cbc_for_test;
+    use assert_fs::TempDir;
+    use assert_fs::prelude::*;
+    use pretty_assertions::assert_eq;
+
+    const FILE_ID: &str = "da39a3ee5e6b4b0d3255bfef95601890afd80709";
+    const CLASS_KEY: [u8; 32] = [3; 32];
+    const FILE_KEY: [u8; 32] = [9; 32];
+
+    fn create_test_file(file_metadata: Option<FileMetadata>) -> Result<File> {
+        let file = File::new(
+            FileId::new(FILE_ID)?,
+            Domain::new("HomeDomain".to_owned())?,
+            RelativePath::new("Library/test.txt".to_owned())?,
+            FileFlags::REGULAR_FILE,
+            vec![],
+        );
+
+        Ok(match file_metadata {
+            Some(file_metadata) => file.with_file_metadata(file_metadata),
+            None => file,
+        })
+    }
+
+    fn read_to_string(reader: Option<Box<dyn Read + Send>>) -> Result<String> {
+        let mut content = String::new();
+        reader
+            .context("Expected the blob to exist")?
+            .read_to_string(&mut content)?;
+        Ok(content)
+    }
+
+    fn create_unlocked_keybag() -> Result<Keybag> {
+        let mut keybag = Keybag::parse(&Keybag::create_for_test("secret", &[(3, CLASS_KEY)])?)?;
+        keybag.unlock("secret")?;
+        Ok(keybag)
+    }
+
+    #[test]
+    fn test_locate_hashed_layout() -> Result<()> {
+        let temp_backup = TempDir::new()?;
+        temp_backup
+            .child("da")
+            .child(FILE_ID)
+            .write_str("content")?;
+
+        let repo = BlobRepositoryImpl::new(temp_backup.path());
+        let file = create_test_file(None)?;
+
+        assert_eq!(
+            repo.locate(&file),
+            Some(temp_backup.path().join("da").join(FILE_ID))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_locate_flat_layout() -> Result<()> {
+        let temp_backup = TempDir::new()?;
+        temp_backup.child(FILE_ID).write_str("content")?;
+
+        let repo = BlobRepositoryImpl::new(temp_backup.path());
+        let file = create_test_file(None)?;
+
+        assert_eq!(repo.locate(&file), Some(temp_backup.path().join(FILE_ID)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_list_blobs() -> Result<()> {
+        let temp_backup = TempDir::new()?;
+        let legacy_id = "3d0d7e5fb2ce288813306e4d4636395e047a3d28";
+        temp_backup
+            .child("da")
+            .child(FILE_ID)
+            .write_str("content")?;
+        temp_backup.child(legacy_id).write_str("content")?;
+        // Other files of the backup are not blobs
+        temp_backup.child("Manifest.db").touch()?;
+        temp_backup.child("Info.plist").touch()?;
+        temp_backup.child("ab").child("not-a-blob").touch()?;
+        temp_backup.child("ab").child(FILE_ID).touch()?;
+
+        let repo = BlobRepositoryImpl::new(temp_backup.path());
+        let blobs = repo.list()?;
+
+        assert_eq!(
+            blobs,
+            vec![
+                (FileId::new(legacy_id)?, temp_backup.path().join(legacy_id)),
+                (
+                    FileId::new(FILE_ID)?,
+                    temp_backup.path().join("da").join(FILE_ID)
+                ),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_open_missing_blob() -> Result<()> {
+        let temp_backup = TempDir::new()?;
+        let repo = BlobRepositoryImpl::new(temp_backup.path());
+
+        assert!(repo.locate(&create_test_file(None)?).is_none());
+        assert!(repo.open(&create_test_file(None)?)?.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_open_unencrypted_blob() -> Result<()> {
+        let temp_backup = TempDir::new()?;
+        temp_backup
+            .child("da")
+            .child(FILE_ID)
+            .write_str("plain content")?;
+
+        let repo = BlobRepositoryImpl::new(temp_backup.path());
+        let content = read_to_string(repo.open(&create_test_file(None)?)?)?;
+
+        assert_eq!(content, "plain content");
+        Ok(())
+    }
+
+    #[test]
+    fn test_open_encrypted_blob() -> Result<()> {
+        let temp_backup = TempDir::new()?;
+        temp_backup
+            .child("da")
+            .child(FILE_ID)
+            .write_binary(&encrypt_cbc_for_test(&FILE_KEY, b"secret content"))?;
+
+        let file_metadata = FileMetadata::new(14, 0o100_644)
+            .with_protection_class(3)
+            .with_encryption_key(Some(Keybag::wrap_for_test(3, CLASS_KEY, &FILE_KEY)?));
+        let file = create_test_file(Some(file_metadata))?;
+
+        let repo =
+            BlobRepositoryImpl::new(temp_backup.path()).with_keybag(create_unlocked_keybag()?);
+        let content = read_to_string(repo.open(&file)?)?;
+
+        assert_eq!(content, "secret content");
+        Ok(())
+    }
+
+    #[test]
+    fn test_open_encrypted_blob_without_keybag() -> Result<()> {
+        let temp_backup = TempDir::new()?;
+        temp_backup
+            .child("da")
+            .child(FILE_ID)
+            .write_str("ciphertext")?;
+
+        let file_metadata = FileMetadata::new(10, 0o100_644)
+            .with_encryption_key(Some(Keybag::wrap_for_test(3, CLASS_KEY, &FILE_KEY)?));
+        let file = create_test_file(Some(file_metadata))?;
+
+        let repo = BlobRepositoryImpl::new(temp_backup.path());
+        let result = repo.open(&file);
+
+        assert!(result.is_err());
+        if let Err(error) = result {
+            assert!(error.to_string().contains("no backup password"));
+        }
+        Ok(())
+    }
+}
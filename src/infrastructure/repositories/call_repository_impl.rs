@@ -0,0 +1,137 @@
+use anyhow::{Context as _, Result};
+use sea_orm::{ConnectionTrait as _, FromQueryResult, Statement};
+use std::time::Duration;
+
+use crate::domain::entities::{Call, CallDirection};
+use crate::domain::repositories::CallRepository;
+use crate::domain::value_objects::AppleTimestamp;
+use crate::infrastructure::database::DatabaseConnection;
+
+/// Row of the `ZCALLRECORD` table
+#[derive(Debug, FromQueryResult)]
+struct CallRow {
+    id: i64,
+    address: Option<String>,
+    name: Option<String>,
+    date: Option<f64>,
+    duration: Option<f64>,
+    originated: Option<i64>,
+    answered: Option<i64>,
+    service_provider: Option<String>,
+}
+
+/// Implementation of `CallRepository` reading the `CallHistory.storedata` Core Data store
+pub struct CallRepositoryImpl {
+    /// Connection to `CallHistory.storedata`
+    db: DatabaseConnection,
+}
+
+impl CallRepositoryImpl {
+    /// Location of the database in the backup
+    pub const DOMAIN: &str = "HomeDomain";
+    /// Relative path of the database in its domain
+    pub const RELATIVE_PATH: &str = "Library/CallHistoryDB/CallHistory.storedata";
+
+    /// Creates a new `CallRepositoryImpl` for a connection to `CallHistory.storedata`
+    #[must_use]
+    #[inline]
+    pub const fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+}
+
+impl CallRepository for CallRepositoryImpl {
+    #[inline]
+    async fn find_all(&self) -> Result<Vec<Call>> {
+        // The address is a blob in some versions of the store, and whole numbers of
+        // seconds are stored as integers
+        let sql = "SELECT Z_PK AS id, CAST(ZADDRESS AS TEXT) AS address, ZNAME AS name, \
+                   CAST(ZDATE AS REAL) AS date, CAST(ZDURATION AS REAL) AS duration, \
+                   ZORIGINATED AS originated, \
+                   ZANSWERED AS answered, ZSERVICE_PROVIDER AS service_provider \
+                   FROM ZCALLRECORD ORDER BY ZDATE DESC, Z_PK DESC";
+        let connection = self.db.get_connection();
+        let statement = Statement::from_string(connection.get_database_backend(), sql);
+        let rows = CallRow::find_by_statement(statement)
+            .all(connection)
+            .await
+            .context("Failed to query CallHistory.storedata")?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| Call {
+                id: row.id,
+                address: row.address.filter(|address| !address.is_empty()),
+                name: row.name.filter(|name| !name.is_empty()),
+                date: row
+                    .date
+                    .and_then(AppleTimestamp::from_fractional_seconds)
+                    .map(AppleTimestamp::to_datetime),
+                duration: row
+                    .duration
+                    .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+                    .unwrap_or_default(),
+                direction: if row.originated.is_some_and(|value| value != 0) {
+                    CallDirection::Outgoing
+                } else {
+                    CallDirection::Incoming
+                },
+                answered: row.answered.is_some_and(|value| value != 0),
+                service_provider: row.service_provider,
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use sea_orm::Database;
+
+    #[tokio::test]
+    async fn test_find_all() -> Result<()> {
+        let connection = Database::connect("sqlite::memory:").await?;
+        connection
+            .execute_unprepared(
+                "CREATE TABLE ZCALLRECORD (Z_PK INTEGER PRIMARY KEY, ZADDRESS BLOB, ZNAME TEXT, \
+                 ZDATE TIMESTAMP, ZDURATION FLOAT, ZORIGINATED INTEGER, ZANSWERED INTEGER, \
+                 ZSERVICE_PROVIDER VARCHAR);
+                 INSERT INTO ZCALLRECORD VALUES \
+                 (1, CAST('+15550001' AS BLOB), NULL, 726978600.5, 62.7, 1, 1, \
+                 'com.apple.Telephony'), \
+                 (2, '', 'Jane', 726978700, 0, 0, 0, 'net.whatsapp.WhatsApp');",
+            )
+            .await?;
+        let repo = CallRepositoryImpl::new(DatabaseConnection::new_from_connection(connection));
+
+        let calls = repo.find_all().await?;
+
+        assert_eq!(
+            calls,
+            vec![
+                Call {
+                    id: 2,
+                    address: None,
+                    name: Some("Jane".to_owned()),
+                    date: Some("2024-01-15T02:31:40Z".parse()?),
+                    duration: Duration::ZERO,
+                    direction: CallDirection::Incoming,
+                    answered: false,
+                    service_provider: Some("net.whatsapp.WhatsApp".to_owned()),
+                },
+                Call {
+                    id: 1,
+                    address: Some("+15550001".to_owned()),
+                    name: None,
+                    date: Some("2024-01-15T02:30:00.500Z".parse()?),
+                    duration: Duration::from_secs_f64(62.7),
+                    direction: CallDirection::Outgoing,
+                    answered: true,
+                    service_provider: Some("com.apple.Telephony".to_owned()),
+                },
+            ]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,374 @@
+use anyhow::{Context as _, Result};
+use chrono::NaiveDate;
+use sea_orm::{ConnectionTrait as _, FromQueryResult, Statement};
+use std::collections::BTreeMap;
+
+use crate::domain::entities::{Contact, Labeled, PostalAddress};
+use crate::domain::repositories::ContactRepository;
+use crate::domain::value_objects::AppleTimestamp;
+use crate::infrastructure::database::DatabaseConnection;
+
+/// `ABMultiValue.property` of phone numbers
+const PROPERTY_PHONE: i64 = 3;
+/// `ABMultiValue.property` of email addresses
+const PROPERTY_EMAIL: i64 = 4;
+/// `ABMultiValue.property` of postal addresses
+const PROPERTY_ADDRESS: i64 = 5;
+/// `ABMultiValue.property` of dates
+const PROPERTY_DATE: i64 = 12;
+/// `ABMultiValue.property` of web pages
+const PROPERTY_URL: i64 = 22;
+/// `ABMultiValue.property` of related names
+const PROPERTY_RELATED_NAME: i64 = 23;
+
+/// Row of the `ABPerson` table
+#[derive(Debug, FromQueryResult)]
+struct PersonRow {
+    id: i64,
+    first_name: Option<String>,
+    middle_name: Option<String>,
+    last_name: Option<String>,
+    prefix: Option<String>,
+    suffix: Option<String>,
+    nickname: Option<String>,
+    organization: Option<String>,
+    department: Option<String>,
+    job_title: Option<String>,
+    note: Option<String>,
+    birthday: Option<f64>,
+}
+
+/// Row of `ABMultiValue` joined with its `ABMultiValueLabel`
+#[derive(Debug, FromQueryResult)]
+struct MultiValueRow {
+    id: i64,
+    record_id: i64,
+    property: i64,
+    label: Option<String>,
+    value: Option<String>,
+}
+
+/// Row of `ABMultiValueEntry` joined with its `ABMultiValueEntryKey`
+#[derive(Debug, FromQueryResult)]
+struct EntryRow {
+    parent_id: i64,
+    key: String,
+    value: Option<String>,
+}
+
+/// Row of `ABFullSizeImage` or `ABThumbnailImage` in `AddressBookImages.sqlitedb`
+#[derive(Debug, FromQueryResult)]
+struct ImageRow {
+    record_id: i64,
+    data: Option<Vec<u8>>,
+}
+
+/// Row of `sqlite_master` naming a table
+#[derive(Debug, FromQueryResult)]
+struct TableRow {
+    name: String,
+}
+
+/// Implementation of `ContactRepository` reading the `AddressBook.sqlitedb` database
+pub struct ContactRepositoryImpl {
+    /// Connection to `AddressBook.sqlitedb`
+    db: DatabaseConnection,
+    /// Connection to `AddressBookImages.sqlitedb`, if the backup has one
+    images: Option<DatabaseConnection>,
+}
+
+impl ContactRepositoryImpl {
+    /// Location of the databases in the backup
+    pub const DOMAIN: &str = "HomeDomain";
+    /// Relative path of the address book in its domain
+    pub const RELATIVE_PATH: &str = "Library/AddressBook/AddressBook.sqlitedb";
+    /// Relative path of the contact photos in their domain
+    pub const IMAGES_RELATIVE_PATH: &str = "Library/AddressBook/AddressBookImages.sqlitedb";
+
+    /// Creates a new `ContactRepositoryImpl` for a connection to `AddressBook.sqlitedb`
+    #[must_use]
+    #[inline]
+    pub const fn new(db: DatabaseConnection) -> Self {
+        Self { db, images: None }
+    }
+
+    /// Sets the connection to `AddressBookImages.sqlitedb` to read photos from
+    #[must_use]
+    #[inline]
+    pub fn with_images(mut self, images: Option<DatabaseConnection>) -> Self {
+        self.images = images;
+        self
+    }
+
+    /// Runs a query on a database and maps its rows
+    async fn query<T: FromQueryResult>(db: &DatabaseConnection, sql: &str) -> Result<Vec<T>> {
+        let connection = db.get_connection();
+        let statement = Statement::from_string(connection.get_database_backend(), sql);
+        T::find_by_statement(statement)
+            .all(connection)
+            .await
+            .with_context(|| format!("Failed to query the address book: {sql}"))
+    }
+
+    /// Reads the photos of the contacts, preferring full-size images over thumbnails
+    async fn find_photos(&self) -> Result<BTreeMap<i64, Vec<u8>>> {
+        let Some(images) = &self.images else {
+            return Ok(BTreeMap::new());
+        };
+
+        let tables: Vec<TableRow> = Self::query(
+            images,
+            "SELECT name FROM sqlite_master WHERE type = 'table'",
+        )
+        .await?;
+        let has_table = |name: &str| tables.iter().any(|table| table.name == name);
+
+        // Later rows win, so thumbnails come first and the largest format last
+        let mut queries = Vec::new();
+        if has_table("ABThumbnailImage") {
+            queries.push("SELECT record_id, data FROM ABThumbnailImage ORDER BY format");
+        }
+        if has_table("ABFullSizeImage") {
+            queries.push("SELECT record_id, data FROM ABFullSizeImage");
+        }
+
+        let mut photos = BTreeMap::new();
+        for sql in queries {
+            let rows: Vec<ImageRow> = Self::query(images, sql).await?;
+            for row in rows {
+                if let Some(data) = row.data.filter(|data| !data.is_empty()) {
+                    photos.insert(row.record_id, data);
+                }
+            }
+        }
+        Ok(photos)
+    }
+}
+
+/// Removes the markers around built-in labels, e.g. `_$!<Mobile>!$_` becomes `Mobile`
+fn normalize_label(label: Option<String>) -> Option<String> {
+    let label = label.filter(|label| !label.is_empty())?;
+    Some(
+        label
+            .strip_prefix("_$!<")
+            .and_then(|label| label.strip_suffix(">!$_"))
+            .map_or_else(|| label.clone(), str::to_owned),
+    )
+}
+
+/// Parses a date stored as seconds since the Apple epoch
+///
+/// Dates are stored at midnight or noon UTC, so the UTC date is the date that was entered.
+fn parse_date(seconds: f64) -> Option<NaiveDate> {
+    AppleTimestamp::from_fractional_seconds(seconds)
+        .map(|timestamp| timestamp.to_datetime().date_naive())
+}
+
+impl ContactRepository for ContactRepositoryImpl {
+    #[inline]
+    async fn find_all(&self) -> Result<Vec<Contact>> {
+        let people: Vec<PersonRow> = Self::query(
+            &self.db,
+            "SELECT ROWID AS id, First AS first_name, Middle AS middle_name, \
+             Last AS last_name, Prefix AS prefix, Suffix AS suffix, Nickname AS nickname, \
+             Organization AS organization, Department AS department, JobTitle AS job_title, \
+             Note AS note, CAST(Birthday AS REAL) AS birthday \
+             FROM ABPerson ORDER BY ROWID",
+        )
+        .await?;
+        let multi_values: Vec<MultiValueRow> = Self::query(
+            &self.db,
+            "SELECT mv.UID AS id, mv.record_id, mv.property, l.value AS label, mv.value \
+             FROM ABMultiValue AS mv LEFT JOIN ABMultiValueLabel AS l ON l.ROWID = mv.label \
+             ORDER BY mv.record_id, mv.UID",
+        )
+        .await?;
+        let entries: Vec<EntryRow> = Self::query(
+            &self.db,
+            "SELECT e.parent_id, k.value AS key, e.value \
+             FROM ABMultiValueEntry AS e JOIN ABMultiValueEntryKey AS k ON k.ROWID = e.key",
+        )
+        .await?;
+        let mut photos = self.find_photos().await?;
+
+        let mut addresses: BTreeMap<i64, PostalAddress> = BTreeMap::new();
+        for entry in entries {
+            let address = addresses.entry(entry.parent_id).or_default();
+            let field = match entry.key.as_str() {
+                "Street" => &mut address.street,
+                "City" => &mut address.city,
+                "State" => &mut address.state,
+                "ZIP" => &mut address.postal_code,
+                "Country" => &mut address.country,
+                "CountryCode" => &mut address.country_code,
+                _ => continue,
+            };
+            *field = entry.value.filter(|value| !value.is_empty());
+        }
+
+        let mut contacts: BTreeMap<i64, Contact> = people
+            .into_iter()
+            .map(|person| {
+                let mut contact = Contact::new(person.id);
+                contact.first_name = person.first_name;
+                contact.middle_name = person.middle_name;
+                contact.last_name = person.last_name;
+                contact.prefix = person.prefix;
+                contact.suffix = person.suffix;
+                contact.nickname = person.nickname;
+                contact.organization = person.organization;
+                contact.department = person.department;
+                contact.job_title = person.job_title;
+                contact.note = person.note;
+                contact.birthday = person.birthday.and_then(parse_date);
+                contact.photo = photos.remove(&person.id);
+                (person.id, contact)
+            })
+            .collect();
+
+        for row in multi_values {
+            let Some(contact) = contacts.get_mut(&row.record_id) else {
+                continue;
+            };
+            let label = normalize_label(row.label);
+            let value = row.value.filter(|value| !value.is_empty());
+
+            match (row.property, value) {
+                (PROPERTY_PHONE, Some(value)) => contact.phones.push(Labeled::new(label, value)),
+                (PROPERTY_EMAIL, Some(value)) => contact.emails.push(Labeled::new(label, value)),
+                (PROPERTY_URL, Some(value)) => contact.urls.push(Labeled::new(label, value)),
+                (PROPERTY_RELATED_NAME, Some(value)) => {
+                    contact.related_names.push(Labeled::new(label, value));
+                }
+                (PROPERTY_DATE, Some(value)) => {
+                    if let Some(date) = value.parse().ok().and_then(parse_date) {
+                        contact.dates.push(Labeled::new(label, date));
+                    }
+                }
+                (PROPERTY_ADDRESS, _) => {
+                    if let Some(address) = addresses.remove(&row.id) {
+                        contact.addresses.push(Labeled::new(label, address));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(contacts.into_values().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use sea_orm::Database;
+
+    /// Creates an in-memory `AddressBook.sqlitedb` with the tables read by the repository
+    async fn create_address_book(rows: &str) -> Result<DatabaseConnection> {
+        let connection = Database::connect("sqlite::memory:").await?;
+        connection
+            .execute_unprepared(
+                "CREATE TABLE ABPerson (ROWID INTEGER PRIMARY KEY, First TEXT, Last TEXT, \
+                 Middle TEXT, Prefix TEXT, Suffix TEXT, Nickname TEXT, Organization TEXT, \
+                 Department TEXT, JobTitle TEXT, Note TEXT, Birthday TEXT);
+                 CREATE TABLE ABMultiValue (UID INTEGER PRIMARY KEY, record_id INTEGER, \
+                 property INTEGER, identifier INTEGER, label INTEGER, value TEXT);
+                 CREATE TABLE ABMultiValueLabel (value TEXT);
+                 CREATE TABLE ABMultiValueEntry (parent_id INTEGER, key INTEGER, value TEXT);
+                 CREATE TABLE ABMultiValueEntryKey (value TEXT);",
+            )
+            .await?;
+        connection.execute_unprepared(rows).await?;
+        Ok(DatabaseConnection::new_from_connection(connection))
+    }
+
+    #[tokio::test]
+    async fn test_find_all() -> Result<()> {
+        let db = create_address_book(
+            "INSERT INTO ABPerson (ROWID, First, Last, Organization, Birthday) \
+             VALUES (1, 'Jane', 'Appleseed', 'Apple', '-31579200');
+             INSERT INTO ABMultiValueLabel (ROWID, value) \
+             VALUES (1, '_$!<Mobile>!$_'), (2, '_$!<Home>!$_'), (3, 'Cabin');
+             INSERT INTO ABMultiValueEntryKey (ROWID, value) \
+             VALUES (1, 'Street'), (2, 'City'), (3, 'ZIP');
+             INSERT INTO ABMultiValue VALUES (10, 1, 3, 0, 1, '+1 555 0001');
+             INSERT INTO ABMultiValue VALUES (11, 1, 4, 0, NULL, 'jane@example.com');
+             INSERT INTO ABMultiValue VALUES (12, 1, 5, 0, 3, NULL);
+             INSERT INTO ABMultiValue VALUES (13, 1, 12, 0, 2, '300000000');
+             INSERT INTO ABMultiValueEntry VALUES (12, 1, '1 Lake Rd'), (12, 2, 'Tahoe'), \
+             (12, 3, '96150');",
+        )
+        .await?;
+        let repo = ContactRepositoryImpl::new(db);
+
+        let contacts = repo.find_all().await?;
+
+        let mut expected = Contact::new(1);
+        expected.first_name = Some("Jane".to_owned());
+        expected.last_name = Some("Appleseed".to_owned());
+        expected.organization = Some("Apple".to_owned());
+        expected.birthday = NaiveDate::from_ymd_opt(2000, 1, 1);
+        expected.phones = vec![Labeled::new(
+            Some("Mobile".to_owned()),
+            "+1 555 0001".to_owned(),
+        )];
+        expected.emails = vec![Labeled::new(None, "jane@example.com".to_owned())];
+        expected.addresses = vec![Labeled::new(
+            Some("Cabin".to_owned()),
+            PostalAddress {
+                street: Some("1 Lake Rd".to_owned()),
+                city: Some("Tahoe".to_owned()),
+                postal_code: Some("96150".to_owned()),
+                ..PostalAddress::default()
+            },
+        )];
+        expected.dates = vec![Labeled::new(
+            Some("Home".to_owned()),
+            NaiveDate::from_ymd_opt(2010, 7, 5).context("invalid date")?,
+        )];
+        assert_eq!(contacts, vec![expected]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_find_all_with_photos() -> Result<()> {
+        let db = create_address_book(
+            "INSERT INTO ABPerson (ROWID, First) VALUES (1, 'Jane'), (2, 'John'), (3, 'Ann');",
+        )
+        .await?;
+        let images = Database::connect("sqlite::memory:").await?;
+        images
+            .execute_unprepared(
+                "CREATE TABLE ABThumbnailImage (record_id INTEGER, format INTEGER, data BLOB);
+                 CREATE TABLE ABFullSizeImage (record_id INTEGER, data BLOB);
+                 INSERT INTO ABThumbnailImage VALUES (1, 0, x'01'), (2, 5, x'03'), (2, 0, x'02');
+                 INSERT INTO ABFullSizeImage VALUES (1, x'FF');",
+            )
+            .await?;
+        let repo = ContactRepositoryImpl::new(db)
+            .with_images(Some(DatabaseConnection::new_from_connection(images)));
+
+        let contacts = repo.find_all().await?;
+
+        let photos: Vec<_> = contacts
+            .iter()
+            .map(|contact| contact.photo.clone())
+            .collect();
+        assert_eq!(photos, vec![Some(vec![0xFF]), Some(vec![0x03]), None]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_normalize_label() {
+        assert_eq!(
+            normalize_label(Some("_$!<HomeFAX>!$_".to_owned())),
+            Some("HomeFAX".to_owned())
+        );
+        assert_eq!(
+            normalize_label(Some("Cabin".to_owned())),
+            Some("Cabin".to_owned())
+        );
+        assert_eq!(normalize_label(Some(String::new())), None);
+    }
+}
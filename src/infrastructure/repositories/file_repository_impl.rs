@@ -1,14 +1,105 @@
 use anyhow::Result;
+use sea_orm::sea_query::{Alias, BinOper, Expr, Func, LikeExpr, SimpleExpr};
 use sea_orm::{ColumnTrait as _, EntityTrait as _, QueryFilter as _, QueryOrder as _};
 
 use crate::domain::entities::File;
 use crate::domain::queries::{BasicQuery, CompositeQuery, FileQuery};
 use crate::domain::repositories::FileRepository;
+use crate::domain::value_objects::{DomainKind, FileId, GlobPattern};
 use crate::infrastructure::database::{
     DatabaseConnection,
     entities::files::{Column, Entity},
 };
 
+/// SQL condition translated from a query
+///
+/// `None` stands for a condition that holds for every row. When the translation is not
+/// `exact`, the condition selects a superset of the matching rows and the results must be
+/// narrowed down with `FileQuery::matches`.
+struct Translation {
+    condition: Option<SimpleExpr>,
+    exact: bool,
+}
+
+impl Translation {
+    /// Condition that holds for every row
+    const fn all(exact: bool) -> Self {
+        Self {
+            condition: None,
+            exact,
+        }
+    }
+
+    /// Condition that selects exactly the matching rows
+    const fn exact(condition: SimpleExpr) -> Self {
+        Self {
+            condition: Some(condition),
+            exact: true,
+        }
+    }
+
+    /// Condition that selects a superset of the matching rows
+    const fn superset(condition: SimpleExpr) -> Self {
+        Self {
+            condition: Some(condition),
+            exact: false,
+        }
+    }
+
+    /// Condition that holds for no row
+    fn none() -> Self {
+        Self {
+            condition: Some(Expr::val(false).into()),
+            exact: true,
+        }
+    }
+
+    /// Conjunction of translations
+    fn and(translations: impl IntoIterator<Item = Self>) -> Self {
+        translations
+            .into_iter()
+            .fold(Self::all(true), |result, translation| Self {
+                condition: match (result.condition, translation.condition) {
+                    (Some(left), Some(right)) => Some(left.and(right)),
+                    (left, right) => left.or(right),
+                },
+                exact: result.exact && translation.exact,
+            })
+    }
+
+    /// Disjunction of translations
+    fn or(translations: impl IntoIterator<Item = Self>) -> Self {
+        let mut translations = translations.into_iter();
+        let Some(first) = translations.next() else {
+            return Self::none();
+        };
+
+        translations.fold(first, |result, translation| Self {
+            condition: match (result.condition, translation.condition) {
+                (Some(left), Some(right)) => Some(left.or(right)),
+                // One side holds for every row, so the disjunction does too
+                _ => None,
+            },
+            exact: result.exact && translation.exact,
+        })
+    }
+
+    /// Negation of a translation
+    fn not(translation: Self) -> Self {
+        if !translation.exact {
+            // The negation of a superset is not a superset of the negation
+            return Self::all(false);
+        }
+
+        translation
+            .condition
+            .map_or_else(Self::none, |condition| Self {
+                condition: Some(condition.not()),
+                exact: true,
+            })
+    }
+}
+
 /// Implementation of `FileRepository` using `SeaORM`
 pub struct FileRepositoryImpl {
     /// Database connection
@@ -23,65 +114,220 @@ impl FileRepositoryImpl {
         Self { db }
     }
 
-    fn apply_basic_query(
-        query: sea_orm::Select<Entity>,
-        basic_query: BasicQuery,
-    ) -> sea_orm::Select<Entity> {
+    fn apply_query(query: &FileQuery) -> Translation {
+        match query {
+            FileQuery::Basic(basic_query) => Self::apply_basic_query(basic_query),
+            FileQuery::Composite(composite_query) => Self::apply_composite_query(composite_query),
+            FileQuery::Not(query) => Translation::not(Self::apply_query(query)),
+            FileQuery::And(queries) => Translation::and(queries.iter().map(Self::apply_query)),
+            FileQuery::Or(queries) => Translation::or(queries.iter().map(Self::apply_query)),
+        }
+    }
+
+    fn apply_basic_query(basic_query: &BasicQuery) -> Translation {
+        Self::apply_basic_query_with_case(basic_query, false)
+    }
+
+    fn apply_basic_query_with_case(basic_query: &BasicQuery, ignore_case: bool) -> Translation {
         match basic_query {
-            BasicQuery::DomainExact(domain) => query.filter(Column::Domain.eq(domain)),
-            BasicQuery::DomainContains(domain) => query.filter(Column::Domain.contains(&domain)),
-            BasicQuery::PathExact(path) => query.filter(Column::RelativePath.eq(path)),
-            BasicQuery::PathContains(path) => query.filter(Column::RelativePath.contains(&path)),
+            // File IDs are normalized to lowercase, so the case never matters
+            BasicQuery::FileId(ids) => {
+                Translation::exact(Column::FileId.is_in(ids.iter().map(FileId::value)))
+            }
+            BasicQuery::DomainExact(domain) => {
+                Self::apply_exact(Column::Domain, domain, ignore_case)
+            }
+            BasicQuery::DomainContains(domain) => {
+                Self::apply_contains(Column::Domain, domain, ignore_case)
+            }
+            BasicQuery::DomainGlob(pattern) => {
+                Self::apply_glob(Column::Domain, pattern, ignore_case)
+            }
+            BasicQuery::DomainKind(kinds) => {
+                Translation::or(kinds.iter().map(|kind| Self::apply_domain_kind(*kind)))
+            }
+            BasicQuery::BundleId(bundle_id) => Self::apply_bundle_id(bundle_id, ignore_case),
+            BasicQuery::PathExact(path) => {
+                Self::apply_exact(Column::RelativePath, path, ignore_case)
+            }
+            BasicQuery::PathContains(path) => {
+                Self::apply_contains(Column::RelativePath, path, ignore_case)
+            }
+            BasicQuery::PathGlob(pattern) => {
+                Self::apply_glob(Column::RelativePath, pattern, ignore_case)
+            }
+            BasicQuery::FileType(flags) => Translation::exact(
+                Expr::expr(
+                    Column::Flags
+                        .into_expr()
+                        .binary(BinOper::BitAnd, flags.bits()),
+                )
+                .ne(0),
+            ),
+            // Regular expressions and conditions on the decoded metadata are evaluated in
+            // Rust after the query
+            BasicQuery::DomainRegex(_)
+            | BasicQuery::PathRegex(_)
+            | BasicQuery::MinSize(_)
+            | BasicQuery::MaxSize(_)
+            | BasicQuery::ModifiedAfter(_)
+            | BasicQuery::ModifiedBefore(_)
+            | BasicQuery::CreatedAfter(_)
+            | BasicQuery::ProtectionClass(_) => Translation::all(false),
+            BasicQuery::IgnoreCase(query) => Self::apply_basic_query_with_case(query, true),
+        }
+    }
+
+    fn apply_exact(column: Column, value: &str, ignore_case: bool) -> Translation {
+        if !ignore_case {
+            return Translation::exact(column.eq(value));
+        }
+
+        // LIKE only folds the case of ASCII letters
+        if value.is_ascii() {
+            Translation::exact(column.like(LikeExpr::new(Self::escape_like(value)).escape('\\')))
+        } else {
+            Translation::all(false)
+        }
+    }
+
+    fn apply_contains(column: Column, value: &str, ignore_case: bool) -> Translation {
+        if !ignore_case {
+            // LIKE ignores case, so use instr() for a case-sensitive substring match
+            let position = Func::cust(Alias::new("instr"))
+                .arg(column.into_expr())
+                .arg(value);
+            return Translation::exact(Expr::expr(position).gt(0));
+        }
+
+        if value.is_ascii() {
+            let pattern = format!("%{}%", Self::escape_like(value));
+            Translation::exact(column.like(LikeExpr::new(pattern).escape('\\')))
+        } else {
+            Translation::all(false)
+        }
+    }
+
+    /// Translates a glob into `GLOB` or `LIKE`
+    ///
+    /// Patterns that only use `*` and `?` behave the same in `SQLite`. Other patterns are
+    /// narrowed down with their literal prefix and evaluated in Rust afterwards.
+    fn apply_glob(column: Column, pattern: &GlobPattern, ignore_case: bool) -> Translation {
+        let value = pattern.value();
+        let is_simple = !value.contains(['[', ']', '{', '}', '\\']) && !value.contains("**");
+
+        if ignore_case {
+            if is_simple && value.is_ascii() {
+                return Translation::exact(
+                    column.like(LikeExpr::new(Self::glob_to_like(value)).escape('\\')),
+                );
+            }
+
+            let prefix = pattern.literal_prefix();
+            return if !prefix.is_empty() && prefix.is_ascii() {
+                let like = format!("{}%", Self::escape_like(prefix));
+                Translation::superset(column.like(LikeExpr::new(like).escape('\\')))
+            } else {
+                Translation::all(false)
+            };
+        }
+
+        if is_simple {
+            return Translation::exact(column.into_expr().binary(BinOper::Custom("GLOB"), value));
+        }
+
+        let prefix = pattern.literal_prefix();
+        if prefix.is_empty() {
+            Translation::all(false)
+        } else {
+            let glob = format!("{prefix}*");
+            Translation::superset(column.into_expr().binary(BinOper::Custom("GLOB"), glob))
         }
     }
 
-    fn apply_composite_query(
-        query: sea_orm::Select<Entity>,
-        composite_query: CompositeQuery,
-    ) -> sea_orm::Select<Entity> {
+    /// Translates a domain kind into a comparison with its name or prefix
+    ///
+    /// Domain kinds are case-sensitive, like the parsing in `Domain`.
+    fn apply_domain_kind(kind: DomainKind) -> Translation {
+        if let Some(name) = kind.domain_name() {
+            return Translation::exact(Column::Domain.eq(name));
+        }
+
+        if let Some(prefix) = kind.prefix() {
+            // Prefixes contain no wildcards
+            let glob = format!("{prefix}*");
+            return Translation::exact(
+                Column::Domain
+                    .into_expr()
+                    .binary(BinOper::Custom("GLOB"), glob),
+            );
+        }
+
+        // Unknown domains are those not matching any other kind
+        Translation::not(Translation::or(
+            DomainKind::ALL
+                .into_iter()
+                .filter(|other| *other != DomainKind::Unknown)
+                .map(Self::apply_domain_kind),
+        ))
+    }
+
+    /// Translates a bundle identifier into the container domains it can appear in
+    fn apply_bundle_id(bundle_id: &str, ignore_case: bool) -> Translation {
+        let domains = DomainKind::ALL
+            .into_iter()
+            .filter_map(DomainKind::prefix)
+            .map(|prefix| format!("{prefix}{bundle_id}"));
+
+        if !ignore_case {
+            return Translation::exact(Column::Domain.is_in(domains));
+        }
+
+        // LIKE would ignore the case of the prefix as well
+        let translation = Translation::or(
+            domains.map(|domain| Self::apply_exact(Column::Domain, &domain, ignore_case)),
+        );
+        Translation {
+            exact: false,
+            ..translation
+        }
+    }
+
+    /// Escapes the wildcards of a `LIKE` pattern with a backslash
+    fn escape_like(value: &str) -> String {
+        value
+            .chars()
+            .flat_map(|c| match c {
+                '%' | '_' | '\\' => vec!['\\', c],
+                _ => vec![c],
+            })
+            .collect()
+    }
+
+    /// Converts a glob that only uses `*` and `?` into a `LIKE` pattern
+    fn glob_to_like(value: &str) -> String {
+        value
+            .chars()
+            .flat_map(|c| match c {
+                '*' => vec!['%'],
+                '?' => vec!['_'],
+                '%' | '_' => vec!['\\', c],
+                _ => vec![c],
+            })
+            .collect()
+    }
+
+    fn apply_composite_query(composite_query: &CompositeQuery) -> Translation {
         match composite_query {
+            // An empty AnyOf matches every file
+            CompositeQuery::AnyOf(basic_queries) if basic_queries.is_empty() => {
+                Translation::all(true)
+            }
             CompositeQuery::AnyOf(basic_queries) => {
-                if basic_queries.is_empty() {
-                    return query;
-                }
-
-                let mut condition = None;
-                for basic_query in basic_queries {
-                    let basic_condition = match basic_query {
-                        BasicQuery::DomainExact(domain) => Column::Domain.eq(domain),
-                        BasicQuery::DomainContains(domain) => Column::Domain.contains(&domain),
-                        BasicQuery::PathExact(path) => Column::RelativePath.eq(path),
-                        BasicQuery::PathContains(path) => Column::RelativePath.contains(&path),
-                    };
-
-                    condition = match condition {
-                        None => Some(basic_condition),
-                        Some(existing) => Some(existing.or(basic_condition)),
-                    };
-                }
-
-                if let Some(final_condition) = condition {
-                    query.filter(final_condition)
-                } else {
-                    query
-                }
+                Translation::or(basic_queries.iter().map(Self::apply_basic_query))
             }
             CompositeQuery::AllOf(basic_queries) => {
-                if basic_queries.is_empty() {
-                    return query;
-                }
-
-                let mut result_query = query;
-                for basic_query in basic_queries {
-                    let basic_condition = match basic_query {
-                        BasicQuery::DomainExact(domain) => Column::Domain.eq(domain),
-                        BasicQuery::DomainContains(domain) => Column::Domain.contains(&domain),
-                        BasicQuery::PathExact(path) => Column::RelativePath.eq(path),
-                        BasicQuery::PathContains(path) => Column::RelativePath.contains(&path),
-                    };
-                    result_query = result_query.filter(basic_condition);
-                }
-                result_query
+                Translation::and(basic_queries.iter().map(Self::apply_basic_query))
             }
         }
     }
@@ -93,12 +339,10 @@ impl FileRepository for FileRepositoryImpl {
         let mut db_query = Entity::find();
 
         // Apply query conditions
-        db_query = match query {
-            FileQuery::Basic(basic_query) => Self::apply_basic_query(db_query, basic_query),
-            FileQuery::Composite(composite_query) => {
-                Self::apply_composite_query(db_query, composite_query)
-            }
-        };
+        let translation = Self::apply_query(&query);
+        if let Some(condition) = translation.condition {
+            db_query = db_query.filter(condition);
+        }
 
         // Add sorting by domain and relative path
         db_query = db_query
@@ -113,6 +357,11 @@ impl FileRepository for FileRepositoryImpl {
             files.push(model.to_domain()?);
         }
 
+        // Narrow down the results for conditions SQLite could not evaluate
+        if !translation.exact {
+            files.retain(|file| query.matches(file));
+        }
+
         Ok(files)
     }
 }
@@ -121,7 +370,9 @@ impl FileRepository for FileRepositoryImpl {
 mod tests {
     use super::*;
     use crate::domain::queries::BasicQuery;
+    use crate::domain::value_objects::FileFlags;
     use crate::infrastructure::database::entities::files::ActiveModel;
+    use crate::infrastructure::plist::entities::MbFile;
     use anyhow::Context as _;
     use sea_orm::{ActiveModelTrait as _, ConnectionTrait as _, Database, Set};
 
@@ -458,4 +709,357 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_search_decodes_file_metadata() -> Result<()> {
+        let db = setup_test_db().await?;
+
+        let mb_file = MbFile {
+            size: 2048,
+            mode: 0o100_600,
+            last_modified: 1_700_000_000,
+            protection_class: 3,
+            ..MbFile::default()
+        };
+        let test_file = ActiveModel {
+            file_id: Set("1b6453892473a467d07372d45eb05abc2031647a".to_owned()),
+            domain: Set("HomeDomain".to_owned()),
+            relative_path: Set("Library/SMS/sms.db".to_owned()),
+            flags: Set(1),
+            file: Set(mb_file.to_bytes()?),
+        };
+        test_file.insert(db.get_connection()).await?;
+        insert_test_data(&db).await?;
+
+        let repo = FileRepositoryImpl::new(db);
+        let results = repo.search(FileQuery::domain_contains("")).await?;
+
+        assert_eq!(results.len(), 4);
+
+        let decoded = results
+            .iter()
+            .find(|f| f.domain().value() == "HomeDomain")
+            .context("Should find the file with a valid MBFile blob")?;
+        assert_eq!(decoded.size(), Some(2048));
+        assert_eq!(decoded.mode(), Some(0o100_600));
+        assert_eq!(decoded.protection_class(), Some(3));
+        assert_eq!(
+            decoded.last_modified().map(chrono::DateTime::timestamp),
+            Some(1_700_000_000)
+        );
+
+        // Rows whose blob cannot be decoded are still returned, without metadata
+        assert!(
+            results
+                .iter()
+                .filter(|f| f.domain().value() != "HomeDomain")
+                .all(|f| f.file_metadata().is_none())
+        );
+
+        Ok(())
+    }
+
+    async fn search_paths(repo: &FileRepositoryImpl, expression: &str) -> Result<Vec<String>> {
+        let results = repo.search(FileQuery::parse(expression)?).await?;
+        Ok(results
+            .iter()
+            .map(|f| f.relative_path().value().to_owned())
+            .collect())
+    }
+
+    #[tokio::test]
+    async fn test_search_boolean_expression() -> Result<()> {
+        let db = setup_test_db().await?;
+        insert_test_data(&db).await?;
+        let repo = FileRepositoryImpl::new(db);
+
+        assert_eq!(
+            search_paths(
+                &repo,
+                "domain ~ apple and (path contains Documents or path = Pictures/photo.jpg)"
+            )
+            .await?,
+            vec!["Documents/news.txt", "Pictures/photo.jpg"]
+        );
+        assert_eq!(
+            search_paths(&repo, "not domain ~ apple").await?,
+            vec!["Documents/example.txt"]
+        );
+        assert_eq!(
+            search_paths(&repo, "not (domain ~ apple or path ~ example)").await?,
+            Vec::<String>::new()
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_search_regex_post_filter() -> Result<()> {
+        let db = setup_test_db().await?;
+        insert_test_data(&db).await?;
+        let repo = FileRepositoryImpl::new(db);
+
+        assert_eq!(
+            search_paths(&repo, r#"path regex "\\.txt$" and domain ~ apple"#).await?,
+            vec!["Documents/news.txt"]
+        );
+        assert_eq!(
+            search_paths(&repo, "path glob '*.jpg' or domain = com.example.app").await?,
+            vec!["Pictures/photo.jpg", "Documents/example.txt"]
+        );
+        // A negated pattern cannot be pushed down, so the whole query is filtered in Rust
+        assert_eq!(
+            search_paths(&repo, "not path regex '^Documents/' and domain ~ apple").await?,
+            vec!["Pictures/photo.jpg"]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_search_glob_pushdown() -> Result<()> {
+        let db = setup_test_db().await?;
+        insert_test_data(&db).await?;
+        let repo = FileRepositoryImpl::new(db);
+
+        assert_eq!(
+            search_paths(&repo, "path glob 'Documents/*.txt'").await?,
+            vec!["Documents/news.txt", "Documents/example.txt"]
+        );
+        assert_eq!(
+            search_paths(&repo, "path glob 'Documents/**/news.{txt,md}'").await?,
+            vec!["Documents/news.txt"]
+        );
+        assert_eq!(
+            search_paths(&repo, "not domain glob 'com.apple.*'").await?,
+            vec!["Documents/example.txt"]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_search_is_case_sensitive() -> Result<()> {
+        let db = setup_test_db().await?;
+        insert_test_data(&db).await?;
+        let repo = FileRepositoryImpl::new(db);
+
+        assert_eq!(
+            search_paths(&repo, "path ~ documents or path glob 'pictures/*'").await?,
+            Vec::<String>::new()
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_search_ignore_case() -> Result<()> {
+        let db = setup_test_db().await?;
+        insert_test_data(&db).await?;
+        let repo = FileRepositoryImpl::new(db);
+
+        let search = |expression: &str| -> Result<FileQuery> {
+            Ok(FileQuery::parse(expression)?.ignore_case())
+        };
+        let paths = |files: Vec<File>| -> Vec<String> {
+            files
+                .iter()
+                .map(|f| f.relative_path().value().to_owned())
+                .collect()
+        };
+
+        assert_eq!(
+            paths(
+                repo.search(search("path ~ DOCUMENTS and not domain = COM.APPLE.NEWS")?)
+                    .await?
+            ),
+            vec!["Documents/example.txt"]
+        );
+        assert_eq!(
+            paths(repo.search(search("path glob 'pictures/*.JPG'")?).await?),
+            vec!["Pictures/photo.jpg"]
+        );
+        assert_eq!(
+            paths(
+                repo.search(search("path glob 'PICTURES/**/*.jpg'")?)
+                    .await?
+            ),
+            vec!["Pictures/photo.jpg"]
+        );
+        assert_eq!(
+            paths(repo.search(search("path regex '^documents/n'")?).await?),
+            vec!["Documents/news.txt"]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_search_domain_kind_and_bundle_id() -> Result<()> {
+        let db = setup_test_db().await?;
+        let domains = [
+            ("356a192b7913b04c54574d18c28d46e6395428ab", "HomeDomain"),
+            (
+                "da4b9237bacccdf19c0760cab7aec4a8359010b0",
+                "AppDomain-net.whatsapp.WhatsApp",
+            ),
+            (
+                "77de68daecd823babbb58edb1c8e14d7106e83bb",
+                "AppDomainGroup-group.net.whatsapp.WhatsApp.shared",
+            ),
+            (
+                "1b6453892473a467d07372d45eb05abc2031647a",
+                "AppDomainPlugin-net.whatsapp.WhatsApp",
+            ),
+            ("ac3478d69a3c81fa62e60f5c3696165a4e5e6ac4", "BooksDomain"),
+            (
+                "c1dfd96eea8cc2b62785275bca38ac261256e278",
+                "appdomain-net.whatsapp.whatsapp",
+            ),
+        ];
+        for (file_id, domain) in domains {
+            ActiveModel {
+                file_id: Set(file_id.to_owned()),
+                domain: Set(domain.to_owned()),
+                relative_path: Set("Library/file".to_owned()),
+                flags: Set(1),
+                file: Set(vec![]),
+            }
+            .insert(db.get_connection())
+            .await?;
+        }
+        let repo = FileRepositoryImpl::new(db);
+
+        let search_domains = async |query: FileQuery| -> Result<(Vec<String>, bool)> {
+            let exact = FileRepositoryImpl::apply_query(&query).exact;
+            let results = repo.search(query).await?;
+            Ok((
+                results
+                    .iter()
+                    .map(|f| f.domain().value().to_owned())
+                    .collect(),
+                exact,
+            ))
+        };
+
+        let (kinds, kinds_exact) = search_domains(FileQuery::Basic(BasicQuery::DomainKind(vec![
+            DomainKind::App,
+            DomainKind::Home,
+        ])))
+        .await?;
+        assert!(kinds_exact);
+        assert_eq!(kinds, vec!["AppDomain-net.whatsapp.WhatsApp", "HomeDomain"]);
+
+        let (unknown, unknown_exact) =
+            search_domains(FileQuery::Basic(BasicQuery::DomainKind(vec![
+                DomainKind::Unknown,
+            ])))
+            .await?;
+        assert!(unknown_exact);
+        assert_eq!(
+            unknown,
+            vec!["BooksDomain", "appdomain-net.whatsapp.whatsapp"]
+        );
+
+        let bundle_id = BasicQuery::BundleId("net.whatsapp.WhatsApp".to_owned());
+        let expected = vec![
+            "AppDomain-net.whatsapp.WhatsApp",
+            "AppDomainPlugin-net.whatsapp.WhatsApp",
+        ];
+        let (case_sensitive, case_sensitive_exact) =
+            search_domains(FileQuery::Basic(bundle_id)).await?;
+        assert!(case_sensitive_exact);
+        assert_eq!(case_sensitive, expected);
+
+        let ignore_case = BasicQuery::BundleId("NET.WHATSAPP.WHATSAPP".to_owned()).ignore_case();
+        let (case_insensitive, case_insensitive_exact) =
+            search_domains(FileQuery::Basic(ignore_case)).await?;
+        assert!(!case_insensitive_exact);
+        assert_eq!(case_insensitive, expected);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_search_file_type() -> Result<()> {
+        let db = setup_test_db().await?;
+        insert_test_data(&db).await?;
+        let repo = FileRepositoryImpl::new(db);
+
+        let query = FileQuery::Basic(BasicQuery::FileType(FileFlags::DIRECTORY));
+        let translation = FileRepositoryImpl::apply_query(&query);
+        let results = repo.search(query).await?;
+
+        assert!(translation.exact);
+        let paths: Vec<_> = results.iter().map(|f| f.relative_path().value()).collect();
+        assert_eq!(paths, vec!["Pictures/photo.jpg", "Documents/example.txt"]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_search_file_id() -> Result<()> {
+        let db = setup_test_db().await?;
+        insert_test_data(&db).await?;
+        let repo = FileRepositoryImpl::new(db);
+
+        let query = FileQuery::Basic(BasicQuery::FileId(vec![
+            FileId::new("77DE68DAECD823BABBB58EDB1C8E14D7106E83BB")?,
+            FileId::new("356a192b7913b04c54574d18c28d46e6395428ab")?,
+        ]))
+        .ignore_case();
+        let translation = FileRepositoryImpl::apply_query(&query);
+        let results = repo.search(query).await?;
+
+        assert!(translation.exact);
+        let paths: Vec<_> = results.iter().map(|f| f.relative_path().value()).collect();
+        assert_eq!(paths, vec!["Documents/news.txt", "Documents/example.txt"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_metadata_filters_are_post_filtered() {
+        let query = FileQuery::and(vec![
+            FileQuery::domain_exact("HomeDomain"),
+            FileQuery::Basic(BasicQuery::MinSize(1024)),
+        ]);
+
+        let translation = FileRepositoryImpl::apply_query(&query);
+
+        assert!(translation.condition.is_some());
+        assert!(!translation.exact);
+    }
+
+    #[test]
+    fn test_glob_translation() -> Result<()> {
+        let exact = |expression: &str| -> Result<bool> {
+            Ok(FileRepositoryImpl::apply_query(&FileQuery::parse(expression)?).exact)
+        };
+
+        assert!(exact("path glob 'Library/SMS/*.db'")?);
+        assert!(!exact("path glob 'Library/SMS/**/*.heic'")?);
+        assert!(!exact("path glob 'Library/{SMS,Notes}/*'")?);
+        assert!(exact("path glob 'Library/SMS/*.db' and path ~ sms")?);
+        assert!(!exact("path regex '^Library/'")?);
+        assert!(exact("path ~ Library")?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_escape_like() {
+        assert_eq!(
+            FileRepositoryImpl::escape_like(r"100%_done\"),
+            r"100\%\_done\\"
+        );
+        assert_eq!(FileRepositoryImpl::glob_to_like("IMG_?.*"), r"IMG\__.%");
+    }
+
+    #[tokio::test]
+    async fn test_search_empty_or_matches_nothing() -> Result<()> {
+        let db = setup_test_db().await?;
+        insert_test_data(&db).await?;
+        let repo = FileRepositoryImpl::new(db);
+
+        assert!(repo.search(FileQuery::or(vec![])).await?.is_empty());
+        assert_eq!(
+            repo.search(FileQuery::negate(FileQuery::or(vec![])))
+                .await?
+                .len(),
+            3
+        );
+        Ok(())
+    }
 }
@@ -0,0 +1,207 @@
+use anyhow::{Context as _, Result};
+use std::path::Path;
+use tokio::fs;
+
+use crate::domain::entities::File;
+use crate::domain::queries::FileQuery;
+use crate::domain::repositories::FileRepository;
+use crate::infrastructure::mbdb::ManifestMbdb;
+
+/// Implementation of `FileRepository` for legacy Manifest.mbdb backups
+///
+/// Manifest.mbdb has no index, so all records are decoded up front and queries are
+/// evaluated in memory. Records with an invalid domain or path are skipped, so that the
+/// other files stay available.
+pub struct MbdbFileRepositoryImpl {
+    /// Files of the backup, sorted by domain and relative path
+    files: Vec<File>,
+    /// Descriptions of the records that were skipped, in manifest order
+    skipped: Vec<String>,
+}
+
+impl MbdbFileRepositoryImpl {
+    /// Creates a new `MbdbFileRepositoryImpl` from the contents of a Manifest.mbdb file
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be parsed
+    #[inline]
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let manifest = ManifestMbdb::from_bytes(bytes)?;
+
+        let mut files = Vec::with_capacity(manifest.records.len());
+        let mut skipped = Vec::new();
+        for record in manifest.records {
+            let location = format!("{}/{}", record.domain, record.path);
+            match record.to_domain() {
+                Ok(file) => files.push(file),
+                Err(e) => skipped.push(format!("{location}: {e:#}")),
+            }
+        }
+        files.sort_by(|a, b| {
+            a.domain()
+                .value()
+                .cmp(b.domain().value())
+                .then_with(|| a.relative_path().value().cmp(b.relative_path().value()))
+        });
+
+        Ok(Self { files, skipped })
+    }
+
+    /// Returns descriptions of the records that were skipped because they are invalid
+    #[must_use]
+    #[inline]
+    pub fn skipped_records(&self) -> &[String] {
+        &self.skipped
+    }
+
+    /// Opens a Manifest.mbdb file
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be read or parsed
+    #[inline]
+    pub async fn open<P: AsRef<Path> + Send>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let content = fs::read(path)
+            .await
+            .with_context(|| format!("Failed to read Manifest.mbdb file: {}", path.display()))?;
+
+        Self::from_bytes(&content)
+            .with_context(|| format!("Failed to parse Manifest.mbdb file: {}", path.display()))
+    }
+}
+
+impl FileRepository for MbdbFileRepositoryImpl {
+    #[inline]
+    async fn search(&self, query: FileQuery) -> Result<Vec<File>> {
+        Ok(self
+            .files
+            .iter()
+            .filter(|file| query.matches(file))
+            .cloned()
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::queries::BasicQuery;
+    use crate::infrastructure::mbdb::MbdbRecord;
+    use assert_fs::TempDir;
+    use assert_fs::prelude::*;
+    use pretty_assertions::assert_eq;
+
+    fn create_record(domain: &str, path: &str) -> MbdbRecord {
+        MbdbRecord {
+            domain: domain.to_owned(),
+            path: path.to_owned(),
+            mode: 0o100_644,
+            ..MbdbRecord::default()
+        }
+    }
+
+    fn create_manifest_bytes() -> Result<Vec<u8>> {
+        ManifestMbdb {
+            records: vec![
+                create_record("HomeDomain", "Library/SMS/sms.db"),
+                create_record("AppDomain-com.apple.news", "Documents/file.txt"),
+                create_record("HomeDomain", "Library/AddressBook/AddressBook.sqlitedb"),
+            ],
+        }
+        .to_bytes()
+    }
+
+    #[tokio::test]
+    async fn test_search_sorted_by_domain_and_path() -> Result<()> {
+        let repo = MbdbFileRepositoryImpl::from_bytes(&create_manifest_bytes()?)?;
+
+        let files = repo.search(FileQuery::all_of(vec![])).await?;
+        let paths: Vec<_> = files
+            .iter()
+            .map(|file| file.relative_path().value())
+            .collect();
+
+        assert_eq!(
+            paths,
+            vec![
+                "Documents/file.txt",
+                "Library/AddressBook/AddressBook.sqlitedb",
+                "Library/SMS/sms.db",
+            ]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_search_filters_in_memory() -> Result<()> {
+        let repo = MbdbFileRepositoryImpl::from_bytes(&create_manifest_bytes()?)?;
+
+        let files = repo
+            .search(FileQuery::all_of(vec![
+                BasicQuery::DomainExact("HomeDomain".to_owned()),
+                BasicQuery::PathContains("SMS".to_owned()),
+            ]))
+            .await?;
+
+        assert_eq!(files.len(), 1);
+        assert_eq!(
+            files[0].id().value(),
+            "3d0d7e5fb2ce288813306e4d4636395e047a3d28"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_skips_invalid_records() -> Result<()> {
+        let bytes = ManifestMbdb {
+            records: vec![
+                create_record("HomeDomain", "Library/SMS/sms.db"),
+                create_record(&"A".repeat(256), "Library/Preferences/evil.plist"),
+                create_record("", "Library/Notes/notes.sqlite"),
+                create_record("AppDomain-com.apple.news", "Documents/file.txt"),
+            ],
+        }
+        .to_bytes()?;
+
+        let repo = MbdbFileRepositoryImpl::from_bytes(&bytes)?;
+        let files = repo.search(FileQuery::all_of(vec![])).await?;
+        let paths: Vec<_> = files
+            .iter()
+            .map(|file| file.relative_path().value())
+            .collect();
+
+        assert_eq!(paths, vec!["Documents/file.txt", "Library/SMS/sms.db"]);
+        assert_eq!(repo.skipped_records().len(), 2);
+        assert!(repo.skipped_records()[0].contains("Domain cannot be longer"));
+        assert!(repo.skipped_records()[1].contains("Domain cannot be empty"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_open_file() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let manifest = temp_dir.child("Manifest.mbdb");
+        manifest.write_binary(&create_manifest_bytes()?)?;
+
+        let repo = MbdbFileRepositoryImpl::open(manifest.path()).await?;
+        let files = repo.search(FileQuery::domain_contains("Domain")).await?;
+
+        assert_eq!(files.len(), 3);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_open_invalid_file() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let manifest = temp_dir.child("Manifest.mbdb");
+        manifest.write_binary(b"not an mbdb file")?;
+
+        let result = MbdbFileRepositoryImpl::open(manifest.path()).await;
+
+        assert!(result.is_err());
+        if let Err(error) = result {
+            assert!(error.to_string().contains("Failed to parse Manifest.mbdb"));
+        }
+        Ok(())
+    }
+}
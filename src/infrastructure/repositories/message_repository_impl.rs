@@ -0,0 +1,421 @@
+use anyhow::{Context as _, Result};
+use sea_orm::{ConnectionTrait as _, FromQueryResult, Statement};
+use std::collections::BTreeMap;
+
+use crate::domain::entities::{Attachment, Conversation, Message};
+use crate::domain::repositories::MessageRepository;
+use crate::domain::value_objects::AppleTimestamp;
+use crate::infrastructure::database::DatabaseConnection;
+
+/// Row of the `chat` table
+#[derive(Debug, FromQueryResult)]
+struct ChatRow {
+    id: i64,
+    chat_identifier: Option<String>,
+    display_name: Option<String>,
+    service_name: Option<String>,
+}
+
+/// Row of `chat_handle_join` joined with `handle`
+#[derive(Debug, FromQueryResult)]
+struct ParticipantRow {
+    chat_id: i64,
+    handle: String,
+}
+
+/// Row of `chat_message_join` joined with `message` and the sender's `handle`
+#[derive(Debug, FromQueryResult)]
+struct MessageRow {
+    chat_id: i64,
+    id: i64,
+    guid: String,
+    text: Option<String>,
+    attributed_body: Option<Vec<u8>>,
+    date: Option<i64>,
+    is_from_me: Option<i64>,
+    service: Option<String>,
+    sender: Option<String>,
+}
+
+/// Row of `message_attachment_join` joined with `attachment`
+#[derive(Debug, FromQueryResult)]
+struct AttachmentRow {
+    message_id: i64,
+    id: i64,
+    filename: Option<String>,
+    mime_type: Option<String>,
+    transfer_name: Option<String>,
+    total_bytes: Option<i64>,
+}
+
+/// Implementation of `MessageRepository` reading the `sms.db` database of the Messages app
+pub struct MessageRepositoryImpl {
+    /// Connection to `sms.db`
+    db: DatabaseConnection,
+}
+
+impl MessageRepositoryImpl {
+    /// Location of the database in the backup
+    pub const DOMAIN: &str = "HomeDomain";
+    /// Relative path of the database in its domain
+    pub const RELATIVE_PATH: &str = "Library/SMS/sms.db";
+
+    /// Creates a new `MessageRepositoryImpl` for a connection to `sms.db`
+    #[must_use]
+    #[inline]
+    pub const fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// Runs a query and maps its rows
+    async fn query<T: FromQueryResult>(&self, sql: &str) -> Result<Vec<T>> {
+        let connection = self.db.get_connection();
+        let statement = Statement::from_string(connection.get_database_backend(), sql);
+        T::find_by_statement(statement)
+            .all(connection)
+            .await
+            .with_context(|| format!("Failed to query sms.db: {sql}"))
+    }
+}
+
+/// Extracts the plain text of a message from its `attributedBody`
+///
+/// Since iOS 16 the text of many messages is only stored there, as an `NSAttributedString`
+/// archived in the typedstream format. Its string follows the `NSString` class name as a
+/// `+`-tagged byte string with a typedstream length prefix.
+fn attributed_body_text(body: &[u8]) -> Option<String> {
+    const CLASS_NAME: &[u8] = b"NSString";
+
+    let class = body
+        .windows(CLASS_NAME.len())
+        .position(|window| window == CLASS_NAME)?;
+    let rest = body.get(class..)?.strip_prefix(CLASS_NAME)?;
+    let tag = rest.iter().position(|byte| *byte == b'+')?;
+    let (_, rest) = rest.get(tag..)?.split_first()?;
+    let (length, rest) = match rest.split_first()? {
+        // Larger lengths follow a tag byte as 16- or 32-bit little-endian integers
+        (0x81, rest) => {
+            let (length, rest) = rest.split_first_chunk::<2>()?;
+            (usize::from(u16::from_le_bytes(*length)), rest)
+        }
+        (0x82, rest) => {
+            let (length, rest) = rest.split_first_chunk::<4>()?;
+            (usize::try_from(u32::from_le_bytes(*length)).ok()?, rest)
+        }
+        (length @ 0..=0x7f, rest) => (usize::from(*length), rest),
+        _ => return None,
+    };
+
+    String::from_utf8(rest.get(..length)?.to_vec()).ok()
+}
+
+impl MessageRepository for MessageRepositoryImpl {
+    #[inline]
+    async fn find_conversations(&self) -> Result<Vec<Conversation>> {
+        let chats: Vec<ChatRow> = self
+            .query(
+                "SELECT ROWID AS id, chat_identifier, display_name, service_name \
+                 FROM chat ORDER BY ROWID",
+            )
+            .await?;
+        let participants: Vec<ParticipantRow> = self
+            .query(
+                "SELECT chj.chat_id, h.id AS handle \
+                 FROM chat_handle_join AS chj JOIN handle AS h ON h.ROWID = chj.handle_id \
+                 ORDER BY chj.chat_id, h.id",
+            )
+            .await?;
+        let messages: Vec<MessageRow> = self
+            .query(
+                "SELECT cmj.chat_id, m.ROWID AS id, m.guid, m.text, \
+                 m.attributedBody AS attributed_body, m.date, m.is_from_me, m.service, \
+                 h.id AS sender \
+                 FROM chat_message_join AS cmj \
+                 JOIN message AS m ON m.ROWID = cmj.message_id \
+                 LEFT JOIN handle AS h ON h.ROWID = m.handle_id \
+                 ORDER BY cmj.chat_id, m.date, m.ROWID",
+            )
+            .await?;
+        let attachments: Vec<AttachmentRow> = self
+            .query(
+                "SELECT maj.message_id, a.ROWID AS id, a.filename, a.mime_type, \
+                 a.transfer_name, a.total_bytes \
+                 FROM message_attachment_join AS maj \
+                 JOIN attachment AS a ON a.ROWID = maj.attachment_id \
+                 ORDER BY maj.message_id, a.ROWID",
+            )
+            .await?;
+
+        let mut attachments_by_message: BTreeMap<i64, Vec<Attachment>> = BTreeMap::new();
+        for row in attachments {
+            attachments_by_message
+                .entry(row.message_id)
+                .or_default()
+                .push(Attachment {
+                    id: row.id,
+                    filename: row.filename,
+                    mime_type: row.mime_type,
+                    transfer_name: row.transfer_name,
+                    total_bytes: row
+                        .total_bytes
+                        .and_then(|bytes| u64::try_from(bytes).ok())
+                        .unwrap_or_default(),
+                });
+        }
+
+        let mut conversations: BTreeMap<i64, Conversation> = chats
+            .into_iter()
+            .map(|chat| {
+                let conversation = Conversation {
+                    id: chat.id,
+                    chat_identifier: chat.chat_identifier.unwrap_or_default(),
+                    display_name: chat.display_name.filter(|name| !name.is_empty()),
+                    service: chat.service_name,
+                    participants: Vec::new(),
+                    messages: Vec::new(),
+                };
+                (chat.id, conversation)
+            })
+            .collect();
+
+        for row in participants {
+            if let Some(conversation) = conversations.get_mut(&row.chat_id) {
+                conversation.participants.push(row.handle);
+            }
+        }
+
+        for row in messages {
+            let Some(conversation) = conversations.get_mut(&row.chat_id) else {
+                continue;
+            };
+            let is_from_me = row.is_from_me.is_some_and(|value| value != 0);
+            conversation.messages.push(Message {
+                id: row.id,
+                guid: row.guid,
+                // A zero date means the time is unknown
+                date: row
+                    .date
+                    .filter(|date| *date != 0)
+                    .and_then(AppleTimestamp::from_seconds_or_nanoseconds)
+                    .map(AppleTimestamp::to_datetime),
+                sender: if is_from_me { None } else { row.sender },
+                is_from_me,
+                text: row.text.or_else(|| {
+                    row.attributed_body
+                        .as_deref()
+                        .and_then(attributed_body_text)
+                }),
+                service: row.service,
+                // A message may be joined to several chats, so its attachments are cloned
+                attachments: attachments_by_message
+                    .get(&row.id)
+                    .cloned()
+                    .unwrap_or_default(),
+            });
+        }
+
+        Ok(conversations.into_values().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use sea_orm::Database;
+    use std::fmt::Write as _;
+
+    /// Creates an in-memory `sms.db` with the tables and columns read by the repository
+    async fn create_sms_db(rows: &str) -> Result<MessageRepositoryImpl> {
+        let connection = Database::connect("sqlite::memory:").await?;
+        connection
+            .execute_unprepared(
+                "CREATE TABLE chat (ROWID INTEGER PRIMARY KEY, guid TEXT, chat_identifier TEXT, \
+                 service_name TEXT, display_name TEXT);
+                 CREATE TABLE handle (ROWID INTEGER PRIMARY KEY, id TEXT, service TEXT);
+                 CREATE TABLE message (ROWID INTEGER PRIMARY KEY, guid TEXT, text TEXT, \
+                 handle_id INTEGER, service TEXT, date INTEGER, is_from_me INTEGER, \
+                 attributedBody BLOB);
+                 CREATE TABLE chat_handle_join (chat_id INTEGER, handle_id INTEGER);
+                 CREATE TABLE chat_message_join (chat_id INTEGER, message_id INTEGER, \
+                 message_date INTEGER);
+                 CREATE TABLE attachment (ROWID INTEGER PRIMARY KEY, guid TEXT, filename TEXT, \
+                 mime_type TEXT, transfer_name TEXT, total_bytes INTEGER);
+                 CREATE TABLE message_attachment_join (message_id INTEGER, attachment_id INTEGER);",
+            )
+            .await?;
+        connection.execute_unprepared(rows).await?;
+        Ok(MessageRepositoryImpl::new(
+            DatabaseConnection::new_from_connection(connection),
+        ))
+    }
+
+    #[tokio::test]
+    async fn test_find_conversations() -> Result<()> {
+        let repo = create_sms_db(
+            "INSERT INTO chat VALUES (1, 'iMessage;-;+15550001', '+15550001', 'iMessage', '');
+             INSERT INTO handle VALUES (7, '+15550001', 'iMessage');
+             INSERT INTO chat_handle_join VALUES (1, 7);
+             INSERT INTO message VALUES \
+             (10, 'G2', 'Sure', 0, 'iMessage', 726978660000000000, 1, NULL);
+             INSERT INTO message VALUES \
+             (11, 'G1', 'Lunch?', 7, 'iMessage', 726978600000000000, 0, NULL);
+             INSERT INTO chat_message_join VALUES (1, 10, 0), (1, 11, 0);
+             INSERT INTO attachment VALUES (3, 'A', '~/Library/SMS/Attachments/ab/11/A/IMG.heic', \
+             'image/heic', 'IMG.heic', 2048);
+             INSERT INTO message_attachment_join VALUES (11, 3);",
+        )
+        .await?;
+
+        let conversations = repo.find_conversations().await?;
+
+        assert_eq!(
+            conversations,
+            vec![Conversation {
+                id: 1,
+                chat_identifier: "+15550001".to_owned(),
+                display_name: None,
+                service: Some("iMessage".to_owned()),
+                participants: vec!["+15550001".to_owned()],
+                messages: vec![
+                    Message {
+                        id: 11,
+                        guid: "G1".to_owned(),
+                        date: Some("2024-01-15T02:30:00Z".parse()?),
+                        sender: Some("+15550001".to_owned()),
+                        is_from_me: false,
+                        text: Some("Lunch?".to_owned()),
+                        service: Some("iMessage".to_owned()),
+                        attachments: vec![Attachment {
+                            id: 3,
+                            filename: Some("~/Library/SMS/Attachments/ab/11/A/IMG.heic".to_owned()),
+                            mime_type: Some("image/heic".to_owned()),
+                            transfer_name: Some("IMG.heic".to_owned()),
+                            total_bytes: 2048,
+                        }],
+                    },
+                    Message {
+                        id: 10,
+                        guid: "G2".to_owned(),
+                        date: Some("2024-01-15T02:31:00Z".parse()?),
+                        sender: None,
+                        is_from_me: true,
+                        text: Some("Sure".to_owned()),
+                        service: Some("iMessage".to_owned()),
+                        attachments: vec![],
+                    },
+                ],
+            }]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_find_conversations_message_in_two_chats() -> Result<()> {
+        let repo = create_sms_db(
+            "INSERT INTO chat VALUES (1, 'SMS;-;+15550001', '+15550001', 'SMS', ''), \
+             (2, 'iMessage;-;+15550001', '+15550001', 'iMessage', '');
+             INSERT INTO message VALUES \
+             (10, 'G1', 'Photo', 0, 'iMessage', 726978600000000000, 1, NULL);
+             INSERT INTO chat_message_join VALUES (1, 10, 0), (2, 10, 0);
+             INSERT INTO attachment VALUES (3, 'A', '~/Library/SMS/Attachments/ab/11/A/IMG.heic', \
+             'image/heic', 'IMG.heic', 2048);
+             INSERT INTO message_attachment_join VALUES (10, 3);",
+        )
+        .await?;
+
+        let conversations = repo.find_conversations().await?;
+
+        assert_eq!(conversations.len(), 2);
+        for conversation in &conversations {
+            assert_eq!(conversation.messages.len(), 1, "chat {}", conversation.id);
+            assert_eq!(
+                conversation.messages[0]
+                    .attachments
+                    .iter()
+                    .map(|attachment| attachment.id)
+                    .collect::<Vec<_>>(),
+                vec![3],
+                "chat {}",
+                conversation.id
+            );
+        }
+        Ok(())
+    }
+
+    /// Archives a string as the `attributedBody` of a message
+    fn attributed_body(text: &str) -> Result<Vec<u8>> {
+        let mut body = b"\x04\x0bstreamtyped\x81\xe8\x03\x84\x01@\x84\x84\x84\x12\
+                         NSAttributedString\x00\x84\x84\x08NSObject\x00\x85\x92\x84\x84\x84\x08\
+                         NSString\x01\x94\x84\x01+"
+            .to_vec();
+        match u8::try_from(text.len()) {
+            Ok(length) if length < 0x80 => body.push(length),
+            _ => {
+                body.push(0x81);
+                body.extend(u16::try_from(text.len())?.to_le_bytes());
+            }
+        }
+        body.extend(text.as_bytes());
+        body.extend(b"\x86\x84\x02iI\x01\x05\x92\x84\x84\x84\x0cNSDictionary\x00");
+        Ok(body)
+    }
+
+    #[test]
+    fn test_attributed_body_text() -> Result<()> {
+        assert_eq!(
+            attributed_body_text(&attributed_body("Hello")?),
+            Some("Hello".to_owned())
+        );
+        let long = "Long message 🙂 ".repeat(20);
+        assert_eq!(
+            attributed_body_text(&attributed_body(&long)?),
+            Some(long.clone())
+        );
+        assert_eq!(attributed_body_text(b"not an archive"), None);
+        let truncated = attributed_body(&long)?;
+        assert_eq!(
+            attributed_body_text(truncated.get(..100).unwrap_or_default()),
+            None
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_find_conversations_attributed_body() -> Result<()> {
+        let mut body = String::new();
+        for byte in attributed_body("Only in the body")? {
+            write!(body, "{byte:02x}")?;
+        }
+        let repo = create_sms_db(&format!(
+            "INSERT INTO chat VALUES (1, 'iMessage;-;+15550001', '+15550001', 'iMessage', '');
+             INSERT INTO message VALUES \
+             (10, 'G1', NULL, 0, 'iMessage', 726978600000000000, 1, X'{body}'), \
+             (11, 'G2', 'Plain', 0, 'iMessage', 726978660000000000, 1, X'{body}'), \
+             (12, 'G3', NULL, 0, 'iMessage', 726978720000000000, 1, NULL);
+             INSERT INTO chat_message_join VALUES (1, 10, 0), (1, 11, 0), (1, 12, 0);"
+        ))
+        .await?;
+
+        let conversations = repo.find_conversations().await?;
+
+        let texts: Vec<_> = conversations[0]
+            .messages
+            .iter()
+            .map(|message| message.text.as_deref())
+            .collect();
+        assert_eq!(texts, vec![Some("Only in the body"), Some("Plain"), None]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_find_conversations_missing_table() -> Result<()> {
+        let connection = Database::connect("sqlite::memory:").await?;
+        let repo = MessageRepositoryImpl::new(DatabaseConnection::new_from_connection(connection));
+
+        let result = repo.find_conversations().await;
+
+        assert!(result.is_err_and(|e| e.to_string().contains("Failed to query sms.db")));
+        Ok(())
+    }
+}
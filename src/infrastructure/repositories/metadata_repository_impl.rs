@@ -1,8 +1,9 @@
 use crate::domain::entities::Metadata;
 use crate::domain::repositories::MetadataRepository;
 use crate::domain::value_objects::MetadataId;
-use crate::infrastructure::plist::entities::BackupInfo;
+use crate::infrastructure::plist::entities::{BackupInfo, ManifestInfo, StatusInfo};
 use anyhow::{Context as _, Result};
+use serde::de::DeserializeOwned;
 use std::path::{Path, PathBuf};
 use tokio::fs;
 
@@ -36,6 +37,47 @@ impl MetadataRepositoryImpl {
         // Convert to domain entity
         plist_info.to_domain()
     }
+
+    /// Reads the metadata of a backup directory
+    ///
+    /// Info.plist is required. Manifest.plist and Status.plist only add details, so they are
+    /// skipped if they are missing or cannot be parsed.
+    pub(crate) async fn read_backup_directory(backup_directory: &Path) -> Result<Metadata> {
+        let info_plist_path = backup_directory.join("Info.plist");
+        let plist_content = fs::read(&info_plist_path).await.with_context(|| {
+            format!(
+                "Failed to read Info.plist file: {}",
+                info_plist_path.display()
+            )
+        })?;
+
+        let mut metadata = Self::load_from_plist_content(&plist_content).with_context(|| {
+            format!(
+                "Failed to parse Info.plist file: {}",
+                info_plist_path.display()
+            )
+        })?;
+
+        if let Some(manifest) =
+            Self::read_optional_plist::<ManifestInfo>(&backup_directory.join("Manifest.plist"))
+                .await
+        {
+            metadata = metadata.with_manifest(manifest.to_details());
+        }
+        if let Some(status) =
+            Self::read_optional_plist::<StatusInfo>(&backup_directory.join("Status.plist")).await
+        {
+            metadata = metadata.with_status(status.to_details());
+        }
+
+        Ok(metadata)
+    }
+
+    /// Reads a plist file, returning `None` if it is missing or cannot be parsed
+    async fn read_optional_plist<T: DeserializeOwned>(path: &Path) -> Option<T> {
+        let content = fs::read(path).await.ok()?;
+        plist::from_bytes(&content).ok()
+    }
 }
 
 impl MetadataRepository for MetadataRepositoryImpl {
@@ -120,26 +162,14 @@ impl MetadataRepositoryImpl {
             ));
         }
 
-        // Read and parse the plist file
-        let plist_content = fs::read(&info_plist_path).await.with_context(|| {
-            format!(
-                "Failed to read Info.plist file: {}",
-                info_plist_path.display()
-            )
-        })?;
-
-        Self::load_from_plist_content(&plist_content).with_context(|| {
-            format!(
-                "Failed to parse Info.plist file: {}",
-                info_plist_path.display()
-            )
-        })
+        Self::read_backup_directory(backup_directory).await
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::domain::entities::StatusDetails;
     use anyhow::Result;
     use assert_fs::TempDir;
     use assert_fs::prelude::*;
@@ -453,6 +483,41 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_find_by_backup_directory_reads_manifest_and_status() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let backup_dir = temp_dir.child("test_backup");
+
+        backup_dir
+            .child("Info.plist")
+            .write_str(&create_valid_plist_content())?;
+        backup_dir.child("Manifest.plist").write_str(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>IsEncrypted</key>
+    <true/>
+    <key>Version</key>
+    <string>10.0</string>
+</dict>
+</plist>"#,
+        )?;
+        backup_dir
+            .child("Status.plist")
+            .write_str(&create_malformed_plist_content())?;
+
+        let repo = MetadataRepositoryImpl::new(temp_dir.path());
+        let metadata = repo.find_by_backup_directory(backup_dir.path()).await?;
+
+        assert_eq!(metadata.manifest().is_encrypted, Some(true));
+        assert_eq!(metadata.manifest().version.as_deref(), Some("10.0"));
+        // A malformed Status.plist is skipped rather than failing the whole backup
+        assert_eq!(metadata.status(), &StatusDetails::default());
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_find_by_backup_directory_not_directory() -> Result<()> {
         let temp_dir = TempDir::new()?;
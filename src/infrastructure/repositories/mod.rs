@@ -1,5 +1,23 @@
+pub mod application_repository_impl;
+pub mod blob_repository_impl;
+pub mod call_repository_impl;
+pub mod contact_repository_impl;
 pub mod file_repository_impl;
+pub mod mbdb_file_repository_impl;
+pub mod message_repository_impl;
 pub mod metadata_repository_impl;
+pub mod photo_repository_impl;
+pub mod safari_repository_impl;
+pub mod voicemail_repository_impl;
 
+pub use application_repository_impl::ApplicationRepositoryImpl;
+pub use blob_repository_impl::BlobRepositoryImpl;
+pub use call_repository_impl::CallRepositoryImpl;
+pub use contact_repository_impl::ContactRepositoryImpl;
 pub use file_repository_impl::FileRepositoryImpl;
+pub use mbdb_file_repository_impl::MbdbFileRepositoryImpl;
+pub use message_repository_impl::MessageRepositoryImpl;
 pub use metadata_repository_impl::MetadataRepositoryImpl;
+pub use photo_repository_impl::PhotoRepositoryImpl;
+pub use safari_repository_impl::SafariRepositoryImpl;
+pub use voicemail_repository_impl::VoicemailRepositoryImpl;
@@ -0,0 +1,276 @@
+use anyhow::{Context as _, Result};
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use sea_orm::{ConnectionTrait as _, FromQueryResult, Statement};
+use std::collections::HashMap;
+
+use crate::domain::entities::{AssetKind, PhotoAsset};
+use crate::domain::repositories::PhotoRepository;
+use crate::domain::value_objects::AppleTimestamp;
+use crate::infrastructure::database::DatabaseConnection;
+
+/// `ZASSET.ZKIND` of videos
+const KIND_VIDEO: i64 = 1;
+/// `ZGENERICALBUM.ZKIND` of albums created by the user
+const ALBUM_KIND_USER: i64 = 2;
+
+/// Row of the asset table joined with its `ZADDITIONALASSETATTRIBUTES`
+#[derive(Debug, FromQueryResult)]
+struct AssetRow {
+    id: i64,
+    directory: Option<String>,
+    filename: Option<String>,
+    original_filename: Option<String>,
+    kind: Option<i64>,
+    date_created: Option<f64>,
+    hidden: Option<i64>,
+    trashed_state: Option<i64>,
+    trashed_date: Option<f64>,
+}
+
+/// Row of the table joining albums and assets, with the title of the album
+#[derive(Debug, FromQueryResult)]
+struct AlbumMemberRow {
+    asset_id: i64,
+    title: String,
+}
+
+/// Row of `sqlite_master` or `pragma_table_info` naming a table or column
+#[derive(Debug, FromQueryResult)]
+struct NameRow {
+    name: String,
+}
+
+/// Implementation of `PhotoRepository` reading the `Photos.sqlite` Core Data store
+pub struct PhotoRepositoryImpl {
+    /// Connection to `Photos.sqlite`
+    db: DatabaseConnection,
+}
+
+impl PhotoRepositoryImpl {
+    /// Location of the database in the backup
+    pub const DOMAIN: &str = "CameraRollDomain";
+    /// Relative path of the database in its domain
+    pub const RELATIVE_PATH: &str = "Media/PhotoData/Photos.sqlite";
+
+    /// Creates a new `PhotoRepositoryImpl` for a connection to `Photos.sqlite`
+    #[must_use]
+    #[inline]
+    pub const fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// Runs a query and maps its rows
+    async fn query<T: FromQueryResult>(&self, sql: &str) -> Result<Vec<T>> {
+        let connection = self.db.get_connection();
+        let statement = Statement::from_string(connection.get_database_backend(), sql);
+        T::find_by_statement(statement)
+            .all(connection)
+            .await
+            .with_context(|| format!("Failed to query Photos.sqlite: {sql}"))
+    }
+
+    /// Finds the table joining albums and assets, with its album and asset columns
+    ///
+    /// Core Data numbers the table and its columns after the entities, e.g. `Z_28ASSETS`
+    /// with `Z_28ALBUMS` and `Z_3ASSETS`, and the numbers differ between iOS versions.
+    async fn find_album_join(
+        &self,
+        tables: &[NameRow],
+    ) -> Result<Option<(String, String, String)>> {
+        let join_table = Regex::new(r"^Z_\d+ASSETS$")?;
+        let album_column = Regex::new(r"^Z_\d+ALBUMS$")?;
+        let asset_column = Regex::new(r"^Z_\d+ASSETS$")?;
+
+        for table in tables
+            .iter()
+            .filter(|table| join_table.is_match(&table.name))
+        {
+            let columns: Vec<NameRow> = self
+                .query(&format!(
+                    "SELECT name FROM pragma_table_info('{}')",
+                    table.name
+                ))
+                .await?;
+            let find = |pattern: &Regex| {
+                columns
+                    .iter()
+                    .find(|column| pattern.is_match(&column.name))
+                    .map(|column| column.name.clone())
+            };
+            if let (Some(albums), Some(assets)) = (find(&album_column), find(&asset_column)) {
+                return Ok(Some((table.name.clone(), albums, assets)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Reads the titles of the user albums holding each asset
+    async fn find_albums(&self, tables: &[NameRow]) -> Result<HashMap<i64, Vec<String>>> {
+        let Some((join_table, album_column, asset_column)) = self.find_album_join(tables).await?
+        else {
+            return Ok(HashMap::new());
+        };
+
+        let members: Vec<AlbumMemberRow> = self
+            .query(&format!(
+                "SELECT j.{asset_column} AS asset_id, al.ZTITLE AS title \
+                 FROM {join_table} AS j JOIN ZGENERICALBUM AS al ON al.Z_PK = j.{album_column} \
+                 WHERE al.ZKIND = {ALBUM_KIND_USER} AND al.ZTITLE IS NOT NULL \
+                 ORDER BY al.ZTITLE, al.Z_PK"
+            ))
+            .await?;
+
+        let mut albums: HashMap<i64, Vec<String>> = HashMap::new();
+        for member in members {
+            albums
+                .entry(member.asset_id)
+                .or_default()
+                .push(member.title);
+        }
+        Ok(albums)
+    }
+}
+
+/// Parses a time stored as seconds since the Apple epoch
+fn parse_time(seconds: f64) -> Option<DateTime<Utc>> {
+    AppleTimestamp::from_fractional_seconds(seconds).map(AppleTimestamp::to_datetime)
+}
+
+impl PhotoRepository for PhotoRepositoryImpl {
+    #[inline]
+    async fn find_all(&self) -> Result<Vec<PhotoAsset>> {
+        let tables: Vec<NameRow> = self
+            .query("SELECT name FROM sqlite_master WHERE type = 'table'")
+            .await?;
+
+        // The asset table was named ZGENERICASSET before iOS 14
+        let asset_table = if tables.iter().any(|table| table.name == "ZASSET") {
+            "ZASSET"
+        } else {
+            "ZGENERICASSET"
+        };
+        let assets: Vec<AssetRow> = self
+            .query(&format!(
+                "SELECT a.Z_PK AS id, a.ZDIRECTORY AS directory, a.ZFILENAME AS filename, \
+                 aa.ZORIGINALFILENAME AS original_filename, a.ZKIND AS kind, \
+                 CAST(a.ZDATECREATED AS REAL) AS date_created, a.ZHIDDEN AS hidden, \
+                 a.ZTRASHEDSTATE AS trashed_state, \
+                 CAST(a.ZTRASHEDDATE AS REAL) AS trashed_date \
+                 FROM {asset_table} AS a \
+                 LEFT JOIN ZADDITIONALASSETATTRIBUTES AS aa ON aa.ZASSET = a.Z_PK \
+                 ORDER BY a.ZDATECREATED, a.Z_PK"
+            ))
+            .await?;
+        let mut albums = self.find_albums(&tables).await?;
+
+        // Assets without a file, such as those only kept in iCloud, cannot be exported
+        Ok(assets
+            .into_iter()
+            .filter_map(|row| {
+                let directory = row.directory.filter(|directory| !directory.is_empty())?;
+                let filename = row.filename.filter(|filename| !filename.is_empty())?;
+                Some(PhotoAsset {
+                    id: row.id,
+                    directory,
+                    filename,
+                    original_filename: row.original_filename,
+                    kind: if row.kind == Some(KIND_VIDEO) {
+                        AssetKind::Video
+                    } else {
+                        AssetKind::Photo
+                    },
+                    date_created: row.date_created.and_then(parse_time),
+                    hidden: row.hidden.is_some_and(|value| value != 0),
+                    trashed_date: row.trashed_date.and_then(parse_time),
+                    trashed: row.trashed_state.is_some_and(|value| value != 0),
+                    albums: albums.remove(&row.id).unwrap_or_default(),
+                })
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use sea_orm::Database;
+
+    async fn create_photos_db(asset_table: &str) -> Result<PhotoRepositoryImpl> {
+        let connection = Database::connect("sqlite::memory:").await?;
+        connection
+            .execute_unprepared(&format!(
+                "CREATE TABLE {asset_table} (Z_PK INTEGER PRIMARY KEY, ZDIRECTORY VARCHAR, \
+                 ZFILENAME VARCHAR, ZKIND INTEGER, ZDATECREATED TIMESTAMP, ZHIDDEN INTEGER, \
+                 ZTRASHEDSTATE INTEGER, ZTRASHEDDATE TIMESTAMP);
+                 CREATE TABLE ZADDITIONALASSETATTRIBUTES (Z_PK INTEGER PRIMARY KEY, \
+                 ZASSET INTEGER, ZORIGINALFILENAME VARCHAR);
+                 CREATE TABLE ZGENERICALBUM (Z_PK INTEGER PRIMARY KEY, ZKIND INTEGER, \
+                 ZTITLE VARCHAR);
+                 CREATE TABLE Z_28ASSETS (Z_28ALBUMS INTEGER, Z_3ASSETS INTEGER, \
+                 Z_FOK_3ASSETS INTEGER);
+                 INSERT INTO {asset_table} VALUES \
+                 (1, 'DCIM/100APPLE', 'IMG_0002.MOV', 1, 726978700, 0, 1, 726978800), \
+                 (2, 'DCIM/100APPLE', 'IMG_0001.HEIC', 0, 726978600, 1, 0, NULL), \
+                 (3, NULL, NULL, 0, 726978500, 0, 0, NULL);
+                 INSERT INTO ZADDITIONALASSETATTRIBUTES VALUES (1, 2, 'beach.heic');
+                 INSERT INTO ZGENERICALBUM VALUES (1, 2, 'Trips'), (2, 2, 'Family'), \
+                 (3, 1505, 'Smart');
+                 INSERT INTO Z_28ASSETS VALUES (1, 2, 1), (2, 2, 1), (3, 1, 1);"
+            ))
+            .await?;
+        Ok(PhotoRepositoryImpl::new(
+            DatabaseConnection::new_from_connection(connection),
+        ))
+    }
+
+    #[tokio::test]
+    async fn test_find_all() -> Result<()> {
+        let repo = create_photos_db("ZASSET").await?;
+
+        let assets = repo.find_all().await?;
+
+        assert_eq!(
+            assets,
+            vec![
+                PhotoAsset {
+                    id: 2,
+                    directory: "DCIM/100APPLE".to_owned(),
+                    filename: "IMG_0001.HEIC".to_owned(),
+                    original_filename: Some("beach.heic".to_owned()),
+                    kind: AssetKind::Photo,
+                    date_created: Some("2024-01-15T02:30:00Z".parse()?),
+                    hidden: true,
+                    trashed_date: None,
+                    trashed: false,
+                    albums: vec!["Family".to_owned(), "Trips".to_owned()],
+                },
+                PhotoAsset {
+                    id: 1,
+                    directory: "DCIM/100APPLE".to_owned(),
+                    filename: "IMG_0002.MOV".to_owned(),
+                    original_filename: None,
+                    kind: AssetKind::Video,
+                    date_created: Some("2024-01-15T02:31:40Z".parse()?),
+                    hidden: false,
+                    trashed_date: Some("2024-01-15T02:33:20Z".parse()?),
+                    trashed: true,
+                    albums: vec![],
+                },
+            ]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_find_all_generic_asset_table() -> Result<()> {
+        let repo = create_photos_db("ZGENERICASSET").await?;
+
+        let assets = repo.find_all().await?;
+
+        let ids: Vec<_> = assets.iter().map(|asset| asset.id).collect();
+        assert_eq!(ids, vec![2, 1]);
+        Ok(())
+    }
+}
@@ -0,0 +1,404 @@
+use anyhow::{Context as _, Result};
+use chrono::{DateTime, Utc};
+use sea_orm::{ConnectionTrait as _, FromQueryResult, Statement};
+use std::collections::{HashMap, HashSet};
+
+use crate::domain::entities::{Bookmark, BrowserTab, HistoryVisit};
+use crate::domain::repositories::SafariRepository;
+use crate::domain::value_objects::AppleTimestamp;
+use crate::infrastructure::database::DatabaseConnection;
+
+/// `bookmarks.type` of folders
+const BOOKMARK_TYPE_FOLDER: i64 = 1;
+
+/// Row of `history_visits` joined with its `history_items`
+#[derive(Debug, FromQueryResult)]
+struct VisitRow {
+    id: i64,
+    url: String,
+    title: Option<String>,
+    visit_time: Option<f64>,
+    visit_count: Option<i64>,
+}
+
+/// Row of the `bookmarks` table
+#[derive(Debug, FromQueryResult)]
+struct BookmarkRow {
+    id: i64,
+    parent: Option<i64>,
+    kind: Option<i64>,
+    title: Option<String>,
+    url: Option<String>,
+}
+
+/// Row of the `tabs` table of `BrowserState.db`
+#[derive(Debug, FromQueryResult)]
+struct TabRow {
+    title: Option<String>,
+    url: Option<String>,
+    last_viewed_time: Option<f64>,
+    private_browsing: Option<i64>,
+}
+
+/// Row of `cloud_tabs` joined with its `cloud_tab_devices`
+#[derive(Debug, FromQueryResult)]
+struct CloudTabRow {
+    title: Option<String>,
+    url: Option<String>,
+    device: Option<String>,
+}
+
+/// Implementation of `SafariRepository` reading the databases of Safari
+pub struct SafariRepositoryImpl {
+    /// Connection to `History.db`
+    history: DatabaseConnection,
+    /// Connection to `Bookmarks.db`, if the backup has one
+    bookmarks: Option<DatabaseConnection>,
+    /// Connection to `BrowserState.db`, if the backup has one
+    browser_state: Option<DatabaseConnection>,
+    /// Connection to `CloudTabs.db`, if the backup has one
+    cloud_tabs: Option<DatabaseConnection>,
+}
+
+impl SafariRepositoryImpl {
+    /// Location of the history, bookmarks and iCloud tabs in the backup
+    pub const DOMAIN: &str = "HomeDomain";
+    /// Location of the open tabs in the backup
+    pub const APP_DOMAIN: &str = "AppDomain-com.apple.mobilesafari";
+    /// Relative path of the history in its domain
+    pub const HISTORY_RELATIVE_PATH: &str = "Library/Safari/History.db";
+    /// Relative path of the bookmarks in their domain
+    pub const BOOKMARKS_RELATIVE_PATH: &str = "Library/Safari/Bookmarks.db";
+    /// Relative path of the open tabs in their domain
+    pub const BROWSER_STATE_RELATIVE_PATH: &str = "Library/Safari/BrowserState.db";
+    /// Relative path of the tabs of other devices, in the app container or in `HomeDomain`
+    pub const CLOUD_TABS_RELATIVE_PATH: &str = "Library/Safari/CloudTabs.db";
+
+    /// Creates a new `SafariRepositoryImpl` for a connection to `History.db`
+    #[must_use]
+    #[inline]
+    pub const fn new(history: DatabaseConnection) -> Self {
+        Self {
+            history,
+            bookmarks: None,
+            browser_state: None,
+            cloud_tabs: None,
+        }
+    }
+
+    /// Sets the connection to `Bookmarks.db` to read bookmarks from
+    #[must_use]
+    #[inline]
+    pub fn with_bookmarks(mut self, bookmarks: Option<DatabaseConnection>) -> Self {
+        self.bookmarks = bookmarks;
+        self
+    }
+
+    /// Sets the connection to `BrowserState.db` to read the open tabs from
+    #[must_use]
+    #[inline]
+    pub fn with_browser_state(mut self, browser_state: Option<DatabaseConnection>) -> Self {
+        self.browser_state = browser_state;
+        self
+    }
+
+    /// Sets the connection to `CloudTabs.db` to read the tabs of other devices from
+    #[must_use]
+    #[inline]
+    pub fn with_cloud_tabs(mut self, cloud_tabs: Option<DatabaseConnection>) -> Self {
+        self.cloud_tabs = cloud_tabs;
+        self
+    }
+
+    /// Runs a query on a database and maps its rows
+    async fn query<T: FromQueryResult>(db: &DatabaseConnection, sql: &str) -> Result<Vec<T>> {
+        let connection = db.get_connection();
+        let statement = Statement::from_string(connection.get_database_backend(), sql);
+        T::find_by_statement(statement)
+            .all(connection)
+            .await
+            .with_context(|| format!("Failed to query Safari data: {sql}"))
+    }
+}
+
+/// Parses a time stored as seconds since the Apple epoch
+fn parse_time(seconds: f64) -> Option<DateTime<Utc>> {
+    AppleTimestamp::from_fractional_seconds(seconds).map(AppleTimestamp::to_datetime)
+}
+
+/// Returns the title Safari shows for a folder, which differs for the built-in folders
+fn folder_title(title: Option<&str>) -> Option<String> {
+    let title = match title? {
+        "BookmarksBar" => "Favorites",
+        "BookmarksMenu" => "Bookmarks Menu",
+        "com.apple.ReadingList" => "Reading List",
+        title => title,
+    };
+    Some(title.to_owned())
+}
+
+/// Builds a bookmark and, for a folder, the tree below it
+///
+/// Rows that were already visited are skipped, so that a corrupt parent chain cannot loop.
+fn build_bookmark(
+    row: &BookmarkRow,
+    children: &HashMap<i64, Vec<&BookmarkRow>>,
+    visited: &mut HashSet<i64>,
+) -> Bookmark {
+    visited.insert(row.id);
+    if row.kind != Some(BOOKMARK_TYPE_FOLDER) {
+        return Bookmark::new(row.id, row.title.clone(), row.url.clone());
+    }
+
+    let mut folder_children = Vec::new();
+    for child in children.get(&row.id).into_iter().flatten() {
+        if !visited.contains(&child.id) {
+            folder_children.push(build_bookmark(child, children, visited));
+        }
+    }
+    Bookmark::folder(row.id, folder_title(row.title.as_deref()), folder_children)
+}
+
+impl SafariRepository for SafariRepositoryImpl {
+    #[inline]
+    async fn find_history(&self) -> Result<Vec<HistoryVisit>> {
+        let visits: Vec<VisitRow> = Self::query(
+            &self.history,
+            "SELECT v.id, i.url, v.title, CAST(v.visit_time AS REAL) AS visit_time, \
+             i.visit_count FROM history_visits AS v \
+             JOIN history_items AS i ON i.id = v.history_item \
+             ORDER BY v.visit_time DESC, v.id DESC",
+        )
+        .await?;
+
+        Ok(visits
+            .into_iter()
+            .map(|row| HistoryVisit {
+                id: row.id,
+                url: row.url,
+                title: row.title.filter(|title| !title.is_empty()),
+                visit_time: row.visit_time.and_then(parse_time),
+                visit_count: row.visit_count.unwrap_or_default(),
+            })
+            .collect())
+    }
+
+    #[inline]
+    async fn find_bookmarks(&self) -> Result<Vec<Bookmark>> {
+        let Some(bookmarks) = &self.bookmarks else {
+            return Ok(Vec::new());
+        };
+        let rows: Vec<BookmarkRow> = Self::query(
+            bookmarks,
+            "SELECT id, parent, type AS kind, title, url FROM bookmarks \
+             ORDER BY parent, order_index, id",
+        )
+        .await?;
+
+        let mut children: HashMap<i64, Vec<&BookmarkRow>> = HashMap::new();
+        for row in &rows {
+            if let Some(parent) = row.parent {
+                children.entry(parent).or_default().push(row);
+            }
+        }
+
+        // The untitled root folder is left out, so the tree starts with Favorites and the
+        // other top-level folders
+        let mut visited = HashSet::new();
+        let mut tree = Vec::new();
+        for root in rows.iter().filter(|row| row.parent.is_none()) {
+            visited.insert(root.id);
+            for row in children.get(&root.id).into_iter().flatten() {
+                if !visited.contains(&row.id) {
+                    tree.push(build_bookmark(row, &children, &mut visited));
+                }
+            }
+        }
+        Ok(tree)
+    }
+
+    #[inline]
+    async fn find_tabs(&self) -> Result<Vec<BrowserTab>> {
+        let mut tabs = Vec::new();
+
+        if let Some(browser_state) = &self.browser_state {
+            let rows: Vec<TabRow> = Self::query(
+                browser_state,
+                "SELECT title, url, CAST(last_viewed_time AS REAL) AS last_viewed_time, \
+                 private_browsing FROM tabs ORDER BY order_index, id",
+            )
+            .await?;
+            tabs.extend(rows.into_iter().map(|row| BrowserTab {
+                title: row.title.filter(|title| !title.is_empty()),
+                url: row.url.filter(|url| !url.is_empty()),
+                last_viewed: row.last_viewed_time.and_then(parse_time),
+                private: row.private_browsing.is_some_and(|value| value != 0),
+                device: None,
+            }));
+        }
+
+        if let Some(cloud_tabs) = &self.cloud_tabs {
+            let rows: Vec<CloudTabRow> = Self::query(
+                cloud_tabs,
+                "SELECT t.title, t.url, d.device_name AS device FROM cloud_tabs AS t \
+                 LEFT JOIN cloud_tab_devices AS d ON d.device_uuid = t.device_uuid \
+                 ORDER BY d.device_name, t.rowid",
+            )
+            .await?;
+            tabs.extend(rows.into_iter().map(|row| BrowserTab {
+                title: row.title.filter(|title| !title.is_empty()),
+                url: row.url.filter(|url| !url.is_empty()),
+                last_viewed: None,
+                private: false,
+                device: Some(row.device.unwrap_or_else(|| "Unknown device".to_owned())),
+            }));
+        }
+
+        Ok(tabs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use sea_orm::Database;
+
+    async fn create_db(sql: &str) -> Result<DatabaseConnection> {
+        let connection = Database::connect("sqlite::memory:").await?;
+        connection.execute_unprepared(sql).await?;
+        Ok(DatabaseConnection::new_from_connection(connection))
+    }
+
+    async fn create_history_db() -> Result<DatabaseConnection> {
+        create_db(
+            "CREATE TABLE history_items (id INTEGER PRIMARY KEY, url TEXT NOT NULL, \
+             visit_count INTEGER NOT NULL);
+             CREATE TABLE history_visits (id INTEGER PRIMARY KEY, history_item INTEGER NOT NULL, \
+             visit_time REAL NOT NULL, title TEXT);
+             INSERT INTO history_items VALUES (1, 'https://example.com/', 2), \
+             (2, 'https://www.apple.com/', 1);
+             INSERT INTO history_visits VALUES (1, 1, 726978600, 'Example'), \
+             (2, 2, 726978700.5, ''), (3, 1, 726978800, 'Example Domain');",
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_find_history() -> Result<()> {
+        let repo = SafariRepositoryImpl::new(create_history_db().await?);
+
+        let visits = repo.find_history().await?;
+
+        assert_eq!(
+            visits[1],
+            HistoryVisit {
+                id: 2,
+                url: "https://www.apple.com/".to_owned(),
+                title: None,
+                visit_time: Some("2024-01-15T02:31:40.500Z".parse()?),
+                visit_count: 1,
+            }
+        );
+        let ids: Vec<_> = visits.iter().map(|visit| visit.id).collect();
+        assert_eq!(ids, vec![3, 2, 1]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_find_bookmarks() -> Result<()> {
+        let bookmarks = create_db(
+            "CREATE TABLE bookmarks (id INTEGER PRIMARY KEY, parent INTEGER, type INTEGER, \
+             title TEXT, url TEXT, order_index INTEGER);
+             INSERT INTO bookmarks VALUES (0, NULL, 1, 'Root', NULL, 0), \
+             (1, 0, 1, 'BookmarksBar', NULL, 0), (2, 1, 0, 'Apple', 'https://www.apple.com/', 1), \
+             (3, 1, 1, 'News', NULL, 0), (4, 3, 0, 'Example', 'https://example.com/', 0), \
+             (5, 0, 1, 'com.apple.ReadingList', NULL, 1);",
+        )
+        .await?;
+        let repo =
+            SafariRepositoryImpl::new(create_history_db().await?).with_bookmarks(Some(bookmarks));
+
+        let tree = repo.find_bookmarks().await?;
+
+        assert_eq!(
+            tree,
+            vec![
+                Bookmark::folder(
+                    1,
+                    Some("Favorites".to_owned()),
+                    vec![
+                        Bookmark::folder(
+                            3,
+                            Some("News".to_owned()),
+                            vec![Bookmark::new(
+                                4,
+                                Some("Example".to_owned()),
+                                Some("https://example.com/".to_owned())
+                            )]
+                        ),
+                        Bookmark::new(
+                            2,
+                            Some("Apple".to_owned()),
+                            Some("https://www.apple.com/".to_owned())
+                        ),
+                    ]
+                ),
+                Bookmark::folder(5, Some("Reading List".to_owned()), vec![]),
+            ]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_find_tabs() -> Result<()> {
+        let browser_state = create_db(
+            "CREATE TABLE tabs (id INTEGER PRIMARY KEY, title TEXT, url TEXT, \
+             order_index INTEGER, last_viewed_time REAL, private_browsing BOOLEAN);
+             INSERT INTO tabs VALUES (1, 'Example', 'https://example.com/', 1, 726978600, 0), \
+             (2, 'Private', 'https://www.apple.com/', 0, NULL, 1);",
+        )
+        .await?;
+        let cloud_tabs = create_db(
+            "CREATE TABLE cloud_tabs (tab_uuid TEXT PRIMARY KEY, device_uuid TEXT, title TEXT, \
+             url TEXT);
+             CREATE TABLE cloud_tab_devices (device_uuid TEXT PRIMARY KEY, device_name TEXT);
+             INSERT INTO cloud_tabs VALUES ('A', 'D', 'Mac tab', 'https://example.org/');
+             INSERT INTO cloud_tab_devices VALUES ('D', 'MacBook');",
+        )
+        .await?;
+        let repo = SafariRepositoryImpl::new(create_history_db().await?)
+            .with_browser_state(Some(browser_state))
+            .with_cloud_tabs(Some(cloud_tabs));
+
+        let tabs = repo.find_tabs().await?;
+
+        assert_eq!(
+            tabs,
+            vec![
+                BrowserTab {
+                    title: Some("Private".to_owned()),
+                    url: Some("https://www.apple.com/".to_owned()),
+                    last_viewed: None,
+                    private: true,
+                    device: None,
+                },
+                BrowserTab {
+                    title: Some("Example".to_owned()),
+                    url: Some("https://example.com/".to_owned()),
+                    last_viewed: Some("2024-01-15T02:30:00Z".parse()?),
+                    private: false,
+                    device: None,
+                },
+                BrowserTab {
+                    title: Some("Mac tab".to_owned()),
+                    url: Some("https://example.org/".to_owned()),
+                    last_viewed: None,
+                    private: false,
+                    device: Some("MacBook".to_owned()),
+                },
+            ]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,141 @@
+use anyhow::{Context as _, Result};
+use chrono::{DateTime, Utc};
+use sea_orm::{ConnectionTrait as _, FromQueryResult, Statement};
+use std::time::Duration;
+
+use crate::domain::entities::Voicemail;
+use crate::domain::repositories::VoicemailRepository;
+use crate::domain::value_objects::AppleTimestamp;
+use crate::infrastructure::database::DatabaseConnection;
+
+/// Row of the `voicemail` table
+#[derive(Debug, FromQueryResult)]
+struct VoicemailRow {
+    id: i64,
+    sender: Option<String>,
+    callback_number: Option<String>,
+    date: Option<i64>,
+    duration: Option<i64>,
+    trashed_date: Option<i64>,
+}
+
+/// Implementation of `VoicemailRepository` reading the `voicemail.db` database
+pub struct VoicemailRepositoryImpl {
+    /// Connection to `voicemail.db`
+    db: DatabaseConnection,
+}
+
+impl VoicemailRepositoryImpl {
+    /// Location of the database in the backup
+    pub const DOMAIN: &str = "HomeDomain";
+    /// Relative path of the database in its domain
+    pub const RELATIVE_PATH: &str = "Library/Voicemail/voicemail.db";
+
+    /// Creates a new `VoicemailRepositoryImpl` for a connection to `voicemail.db`
+    #[must_use]
+    #[inline]
+    pub const fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+}
+
+/// Converts seconds since the Unix epoch, where zero means no time
+fn unix_time(seconds: Option<i64>) -> Option<DateTime<Utc>> {
+    seconds
+        .filter(|seconds| *seconds != 0)
+        .and_then(|seconds| DateTime::from_timestamp(seconds, 0))
+}
+
+/// Converts seconds since the Apple epoch, where zero means no time
+fn apple_time(seconds: Option<i64>) -> Option<DateTime<Utc>> {
+    seconds
+        .filter(|seconds| *seconds != 0)
+        .and_then(AppleTimestamp::from_seconds)
+        .map(AppleTimestamp::to_datetime)
+}
+
+impl VoicemailRepository for VoicemailRepositoryImpl {
+    #[inline]
+    async fn find_all(&self) -> Result<Vec<Voicemail>> {
+        let sql = "SELECT ROWID AS id, sender, callback_num AS callback_number, date, duration, \
+                   trashed_date FROM voicemail ORDER BY date DESC, ROWID DESC";
+        let connection = self.db.get_connection();
+        let statement = Statement::from_string(connection.get_database_backend(), sql);
+        let rows = VoicemailRow::find_by_statement(statement)
+            .all(connection)
+            .await
+            .context("Failed to query voicemail.db")?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| Voicemail {
+                id: row.id,
+                sender: row.sender.filter(|sender| !sender.is_empty()),
+                callback_number: row.callback_number.filter(|number| !number.is_empty()),
+                // Only `date` counts from the Unix epoch; `trashed_date`, like most Apple
+                // databases, counts from the Apple epoch
+                date: unix_time(row.date),
+                duration: Duration::from_secs(
+                    row.duration
+                        .and_then(|seconds| u64::try_from(seconds).ok())
+                        .unwrap_or_default(),
+                ),
+                trashed_date: apple_time(row.trashed_date),
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use sea_orm::Database;
+
+    #[tokio::test]
+    async fn test_find_all() -> Result<()> {
+        let connection = Database::connect("sqlite::memory:").await?;
+        connection
+            .execute_unprepared(
+                "CREATE TABLE voicemail (ROWID INTEGER PRIMARY KEY, remote_uid INTEGER, \
+                 date INTEGER, token TEXT, sender TEXT, callback_num TEXT, duration INTEGER, \
+                 expiration INTEGER, trashed_date INTEGER, flags INTEGER);
+                 INSERT INTO voicemail VALUES \
+                 (1, 100, 1700000000, 'T1', '+15550001', '+15550001', 23, 0, 0, 3), \
+                 (2, 101, 1700000100, 'T2', NULL, '', 5, 0, 721693000, 3);",
+            )
+            .await?;
+        let repo =
+            VoicemailRepositoryImpl::new(DatabaseConnection::new_from_connection(connection));
+
+        let voicemails = repo.find_all().await?;
+
+        assert_eq!(
+            voicemails,
+            vec![
+                Voicemail {
+                    id: 2,
+                    sender: None,
+                    callback_number: None,
+                    date: DateTime::from_timestamp(1_700_000_100, 0),
+                    duration: Duration::from_secs(5),
+                    trashed_date: AppleTimestamp::from_seconds(721_693_000)
+                        .map(AppleTimestamp::to_datetime),
+                },
+                Voicemail {
+                    id: 1,
+                    sender: Some("+15550001".to_owned()),
+                    callback_number: Some("+15550001".to_owned()),
+                    date: DateTime::from_timestamp(1_700_000_000, 0),
+                    duration: Duration::from_secs(23),
+                    trashed_date: None,
+                },
+            ]
+        );
+        assert_eq!(
+            voicemails[0].trashed_date,
+            DateTime::from_timestamp(1_700_000_200, 0)
+        );
+        Ok(())
+    }
+}
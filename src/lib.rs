@@ -10,13 +10,39 @@ pub mod infrastructure;
 
 // Re-exports for convenience
 pub use application::{
-    DisplayService, ExtractError, ExtractResult, ExtractService, ListService, SearchParams,
-    SearchService,
+    AlbumExport, AppSummary, AppsService, CallsService, ContactsService, DelimitedFormatter,
+    DiffReport, DiffService, DisplayService, ExportedContact, ExportedConversation, ExportedPhoto,
+    ExportedSafariFile, ExtractError, ExtractErrorKind, ExtractPlan, ExtractProgress,
+    ExtractResult, ExtractService, ExtractStatus, FileOutcome, FileType, HashAlgorithm,
+    HashManifest, JsonFormatter, ListService, ManifestFormat, MessagesService, MetadataColumn,
+    NdjsonFormatter, OutputFormat, OutputFormatter, PhotosService, SafariContent, SafariFormat,
+    SafariService, SearchParams, SearchService, TableFormatter, TranscriptFormat, VCardVersion,
+    VerifyReport, VerifyService, VoicemailService, VoicemailSummary,
+};
+pub use cli::{
+    CallsArgs, Cli, Commands, ContactsArgs, ExtractArgs, MessagesArgs, PhotosArgs, SafariArgs,
+    SearchArgs, VoicemailArgs,
+};
+pub use domain::entities::{
+    Application, AssetKind, Attachment, Bookmark, BrowserTab, Call, CallDirection, Contact,
+    Conversation, DeviceDetails, File, HistoryVisit, Labeled, ManifestDetails, Message, Metadata,
+    PhotoAsset, PostalAddress, StatusDetails, Voicemail,
 };
-pub use cli::{Cli, Commands};
-pub use domain::entities::{File, Metadata};
 pub use domain::queries::{BasicQuery, CompositeQuery, FileQuery};
-pub use domain::repositories::{FileRepository, MetadataRepository};
-pub use domain::value_objects::{Domain, FileFlags, FileId, RelativePath};
+pub use domain::repositories::{
+    ApplicationRepository, BlobRepository, CallRepository, ContactRepository, FileRepository,
+    MessageRepository, MetadataRepository, PhotoRepository, SafariRepository, VoicemailRepository,
+};
+pub use domain::value_objects::{
+    AppleTimestamp, Domain, DomainKind, FileFlags, FileId, FileMetadata, RelativePath,
+    UnsafePathError,
+};
+pub use infrastructure::backup::{
+    BackupDirectory, BackupFileRepository, BackupFormat, open_database, open_database_if_exists,
+};
 pub use infrastructure::database::DatabaseConnection;
-pub use infrastructure::repositories::{FileRepositoryImpl, MetadataRepositoryImpl};
+pub use infrastructure::repositories::{
+    ApplicationRepositoryImpl, BlobRepositoryImpl, CallRepositoryImpl, ContactRepositoryImpl,
+    FileRepositoryImpl, MbdbFileRepositoryImpl, MessageRepositoryImpl, MetadataRepositoryImpl,
+    PhotoRepositoryImpl, SafariRepositoryImpl, VoicemailRepositoryImpl,
+};
@@ -1,22 +1,352 @@
 //! idig - A tool for extracting files from iPhone backups
 
-use anyhow::Result;
+use anyhow::{Context as _, Result};
+use chrono::{DateTime, Utc};
 use clap::Parser as _;
 use idig::{
-    Cli, Commands, DatabaseConnection, DisplayService, ExtractService, FileRepositoryImpl,
-    ListService, MetadataRepositoryImpl, SearchParams, SearchService,
+    AppsService, BackupDirectory, BackupFileRepository, CallsArgs, CallsService, Cli, Commands,
+    ContactsArgs, ContactsService, DiffService, DisplayService, ExtractArgs, ExtractPlan,
+    ExtractProgress, ExtractResult, ExtractService, HashManifest, ListService, ManifestFormat,
+    MessagesArgs, MessagesService, MetadataRepositoryImpl, PhotosArgs, PhotosService, SafariArgs,
+    SafariService, SearchArgs, SearchParams, SearchService, VerifyReport, VerifyService,
+    VoicemailArgs, VoicemailService,
 };
-use std::path::PathBuf;
+use indicatif::{ProgressBar, ProgressStyle};
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
 use std::sync::Arc;
+use std::thread;
+
+/// Opens a backup directory, unlocking it first if it is encrypted
+async fn open_backup(backup_dir: &Path, password: Option<String>) -> Result<BackupDirectory> {
+    open_backup_with_prompt(backup_dir, password, "Backup password: ").await
+}
+
+/// Opens a backup directory like `open_backup`, asking for the password with a prompt of its own
+async fn open_backup_with_prompt(
+    backup_dir: &Path,
+    password: Option<String>,
+    prompt: &str,
+) -> Result<BackupDirectory> {
+    let backup_dir_str = backup_dir.to_string_lossy();
+    let expanded_backup_dir = shellexpand::tilde(&backup_dir_str);
+    let mut backup = BackupDirectory::open(PathBuf::from(expanded_backup_dir.as_ref())).await?;
+
+    if backup.is_encrypted() {
+        let password = match password {
+            Some(password) => password,
+            None => rpassword::prompt_password(prompt)?,
+        };
+        backup.unlock(&password)?;
+    }
+
+    Ok(backup)
+}
+
+/// Opens the file repository of a backup, warning about manifest records that were skipped
+async fn open_files(backup: &BackupDirectory) -> Result<BackupFileRepository> {
+    let files = backup.file_repository().await?;
+    for record in files.skipped_records() {
+        eprintln!("Warning: skipped invalid manifest record {record}");
+    }
+    Ok(files)
+}
+
+/// Returns the date of the backup, which relative times such as `48h` are counted back from
+async fn backup_date(backup: &BackupDirectory) -> Option<DateTime<Utc>> {
+    backup
+        .metadata()
+        .await
+        .ok()
+        .map(|metadata| *metadata.last_backup_date())
+}
+
+/// Builds the search parameters from the command line arguments
+fn search_params(args: SearchArgs, reference_time: Option<DateTime<Utc>>) -> SearchParams {
+    SearchParams::new(
+        args.domain_exact,
+        args.domain_contains,
+        args.path_exact,
+        args.path_contains,
+        args.or,
+    )
+    .with_domain_glob(args.domain_glob)
+    .with_domain_regex(args.domain_regex)
+    .with_bundle_id(args.bundle_id)
+    .with_path_glob(args.path_glob)
+    .with_path_regex(args.path_regex)
+    .with_ignore_case(args.ignore_case)
+    .with_file_types(args.file_type)
+    .with_domain_kinds(args.domain_kind)
+    .with_size_range(args.min_size, args.max_size)
+    .with_modified_range(args.modified_after, args.modified_before)
+    .with_created_after(args.created_after)
+    .with_protection_class(args.protection_class)
+    .with_reference_time(reference_time)
+    .with_query(args.query)
+}
+
+/// Creates a progress bar following the bytes and files an extraction has copied
+///
+/// The bar is drawn to stderr and hidden if it is not a terminal.
+fn progress_bar() -> Result<(
+    ProgressBar,
+    impl Fn(ExtractProgress) + Send + Sync + 'static,
+)> {
+    let style = ProgressStyle::with_template("{wide_bar} {bytes}/{total_bytes} ({msg})")?;
+    let bar = ProgressBar::no_length().with_style(style);
+
+    let handle = bar.clone();
+    let update = move |progress: ExtractProgress| {
+        handle.set_length(progress.total_bytes);
+        handle.set_position(progress.completed_bytes);
+        handle.set_message(format!(
+            "{}/{} files",
+            progress.completed_files, progress.total_files
+        ));
+    };
+
+    Ok((bar, update))
+}
+
+/// Saves the hash manifest of an extraction
+///
+/// The manifest is written into the output directory unless another path is given.
+fn save_hash_manifest(
+    result: &ExtractResult,
+    output_dir: &Path,
+    path: Option<PathBuf>,
+    format: ManifestFormat,
+) -> Result<()> {
+    let path =
+        path.unwrap_or_else(|| output_dir.join(format!("idig-hashes.{}", format.extension())));
+    HashManifest::from_result(result, output_dir).save(&path, format)?;
+    eprintln!("Hash manifest written to {}", path.display());
+    Ok(())
+}
+
+/// Runs the extract command
+async fn extract(args: ExtractArgs, display_service: &DisplayService) -> Result<()> {
+    let ExtractArgs {
+        backup_dir,
+        output,
+        search,
+        layout,
+        on_conflict,
+        dry_run,
+        plan: plan_file,
+        jobs,
+        preserve_owner,
+        hash,
+        hash_manifest,
+        hash_format,
+        password,
+        ..
+    } = args;
+
+    let backup = open_backup(&backup_dir, password).await?;
+    let file_repo = open_files(&backup).await?;
+    let jobs = jobs
+        .or_else(|| thread::available_parallelism().ok())
+        .unwrap_or(NonZeroUsize::MIN);
+    let hashing = !hash.is_empty();
+    let extract_service = ExtractService::new()
+        .with_layout(layout)
+        .with_on_conflict(on_conflict)
+        .with_preserve_ownership(preserve_owner)
+        .with_jobs(jobs)
+        .with_hashes(hash);
+
+    if dry_run {
+        let output = output.context("--output is required")?;
+        let params = search_params(search, backup_date(&backup).await);
+        let planned = extract_service.plan(&file_repo, &output, params).await?;
+        return display_service.display_extract_plan(&planned);
+    }
+
+    // Progress is only shown while files are copied
+    let (bar, update) = progress_bar()?;
+    let extract_service = extract_service.with_progress(update);
+    let blob_repo = backup.blob_repository();
+
+    let (result, output_dir) = if let Some(path) = plan_file {
+        let plan = ExtractPlan::load(path)?;
+        let result = extract_service
+            .execute(&file_repo, &blob_repo, &plan)
+            .await?;
+        (result, plan.output_dir)
+    } else {
+        let output = output.context("--output is required")?;
+        let params = search_params(search, backup_date(&backup).await);
+        let result = extract_service
+            .extract(&file_repo, &blob_repo, &output, params)
+            .await?;
+        (result, PathBuf::from(output))
+    };
+    bar.finish_and_clear();
+    display_service.display_extract_results(&result)?;
+
+    if hashing {
+        save_hash_manifest(&result, &output_dir, hash_manifest, hash_format)?;
+    }
+
+    Ok(())
+}
+
+/// Runs the messages command
+async fn messages(args: MessagesArgs, display_service: &DisplayService) -> Result<()> {
+    let MessagesArgs {
+        backup_dir,
+        output,
+        transcript_format,
+        no_attachments,
+        password,
+        ..
+    } = args;
+
+    let backup = open_backup(&backup_dir, password).await?;
+    let file_repo = open_files(&backup).await?;
+    let message_repo = backup.message_repository(&file_repo).await?;
+    let blob_repo = backup.blob_repository();
+
+    let conversations = MessagesService::new()
+        .with_format(transcript_format)
+        .with_attachments(!no_attachments)
+        .export(&message_repo, &file_repo, &blob_repo, &output)
+        .await?;
+    display_service.display_messages_export(&conversations)
+}
+
+/// Runs the contacts command
+async fn contacts(args: ContactsArgs, display_service: &DisplayService) -> Result<()> {
+    let ContactsArgs {
+        backup_dir,
+        output,
+        vcard_version,
+        split,
+        password,
+        ..
+    } = args;
+
+    let backup = open_backup(&backup_dir, password).await?;
+    let file_repo = open_files(&backup).await?;
+    let contact_repo = backup.contact_repository(&file_repo).await?;
+
+    let contacts = ContactsService::new()
+        .with_version(vcard_version)
+        .with_split(split)
+        .export(&contact_repo, &output)
+        .await?;
+    display_service.display_contacts_export(&contacts)
+}
+
+/// Runs the calls command
+async fn calls(args: CallsArgs, display_service: &DisplayService) -> Result<()> {
+    let CallsArgs {
+        backup_dir,
+        password,
+        ..
+    } = args;
+
+    let backup = open_backup(&backup_dir, password).await?;
+    let file_repo = open_files(&backup).await?;
+    let call_repo = backup.call_repository(&file_repo).await?;
+
+    let calls = CallsService::new().list(&call_repo).await?;
+    display_service.display_calls(&calls)
+}
+
+/// Runs the voicemail command
+async fn voicemail(args: VoicemailArgs, display_service: &DisplayService) -> Result<()> {
+    let VoicemailArgs {
+        backup_dir,
+        extract_audio,
+        password,
+        ..
+    } = args;
+
+    let backup = open_backup(&backup_dir, password).await?;
+    let file_repo = open_files(&backup).await?;
+    let voicemail_repo = backup.voicemail_repository(&file_repo).await?;
+    let blob_repo = backup.blob_repository();
+
+    let voicemails = VoicemailService::new()
+        .with_audio_dir(extract_audio)
+        .list(&voicemail_repo, &file_repo, &blob_repo)
+        .await?;
+    display_service.display_voicemails(&voicemails)
+}
+
+/// Runs the photos command
+async fn photos(args: PhotosArgs, display_service: &DisplayService) -> Result<()> {
+    let PhotosArgs {
+        backup_dir,
+        output,
+        by_date,
+        albums,
+        password,
+        ..
+    } = args;
+
+    let backup = open_backup(&backup_dir, password).await?;
+    let file_repo = open_files(&backup).await?;
+    let photo_repo = backup.photo_repository(&file_repo).await?;
+    let blob_repo = backup.blob_repository();
+
+    let photos = PhotosService::new()
+        .with_date_folders(by_date)
+        .with_albums(albums)
+        .export(&photo_repo, &file_repo, &blob_repo, &output)
+        .await?;
+    display_service.display_photos_export(&photos)
+}
+
+/// Runs the safari command
+async fn safari(args: SafariArgs, display_service: &DisplayService) -> Result<()> {
+    let SafariArgs {
+        backup_dir,
+        output,
+        file_format,
+        netscape_bookmarks,
+        password,
+        ..
+    } = args;
+
+    let backup = open_backup(&backup_dir, password).await?;
+    let file_repo = open_files(&backup).await?;
+    let safari_repo = backup.safari_repository(&file_repo).await?;
+
+    let files = SafariService::new()
+        .with_format(file_format)
+        .with_netscape_bookmarks(netscape_bookmarks)
+        .export(&safari_repo, &output)
+        .await?;
+    display_service.display_safari_export(&files)
+}
+
+/// Returns the exit status of a verification
+///
+/// Problems are reported with a failure status, so scripts can rely on it.
+const fn verify_status(report: &VerifyReport) -> ExitCode {
+    if report.is_healthy() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
 
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() -> Result<ExitCode> {
     let cli = Cli::parse();
 
-    let display_service = DisplayService::new();
+    let display_service = DisplayService::new().with_formatter(cli.format.formatter());
 
     match cli.command {
-        Commands::List { backups_root } => {
+        Commands::List {
+            backups_root,
+            columns,
+        } => {
             // Create backup metadata repository and list service
             let backups_root_str = backups_root.to_string_lossy();
             let expanded_backups_root = shellexpand::tilde(&backups_root_str);
@@ -30,75 +360,94 @@ async fn main() -> Result<()> {
                 .await
                 .map_err(|e| anyhow::anyhow!("Error listing backups: {e}"))?;
 
-            display_service.display_metadata_list(&metadata_list);
+            display_service.display_metadata_list(&metadata_list, &columns)?;
+        }
+        Commands::Info { backup_dir } => {
+            let backup_dir_str = backup_dir.to_string_lossy();
+            let expanded_backup_dir = shellexpand::tilde(&backup_dir_str);
+            let backup = BackupDirectory::open(PathBuf::from(expanded_backup_dir.as_ref())).await?;
+
+            display_service.display_metadata(&backup.metadata().await?)?;
         }
         Commands::Search {
             backup_dir,
-            domain_exact,
-            domain_contains,
-            path_exact,
-            path_contains,
-            or,
+            search,
+            password,
         } => {
-            // Database connection initialization
-            let backup_dir_str = backup_dir.to_string_lossy();
-            let expanded_backup_dir = shellexpand::tilde(&backup_dir_str);
-            let backup_path = PathBuf::from(expanded_backup_dir.as_ref());
-            let manifest_path = backup_path.join("Manifest.db");
-            if !manifest_path.exists() {
-                return Err(anyhow::anyhow!(
-                    "Manifest.db not found in backup directory: {}",
-                    backup_path.display()
-                ));
-            }
-
-            let db_url = format!("sqlite://{}", manifest_path.display());
-            let db = DatabaseConnection::new(&db_url).await?;
-            let file_repo = FileRepositoryImpl::new(db);
+            let backup = open_backup(&backup_dir, password).await?;
+            let file_repo = open_files(&backup).await?;
             let search_service = SearchService::new();
 
-            let params =
-                SearchParams::new(domain_exact, domain_contains, path_exact, path_contains, or);
+            let params = search_params(search, backup_date(&backup).await);
 
             let results = search_service.search(&file_repo, params).await?;
-            display_service.display_search_results(results);
+            display_service.display_search_results(&results)?;
         }
-        Commands::Extract {
+        Commands::Extract(args) => extract(args, &display_service).await?,
+        Commands::Verify {
             backup_dir,
-            output,
-            domain_exact,
-            domain_contains,
-            path_exact,
-            path_contains,
-            or,
+            password,
         } => {
-            // Database connection initialization
-            let backup_dir_str = backup_dir.to_string_lossy();
-            let expanded_backup_dir = shellexpand::tilde(&backup_dir_str);
-            let backup_path = PathBuf::from(expanded_backup_dir.as_ref());
-            let manifest_path = backup_path.join("Manifest.db");
-            if !manifest_path.exists() {
-                return Err(anyhow::anyhow!(
-                    "Manifest.db not found in backup directory: {}",
-                    backup_path.display()
-                ));
-            }
-
-            let db_url = format!("sqlite://{}", manifest_path.display());
-            let db = DatabaseConnection::new(&db_url).await?;
-            let file_repo = FileRepositoryImpl::new(db);
-            let extract_service = ExtractService::new();
-
-            let params =
-                SearchParams::new(domain_exact, domain_contains, path_exact, path_contains, or);
-
-            let result = extract_service
-                .extract(&file_repo, backup_path, &output, params)
+            let backup = open_backup(&backup_dir, password).await?;
+            let file_repo = open_files(&backup).await?;
+            let blob_repo = backup.blob_repository();
+
+            let report = VerifyService::new().verify(&file_repo, &blob_repo).await?;
+            display_service.display_verify_report(&report)?;
+            return Ok(verify_status(&report));
+        }
+        Commands::Apps {
+            backup_dir,
+            password,
+        } => {
+            let backup = open_backup(&backup_dir, password).await?;
+            let file_repo = open_files(&backup).await?;
+            let app_repo = backup.application_repository();
+
+            let apps = AppsService::new().list(&app_repo, &file_repo).await?;
+            display_service.display_apps(&apps)?;
+        }
+        Commands::Messages(args) => messages(args, &display_service).await?,
+        Commands::Contacts(args) => contacts(args, &display_service).await?,
+        Commands::Calls(args) => calls(args, &display_service).await?,
+        Commands::Voicemail(args) => voicemail(args, &display_service).await?,
+        Commands::Photos(args) => photos(args, &display_service).await?,
+        Commands::Safari(args) => safari(args, &display_service).await?,
+        Commands::Diff {
+            from,
+            to,
+            search,
+            from_password,
+            to_password,
+        } => {
+            let from_backup =
+                open_backup_with_prompt(&from, from_password, "Older backup password: ").await?;
+            let to_backup =
+                open_backup_with_prompt(&to, to_password, "Newer backup password: ").await?;
+            let from_repo = open_files(&from_backup).await?;
+            let to_repo = open_files(&to_backup).await?;
+
+            // Relative times such as `7d` are counted back from the newer backup
+            let params = search_params(search, backup_date(&to_backup).await);
+
+            let report = DiffService::new()
+                .diff(&from_repo, &to_repo, params)
                 .await?;
+            display_service.display_diff_report(&report)?;
+        }
+        Commands::VerifyExtraction { dir, manifest } => {
+            let dir_str = dir.to_string_lossy();
+            let expanded_dir = shellexpand::tilde(&dir_str);
+            let manifest_str = manifest.to_string_lossy();
+            let expanded_manifest = shellexpand::tilde(&manifest_str);
+            let hash_manifest = HashManifest::load(Path::new(expanded_manifest.as_ref()))?;
 
-            display_service.display_extract_results(&result);
+            let report = VerifyService::new()
+                .verify_extraction(Path::new(expanded_dir.as_ref()), &hash_manifest);
+            display_service.display_verify_report(&report)?;
+            return Ok(verify_status(&report));
         }
     }
 
-    Ok(())
+    Ok(ExitCode::SUCCESS)
 }